@@ -35,13 +35,22 @@ async fn main() -> anyhow::Result<()> {
         println!("\n📄 Detailed status for bundle: {first_id}");
         let status = node.get_bundle_status(Some(first_id))?;
         match status {
-            BundleStatus::Single { id, bundle } => {
+            BundleStatus::Single {
+                id,
+                bundle,
+                remaining_lifetime,
+                latency,
+            } => {
                 println!("  ID: {id}");
                 println!("  Source: {}", bundle.primary.source);
                 println!("  Destination: {}", bundle.primary.destination);
                 println!("  Creation Time: {}", bundle.primary.creation_timestamp);
                 println!("  Lifetime: {} seconds", bundle.primary.lifetime);
                 println!("  Expired: {}", bundle.is_expired());
+                println!("  Remaining: {remaining_lifetime}s");
+                if let Some(latency) = latency {
+                    println!("  Latency: {:.3}s", latency.as_secs_f64());
+                }
                 println!("  Message: {}", String::from_utf8_lossy(&bundle.payload));
             }
             _ => unreachable!(),
@@ -94,11 +103,13 @@ async fn main() -> anyhow::Result<()> {
         BundleStatus::Summary {
             active,
             expired,
+            expiring_soon,
             total,
         } => {
             println!("  📊 Bundle Summary:");
             println!("    ✅ Active: {active}");
             println!("    ⏰ Expired: {expired}");
+            println!("    ⌛ Expiring soon: {expiring_soon}");
             println!("    📦 Total: {total}");
 
             if expired > 0 {
@@ -111,6 +122,7 @@ async fn main() -> anyhow::Result<()> {
                     active: new_active,
                     expired: new_expired,
                     total: new_total,
+                    ..
                 } = after_cleanup
                 {
                     println!("  📊 After cleanup:");
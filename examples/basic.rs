@@ -35,10 +35,12 @@ async fn main() -> anyhow::Result<()> {
         BundleStatus::Summary {
             active,
             expired,
+            expiring_soon,
             total,
         } => {
             println!("  Active bundles: {active}");
             println!("  Expired bundles: {expired}");
+            println!("  Expiring soon: {expiring_soon}");
             println!("  Total bundles: {total}");
         }
         _ => unreachable!(),
@@ -18,6 +18,10 @@ async fn main() -> anyhow::Result<()> {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     node.add_route(RouteEntry {
@@ -26,6 +30,10 @@ async fn main() -> anyhow::Result<()> {
         cla_type: "ble".to_string(),
         cost: 5,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     node.add_route(RouteEntry {
@@ -34,6 +42,10 @@ async fn main() -> anyhow::Result<()> {
         cla_type: "lora".to_string(),
         cost: 15,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     // Show all routes
@@ -113,6 +125,10 @@ async fn main() -> anyhow::Result<()> {
         cla_type: "tcp".to_string(),
         cost: 8,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     custom_node.add_route(RouteEntry {
@@ -121,6 +137,10 @@ async fn main() -> anyhow::Result<()> {
         cla_type: "ble".to_string(),
         cost: 12,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     // Insert a test bundle with custom routing
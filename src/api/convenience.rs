@@ -2,11 +2,21 @@ use super::DtnNode;
 use crate::bpv7::bundle::Bundle;
 
 /// Quick bundle insertion using default settings
-pub async fn insert_bundle_quick(message: &str) -> anyhow::Result<()> {
+pub async fn insert_bundle_quick(message: &str) -> anyhow::Result<String> {
     let node = DtnNode::new()?;
     node.insert_bundle(message.to_string()).await
 }
 
+/// Quick file import using default settings, returning the ID of the stored
+/// bundle, or of its first fragment if the file was split
+pub async fn insert_file_quick(path: &str, destination: &str) -> anyhow::Result<String> {
+    let node = DtnNode::new()?;
+    let ids = node.insert_file(path, destination).await?;
+    ids.into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("No bundle was stored for {path}"))
+}
+
 /// Quick bundle listing using default settings
 pub fn list_bundles_quick() -> anyhow::Result<Vec<String>> {
     let node = DtnNode::new()?;
@@ -0,0 +1,51 @@
+use crate::bpv7::EndpointId;
+use crate::store::queue::QueuedBundle;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// A bundle forwarded under custody and the peers it was sent to
+struct Pending {
+    entry: QueuedBundle,
+    peers: Vec<EndpointId>,
+}
+
+/// Bundles this node forwarded as their custodian and still holds until the next
+/// node signals whether it took custody, shared with the receive path
+#[derive(Clone, Default)]
+pub(crate) struct CustodyTracker {
+    pending: Arc<Mutex<HashMap<String, Pending>>>,
+}
+
+impl CustodyTracker {
+    /// Hold the bundle behind `entry`, just sent to `peers`, until one of them
+    /// signals whether it took custody
+    pub(crate) fn record(&self, entry: QueuedBundle, peers: Vec<EndpointId>) {
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(entry.id.clone(), Pending { entry, peers });
+    }
+
+    /// Stop waiting for a signal about the bundle with store ID `id`, returning its
+    /// queue entry, if it was pending and sent to the node `from` the signal came from
+    pub(crate) fn take(&self, id: &str, from: &EndpointId) -> Option<QueuedBundle> {
+        let mut pending = self.pending.lock().unwrap();
+        if !pending
+            .get(id)?
+            .peers
+            .iter()
+            .any(|peer| same_node(peer, from))
+        {
+            return None;
+        }
+        pending.remove(id).map(|pending| pending.entry)
+    }
+}
+
+/// Whether both endpoints belong to the same node; signals come from a node's own
+/// EID, which may differ from the peer EID by its service part
+fn same_node(a: &EndpointId, b: &EndpointId) -> bool {
+    a.normalized() == b.normalized()
+        || a.admin_endpoint()
+            .is_some_and(|admin| Some(admin) == b.admin_endpoint())
+}
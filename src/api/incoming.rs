@@ -0,0 +1,51 @@
+use crate::bpv7::bundle::Bundle;
+use crate::consts::INCOMING_STREAM_CAPACITY;
+use futures::stream::{self, Stream};
+use tokio::sync::broadcast::{self, error::RecvError, Sender};
+
+/// Subscribers to [`DtnNode::incoming_bundles`](super::DtnNode::incoming_bundles),
+/// shared with the node's receive path. Bundles are buffered in one bounded ring, so
+/// a subscriber that stops reading cannot grow the node's memory without limit.
+#[derive(Clone)]
+pub(crate) struct IncomingSubscribers {
+    sender: Sender<Bundle>,
+}
+
+impl Default for IncomingSubscribers {
+    fn default() -> Self {
+        Self::with_capacity(INCOMING_STREAM_CAPACITY)
+    }
+}
+
+impl IncomingSubscribers {
+    /// Subscribers that may each fall up to `capacity` bundles behind
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// Open a new subscription that yields every bundle published after this call.
+    /// A subscription that falls more than the capacity behind skips the oldest
+    /// bundles it missed, logging how many.
+    pub(crate) fn subscribe(&self) -> impl Stream<Item = Bundle> + Send + Unpin + 'static {
+        let receiver = self.sender.subscribe();
+        Box::pin(stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(bundle) => return Some((bundle, receiver)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Incoming bundle stream fell behind; skipped {skipped} bundles");
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }))
+    }
+
+    /// Hand a copy of `bundle` to every open subscription. Dropped streams are
+    /// unsubscribed.
+    pub(crate) fn publish(&self, bundle: &Bundle) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(bundle.clone());
+    }
+}
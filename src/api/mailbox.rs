@@ -0,0 +1,56 @@
+use crate::bpv7::bundle::Bundle;
+use crate::bpv7::EndpointId;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Receive queue for bundles addressed to one service endpoint, filled by the
+/// node's receive path as bundles arrive
+pub struct Mailbox {
+    eid: EndpointId,
+    receiver: UnboundedReceiver<Bundle>,
+}
+
+impl Mailbox {
+    /// Endpoint (or `*` wildcard pattern) this mailbox collects bundles for
+    pub fn eid(&self) -> &EndpointId {
+        &self.eid
+    }
+
+    /// Next queued bundle, or `None` if the mailbox is currently empty
+    pub fn try_recv(&mut self) -> Option<Bundle> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Wait for the next bundle; `None` once the node has been dropped
+    pub async fn recv(&mut self) -> Option<Bundle> {
+        self.receiver.recv().await
+    }
+}
+
+type MailboxSender = (EndpointId, UnboundedSender<Bundle>);
+
+/// Mailboxes registered with a node, shared with its receive path
+#[derive(Clone, Default)]
+pub(crate) struct MailboxRegistry {
+    senders: Arc<Mutex<Vec<MailboxSender>>>,
+}
+
+impl MailboxRegistry {
+    pub(crate) fn register(&self, eid: EndpointId) -> Mailbox {
+        let (sender, receiver) = unbounded_channel();
+        self.senders.lock().unwrap().push((eid.clone(), sender));
+        Mailbox { eid, receiver }
+    }
+
+    /// Queue `bundle` in the first open mailbox matching its destination, returning
+    /// whether one took it. Dropped mailboxes are unregistered.
+    pub(crate) fn route(&self, bundle: &Bundle) -> bool {
+        let destination = EndpointId::from(bundle.primary.destination.as_str());
+        let mut senders = self.senders.lock().unwrap();
+        senders.retain(|(_, sender)| !sender.is_closed());
+        match senders.iter().find(|(eid, _)| destination.matches(eid)) {
+            Some((_, sender)) => sender.send(bundle.clone()).is_ok(),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counters shared by the insert path, receive path, forwarding and cleanup task
+#[derive(Debug, Default)]
+pub struct NodeMetrics {
+    bundles_stored: AtomicU64,
+    bundles_forwarded: AtomicU64,
+    bundles_delivered: AtomicU64,
+    bundles_expired: AtomicU64,
+    receive_failures: AtomicU64,
+    duplicates_suppressed: AtomicU64,
+    expiry_warnings: AtomicU64,
+}
+
+impl NodeMetrics {
+    /// A bundle was written to the store, either inserted locally or received
+    pub fn record_stored(&self) {
+        self.bundles_stored.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A bundle was handed to a next hop and moved out of the active store
+    pub fn record_forwarded(&self) {
+        self.bundles_forwarded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A received bundle was handed to a mailbox or an endpoint served by this node;
+    /// bundles only stored for forwarding do not count
+    pub fn record_delivered(&self) {
+        self.bundles_delivered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// `count` expired bundles were dropped by cleanup
+    pub fn record_expired(&self, count: u64) {
+        self.bundles_expired.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// A received bundle could not be stored
+    pub fn record_receive_failure(&self) {
+        self.receive_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A received bundle was dropped because an identical copy was already stored
+    pub fn record_duplicate(&self) {
+        self.duplicates_suppressed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A stored bundle came within the expiry warning threshold of its end of life
+    pub fn record_expiry_warning(&self) {
+        self.expiry_warnings.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> Metrics {
+        Metrics {
+            bundles_stored: self.bundles_stored.load(Ordering::Relaxed),
+            bundles_forwarded: self.bundles_forwarded.load(Ordering::Relaxed),
+            bundles_delivered: self.bundles_delivered.load(Ordering::Relaxed),
+            bundles_expired: self.bundles_expired.load(Ordering::Relaxed),
+            receive_failures: self.receive_failures.load(Ordering::Relaxed),
+            duplicates_suppressed: self.duplicates_suppressed.load(Ordering::Relaxed),
+            expiry_warnings: self.expiry_warnings.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Point-in-time copy of a node's counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Metrics {
+    pub bundles_stored: u64,
+    pub bundles_forwarded: u64,
+    pub bundles_delivered: u64,
+    pub bundles_expired: u64,
+    pub receive_failures: u64,
+    pub duplicates_suppressed: u64,
+    pub expiry_warnings: u64,
+}
+
+impl Metrics {
+    /// Render the counters in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let counters = [
+            (
+                "sdtn_bundles_stored_total",
+                "Bundles written to the store",
+                self.bundles_stored,
+            ),
+            (
+                "sdtn_bundles_forwarded_total",
+                "Bundles handed to a next hop",
+                self.bundles_forwarded,
+            ),
+            (
+                "sdtn_bundles_delivered_total",
+                "Received bundles delivered to this node",
+                self.bundles_delivered,
+            ),
+            (
+                "sdtn_bundles_dropped_expired_total",
+                "Expired bundles removed by cleanup",
+                self.bundles_expired,
+            ),
+            (
+                "sdtn_receive_failures_total",
+                "Received bundles that could not be stored",
+                self.receive_failures,
+            ),
+            (
+                "sdtn_duplicates_suppressed_total",
+                "Received bundles dropped as duplicates",
+                self.duplicates_suppressed,
+            ),
+            (
+                "sdtn_expiry_warnings_total",
+                "Stored bundles that came close to expiring",
+                self.expiry_warnings,
+            ),
+        ];
+
+        let mut out = String::new();
+        for (name, help, value) in counters {
+            out.push_str(&format!(
+                "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}\n"
+            ));
+        }
+        out
+    }
+}
+
+/// Serve `GET /metrics` in the Prometheus text format until `shutdown` is cancelled
+#[cfg(feature = "metrics")]
+pub async fn serve_metrics(
+    metrics: std::sync::Arc<NodeMetrics>,
+    listener: tokio::net::TcpListener,
+    shutdown: tokio_util::sync::CancellationToken,
+) -> anyhow::Result<()> {
+    loop {
+        let (stream, _) = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            accepted = listener.accept() => accepted?,
+        };
+        let metrics = std::sync::Arc::clone(&metrics);
+        tokio::spawn(async move {
+            if let Err(e) = handle_metrics_request(stream, &metrics).await {
+                log::warn!("Metrics request failed: {e}");
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "metrics")]
+async fn handle_metrics_request(
+    mut stream: tokio::net::TcpStream,
+    metrics: &NodeMetrics,
+) -> anyhow::Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Only the request line matters; read until the end of the headers
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let response = if request.starts_with("GET /metrics ") {
+        let body = metrics.snapshot().to_prometheus();
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
@@ -1,13 +1,21 @@
 // API modules
 pub mod convenience;
+mod custody;
+mod incoming;
+pub mod mailbox;
+pub mod metrics;
 pub mod node;
+mod registration;
 
 pub mod types;
 
 // Re-export main types for convenience
+pub use crate::store::{BundleFilter, BundleMeta, SortKey};
 pub use convenience::*;
-pub use node::DtnNode;
-pub use types::BundleStatus;
+pub use mailbox::Mailbox;
+pub use metrics::{Metrics, NodeMetrics};
+pub use node::{DeliveryHandler, DtnNode};
+pub use types::{BundleStatus, ExportFormat, HealthStatus};
 
 #[cfg(test)]
 mod tests;
@@ -1,26 +1,287 @@
 use crate::bpv7::bundle::*;
-use crate::bpv7::EndpointId;
+use crate::bpv7::{
+    BundleId, CustodySignal, EndpointId, ReassemblyBuffer, ReportPriorityPolicy, StatusReport,
+    StatusReportReason, VerifyingKey,
+};
+use crate::cla::beacon::{BeaconConfig, BeaconService};
+use crate::cla::factory::{ClaFactory, DefaultClaFactory, ReceiveCallback};
 use crate::cla::manager::ClaManager;
-use crate::cla::manager::ConvergenceLayer;
 use crate::cla::peer::ClaPeer;
+use crate::cla::rate_limit::RateLimiter;
+use crate::cla::tcp::ack::AckStatus;
+use crate::cla::tcp::contact::ContactHeader;
+use crate::cla::tcp::summary::SummaryVector;
+use crate::cla::tcp::wire::WireFormat;
 use crate::cla::TcpPeer;
-use crate::config::{generate_creation_timestamp, Config};
-use crate::consts::BUNDLES_DIR;
+use crate::config::{generate_creation_timestamp, ClaConfig, ClaRole, ClaType, Config};
+use crate::consts::{
+    BUNDLES_DIR, DEFAULT_CUSTODY_TIMEOUT, DEFAULT_EXPIRING_SOON_WINDOW, DEFAULT_FRAGMENT_THRESHOLD,
+    DEFAULT_MAX_CONCURRENT_SENDS, DEFAULT_MAX_FORWARDING_ATTEMPTS, DEFAULT_RETRY_BASE_DELAY,
+    DEFAULT_RETRY_MAX_DELAY, ROUTING_STATE_FILE, SEEN_BUNDLES_FILE,
+};
 use crate::routing::algorithm::{RouteEntry, RoutingAlgorithm, RoutingConfig, RoutingTable};
+use crate::routing::explain::{ForwardingExplanation, PeerDecision, RejectionReason};
 use crate::store::bundle_descriptor::BundleDescriptor;
-use crate::store::BundleStore;
+use crate::store::file::write_atomically;
+use crate::store::seen::DEFAULT_SEEN_CAPACITY;
+use crate::store::storage::spawn_blocking;
+use crate::store::{
+    BundleFilter, BundleMeta, BundleStorage, BundleStore, ForwardingQueue, HistoryEntry,
+    InsertOutcome, QueuedBundle, SeenBundles, SortKey, StoreError, StoreStats,
+};
+use futures::{Stream, StreamExt};
+use parking_lot::RwLock;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use tokio::sync::Mutex as TokioMutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex as TokioMutex, Semaphore};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 
-use super::BundleStatus;
+use super::custody::CustodyTracker;
+use super::incoming::IncomingSubscribers;
+use super::mailbox::{Mailbox, MailboxRegistry};
+use super::metrics::{Metrics, NodeMetrics};
+use super::registration::Registrations;
+use super::{BundleStatus, ExportFormat, HealthStatus};
 
 /// DTN Node API for managing DTN bundles and network operations
 pub struct DtnNode {
-    store: BundleStore,
-    store_path: String,
+    store: Arc<dyn BundleStorage>,
     routing_algorithm: Arc<TokioMutex<Box<dyn RoutingAlgorithm>>>,
-    routing_table: Arc<Mutex<RoutingTable>>,
+    routing_table: Arc<RwLock<RoutingTable>>,
     cla_manager: Arc<ClaManager>,
+    metrics: Arc<NodeMetrics>,
+    /// Forward bundles received by the listener onward instead of only storing them
+    relay_mode: bool,
+    /// Failed forwarding passes after which a stored bundle is moved to `failed/`
+    max_forwarding_attempts: u32,
+    /// Base of the retry backoff: `base * 2^n` after `n` failed forwarding passes
+    retry_base_delay: Duration,
+    /// Upper bound on the backoff between forwarding retries
+    retry_max_delay: Duration,
+    /// Stored bundles the forwarding loop works on at once
+    max_concurrent_sends: usize,
+    /// Bounds the transmissions to peers in flight across all forwarding paths
+    send_permits: Arc<Semaphore>,
+    /// Stored bundles in the order the forwarding loop tries them, with their retry
+    /// backoff; built from the store on the first forwarding pass
+    forwarding_queue: Arc<Mutex<Option<ForwardingQueue>>>,
+    /// Hands received bundles addressed to this node to the application
+    local_delivery: LocalDelivery,
+    /// Exchange TCPCL v4 contact headers on TCP connections
+    tcpcl_v4: bool,
+    /// Bundle encoding announced in the contact header
+    wire_format: WireFormat,
+    /// Exchange summary vectors of held bundle IDs after the contact headers
+    summary_vectors: bool,
+    /// Signature check applied to bundles received by the listener
+    signature_policy: SignaturePolicy,
+    /// Per-endpoint receive queues that take received bundles ahead of the store
+    mailboxes: MailboxRegistry,
+    /// Streams handed out by [`DtnNode::incoming_bundles`]
+    incoming: IncomingSubscribers,
+    /// TCP listener address announced to neighbors in discovery beacons
+    advertised_address: Option<String>,
+    /// Convergence layers started by [`DtnNode::start_from_config`]
+    cla_configs: Vec<ClaConfig>,
+    /// File the routing algorithm's state is restored from and saved to
+    routing_state_path: Option<PathBuf>,
+    /// File the routing table is loaded from and saved to after every change
+    routes_path: Option<PathBuf>,
+    /// Payload bytes per fragment when importing files with [`DtnNode::insert_file`]
+    fragment_threshold: u64,
+    /// Remaining lifetime (seconds) under which the status summary counts a bundle
+    /// as expiring soon and the cleanup task warns about it
+    expiring_soon_window: u64,
+    /// Fragments held until their set is complete, when received fragments are
+    /// reassembled before storing
+    reassembly: Option<Arc<Mutex<ReassemblyBuffer>>>,
+    /// Bundles forwarded under custody, kept until the next node signals
+    custody: CustodyTracker,
+    /// Seconds to wait for a custody signal before forwarding such a bundle again
+    custody_timeout: u64,
+    /// IDs of bundles already received, so late copies are dropped as duplicates
+    seen: Arc<SeenBundles>,
+    /// Priority of generated status reports relative to the bundle they report on
+    report_priority_policy: ReportPriorityPolicy,
+    /// Lowercase EID schemes inserted bundles must use; empty accepts any scheme
+    allowed_schemes: Vec<String>,
+}
+
+/// Application callback invoked with bundles delivered to the local node
+pub type DeliveryHandler = Arc<dyn Fn(Bundle) + Send + Sync>;
+
+/// Local delivery settings: bundles addressed to a registered endpoint go to the
+/// application instead of the forwarding path
+#[derive(Clone)]
+struct LocalDelivery {
+    /// This node's own endpoint, registered together with its administrative endpoint
+    node_eid: EndpointId,
+    registrations: Registrations,
+    handler: Option<DeliveryHandler>,
+    /// Move delivered bundles to `delivered/` instead of keeping them active
+    archive: bool,
+}
+
+impl LocalDelivery {
+    fn is_local(&self, bundle: &Bundle) -> bool {
+        self.registrations
+            .is_registered(&EndpointId::from(bundle.primary.destination.as_str()))
+    }
+
+    /// Deliver `bundle` if it is addressed to this node, returning whether it was
+    fn try_deliver(&self, bundle: &Bundle, store: &dyn BundleStorage) -> bool {
+        if !self.is_local(bundle) {
+            return false;
+        }
+        log::info!(
+            "Delivering bundle {} to {}",
+            bundle.id(),
+            bundle.primary.destination
+        );
+        if let Some(handler) = &self.handler {
+            handler(bundle.clone());
+        }
+        if self.archive {
+            if let Err(e) = store.deliver(bundle) {
+                log::warn!("Failed to archive delivered bundle {}: {e}", bundle.id());
+            }
+        }
+        true
+    }
+}
+
+/// Receive-side signature check; bundles are accepted unchecked unless `require_signed` is set
+#[derive(Clone, Default)]
+struct SignaturePolicy {
+    require_signed: bool,
+    verifying_key: Option<VerifyingKey>,
+}
+
+impl SignaturePolicy {
+    fn accepts(&self, bundle: &Bundle) -> bool {
+        if !self.require_signed {
+            return true;
+        }
+        self.verifying_key
+            .as_ref()
+            .is_some_and(|key| bundle.verify(key))
+    }
+}
+
+/// Shared handles needed to push a bundle through routing and out to peers
+#[derive(Clone)]
+struct Forwarder {
+    store: Arc<dyn BundleStorage>,
+    /// Written into the previous node block of every forwarded copy
+    node_eid: EndpointId,
+    /// Endpoints served by this node, which are never selected as a next hop
+    registrations: Registrations,
+    routing_algorithm: Arc<TokioMutex<Box<dyn RoutingAlgorithm>>>,
+    /// With ECMP enabled, spreads bundles over equal-cost next hops
+    routing_table: Arc<RwLock<RoutingTable>>,
+    cla_manager: Arc<ClaManager>,
+    metrics: Arc<NodeMetrics>,
+    send_permits: Arc<Semaphore>,
+}
+
+impl Forwarder {
+    /// Send `bundle` to every reachable peer the routing algorithm selects, never
+    /// back to `received_from`, the bundle's previous node or this node itself.
+    /// Returns the peers it was delivered to.
+    async fn forward(&self, bundle: &Bundle, received_from: Option<EndpointId>) -> Vec<EndpointId> {
+        let mut descriptor = BundleDescriptor::new(bundle.clone());
+        if let Some(previous_hop) = received_from {
+            descriptor.mark_sent(previous_hop);
+        }
+        // Each hop replaces the previous node block with its own endpoint
+        let mut outgoing = bundle.clone();
+        outgoing.previous_node = (!self.node_eid.is_null()).then(|| self.node_eid.clone());
+        // and, as the bundle's custodian, names itself for the custody signal
+        if outgoing.primary.custody_requested() && !self.node_eid.is_null() {
+            outgoing.primary.custodian = Some(self.node_eid.to_string());
+        }
+        let bundle = &outgoing;
+
+        let peers = self.cla_manager.list_reachable_peers().await;
+        let mut targets: Vec<EndpointId> = {
+            let algorithm = self.routing_algorithm.lock().await;
+            algorithm
+                .select_peers_for_forwarding(&descriptor, &peers)
+                .into_iter()
+                .map(|peer| peer.get_peer_endpoint_id())
+                .filter(|eid| !self.registrations.is_registered(eid))
+                .collect()
+        };
+        self.spread_over_equal_cost_routes(bundle, &mut targets);
+
+        // Peers are sent to concurrently, each send holding one of the node's permits
+        let sends = targets.into_iter().map(|target| async move {
+            let _permit = self
+                .send_permits
+                .acquire()
+                .await
+                .expect("send semaphore is never closed");
+            let result = self.cla_manager.send_bundle(&target, bundle).await;
+            (target, result)
+        });
+
+        let mut delivered = Vec::new();
+        for (target, result) in futures::future::join_all(sends).await {
+            match result {
+                Ok(()) => {
+                    self.record_history(HistoryEntry::sent(bundle, Some(target.as_str())))
+                        .await;
+                    self.metrics.record_forwarded();
+                    self.routing_algorithm
+                        .lock()
+                        .await
+                        .notify_delivery_confirmed(&descriptor, &target);
+                    delivered.push(target);
+                }
+                Err(e) => {
+                    log::warn!("Failed to forward bundle to {target}: {e}");
+                    self.record_history(HistoryEntry::failed(bundle, target.as_str(), &e))
+                        .await;
+                }
+            }
+        }
+        delivered
+    }
+
+    /// With ECMP enabled, keep only one of the selected peers that are next hops of
+    /// equal lowest-cost routes to the bundle's destination, rotating round-robin
+    /// across bundles. Peers outside that set are left selected.
+    fn spread_over_equal_cost_routes(&self, bundle: &Bundle, targets: &mut Vec<EndpointId>) {
+        let table = self.routing_table.read();
+        if !table.ecmp() {
+            return;
+        }
+        let destination = EndpointId::from(bundle.primary.destination.as_str());
+        let Some(chosen) =
+            table.select_route_where(&destination, |route| targets.contains(&route.next_hop))
+        else {
+            return;
+        };
+        let lowest = table.route_score(&chosen);
+        let equal_cost: Vec<EndpointId> = table
+            .find_routes_ranked(&destination)
+            .into_iter()
+            .filter(|route| table.route_score(route) == lowest)
+            .map(|route| route.next_hop)
+            .collect();
+        targets.retain(|target| *target == chosen.next_hop || !equal_cost.contains(target));
+    }
+
+    /// Append to the forwarding history; a failure to record never fails the send
+    async fn record_history(&self, entry: HistoryEntry) {
+        let store = Arc::clone(&self.store);
+        if let Err(e) = spawn_blocking(move || store.record_history(&entry)).await {
+            log::warn!("Failed to record forwarding history: {e}");
+        }
+    }
 }
 
 impl DtnNode {
@@ -40,20 +301,116 @@ impl DtnNode {
 
     /// Create a new DTN CLI instance with a custom bundle store path
     pub fn with_store_path(store_path: &str) -> anyhow::Result<Self> {
-        let store = BundleStore::new(store_path)?;
-        let config = Config::load()?;
-        let routing_config = RoutingConfig::new(config.get_routing_algorithm_type());
+        Self::from_config(store_path, Config::load()?)
+    }
+
+    /// Create a new DTN CLI instance from an already loaded configuration
+    pub fn from_config(store_path: &str, config: Config) -> anyhow::Result<Self> {
+        config.validate()?;
+        let mut store =
+            BundleStore::new(store_path)?.with_max_bytes(config.storage.max_size * 1024 * 1024);
+        if let Some(policy) = config.storage.eviction_policy {
+            store = store.with_eviction_policy(policy);
+        }
+        if let Some(path) = &config.storage.dispatched_path {
+            store = store.with_dispatched_dir(path);
+        }
+        if let Some(secs) = config.storage.dispatched_retention_secs {
+            store = store.with_dispatched_retention(Duration::from_secs(secs));
+        }
+        if let Some(max_bundles) = config.storage.per_destination_max_bundles {
+            store = store.with_per_destination_max_bundles(max_bundles);
+        }
+        if let Some(max_bytes) = config.storage.per_destination_max_bytes {
+            store = store.with_per_destination_max_bytes(max_bytes);
+        }
+        let store = Arc::new(store);
+        let routing_config =
+            RoutingConfig::new(config.get_routing_algorithm_type()).with_ecmp(config.routing.ecmp);
+        let mut node = Self::with_store(store, routing_config)
+            .with_max_forwarding_attempts(config.storage.max_forwarding_attempts)
+            .with_max_concurrent_sends(config.storage.max_concurrent_sends)
+            .with_retry_backoff(
+                Duration::from_secs(config.storage.retry_base_delay),
+                Duration::from_secs(config.storage.retry_max_delay),
+            )
+            .with_fragment_threshold(config.bundle.fragment_threshold)
+            .with_report_priority_policy(config.bundle.report_priority_policy);
+        if let Some(secs) = config.bundle.reassembly_timeout_secs {
+            node = node.with_reassembly_timeout(Duration::from_secs(secs));
+        }
+        if let Some(key) = config.security.verifying_key()? {
+            node = node.with_verifying_key(key);
+        }
+        if let Some(node_eid) = config.node_eid() {
+            node = node.with_node_eid(node_eid);
+        }
+        if let Some(path) = &config.routing.routes_path {
+            node = node.with_routes_path(path)?;
+        }
+        if let Some(listener) = config.tcp_listener() {
+            node = node
+                .with_tcpcl_v4(listener.tcpcl_v4)
+                .with_wire_format(listener.wire_format)
+                .with_advertised_address(listener.address.clone());
+        }
+        let seen_path = Path::new(store_path).join(SEEN_BUNDLES_FILE);
+        match SeenBundles::open(&seen_path, DEFAULT_SEEN_CAPACITY) {
+            Ok(seen) => node = node.with_seen_bundles(seen),
+            Err(e) => log::warn!("Ignoring seen bundle IDs {}: {e}", seen_path.display()),
+        }
+        Ok(node
+            .with_require_signed(config.security.require_signed)
+            .with_allowed_schemes(config.endpoints.allowed_schemes)
+            .with_cla_configs(config.cla)
+            .with_routing_state_path(Path::new(store_path).join(ROUTING_STATE_FILE)))
+    }
+
+    /// Create a new DTN CLI instance around an existing storage backend, which may
+    /// be shared with other components
+    pub fn with_store(store: Arc<dyn BundleStorage>, routing_config: RoutingConfig) -> Self {
         let routing_algorithm = Arc::new(TokioMutex::new(routing_config.create_algorithm()));
-        let routing_table = Arc::new(Mutex::new(RoutingTable::new()));
+        let routing_table = Arc::new(RwLock::new(routing_config.create_routing_table()));
         let cla_manager = Arc::new(ClaManager::new(|_bundle| {}));
 
-        Ok(Self {
+        Self {
             store,
-            store_path: store_path.to_string(),
             routing_algorithm,
             routing_table,
             cla_manager,
-        })
+            metrics: Arc::new(NodeMetrics::default()),
+            relay_mode: false,
+            max_forwarding_attempts: DEFAULT_MAX_FORWARDING_ATTEMPTS,
+            retry_base_delay: Duration::from_secs(DEFAULT_RETRY_BASE_DELAY),
+            retry_max_delay: Duration::from_secs(DEFAULT_RETRY_MAX_DELAY),
+            max_concurrent_sends: DEFAULT_MAX_CONCURRENT_SENDS,
+            send_permits: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_SENDS)),
+            forwarding_queue: Arc::new(Mutex::new(None)),
+            local_delivery: LocalDelivery {
+                node_eid: EndpointId::from("dtn:none"),
+                registrations: Registrations::default(),
+                handler: None,
+                archive: false,
+            },
+            tcpcl_v4: false,
+            wire_format: WireFormat::Cbor,
+            summary_vectors: false,
+            signature_policy: SignaturePolicy::default(),
+            mailboxes: MailboxRegistry::default(),
+            incoming: IncomingSubscribers::default(),
+            advertised_address: None,
+            cla_configs: Vec::new(),
+            routing_state_path: None,
+            routes_path: None,
+            fragment_threshold: DEFAULT_FRAGMENT_THRESHOLD,
+            expiring_soon_window: DEFAULT_EXPIRING_SOON_WINDOW,
+            reassembly: None,
+            custody: CustodyTracker::default(),
+            custody_timeout: DEFAULT_CUSTODY_TIMEOUT,
+            seen: Arc::new(SeenBundles::default()),
+            report_priority_policy: ReportPriorityPolicy::default(),
+            allowed_schemes: Vec::new(),
+        }
     }
 
     /// Create a new DTN CLI instance with custom configuration
@@ -67,58 +424,860 @@ impl DtnNode {
         store_path: &str,
         routing_config: RoutingConfig,
     ) -> anyhow::Result<Self> {
-        let store = BundleStore::new(store_path)?;
-        let routing_algorithm = Arc::new(TokioMutex::new(routing_config.create_algorithm()));
-        let routing_table = Arc::new(Mutex::new(RoutingTable::new()));
-        let cla_manager = Arc::new(ClaManager::new(|_bundle| {}));
+        let store = Arc::new(BundleStore::new(store_path)?);
+        Ok(Self::with_store(store, routing_config))
+    }
 
-        Ok(Self {
-            store,
-            store_path: store_path.to_string(),
-            routing_algorithm,
-            routing_table,
-            cla_manager,
+    /// Use a custom routing algorithm instead of the one chosen by the routing config
+    pub fn with_custom_routing_algorithm(mut self, algorithm: Box<dyn RoutingAlgorithm>) -> Self {
+        self.routing_algorithm = Arc::new(TokioMutex::new(algorithm));
+        self.restore_routing_state();
+        self
+    }
+
+    /// Restore the routing algorithm's state from `path` now, and save it there with
+    /// [`DtnNode::save_routing_state`]. Nodes built with `with_store_path` use
+    /// `<store>/routing_state.bin`.
+    pub fn with_routing_state_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.routing_state_path = Some(path.into());
+        self.restore_routing_state();
+        self
+    }
+
+    /// Load saved routing state into the current algorithm. A missing file means a
+    /// fresh start; unreadable state is logged and ignored so the node still starts.
+    fn restore_routing_state(&self) {
+        let Some(path) = &self.routing_state_path else {
+            return;
+        };
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+            Err(e) => {
+                log::warn!("Failed to read routing state {}: {e}", path.display());
+                return;
+            }
+        };
+        // Builders run before the algorithm is shared, so the lock is always free here
+        let Ok(mut algorithm) = self.routing_algorithm.try_lock() else {
+            log::warn!("Routing algorithm busy, not restoring state");
+            return;
+        };
+        match algorithm.load_state(&bytes) {
+            Ok(()) => log::info!("Restored routing state from {}", path.display()),
+            Err(e) => log::warn!("Ignoring routing state {}: {e}", path.display()),
+        }
+    }
+
+    /// Save the routing algorithm's state to the routing state file, if one is set.
+    /// Call this on shutdown so learned state survives a restart.
+    pub async fn save_routing_state(&self) -> anyhow::Result<()> {
+        let Some(path) = self.routing_state_path.clone() else {
+            return Ok(());
+        };
+        let state = self.routing_algorithm.lock().await.save_state()?;
+        if state.is_empty() {
+            return Ok(());
+        }
+        spawn_blocking(move || write_atomically(&path, &state)).await?;
+        Ok(())
+    }
+
+    /// Load the routing table from `path` now, replacing any routes already added,
+    /// and save it there whenever a route is added or removed. A missing file
+    /// starts an empty table.
+    pub fn with_routes_path(mut self, path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let loaded = self.routing_table.write().load(&path)?;
+        if loaded > 0 {
+            log::info!("Loaded {loaded} routes from {}", path.display());
+        }
+        self.routes_path = Some(path);
+        Ok(self)
+    }
+
+    /// Save the routing table to the routes file, if one is set
+    pub fn save_routes(&self) -> anyhow::Result<()> {
+        match &self.routes_path {
+            Some(path) => self.routing_table.read().save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Remember received bundle IDs in `seen` instead of the default in-memory set.
+    /// Nodes built with `with_store_path` persist them in `<store>/seen_bundles.log`.
+    pub fn with_seen_bundles(mut self, seen: SeenBundles) -> Self {
+        self.seen = Arc::new(seen);
+        self
+    }
+
+    /// Write the IDs of received bundles to disk, if the seen set is persistent.
+    /// Listeners started with a shutdown token save them when they stop.
+    pub async fn save_seen_bundles(&self) -> anyhow::Result<()> {
+        let seen = Arc::clone(&self.seen);
+        spawn_blocking(move || seen.save()).await?;
+        Ok(())
+    }
+
+    /// Enable or disable relay mode: when enabled, bundles newly received by the
+    /// TCP listener are forwarded to reachable peers as soon as they are stored
+    pub fn with_relay_mode(mut self, relay_mode: bool) -> Self {
+        self.relay_mode = relay_mode;
+        self
+    }
+
+    /// Number of failed forwarding passes after which a stored bundle is given up on
+    pub fn with_max_forwarding_attempts(mut self, max_forwarding_attempts: u32) -> Self {
+        self.max_forwarding_attempts = max_forwarding_attempts;
+        self
+    }
+
+    /// Keep at most `max_concurrent_sends` transmissions to peers in flight, across
+    /// the forwarding loop and relayed bundles (at least one)
+    pub fn with_max_concurrent_sends(mut self, max_concurrent_sends: usize) -> Self {
+        self.max_concurrent_sends = max_concurrent_sends.max(1);
+        self.send_permits = Arc::new(Semaphore::new(self.max_concurrent_sends));
+        self
+    }
+
+    /// Split files imported with [`DtnNode::insert_file`] into fragments of at most
+    /// `fragment_threshold` payload bytes
+    pub fn with_fragment_threshold(mut self, fragment_threshold: u64) -> Self {
+        self.fragment_threshold = fragment_threshold.max(1);
+        self
+    }
+
+    /// Choose the priority of the status reports this node generates
+    pub fn with_report_priority_policy(mut self, policy: ReportPriorityPolicy) -> Self {
+        self.report_priority_policy = policy;
+        self
+    }
+
+    /// Count active bundles with at most `window` left of their lifetime as
+    /// expiring soon in the status summary, and have the cleanup task warn about
+    /// them once per bundle
+    pub fn with_expiring_soon_window(mut self, window: Duration) -> Self {
+        self.expiring_soon_window = window.as_secs();
+        self
+    }
+
+    /// Reassemble received fragments before storing them, instead of storing each
+    /// fragment as it arrives. Sets still incomplete `timeout` after their first
+    /// fragment arrived are discarded.
+    pub fn with_reassembly_timeout(mut self, timeout: Duration) -> Self {
+        self.reassembly = Some(Arc::new(Mutex::new(ReassemblyBuffer::new(timeout))));
+        self
+    }
+
+    /// Wait `timeout` for the custody signal of a bundle forwarded under custody
+    /// before sending it again (default 10 minutes)
+    pub fn with_custody_timeout(mut self, timeout: Duration) -> Self {
+        self.custody_timeout = timeout.as_secs();
+        self
+    }
+
+    /// After `n` failed forwarding passes, wait `base_delay * 2^n` (at most
+    /// `max_delay`) before the forwarding loop retries a bundle
+    pub fn with_retry_backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.retry_base_delay = base_delay;
+        self.retry_max_delay = max_delay;
+        self
+    }
+
+    /// Set this node's own endpoint: bundles it creates carry it as their source.
+    /// It is registered together with its administrative endpoint (replacing the
+    /// previous node EID's), so received bundles addressed to either are delivered
+    /// locally instead of being forwarded.
+    pub fn with_node_eid(mut self, node_eid: EndpointId) -> Self {
+        let registrations = &self.local_delivery.registrations;
+        let previous = &self.local_delivery.node_eid;
+        registrations.unregister(previous);
+        if let Some(admin) = previous.admin_endpoint() {
+            registrations.unregister(&admin);
+        }
+        registrations.register(node_eid.clone());
+        if let Some(admin) = node_eid.admin_endpoint() {
+            registrations.register(admin);
+        }
+        self.local_delivery.node_eid = node_eid;
+        self
+    }
+
+    /// Administrative endpoint of this node, where status reports are delivered
+    /// (`None` while the node EID is unset)
+    pub fn admin_endpoint(&self) -> Option<EndpointId> {
+        self.local_delivery.node_eid.admin_endpoint()
+    }
+
+    /// Serve `eid` locally: received bundles addressed to it are delivered to the
+    /// application instead of being forwarded. Returns whether it was newly registered.
+    pub fn register_endpoint(&self, eid: EndpointId) -> bool {
+        self.local_delivery.registrations.register(eid)
+    }
+
+    /// Stop serving `eid` locally, returning whether it was registered
+    pub fn unregister_endpoint(&self, eid: &EndpointId) -> bool {
+        self.local_delivery.registrations.unregister(eid)
+    }
+
+    pub fn is_registered(&self, eid: &EndpointId) -> bool {
+        self.local_delivery.registrations.is_registered(eid)
+    }
+
+    /// Endpoints currently served locally, including the node and admin EIDs
+    pub fn registered_endpoints(&self) -> Vec<EndpointId> {
+        self.local_delivery.registrations.list()
+    }
+
+    /// This node's own endpoint (`dtn:none` if unset)
+    pub fn endpoint_id(&self) -> &EndpointId {
+        &self.local_delivery.node_eid
+    }
+
+    /// Same as [`DtnNode::endpoint_id`]
+    pub fn node_eid(&self) -> &EndpointId {
+        self.endpoint_id()
+    }
+
+    /// Move bundles delivered to this node into `delivered/` once the handler has run
+    pub fn with_delivered_archive(mut self, archive: bool) -> Self {
+        self.local_delivery.archive = archive;
+        self
+    }
+
+    /// Call `handler` with every received bundle addressed to this node.
+    /// Must be set before the listener is started.
+    pub fn set_delivery_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(Bundle) + Send + Sync + 'static,
+    {
+        self.local_delivery.handler = Some(Arc::new(handler));
+    }
+
+    /// Require a TCPCL v4 contact header exchange on TCP connections instead of
+    /// the legacy framing alone
+    pub fn with_tcpcl_v4(mut self, tcpcl_v4: bool) -> Self {
+        self.tcpcl_v4 = tcpcl_v4;
+        self
+    }
+
+    /// Bundle encoding to announce in the contact header and use on TCP connections;
+    /// only takes effect with [`DtnNode::with_tcpcl_v4`]
+    pub fn with_wire_format(mut self, wire_format: WireFormat) -> Self {
+        self.wire_format = wire_format;
+        self
+    }
+
+    /// Anti-entropy for epidemic flooding: the TCP listener tells connecting peers
+    /// which bundles it holds, and this node skips those bundles when sending.
+    /// Requires [`DtnNode::with_tcpcl_v4`] on both nodes.
+    pub fn with_summary_vectors(mut self, summary_vectors: bool) -> Self {
+        self.summary_vectors = summary_vectors;
+        self
+    }
+
+    /// Contact header to exchange on TCP connections, if TCPCL v4 is enabled
+    pub fn contact_header(&self) -> Option<ContactHeader> {
+        self.tcpcl_v4.then(|| {
+            ContactHeader::default()
+                .with_wire_format(self.wire_format)
+                .with_summary_vector(self.summary_vectors)
         })
     }
 
-    /// Add a route to the routing table
-    pub fn add_route(&self, entry: RouteEntry) -> anyhow::Result<()> {
-        if let Ok(mut table) = self.routing_table.lock() {
-            table.add_route(entry);
-            Ok(())
+    /// IDs of the bundles in the store. Every bundle is loaded, and those that cannot
+    /// be are left out; [`DtnNode::summary_vector`] is the cheaper choice when only
+    /// membership matters.
+    pub fn bundle_id_digest(&self) -> Vec<BundleId> {
+        held_bundle_ids(self.store.as_ref())
+    }
+
+    /// The summary vector sent to connecting peers, built from the store's keys
+    /// without loading any bundle
+    pub fn summary_vector(&self) -> SummaryVector {
+        held_summary_vector(self.store.as_ref())
+    }
+
+    /// Drop received bundles that are unsigned or whose signature does not verify
+    /// against the key set with [`DtnNode::with_verifying_key`]
+    pub fn with_require_signed(mut self, require_signed: bool) -> Self {
+        self.signature_policy.require_signed = require_signed;
+        self
+    }
+
+    /// Public key that signatures of received bundles are checked against
+    pub fn with_verifying_key(mut self, key: VerifyingKey) -> Self {
+        self.signature_policy.verifying_key = Some(key);
+        self
+    }
+
+    /// Reject inserted bundles whose source or destination uses a scheme other
+    /// than `schemes` (e.g. `["dtn", "ipn"]`); an empty list turns the check off
+    pub fn with_allowed_schemes(mut self, schemes: Vec<String>) -> Self {
+        self.allowed_schemes = schemes.iter().map(|s| s.to_ascii_lowercase()).collect();
+        self
+    }
+
+    /// Open a mailbox for bundles addressed to `eid` (which may be a `*` wildcard).
+    /// Received bundles matching a mailbox are queued there instead of being stored;
+    /// dropping the mailbox unregisters it.
+    pub fn register_mailbox(&self, eid: EndpointId) -> Mailbox {
+        self.mailboxes.register(eid)
+    }
+
+    /// Stream of every bundle this node's listeners accept from now on, including
+    /// those taken by mailboxes or delivered locally. Each stream gets its own copy
+    /// of every bundle; dropping the stream unsubscribes it. A stream that falls more
+    /// than [`INCOMING_STREAM_CAPACITY`](crate::consts::INCOMING_STREAM_CAPACITY)
+    /// bundles behind skips the oldest ones.
+    pub fn incoming_bundles(&self) -> impl Stream<Item = Bundle> + Send + Unpin + 'static {
+        self.incoming.subscribe()
+    }
+
+    /// TCP listener address announced in discovery beacons
+    /// (defaults to the address of the first TCP `[[cla]]` listener)
+    pub fn with_advertised_address(mut self, address: String) -> Self {
+        self.advertised_address = Some(address);
+        self
+    }
+
+    /// Convergence layers for [`DtnNode::start_from_config`] to start
+    /// (defaults to the `[[cla]]` entries of the config file)
+    pub fn with_cla_configs(mut self, cla_configs: Vec<ClaConfig>) -> Self {
+        self.cla_configs = cla_configs;
+        self
+    }
+
+    /// Announce this node via UDP multicast beacons sent from `bind` every `interval`,
+    /// registering the neighbors that announce themselves as TCP peers
+    pub fn start_beacon(
+        &self,
+        bind: &str,
+        interval: Duration,
+    ) -> anyhow::Result<JoinHandle<anyhow::Result<()>>> {
+        let bind = bind
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid beacon bind address '{bind}': {e}"))?;
+        self.start_beacon_with_config(BeaconConfig::new(bind, interval), CancellationToken::new())
+    }
+
+    /// Run neighbor discovery with a custom beacon configuration until `shutdown` is cancelled.
+    /// Requires the node EID and the advertised address to be set.
+    pub fn start_beacon_with_config(
+        &self,
+        config: BeaconConfig,
+        shutdown: CancellationToken,
+    ) -> anyhow::Result<JoinHandle<anyhow::Result<()>>> {
+        if self.node_eid().is_null() {
+            anyhow::bail!("Set the node EID before starting the beacon");
+        }
+        let Some(address) = self.advertised_address.clone() else {
+            anyhow::bail!("Set an advertised address before starting the beacon");
+        };
+        BeaconService::new(
+            config,
+            self.node_eid().clone(),
+            address,
+            Arc::clone(&self.cla_manager),
+        )
+        .with_tcpcl_v4(self.tcpcl_v4)
+        .spawn(shutdown)
+    }
+
+    /// Check whether the node can do its job: the store accepts writes and is not
+    /// full, and at least one registered peer is reachable
+    pub async fn health_check(&self) -> HealthStatus {
+        let store = Arc::clone(&self.store);
+        let store_writable = match spawn_blocking(move || store.check_writable()).await {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("Bundle store is not writable: {e}");
+                false
+            }
+        };
+        let store = Arc::clone(&self.store);
+        let store_usage_ratio = spawn_blocking(move || store.usage_ratio())
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to measure bundle store usage: {e}");
+                None
+            });
+        let reachable_peer_count = self.cla_manager.list_reachable_peers().await.len();
+
+        HealthStatus {
+            store_writable,
+            reachable_peer_count,
+            store_usage_ratio,
+            ready: store_writable
+                && reachable_peer_count > 0
+                && store_usage_ratio.is_none_or(|ratio| ratio < 1.0),
+        }
+    }
+
+    /// Register a peer that forwarded bundles may be sent to
+    pub async fn register_peer(&self, peer: Box<dyn ClaPeer>) {
+        self.cla_manager.register_peer(peer).await;
+    }
+
+    /// Endpoint IDs of all registered peers, including neighbors found by the beacon
+    pub async fn list_peers(&self) -> Vec<EndpointId> {
+        self.cla_manager
+            .list_all_peers()
+            .await
+            .iter()
+            .map(|peer| peer.get_peer_endpoint_id())
+            .collect()
+    }
+
+    /// Forward a bundle to the reachable peers selected by the routing algorithm,
+    /// skipping the peer it was received from. Returns the peers it was sent to.
+    pub async fn forward_bundle(
+        &self,
+        bundle: &Bundle,
+        received_from: Option<EndpointId>,
+    ) -> anyhow::Result<Vec<EndpointId>> {
+        Ok(self.forwarder().forward(bundle, received_from).await)
+    }
+
+    /// Run one forwarding pass over the stored bundles, returning how many were delivered.
+    ///
+    /// Bundles are taken from the forwarding queue in priority order, skipping those
+    /// still in retry backoff; the first pass builds the queue from the store.
+    /// Delivered bundles are dispatched. A pass that reaches no peer counts as a failed
+    /// attempt and is persisted in the store; once `max_forwarding_attempts` is reached
+    /// the bundle is moved to `failed/` and a deletion status report is queued for its
+    /// `report_to` endpoint. Expired bundles are left to the cleanup task. A bundle
+    /// whose store update fails is logged and retried after the base retry delay,
+    /// without holding up the rest of the pass.
+    pub async fn forward_stored_bundles(&self) -> anyhow::Result<usize> {
+        let forwarder = self.forwarder();
+        let mut delivered = 0;
+
+        if self.forwarding_queue.lock().unwrap().is_none() {
+            let queue = self
+                .store_blocking(|store| ForwardingQueue::from_store(store))
+                .await?;
+            self.forwarding_queue.lock().unwrap().get_or_insert(queue);
+        }
+        let now = generate_creation_timestamp();
+        let due: Vec<QueuedBundle> = {
+            let mut guard = self.forwarding_queue.lock().unwrap();
+            let queue = guard.get_or_insert_with(ForwardingQueue::new);
+            std::iter::from_fn(|| queue.pop(now)).collect()
+        };
+
+        // Up to `max_concurrent_sends` bundles are loaded and sent at a time; the
+        // outcomes are applied in queue order
+        let mut attempts = futures::stream::iter(due)
+            .map(|entry| async {
+                let attempt = self.try_forward(&forwarder, &entry).await;
+                (entry, attempt)
+            })
+            .buffered(self.max_concurrent_sends);
+        while let Some((entry, attempt)) = attempts.next().await {
+            let outcome = match attempt {
+                Ok(Some((descriptor, peers))) => {
+                    self.apply_attempt(&forwarder, &entry, descriptor, peers, now)
+                        .await
+                }
+                Ok(None) => Ok(false),
+                Err(e) => Err(e),
+            };
+            match outcome {
+                Ok(sent) => delivered += usize::from(sent),
+                Err(e) => {
+                    // Try the bundle again later rather than dropping it from the queue
+                    log::warn!("Failed to forward stored bundle {}: {e}", entry.id);
+                    if let Some(queue) = self.forwarding_queue.lock().unwrap().as_mut() {
+                        queue.requeue_with_backoff(
+                            entry,
+                            now + self.retry_base_delay.as_secs().max(1),
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    /// Act on the outcome of one forwarding attempt: dispatch a delivered bundle (or
+    /// keep it under custody), or record the failed attempt and requeue or give up
+    /// on it. Returns whether the bundle reached a peer.
+    async fn apply_attempt(
+        &self,
+        forwarder: &Forwarder,
+        entry: &QueuedBundle,
+        mut descriptor: BundleDescriptor,
+        peers: Vec<EndpointId>,
+        now: u64,
+    ) -> anyhow::Result<bool> {
+        if !peers.is_empty() {
+            // A custodian keeps its copy until the next node accepts custody,
+            // and sends it again if no signal arrives in time
+            if descriptor.bundle.primary.custody_requested() && !forwarder.node_eid.is_null() {
+                self.custody.record(entry.clone(), peers);
+                if let Some(queue) = self.forwarding_queue.lock().unwrap().as_mut() {
+                    queue.requeue_with_backoff(entry.clone(), now + self.custody_timeout);
+                }
+                return Ok(true);
+            }
+            let bundle = descriptor.bundle.clone();
+            self.store_blocking(move |store| store.dispatch(&bundle))
+                .await?;
+            return Ok(true);
+        }
+        if descriptor.is_ready_for_forwarding(self.max_forwarding_attempts) {
+            descriptor.increment_forwarding_attempts();
+            let attempts = descriptor.get_forwarding_attempts();
+            let id = entry.id.clone();
+            self.store_blocking(move |store| store.set_forwarding_attempts(&id, attempts))
+                .await?;
+        }
+
+        if !descriptor.is_ready_for_forwarding(self.max_forwarding_attempts) {
+            self.fail_bundle(&descriptor).await?;
+            return Ok(false);
+        }
+        descriptor.schedule_retry(self.retry_base_delay, self.retry_max_delay, now);
+        // Persisted with the attempt count, so a restart does not retry early
+        let (id, retry_at) = (entry.id.clone(), descriptor.next_retry_at);
+        self.store_blocking(move |store| store.set_next_retry_at(&id, retry_at))
+            .await?;
+        if let Some(queue) = self.forwarding_queue.lock().unwrap().as_mut() {
+            queue.requeue_with_backoff(entry.clone(), descriptor.next_retry_at);
+        }
+        Ok(false)
+    }
+
+    /// Load a queued bundle and, if it may still be forwarded, send it to the peers
+    /// routing selects. Returns the peers it reached, or `None` for bundles that are
+    /// gone, expired or addressed to this node.
+    async fn try_forward(
+        &self,
+        forwarder: &Forwarder,
+        entry: &QueuedBundle,
+    ) -> anyhow::Result<Option<(BundleDescriptor, Vec<EndpointId>)>> {
+        let load_id = entry.id.clone();
+        let loaded = self
+            .store_blocking(move |store| {
+                let bundle = store.load(&load_id)?;
+                Ok((bundle, store.forwarding_attempts(&load_id)?))
+            })
+            .await;
+        let (bundle, attempts) = match loaded {
+            Ok(loaded) => loaded,
+            Err(StoreError::NotFound) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if bundle.is_expired() || self.local_delivery.is_local(&bundle) {
+            return Ok(None);
+        }
+
+        let mut descriptor = BundleDescriptor::new(bundle);
+        descriptor.forwarding_attempts = attempts;
+        let peers = if descriptor.is_ready_for_forwarding(self.max_forwarding_attempts) {
+            forwarder.forward(&descriptor.bundle, None).await
         } else {
-            anyhow::bail!("Failed to lock routing table")
+            Vec::new()
+        };
+        Ok(Some((descriptor, peers)))
+    }
+
+    /// Unix time before which the forwarding loop skips the stored bundle `id`;
+    /// 0 if it is not waiting to be retried
+    pub fn next_retry_at(&self, id: &str) -> u64 {
+        self.forwarding_queue
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|queue| queue.retry_at(id))
+            .unwrap_or_default()
+    }
+
+    /// Give up on an undeliverable bundle and report its deletion
+    async fn fail_bundle(&self, descriptor: &BundleDescriptor) -> anyhow::Result<()> {
+        let bundle = &descriptor.bundle;
+        let failed = bundle.clone();
+        self.store_blocking(move |store| store.fail(&failed))
+            .await?;
+        log::warn!(
+            "Giving up on bundle {} after {} forwarding attempts",
+            bundle.id(),
+            descriptor.get_forwarding_attempts()
+        );
+
+        let report = StatusReport::deletion(bundle, StatusReportReason::NoTimelyContact);
+        if let Some(report_bundle) =
+            report.to_bundle_with_policy(bundle, self.report_priority_policy)?
+        {
+            let queued = report_bundle.clone();
+            self.store_blocking(move |store| store.insert(&report_bundle))
+                .await?;
+            enqueue(&self.forwarding_queue, &queued);
+        }
+        Ok(())
+    }
+
+    /// Run a store operation on the blocking thread pool so async callers never
+    /// block the runtime on disk I/O
+    async fn store_blocking<T, F>(&self, op: F) -> Result<T, StoreError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn BundleStorage) -> Result<T, StoreError> + Send + 'static,
+    {
+        let store = Arc::clone(&self.store);
+        spawn_blocking(move || op(store.as_ref())).await
+    }
+
+    fn forwarder(&self) -> Forwarder {
+        Forwarder {
+            store: Arc::clone(&self.store),
+            node_eid: self.local_delivery.node_eid.clone(),
+            registrations: self.local_delivery.registrations.clone(),
+            routing_algorithm: Arc::clone(&self.routing_algorithm),
+            routing_table: Arc::clone(&self.routing_table),
+            cla_manager: Arc::clone(&self.cla_manager),
+            metrics: Arc::clone(&self.metrics),
+            send_permits: Arc::clone(&self.send_permits),
+        }
+    }
+
+    /// Number of received bundles that were dropped because an identical copy was already stored
+    pub fn duplicates_suppressed(&self) -> u64 {
+        self.metrics.snapshot().duplicates_suppressed
+    }
+
+    /// Current values of the node's counters
+    pub fn metrics_snapshot(&self) -> Metrics {
+        self.metrics.snapshot()
+    }
+
+    /// Serve the node's counters at `GET /metrics` in the Prometheus text format
+    /// until `shutdown` is cancelled
+    #[cfg(feature = "metrics")]
+    pub async fn serve_metrics(
+        &self,
+        bind_addr: &str,
+        shutdown: CancellationToken,
+    ) -> anyhow::Result<()> {
+        let listener = tokio::net::TcpListener::bind(bind_addr).await?;
+        log::info!("Metrics endpoint listening on {bind_addr}");
+        super::metrics::serve_metrics(Arc::clone(&self.metrics), listener, shutdown).await
+    }
+
+    /// Add a route to the routing table, saving the table if it has a routes file
+    pub fn add_route(&self, entry: RouteEntry) -> anyhow::Result<()> {
+        self.routing_table.write().add_route(entry);
+        self.save_routes()
+    }
+
+    /// Remove the routes to `destination` through `next_hop`, returning whether
+    /// there were any; other routes to the same destination are kept
+    pub fn remove_route(
+        &self,
+        destination: &EndpointId,
+        next_hop: &EndpointId,
+    ) -> anyhow::Result<bool> {
+        let removed = self
+            .routing_table
+            .write()
+            .remove_route(destination, next_hop);
+        if removed {
+            self.save_routes()?;
         }
+        Ok(removed)
     }
 
-    /// Get access to the routing table for advanced operations
-    pub fn get_routing_table(&self) -> Arc<Mutex<RoutingTable>> {
+    /// Remove every route from the routing table, returning how many were removed
+    pub fn clear_routes(&self) -> anyhow::Result<usize> {
+        let removed = self.routing_table.write().clear();
+        self.save_routes()?;
+        Ok(removed)
+    }
+
+    /// Get access to the routing table for advanced operations.
+    ///
+    /// The lock does not poison: a panic while it is held leaves the table usable.
+    pub fn get_routing_table(&self) -> Arc<RwLock<RoutingTable>> {
         Arc::clone(&self.routing_table)
     }
 
     /// Get all routes from the routing table
     pub fn get_all_routes(&self) -> anyhow::Result<Vec<RouteEntry>> {
-        if let Ok(table) = self.routing_table.lock() {
-            Ok(table.get_all_routes().into_iter().cloned().collect())
-        } else {
-            anyhow::bail!("Failed to lock routing table")
-        }
+        let table = self.routing_table.read();
+        Ok(table.get_all_routes().into_iter().cloned().collect())
     }
 
-    /// Find the best route for a destination
+    /// Find the best route for a destination; with ECMP enabled, successive calls
+    /// rotate through the routes sharing the lowest cost
     pub fn find_best_route(
         &self,
         destination: &crate::bpv7::EndpointId,
     ) -> anyhow::Result<Option<RouteEntry>> {
-        if let Ok(table) = self.routing_table.lock() {
-            Ok(table.find_best_route(destination).cloned())
-        } else {
-            anyhow::bail!("Failed to lock routing table")
+        Ok(self.routing_table.read().select_route(destination))
+    }
+
+    /// Every usable route for a destination, cheapest first
+    pub fn find_routes_ranked(
+        &self,
+        destination: &crate::bpv7::EndpointId,
+    ) -> anyhow::Result<Vec<RouteEntry>> {
+        Ok(self.routing_table.read().find_routes_ranked(destination))
+    }
+
+    /// Insert a new bundle with the given message, returning its store ID
+    pub async fn insert_bundle(&self, message: String) -> anyhow::Result<String> {
+        self.insert_bundle_with_priority(message, Priority::Normal)
+            .await
+    }
+
+    /// Insert a new bundle with the given message and class of service
+    pub async fn insert_bundle_with_priority(
+        &self,
+        message: String,
+        priority: Priority,
+    ) -> anyhow::Result<String> {
+        self.insert_bundle_with_options(message, priority, None, None)
+            .await
+    }
+
+    /// Insert a new bundle that expires `lifetime_secs` seconds after creation,
+    /// overriding the configured default lifetime
+    pub async fn insert_bundle_with_lifetime(
+        &self,
+        message: String,
+        lifetime_secs: u64,
+    ) -> anyhow::Result<String> {
+        self.insert_bundle_with_options(message, Priority::Normal, Some(lifetime_secs), None)
+            .await
+    }
+
+    /// Insert a new bundle with a gzip-compressed payload regardless of the
+    /// `compress_payloads` setting, optionally overriding the default lifetime
+    pub async fn insert_bundle_compressed(
+        &self,
+        message: String,
+        lifetime_secs: Option<u64>,
+    ) -> anyhow::Result<String> {
+        self.insert_bundle_with_options(message, Priority::Normal, lifetime_secs, Some(true))
+            .await
+    }
+
+    async fn insert_bundle_with_options(
+        &self,
+        message: String,
+        priority: Priority,
+        lifetime: Option<u64>,
+        compress: Option<bool>,
+    ) -> anyhow::Result<String> {
+        let config = Self::insert_config()?;
+        let mut bundle =
+            self.new_local_bundle(&config, message.into_bytes(), priority, lifetime, compress)?;
+
+        // Bundles created in the same second are told apart by their sequence number
+        let (bundle, outcome) = self
+            .store_blocking(move |store| {
+                while store.contains(&bundle) {
+                    bundle.primary.sequence_number += 1;
+                }
+                let outcome = store.insert(&bundle)?;
+                Ok((bundle, outcome))
+            })
+            .await?;
+        if outcome == InsertOutcome::Inserted {
+            self.metrics.record_stored();
+        }
+        let id = bundle.id().digest();
+        enqueue(&self.forwarding_queue, &bundle);
+
+        // Notify routing algorithm about new bundle
+        let descriptor = BundleDescriptor::new(bundle);
+        let mut algorithm = self.routing_algorithm.lock().await;
+        algorithm.notify_new_bundle(&descriptor);
+
+        Ok(id)
+    }
+
+    /// Insert one new bundle per message with a single batched store write,
+    /// returning their store IDs in order
+    pub async fn insert_bundles(&self, messages: Vec<String>) -> anyhow::Result<Vec<String>> {
+        let config = Self::insert_config()?;
+        let mut bundles = messages
+            .into_iter()
+            .map(|message| {
+                self.new_local_bundle(&config, message.into_bytes(), Priority::Normal, None, None)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let (bundles, ids) = self
+            .store_blocking(move |store| {
+                // Sequence numbers must be unique within the batch as well as in the store
+                let mut assigned = HashSet::new();
+                for bundle in &mut bundles {
+                    while assigned.contains(&bundle.id()) || store.contains(bundle) {
+                        bundle.primary.sequence_number += 1;
+                    }
+                    assigned.insert(bundle.id());
+                }
+                let ids = store.insert_batch(&bundles)?;
+                Ok((bundles, ids))
+            })
+            .await?;
+
+        self.notify_stored(bundles).await;
+        Ok(ids)
+    }
+
+    /// Import the file at `path` as a bundle addressed to `destination`, returning
+    /// the store IDs of what was stored. Files larger than the fragment threshold
+    /// are split into fragments, whose IDs are returned in payload order.
+    pub async fn insert_file(
+        &self,
+        path: impl AsRef<Path>,
+        destination: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        let path = path.as_ref();
+        let destination = EndpointId::parse(destination)
+            .map_err(|e| anyhow::anyhow!("Invalid destination '{destination}': {e}"))?;
+        let payload = tokio::fs::read(path)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+
+        let config = Self::insert_config()?;
+        let mut bundle = self.new_local_bundle(&config, payload, Priority::Normal, None, None)?;
+        bundle.primary.destination = destination.to_string();
+        self.check_schemes(&bundle)?;
+        let threshold = usize::try_from(self.fragment_threshold).unwrap_or(usize::MAX);
+
+        let (fragments, ids) = self
+            .store_blocking(move |store| {
+                // Every fragment shares the original's sequence number, so bump it
+                // until none of them collides with a stored bundle
+                let mut fragments = bundle.fragment(threshold);
+                while fragments.iter().any(|fragment| store.contains(fragment)) {
+                    bundle.primary.sequence_number += 1;
+                    fragments = bundle.fragment(threshold);
+                }
+                let ids = store.insert_batch(&fragments)?;
+                Ok((fragments, ids))
+            })
+            .await?;
+        self.notify_stored(fragments).await;
+        Ok(ids)
+    }
+
+    /// Count newly stored bundles and hand them to the routing algorithm
+    async fn notify_stored(&self, bundles: Vec<Bundle>) {
+        let mut algorithm = self.routing_algorithm.lock().await;
+        for bundle in bundles {
+            self.metrics.record_stored();
+            enqueue(&self.forwarding_queue, &bundle);
+            algorithm.notify_new_bundle(&BundleDescriptor::new(bundle));
         }
     }
 
-    /// Insert a new bundle with the given message
-    pub async fn insert_bundle(&self, message: String) -> anyhow::Result<()> {
+    fn insert_config() -> anyhow::Result<Config> {
         #[cfg(test)]
         let config = {
             // In tests, use a slightly different timestamp each time to avoid duplicates
@@ -127,30 +1286,119 @@ impl DtnNode {
         };
         #[cfg(not(test))]
         let config = Config::load()?;
+        Ok(config)
+    }
 
-        let bundle = Bundle {
+    /// A new bundle carrying `payload` from this node's endpoint, or the configured
+    /// source endpoint if the node has none
+    fn new_local_bundle(
+        &self,
+        config: &Config,
+        payload: Vec<u8>,
+        priority: Priority,
+        lifetime: Option<u64>,
+        compress: Option<bool>,
+    ) -> anyhow::Result<Bundle> {
+        let mut bundle = Bundle {
             primary: PrimaryBlock {
                 version: config.bundle.version,
-                destination: config.endpoints.destination,
-                source: config.endpoints.source,
-                report_to: config.endpoints.report_to,
+                destination: config.endpoints.destination.clone(),
+                source: match self.endpoint_id() {
+                    eid if eid.is_null() => config.endpoints.source.clone(),
+                    eid => eid.to_string(),
+                },
+                report_to: config.endpoints.report_to.clone(),
                 creation_timestamp: generate_creation_timestamp(),
-                lifetime: config.bundle.lifetime,
+                sequence_number: 0,
+                lifetime: lifetime.unwrap_or(config.bundle.lifetime),
+                priority,
+                payload_compressed: false,
+                fragment: None,
+                flags: 0,
+                custodian: None,
             },
-            payload: message.into_bytes(),
+            payload,
+            signature: None,
+            previous_node: None,
         };
+        self.check_schemes(&bundle)?;
+        if compress.unwrap_or(config.bundle.compress_payloads) {
+            bundle.compress_payload()?;
+        }
+        Ok(bundle)
+    }
 
-        self.store.insert(&bundle)?;
+    /// Refuse a new bundle whose source or destination is outside the schemes
+    /// set with [`DtnNode::with_allowed_schemes`]
+    fn check_schemes(&self, bundle: &Bundle) -> anyhow::Result<()> {
+        if self.allowed_schemes.is_empty() {
+            return Ok(());
+        }
+        for (field, eid) in [
+            ("source", &bundle.primary.source),
+            ("destination", &bundle.primary.destination),
+        ] {
+            let scheme = eid
+                .split_once(':')
+                .map(|(scheme, _)| scheme.to_ascii_lowercase());
+            if !scheme.is_some_and(|scheme| self.allowed_schemes.contains(&scheme)) {
+                anyhow::bail!(
+                    "Bundle {field} '{eid}' does not use an allowed scheme ({})",
+                    self.allowed_schemes.join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
 
-        // Notify routing algorithm about new bundle
-        let descriptor = BundleDescriptor::new(bundle);
-        let mut algorithm = self.routing_algorithm.lock().await;
-        algorithm.notify_new_bundle(&descriptor);
+    /// Dry-run route selection for `bundle`: every registered peer and routing table
+    /// entry, with the reason it would not be used. Nothing is sent.
+    pub async fn explain_forwarding(&self, bundle: &Bundle) -> ForwardingExplanation {
+        self.explain_forwarding_from(bundle, None).await
+    }
 
-        Ok(())
+    /// Like [`DtnNode::explain_forwarding`] for a bundle received from `received_from`,
+    /// which is treated as already sent to, as in [`DtnNode::forward_bundle`]
+    pub async fn explain_forwarding_from(
+        &self,
+        bundle: &Bundle,
+        received_from: Option<EndpointId>,
+    ) -> ForwardingExplanation {
+        let mut descriptor = BundleDescriptor::new(bundle.clone());
+        if let Some(previous_hop) = received_from {
+            descriptor.mark_sent(previous_hop);
+        }
+
+        let mut peers = Vec::new();
+        let mut reachable = Vec::new();
+        for peer in self.cla_manager.list_all_peers().await {
+            if peer.is_reachable().await {
+                reachable.push(peer);
+            } else {
+                peers.push(PeerDecision {
+                    peer: peer.get_peer_endpoint_id(),
+                    cla_type: peer.get_cla_type().to_string(),
+                    rejection: Some(RejectionReason::Unreachable),
+                });
+            }
+        }
+
+        let algorithm = self.routing_algorithm.lock().await;
+        peers.extend(algorithm.explain_peer_selection(&descriptor, &reachable));
+        let routes = algorithm.explain_route_selection(&descriptor, &self.routing_table.read());
+
+        ForwardingExplanation {
+            bundle_id: bundle.id().to_string(),
+            destination: EndpointId::from(bundle.primary.destination.as_str()),
+            peers,
+            routes,
+        }
     }
 
-    /// Select peers for forwarding a bundle (legacy method)
+    /// Select peers for forwarding a bundle (legacy method).
+    ///
+    /// The selected peers are cloned with [`ClaPeer::clone_box`], so they keep their
+    /// CLA type and connection details.
     pub async fn select_peers_for_forwarding(
         &self,
         bundle: &Bundle,
@@ -161,19 +1409,11 @@ impl DtnNode {
         let peers = self.cla_manager.list_reachable_peers().await;
 
         let algorithm = self.routing_algorithm.lock().await;
-        let selected_refs = algorithm.select_peers_for_forwarding(&descriptor, &peers);
-
-        // Convert references back to owned boxes (this is a bit awkward, but necessary for the trait)
-        let result = selected_refs
+        Ok(algorithm
+            .select_peers_for_forwarding(&descriptor, &peers)
             .into_iter()
-            .map(|peer_ref| {
-                let eid = peer_ref.get_peer_endpoint_id();
-                let address = peer_ref.get_connection_address();
-                Box::new(TcpPeer::new(eid, address)) as Box<dyn ClaPeer>
-            })
-            .collect();
-
-        Ok(result)
+            .map(|peer| peer.clone_box())
+            .collect())
     }
 
     /// Select routes for forwarding a bundle (new method using routing table)
@@ -184,31 +1424,161 @@ impl DtnNode {
         let descriptor = BundleDescriptor::new(bundle.clone());
 
         let algorithm = self.routing_algorithm.lock().await;
-        if let Ok(table) = self.routing_table.lock() {
-            let routes = algorithm.select_routes_for_forwarding(&descriptor, &table);
-            Ok(routes)
-        } else {
-            anyhow::bail!("Failed to lock routing table")
+        let table = self.routing_table.read();
+        Ok(algorithm.select_routes_for_forwarding(&descriptor, &table))
+    }
+
+    /// Select routes for every stored bundle in forwarding order
+    /// (`Expedited` bundles first, then `Normal`, then `Bulk`)
+    pub async fn select_routes_for_stored_bundles(
+        &self,
+    ) -> anyhow::Result<Vec<(String, Vec<RouteEntry>)>> {
+        let mut result = Vec::new();
+        for id in self
+            .store_blocking(|store| store.list_by_priority())
+            .await?
+        {
+            let load_id = id.clone();
+            let bundle = self
+                .store_blocking(move |store| store.load(&load_id))
+                .await?;
+            let routes = self.select_routes_for_forwarding(&bundle).await?;
+            result.push((id, routes));
         }
+        Ok(result)
     }
 
     /// List all bundle IDs
     pub fn list_bundles(&self) -> anyhow::Result<Vec<String>> {
-        self.store.list()
+        Ok(self.store.list()?)
+    }
+
+    /// List metadata for one page of bundles ordered by `sort`
+    pub fn list_bundles_paged(
+        &self,
+        offset: usize,
+        limit: usize,
+        sort: SortKey,
+    ) -> anyhow::Result<Vec<BundleMeta>> {
+        Ok(self.store.list_meta(offset, limit, sort)?)
+    }
+
+    /// IDs of the stored bundles matching `filter`
+    pub fn query_bundles(&self, filter: BundleFilter) -> anyhow::Result<Vec<String>> {
+        Ok(self.store.find(&filter)?)
+    }
+
+    /// IDs of the stored bundles whose lifetime has elapsed; only headers are read
+    pub fn list_expired_bundles(&self) -> anyhow::Result<Vec<String>> {
+        self.query_bundles(BundleFilter::new().with_expired(true))
+    }
+
+    /// IDs of the stored bundles still within their lifetime; only headers are read
+    pub fn list_active_bundles(&self) -> anyhow::Result<Vec<String>> {
+        self.query_bundles(BundleFilter::new().with_expired(false))
+    }
+
+    /// IDs of the active bundles that expire within `window`; only headers are read
+    pub fn list_expiring_soon(&self, window: Duration) -> anyhow::Result<Vec<String>> {
+        self.query_bundles(BundleFilter::new().with_expiring_within(window.as_secs()))
     }
 
     /// Show bundle details by partial ID
     pub fn show_bundle(&self, partial_id: &str) -> anyhow::Result<Bundle> {
-        self.store.load_by_partial_id(partial_id)
+        let mut bundle = self.store.load_by_partial_id(partial_id)?;
+        bundle.decompress_payload()?;
+        Ok(bundle)
+    }
+
+    /// Time a received bundle took to get here: its arrival time minus its creation
+    /// timestamp. `None` for bundles inserted locally rather than received, and for
+    /// bundles that claim to be created after they arrived.
+    ///
+    /// Both times are wall-clock readings from different nodes, so the result is only
+    /// as accurate as their clock synchronisation. A Bundle Age block would avoid that,
+    /// but this node does not carry one yet.
+    pub fn bundle_latency(&self, partial_id: &str) -> Option<Duration> {
+        let id = self.store.find_by_partial_id(partial_id)?;
+        let arrived_at = self.store.arrival_time(&id).ok()??;
+        let bundle = self.store.load(&id).ok()?;
+        let created_at = UNIX_EPOCH + Duration::from_secs(bundle.primary.creation_timestamp);
+        arrived_at.duration_since(created_at).ok()
+    }
+
+    /// Encode a stored bundle for offline analysis. `Cbor` and `JsonPretty` export
+    /// the bundle exactly as stored; `RawPayload` exports the decompressed payload.
+    pub fn export_bundle(&self, partial_id: &str, format: ExportFormat) -> anyhow::Result<Vec<u8>> {
+        let mut bundle = self.store.load_by_partial_id(partial_id)?;
+        match format {
+            ExportFormat::Cbor => bundle.to_canonical_cbor(),
+            ExportFormat::JsonPretty => Ok(serde_json::to_vec_pretty(&bundle)?),
+            ExportFormat::RawPayload => {
+                bundle.decompress_payload()?;
+                Ok(bundle.payload)
+            }
+        }
+    }
+
+    /// Delete a bundle by partial ID
+    pub fn delete_bundle(&self, partial_id: &str) -> anyhow::Result<()> {
+        match self.store.find_by_partial_id(partial_id) {
+            Some(full_id) => Ok(self.store.remove(&full_id)?),
+            None => Err(StoreError::NotFound.into()),
+        }
+    }
+
+    /// Move a delivered bundle out of the active store into its dispatched directory
+    pub fn dispatch_bundle(&self, partial_id: &str) -> anyhow::Result<()> {
+        self.dispatch_bundle_via(partial_id, None)
+    }
+
+    /// Like [`DtnNode::dispatch_bundle`], recording `next_hop` as the peer the bundle
+    /// was sent to in the forwarding history
+    pub fn dispatch_bundle_via(
+        &self,
+        partial_id: &str,
+        next_hop: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let bundle = self.store.load_by_partial_id(partial_id)?;
+        self.store.dispatch(&bundle)?;
+        self.store
+            .record_history(&HistoryEntry::sent(&bundle, next_hop))?;
+        self.metrics.record_forwarded();
+        Ok(())
+    }
+
+    /// Record in the forwarding history that sending a stored bundle to `next_hop` failed
+    pub fn record_send_failure(
+        &self,
+        partial_id: &str,
+        next_hop: &str,
+        error: &anyhow::Error,
+    ) -> anyhow::Result<()> {
+        let bundle = self.store.load_by_partial_id(partial_id)?;
+        self.store
+            .record_history(&HistoryEntry::failed(&bundle, next_hop, error))?;
+        Ok(())
+    }
+
+    /// Forwarding history of this node's store, oldest entry first
+    pub fn forwarding_history(&self) -> anyhow::Result<Vec<HistoryEntry>> {
+        Ok(self.store.history()?)
+    }
+
+    /// Bundle count, bytes, age and expiry of this node's store
+    pub fn store_stats(&self) -> anyhow::Result<StoreStats> {
+        Ok(self.store.stats()?)
     }
 
     /// Get bundle status information
     pub fn get_bundle_status(&self, partial_id: Option<&str>) -> anyhow::Result<BundleStatus> {
         match partial_id {
             Some(id) => {
-                let bundle = self.store.load_by_partial_id(id)?;
+                let bundle = self.show_bundle(id)?;
                 Ok(BundleStatus::Single {
                     id: id.to_string(),
+                    remaining_lifetime: bundle.remaining_lifetime(),
+                    latency: self.bundle_latency(id),
                     bundle,
                 })
             }
@@ -216,6 +1586,7 @@ impl DtnNode {
                 let bundles = self.store.list()?;
                 let mut active_count = 0;
                 let mut expired_count = 0;
+                let mut expiring_soon_count = 0;
 
                 for id in &bundles {
                     if let Ok(bundle) = self.store.load_by_partial_id(id) {
@@ -223,6 +1594,9 @@ impl DtnNode {
                             expired_count += 1;
                         } else {
                             active_count += 1;
+                            if bundle.remaining_lifetime() <= self.expiring_soon_window {
+                                expiring_soon_count += 1;
+                            }
                         }
                     }
                 }
@@ -230,48 +1604,320 @@ impl DtnNode {
                 Ok(BundleStatus::Summary {
                     active: active_count,
                     expired: expired_count,
+                    expiring_soon: expiring_soon_count,
                     total: active_count + expired_count,
                 })
             }
         }
     }
 
-    /// Clean up expired bundles
-    pub fn cleanup_expired(&self) -> anyhow::Result<()> {
-        self.store.cleanup_expired()
+    /// Clean up expired bundles, returning how many were removed
+    pub fn cleanup_expired(&self) -> anyhow::Result<usize> {
+        let removed = self.store.cleanup_expired()?;
+        self.metrics.record_expired(removed as u64);
+        Ok(removed)
+    }
+
+    /// Periodically remove expired bundles every `interval` until the returned handle is aborted
+    pub fn spawn_cleanup_task(&self, interval: Duration) -> JoinHandle<()> {
+        self.spawn_cleanup_task_with_shutdown(interval, CancellationToken::new())
+    }
+
+    /// Periodically remove expired bundles every `interval` until `shutdown` is cancelled.
+    ///
+    /// Each pass runs on the blocking pool; bundles removed concurrently by the
+    /// forwarding or receive path are skipped by the store. Each pass also logs a
+    /// warning the first time it finds a bundle within the expiring-soon window
+    /// and counts it in the node's metrics.
+    pub fn spawn_cleanup_task_with_shutdown(
+        &self,
+        interval: Duration,
+        shutdown: CancellationToken,
+    ) -> JoinHandle<()> {
+        let store = Arc::clone(&self.store);
+        let metrics = Arc::clone(&self.metrics);
+        let expiring_soon_window = self.expiring_soon_window;
+        let reassembly = self.reassembly.clone();
+        tokio::spawn(async move {
+            let mut warned = HashSet::new();
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = ticker.tick() => {}
+                }
+
+                let cleanup_store = Arc::clone(&store);
+                match tokio::task::spawn_blocking(move || cleanup_store.cleanup_expired()).await {
+                    Ok(Ok(0)) => {}
+                    Ok(Ok(removed)) => {
+                        metrics.record_expired(removed as u64);
+                        log::info!("Cleanup task removed {removed} expired bundles");
+                    }
+                    Ok(Err(e)) => log::warn!("Cleanup task failed: {e}"),
+                    Err(e) => log::error!("Cleanup task panicked: {e}"),
+                }
+
+                let expiring_store = Arc::clone(&store);
+                let filter = BundleFilter::new().with_expiring_within(expiring_soon_window);
+                match spawn_blocking(move || expiring_store.find(&filter)).await {
+                    Ok(expiring) => {
+                        warn_expiring(expiring, &mut warned, expiring_soon_window, &metrics)
+                    }
+                    Err(e) => log::warn!("Failed to check for bundles near expiry: {e}"),
+                }
+
+                if let Some(reassembly) = &reassembly {
+                    for expired in reassembly.lock().unwrap().discard_expired() {
+                        log::warn!("Discarding incomplete fragments of bundle {expired}");
+                    }
+                }
+            }
+        })
+    }
+
+    /// Receive path shared by every listener: signature check, mailboxes, store,
+    /// local delivery and, in relay mode, onward forwarding. The returned status is
+    /// the ACK a TCP listener sends back.
+    fn receive_callback(&self) -> ReceiveCallback {
+        let store = Arc::clone(&self.store);
+        let metrics = Arc::clone(&self.metrics);
+        let local_delivery = self.local_delivery.clone();
+        let signature_policy = self.signature_policy.clone();
+        let mailboxes = self.mailboxes.clone();
+        let incoming = self.incoming.clone();
+        let seen = Arc::clone(&self.seen);
+        let forwarding_queue = Arc::clone(&self.forwarding_queue);
+        let reassembly = self.reassembly.clone();
+        let node_eid = self.local_delivery.node_eid.clone();
+        let report_priority_policy = self.report_priority_policy;
+        let custody = self.custody.clone();
+
+        // In relay mode newly stored bundles are queued for the forwarding loop,
+        // which runs until the listener and its connections drop the sender
+        let relay_tx = if self.relay_mode {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Bundle>();
+            let forwarder = self.forwarder();
+            tokio::spawn(async move {
+                while let Some(bundle) = rx.recv().await {
+                    // Without a previous node block the CLAs carry no sender
+                    // endpoint, so the bundle's source is treated as the hop it
+                    // arrived from
+                    let previous_hop = bundle
+                        .previous_node
+                        .clone()
+                        .unwrap_or_else(|| EndpointId::from(bundle.primary.source.as_str()));
+                    let delivered = forwarder.forward(&bundle, Some(previous_hop)).await;
+                    log::info!(
+                        "Relayed bundle {} to {} peers",
+                        bundle.id(),
+                        delivered.len()
+                    );
+                }
+            });
+            Some(tx)
+        } else {
+            None
+        };
+
+        Arc::new(move |bundle| {
+            let arrived_at = SystemTime::now();
+            if let Err(e) = bundle.validate() {
+                metrics.record_receive_failure();
+                log::warn!("Dropping malformed bundle {}: {e}", bundle.id());
+                return AckStatus::Bad;
+            }
+            if !signature_policy.accepts(&bundle) {
+                metrics.record_receive_failure();
+                log::warn!("Dropping bundle {} without a valid signature", bundle.id());
+                return AckStatus::Bad;
+            }
+            // A late copy of a bundle that was already forwarded and deleted is
+            // no longer in the store, so only the seen set recognises it
+            let id = bundle.id();
+            let (bundle, id) = match (&reassembly, bundle.primary.fragment) {
+                // Fragments stay out of the seen set: if their set is discarded or
+                // lost in a restart, resent copies must still be taken in
+                (Some(reassembly), Some(_)) => {
+                    let original = BundleId {
+                        fragment_offset: None,
+                        ..id.clone()
+                    };
+                    if seen.contains(&original) {
+                        metrics.record_duplicate();
+                        log::debug!("Dropping fragment {id} of already seen bundle");
+                        return AckStatus::Ok;
+                    }
+                    let mut reassembly = reassembly.lock().unwrap();
+                    for expired in reassembly.discard_expired() {
+                        log::warn!("Discarding incomplete fragments of bundle {expired}");
+                    }
+                    match reassembly.insert(bundle) {
+                        Ok(Some(whole)) => {
+                            let id = whole.id();
+                            if seen.check_and_insert(&id) {
+                                metrics.record_duplicate();
+                                log::debug!("Dropping already seen bundle {id}");
+                                return AckStatus::Ok;
+                            }
+                            (whole, id)
+                        }
+                        Ok(None) => return AckStatus::Ok,
+                        Err(e) => {
+                            metrics.record_receive_failure();
+                            log::warn!("Failed to reassemble bundle {id}: {e}");
+                            return AckStatus::Bad;
+                        }
+                    }
+                }
+                _ => {
+                    if seen.check_and_insert(&id) {
+                        metrics.record_duplicate();
+                        log::debug!("Dropping already seen bundle {id}");
+                        return AckStatus::Ok;
+                    }
+                    (bundle, id)
+                }
+            };
+            if bundle.primary.is_admin_record() && local_delivery.is_local(&bundle) {
+                if let Ok(signal) = CustodySignal::from_bundle(&bundle) {
+                    let from = EndpointId::from(bundle.primary.source.as_str());
+                    handle_custody_signal(
+                        store.as_ref(),
+                        &custody,
+                        &forwarding_queue,
+                        &signal,
+                        &from,
+                    );
+                    return AckStatus::Ok;
+                }
+            }
+            // Only a node with its own endpoint can take custody
+            let custodian =
+                (bundle.primary.custody_requested() && !node_eid.is_null()).then_some(&node_eid);
+            if mailboxes.route(&bundle) {
+                metrics.record_delivered();
+                incoming.publish(&bundle);
+                return AckStatus::Ok;
+            }
+            // バンドル受信時の保存処理 (the listener runs this on the blocking pool)
+            match store.insert(&bundle) {
+                Ok(InsertOutcome::AlreadyPresent) => metrics.record_duplicate(),
+                Ok(InsertOutcome::Inserted) => {
+                    metrics.record_stored();
+                    if let Err(e) = store.set_arrival_time(&id.digest(), arrived_at) {
+                        log::warn!("Failed to record arrival time of bundle {id}: {e}");
+                    }
+                    if let Some(node_eid) = custodian {
+                        let signal = CustodySignal::acceptance(&bundle);
+                        send_custody_signal(
+                            store.as_ref(),
+                            &forwarding_queue,
+                            node_eid,
+                            report_priority_policy,
+                            &bundle,
+                            signal,
+                        );
+                    }
+                    incoming.publish(&bundle);
+                    if local_delivery.try_deliver(&bundle, store.as_ref()) {
+                        metrics.record_delivered();
+                        return AckStatus::Ok;
+                    }
+                    enqueue(&forwarding_queue, &bundle);
+                    if let Some(tx) = &relay_tx {
+                        let _ = tx.send(bundle);
+                    }
+                }
+                Err(e) => {
+                    // Let the sender's retransmission through once there is room
+                    seen.forget(&id);
+                    metrics.record_receive_failure();
+                    log::error!("Failed to store received bundle: {e}");
+                    let full = matches!(
+                        e,
+                        StoreError::CapacityExceeded { .. } | StoreError::QuotaExceeded { .. }
+                    );
+                    if let (Some(node_eid), true) = (custodian, full) {
+                        let signal =
+                            CustodySignal::refusal(&bundle, StatusReportReason::DepletedStorage);
+                        send_custody_signal(
+                            store.as_ref(),
+                            &forwarding_queue,
+                            node_eid,
+                            report_priority_policy,
+                            &bundle,
+                            signal,
+                        );
+                    }
+                    return match e {
+                        StoreError::CapacityExceeded { .. } | StoreError::QuotaExceeded { .. } => {
+                            AckStatus::Full
+                        }
+                        _ => AckStatus::Error,
+                    };
+                }
+            }
+            AckStatus::Ok
+        })
+    }
+
+    /// Run `bundle` through the receive path as if a listener had accepted it,
+    /// returning the ACK the listener would send
+    #[cfg(any(test, feature = "testkit"))]
+    pub fn receive_bundle(&self, bundle: Bundle) -> AckStatus {
+        (self.receive_callback())(bundle)
     }
 
     /// Start a TCP listener daemon
     pub async fn start_tcp_listener(&self, bind_addr: String) -> anyhow::Result<()> {
-        let store_path = self.store_path.clone();
-        let cla = Arc::new(crate::cla::TcpClaListener {
-            bind_addr: bind_addr.clone(),
-            receive_callback: Arc::new(move |bundle| {
-                // バンドル受信時の保存処理
-                if let Ok(store) = BundleStore::new(&store_path) {
-                    let _ = store.insert(&bundle);
-                }
-            }),
-        });
+        self.start_tcp_listener_with_shutdown(bind_addr, CancellationToken::new())
+            .await
+    }
+
+    /// Start a TCP listener daemon that stops accepting connections once `shutdown` is cancelled
+    pub async fn start_tcp_listener_with_shutdown(
+        &self,
+        bind_addr: String,
+        shutdown: CancellationToken,
+    ) -> anyhow::Result<()> {
+        let mut listener = crate::cla::TcpClaListener::with_status_callback(
+            bind_addr.clone(),
+            self.receive_callback(),
+        );
+        if let Some(contact_header) = self.contact_header() {
+            listener = listener.with_contact_header(contact_header);
+        }
+        if self.summary_vectors {
+            let store = Arc::clone(&self.store);
+            listener =
+                listener.with_summary_vector(Arc::new(move || held_summary_vector(store.as_ref())));
+        }
+        let cla = Arc::new(listener);
 
         // CLAマネージャにピア登録（必要なら）
         let manager = ClaManager::new(|bundle| {
-            println!("📥 Received: {bundle:?}");
+            log::debug!("Received: {bundle:?}");
         });
-        let peer: Box<dyn ClaPeer> =
-            Box::new(TcpPeer::new(EndpointId::from("dtn://listener"), bind_addr));
+        let listener_eid = match self.endpoint_id() {
+            eid if eid.is_null() => EndpointId::from("dtn://listener"),
+            eid => eid.clone(),
+        };
+        let peer: Box<dyn ClaPeer> = Box::new(TcpPeer::new(listener_eid, bind_addr));
         manager.register_peer(peer).await;
 
         // CLAリスナーを起動
-        cla.activate().await?;
+        cla.activate_until(shutdown).await?;
 
-        Ok(())
+        self.save_routing_state().await?;
+        self.save_seen_bundles().await
     }
 
     /// Start a TCP dialer daemon
     pub async fn start_tcp_dialer(&self, target_addr: String) -> anyhow::Result<()> {
         let manager = ClaManager::new(|bundle| {
-            println!("📤 Should not receive here (Dialer): {bundle:?}");
+            log::warn!("Should not receive here (Dialer): {bundle:?}");
         });
 
         let peer: Box<dyn ClaPeer> =
@@ -281,6 +1927,97 @@ impl DtnNode {
         Ok(())
     }
 
+    /// Start every convergence layer from the `[[cla]]` config: dialers are registered
+    /// as peers of the node's CLA manager and each listener runs in its own task until
+    /// `shutdown` is cancelled
+    pub async fn start_from_config(
+        &self,
+        shutdown: CancellationToken,
+    ) -> anyhow::Result<Vec<JoinHandle<anyhow::Result<()>>>> {
+        self.start_clas(&DefaultClaFactory, &self.cla_configs, shutdown)
+            .await
+    }
+
+    /// Start the convergence layers in `configs`, building them with `factory`.
+    /// Nothing is started if any entry cannot be built.
+    pub async fn start_clas(
+        &self,
+        factory: &dyn ClaFactory,
+        configs: &[ClaConfig],
+        shutdown: CancellationToken,
+    ) -> anyhow::Result<Vec<JoinHandle<anyhow::Result<()>>>> {
+        let receive_callback = self.receive_callback();
+        let mut listeners = Vec::new();
+        let mut dialers = Vec::new();
+        for config in configs {
+            let config = self.effective_cla_config(config);
+            match config.role {
+                ClaRole::Listener => {
+                    listeners.push(factory.create_listener(&config, Arc::clone(&receive_callback))?)
+                }
+                ClaRole::Dialer => dialers.push((factory.create_dialer(&config)?, config.max_bps)),
+            }
+        }
+
+        for (peer, max_bps) in dialers {
+            self.register_dialer(peer, max_bps).await;
+        }
+
+        Ok(listeners
+            .into_iter()
+            .map(|listener| {
+                log::info!(
+                    "Starting {} listener on {}",
+                    listener.cla_type(),
+                    listener.address()
+                );
+                let shutdown = shutdown.clone();
+                tokio::spawn(async move { listener.run(shutdown).await })
+            })
+            .collect())
+    }
+
+    /// Register the dialers listed under [[cla]] in the config as peers without
+    /// starting any listener, returning how many were registered
+    pub async fn register_configured_peers(&self) -> anyhow::Result<usize> {
+        let mut dialers = Vec::new();
+        for config in &self.cla_configs {
+            let config = self.effective_cla_config(config);
+            if config.role == ClaRole::Dialer {
+                dialers.push((DefaultClaFactory.create_dialer(&config)?, config.max_bps));
+            }
+        }
+        let count = dialers.len();
+        for (peer, max_bps) in dialers {
+            self.register_dialer(peer, max_bps).await;
+        }
+        Ok(count)
+    }
+
+    /// A CLA entry with the node-wide settings applied: TCPCL v4 covers every TCP entry
+    fn effective_cla_config(&self, config: &ClaConfig) -> ClaConfig {
+        let mut config = config.clone();
+        config.tcpcl_v4 |= self.tcpcl_v4 && config.cla_type == ClaType::Tcp;
+        config
+    }
+
+    async fn register_dialer(&self, peer: Box<dyn ClaPeer>, max_bps: Option<u64>) {
+        let peer_id = peer.get_peer_endpoint_id();
+        log::info!(
+            "Registering {} peer {} at {}",
+            peer.get_cla_type(),
+            peer_id,
+            peer.get_connection_address()
+        );
+        self.cla_manager.register_peer(peer).await;
+        if let Some(max_bps) = max_bps.filter(|&bps| bps > 0) {
+            log::info!("Limiting sends to {peer_id} to {max_bps} bytes/s");
+            self.cla_manager
+                .set_rate_limit(peer_id, RateLimiter::new(max_bps))
+                .await;
+        }
+    }
+
     /// Select peers for forwarding a bundle with connectivity check (async version)
     pub async fn select_peers_for_forwarding_async(
         &self,
@@ -292,21 +2029,128 @@ impl DtnNode {
         let peers = self.cla_manager.list_reachable_peers().await;
 
         let algorithm = self.routing_algorithm.lock().await;
-        let selected_refs = algorithm
+        Ok(algorithm
             .select_peers_for_forwarding_async(&descriptor, &peers)
-            .await;
-
-        // Convert references back to owned boxes
-        let result = selected_refs
+            .await
             .into_iter()
-            .map(|peer_ref| {
-                let eid = peer_ref.get_peer_endpoint_id();
-                let address = peer_ref.get_connection_address();
-                Box::new(TcpPeer::new(eid, address)) as Box<dyn ClaPeer>
-            })
-            .collect();
+            .map(|peer| peer.clone_box())
+            .collect())
+    }
+}
 
-        Ok(result)
+/// Warn about each bundle in `expiring` that was not in the previous pass. Bundles
+/// that have since left the set (sent, removed or expired) are forgotten.
+fn warn_expiring(
+    expiring: Vec<String>,
+    warned: &mut HashSet<String>,
+    window: u64,
+    metrics: &NodeMetrics,
+) {
+    let expiring: HashSet<String> = expiring.into_iter().collect();
+    for id in expiring.difference(warned) {
+        metrics.record_expiry_warning();
+        log::warn!("Bundle {id} expires in less than {window}s");
+    }
+    *warned = expiring;
+}
+
+/// Add a newly stored bundle to the forwarding queue. Until the first forwarding
+/// pass builds the queue there is nothing to add to; that pass picks the bundle up
+/// from the store.
+fn enqueue(queue: &Mutex<Option<ForwardingQueue>>, bundle: &Bundle) {
+    if let Some(queue) = queue.lock().unwrap().as_mut() {
+        queue.push(QueuedBundle::new(bundle));
+    }
+}
+
+/// Store `signal` about `subject` and queue it for the subject's previous custodian
+fn send_custody_signal(
+    store: &dyn BundleStorage,
+    queue: &Mutex<Option<ForwardingQueue>>,
+    node_eid: &EndpointId,
+    policy: ReportPriorityPolicy,
+    subject: &Bundle,
+    signal: CustodySignal,
+) {
+    let signal_bundle = match signal.to_bundle(subject, node_eid, policy) {
+        Ok(Some(signal_bundle)) => signal_bundle,
+        Ok(None) => return,
+        Err(e) => {
+            log::warn!("Failed to encode custody signal for {}: {e}", subject.id());
+            return;
+        }
+    };
+    match store.insert(&signal_bundle) {
+        Ok(_) => enqueue(queue, &signal_bundle),
+        Err(e) => log::warn!("Failed to store custody signal for {}: {e}", subject.id()),
+    }
+}
+
+/// Act on a custody signal from `from` about a bundle this node forwarded to it as
+/// custodian: once the next node has accepted custody the local copy is released,
+/// while a refused bundle is queued to be forwarded again right away. Signals from
+/// nodes the bundle was not sent to are ignored.
+fn handle_custody_signal(
+    store: &dyn BundleStorage,
+    custody: &CustodyTracker,
+    queue: &Mutex<Option<ForwardingQueue>>,
+    signal: &CustodySignal,
+    from: &EndpointId,
+) {
+    let digest = match signal.subject.parse::<BundleId>() {
+        Ok(id) => id.digest(),
+        Err(e) => {
+            log::warn!("Ignoring custody signal: {e}");
+            return;
+        }
+    };
+    let Some(entry) = custody.take(&digest, from) else {
+        log::warn!(
+            "Ignoring custody signal from {from} about bundle {}, which was not sent there",
+            signal.subject
+        );
+        return;
+    };
+    if !signal.accepted {
+        log::info!(
+            "Custody of bundle {} was refused ({:?}), forwarding it again",
+            signal.subject,
+            signal.reason
+        );
+        if let Some(queue) = queue.lock().unwrap().as_mut() {
+            queue.reschedule(entry, 0);
+        }
+        return;
+    }
+    match store.remove(&digest) {
+        Ok(()) => log::info!("Custody of bundle {} accepted, released it", signal.subject),
+        Err(StoreError::NotFound) => {}
+        Err(e) => log::warn!("Failed to release bundle {}: {e}", signal.subject),
+    }
+}
+
+/// Summary vector of the bundles in the store, built from the store keys alone
+fn held_summary_vector(store: &dyn BundleStorage) -> SummaryVector {
+    match store.list() {
+        Ok(ids) => SummaryVector::from_digests(ids.iter().map(String::as_str)),
+        Err(e) => {
+            log::warn!("Failed to list bundles for the summary vector: {e}");
+            SummaryVector::default()
+        }
+    }
+}
+
+fn held_bundle_ids(store: &dyn BundleStorage) -> Vec<BundleId> {
+    match store.list() {
+        Ok(ids) => ids
+            .iter()
+            .filter_map(|id| store.load(id).ok())
+            .map(|bundle| bundle.id())
+            .collect(),
+        Err(e) => {
+            log::warn!("Failed to list bundles for the summary vector: {e}");
+            Vec::new()
+        }
     }
 }
 
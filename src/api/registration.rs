@@ -0,0 +1,36 @@
+use crate::bpv7::EndpointId;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+/// Endpoints served by this node, shared with its receive and forwarding paths.
+/// Bundles addressed to a registered endpoint are delivered locally, and no
+/// registered endpoint is ever chosen as a next hop.
+#[derive(Clone, Default)]
+pub(crate) struct Registrations {
+    endpoints: Arc<Mutex<HashSet<EndpointId>>>,
+}
+
+impl Registrations {
+    /// Register `eid`, returning whether it was not registered before.
+    /// The null endpoint is never registered.
+    pub(crate) fn register(&self, eid: EndpointId) -> bool {
+        !eid.is_null() && self.endpoints.lock().unwrap().insert(eid)
+    }
+
+    /// Unregister `eid`, returning whether it was registered
+    pub(crate) fn unregister(&self, eid: &EndpointId) -> bool {
+        self.endpoints.lock().unwrap().remove(eid)
+    }
+
+    pub(crate) fn is_registered(&self, eid: &EndpointId) -> bool {
+        self.endpoints.lock().unwrap().contains(eid)
+    }
+
+    /// Registered endpoints, sorted by their normalized form
+    pub(crate) fn list(&self) -> Vec<EndpointId> {
+        let mut endpoints: Vec<EndpointId> =
+            self.endpoints.lock().unwrap().iter().cloned().collect();
+        endpoints.sort_by_key(|eid| eid.normalized().as_str().to_string());
+        endpoints
+    }
+}
@@ -1,4 +1,5 @@
 use crate::api::convenience::{insert_bundle_quick, list_bundles_quick, show_bundle_quick};
+use crate::testkit::MockCla;
 use std::env;
 use tempfile::TempDir;
 
@@ -119,9 +120,11 @@ async fn test_convenience_functions_unicode() -> anyhow::Result<()> {
     Ok(())
 }
 
-use crate::api::{node::DtnNode, BundleStatus};
+use crate::api::{node::DtnNode, BundleStatus, ExportFormat};
+use crate::bpv7::bundle::{Bundle, Priority};
 use crate::bpv7::EndpointId;
 use crate::routing::algorithm::{RouteEntry, RoutingAlgorithmType, RoutingConfig};
+use std::sync::Arc;
 
 #[tokio::test]
 async fn test_dtn_node_new() -> anyhow::Result<()> {
@@ -207,6 +210,376 @@ async fn test_show_bundle() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_delete_bundle() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+
+    node.insert_bundle("Keep me".to_string()).await?;
+    node.insert_bundle("Delete me".to_string()).await?;
+    assert_eq!(node.list_bundles()?.len(), 2);
+
+    let bundle_id = node.list_bundles()?.into_iter().next().unwrap();
+    node.delete_bundle(&bundle_id[..8])?;
+
+    let remaining = node.list_bundles()?;
+    assert_eq!(remaining.len(), 1);
+    assert!(!remaining.contains(&bundle_id));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_bundle_nonexistent() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+
+    let result = node.delete_bundle("nonexistent");
+    let error = result.unwrap_err();
+    assert!(error.to_string().contains("Bundle ID not found"));
+    assert!(matches!(
+        error.downcast_ref::<crate::store::StoreError>(),
+        Some(crate::store::StoreError::NotFound)
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_insert_bundle_with_priority() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+
+    node.insert_bundle_with_priority("urgent".to_string(), Priority::Expedited)
+        .await?;
+
+    let bundles = node.list_bundles()?;
+    let bundle = node.show_bundle(&bundles[0])?;
+    assert_eq!(bundle.primary.priority, Priority::Expedited);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_start_tcp_listener_with_shutdown() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let handle = tokio::spawn(async move {
+        node.start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    shutdown.cancel();
+
+    let result = tokio::time::timeout(std::time::Duration::from_secs(5), handle).await;
+    assert!(result.is_ok(), "node listener did not shut down");
+    result???;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_receive_duplicate_bundles_suppressed() -> anyhow::Result<()> {
+    use crate::cla::tcp::client::send_bundle;
+
+    let temp_dir = TempDir::new()?;
+    let node = Arc::new(DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?);
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let listener_node = Arc::clone(&node);
+    let handle = tokio::spawn(async move {
+        listener_node
+            .start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = tokio::net::TcpStream::connect(addr).await {
+            stream = Some(s);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let mut stream = stream.expect("listener did not start");
+
+    let bundle = Bundle::new("dtn://neighbor", "dtn://dest", b"flooded".to_vec());
+    send_bundle(&mut stream, &bundle).await?;
+    send_bundle(&mut stream, &bundle).await?;
+    send_bundle(&mut stream, &bundle).await?;
+
+    assert_eq!(node.list_bundles()?.len(), 1);
+    assert_eq!(node.duplicates_suppressed(), 2);
+
+    shutdown.cancel();
+    handle.await??;
+    Ok(())
+}
+
+#[test]
+fn test_receive_rejects_invalid_bundles() {
+    use crate::cla::tcp::ack::AckStatus;
+    use crate::store::MemoryBundleStore;
+
+    let node = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    );
+    let mut bundle = Bundle::new("dtn://neighbor", "dtn://dest", b"forever".to_vec());
+    bundle.primary.lifetime = 0;
+
+    assert_eq!(node.receive_bundle(bundle), AckStatus::Bad);
+    assert!(node.list_bundles().unwrap().is_empty());
+
+    let bundle = Bundle::new("dtn://neighbor", "dtn://dest", b"fine".to_vec());
+    assert_eq!(node.receive_bundle(bundle), AckStatus::Ok);
+    assert_eq!(node.list_bundles().unwrap().len(), 1);
+}
+
+#[test]
+fn test_receive_reassembles_fragments() {
+    use crate::cla::tcp::ack::AckStatus;
+    use crate::store::MemoryBundleStore;
+
+    let node = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_reassembly_timeout(std::time::Duration::from_secs(60));
+    let payload: Vec<u8> = (0..250u32).map(|i| (i * 3) as u8).collect();
+    let bundle = Bundle::new("dtn://neighbor", "dtn://dest", payload);
+    let mut fragments = bundle.fragment(100);
+    assert_eq!(fragments.len(), 3);
+    fragments.reverse();
+
+    for fragment in fragments {
+        assert_eq!(node.receive_bundle(fragment), AckStatus::Ok);
+    }
+
+    let ids = node.list_bundles().unwrap();
+    assert_eq!(ids.len(), 1);
+    let stored = node.show_bundle(&ids[0]).unwrap();
+    assert!(!stored.is_fragment());
+    assert!(stored.content_eq(&bundle));
+}
+
+#[test]
+fn test_receive_takes_resent_fragments_after_discard() {
+    use crate::cla::tcp::ack::AckStatus;
+    use crate::clock::MockClock;
+    use crate::store::MemoryBundleStore;
+
+    let clock = MockClock::starting_now();
+    let _guard = clock.install();
+    let node = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_reassembly_timeout(std::time::Duration::from_secs(60));
+    let bundle = Bundle::new(
+        "dtn://neighbor",
+        "dtn://dest",
+        (0..250u32).map(|i| i as u8).collect(),
+    );
+    let fragments = bundle.fragment(100);
+
+    // The first attempt stalls and its set is discarded
+    assert_eq!(node.receive_bundle(fragments[0].clone()), AckStatus::Ok);
+    clock.advance(std::time::Duration::from_secs(61));
+
+    // The sender's retransmission is not mistaken for a duplicate
+    for fragment in &fragments {
+        assert_eq!(node.receive_bundle(fragment.clone()), AckStatus::Ok);
+    }
+    let ids = node.list_bundles().unwrap();
+    assert_eq!(ids.len(), 1);
+    assert!(node.show_bundle(&ids[0]).unwrap().content_eq(&bundle));
+
+    // Once reassembled, late copies of its fragments are duplicates
+    assert_eq!(node.receive_bundle(fragments[1].clone()), AckStatus::Ok);
+    assert_eq!(node.duplicates_suppressed(), 1);
+}
+
+#[tokio::test]
+async fn test_receive_suppresses_bundles_seen_before_deletion() -> anyhow::Result<()> {
+    use crate::cla::tcp::client::send_bundle;
+    use crate::consts::SEEN_BUNDLES_FILE;
+    use crate::store::SeenBundles;
+
+    let temp_dir = TempDir::new()?;
+    let node = Arc::new(DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?);
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let listener_node = Arc::clone(&node);
+    let handle = tokio::spawn(async move {
+        listener_node
+            .start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = tokio::net::TcpStream::connect(addr).await {
+            stream = Some(s);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let mut stream = stream.expect("listener did not start");
+
+    let forwarded = Bundle::new("dtn://neighbor", "dtn://dest", b"forwarded".to_vec());
+    send_bundle(&mut stream, &forwarded).await?;
+    // Forwarded and deleted, as a relay does once a bundle has been delivered onward
+    node.delete_bundle(&forwarded.id().digest())?;
+
+    send_bundle(&mut stream, &forwarded).await?;
+    assert!(node.list_bundles()?.is_empty());
+    assert_eq!(node.duplicates_suppressed(), 1);
+
+    let fresh = Bundle::new("dtn://other", "dtn://dest", b"fresh".to_vec());
+    send_bundle(&mut stream, &fresh).await?;
+    assert_eq!(node.list_bundles()?, vec![fresh.id().digest()]);
+
+    shutdown.cancel();
+    handle.await??;
+
+    // The seen set is saved on shutdown and restored on restart
+    let restored = SeenBundles::open(temp_dir.path().join(SEEN_BUNDLES_FILE), 100)?;
+    assert!(restored.contains(&forwarded.id()));
+    assert!(restored.contains(&fresh.id()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_listener_acks_full_when_store_capacity_exceeded() -> anyhow::Result<()> {
+    use crate::cla::tcp::ack::AckError;
+    use crate::cla::tcp::client::send_bundle;
+    use crate::store::BundleStore;
+
+    let temp_dir = TempDir::new()?;
+    let store = Arc::new(BundleStore::new(temp_dir.path())?.with_max_bytes(64));
+    let node = Arc::new(DtnNode::with_store(
+        store,
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    ));
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let listener_node = Arc::clone(&node);
+    let handle = tokio::spawn(async move {
+        listener_node
+            .start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = tokio::net::TcpStream::connect(addr).await {
+            stream = Some(s);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let mut stream = stream.expect("listener did not start");
+
+    let bundle = Bundle::new("dtn://neighbor", "dtn://dest", vec![0u8; 256]);
+    let err = send_bundle(&mut stream, &bundle).await.unwrap_err();
+    assert_eq!(err.downcast_ref::<AckError>(), Some(&AckError::Full));
+    assert!(node.list_bundles()?.is_empty());
+
+    shutdown.cancel();
+    handle.await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_insert_same_message_gets_new_sequence_number() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+
+    node.insert_bundle("repeat".to_string()).await?;
+    node.insert_bundle("repeat".to_string()).await?;
+
+    let mut ids: Vec<_> = node
+        .list_bundles()?
+        .iter()
+        .map(|id| node.show_bundle(id).map(|b| b.id()))
+        .collect::<anyhow::Result<_>>()?;
+    assert_eq!(ids.len(), 2);
+    ids.dedup();
+    assert_eq!(ids.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_insert_bundle_with_lifetime() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+
+    node.insert_bundle_with_lifetime("perishable".to_string(), 1)
+        .await?;
+    node.insert_bundle_with_lifetime("long-lived".to_string(), 3 * 24 * 3600)
+        .await?;
+
+    let find = |payload: &str| -> anyhow::Result<Bundle> {
+        for id in node.list_bundles()? {
+            let bundle = node.show_bundle(&id)?;
+            if bundle.payload == payload.as_bytes() {
+                return Ok(bundle);
+            }
+        }
+        anyhow::bail!("bundle not found: {payload}")
+    };
+    assert_eq!(find("perishable")?.primary.lifetime, 1);
+    assert_eq!(find("long-lived")?.primary.lifetime, 3 * 24 * 3600);
+
+    tokio::time::sleep(std::time::Duration::from_millis(2100)).await;
+
+    assert!(find("perishable")?.is_expired());
+    assert!(!find("long-lived")?.is_expired());
+
+    node.cleanup_expired()?;
+    let bundles = node.list_bundles()?;
+    assert_eq!(bundles.len(), 1);
+    assert_eq!(node.show_bundle(&bundles[0])?.payload, b"long-lived");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_select_routes_for_stored_bundles_priority_order() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+
+    node.insert_bundle_with_priority("bulk".to_string(), Priority::Bulk)
+        .await?;
+    node.insert_bundle("normal".to_string()).await?;
+    node.insert_bundle_with_priority("expedited".to_string(), Priority::Expedited)
+        .await?;
+
+    let selections = node.select_routes_for_stored_bundles().await?;
+    let payloads: Vec<String> = selections
+        .iter()
+        .map(|(id, _)| String::from_utf8(node.show_bundle(id).unwrap().payload).unwrap())
+        .collect();
+
+    assert_eq!(payloads, vec!["expedited", "normal", "bulk"]);
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_add_route() -> anyhow::Result<()> {
     let temp_dir = TempDir::new()?;
@@ -218,6 +591,10 @@ async fn test_add_route() -> anyhow::Result<()> {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     node.add_route(route.clone())?;
@@ -239,6 +616,10 @@ async fn test_add_multiple_routes() -> anyhow::Result<()> {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     let route2 = RouteEntry {
@@ -247,6 +628,10 @@ async fn test_add_multiple_routes() -> anyhow::Result<()> {
         cla_type: "ble".to_string(),
         cost: 5,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     node.add_route(route1)?;
@@ -257,6 +642,73 @@ async fn test_add_multiple_routes() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_remove_route_keeps_other_next_hops() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+
+    for next_hop in ["dtn://router1", "dtn://router2"] {
+        node.add_route(RouteEntry {
+            destination: EndpointId::from("dtn://dest"),
+            next_hop: EndpointId::from(next_hop),
+            cla_type: "tcp".to_string(),
+            cost: 10,
+            is_active: true,
+            valid_until: None,
+            latency_ms: None,
+            data_rate_bps: None,
+            reliability: None,
+        })?;
+    }
+
+    let dest = EndpointId::from("dtn://dest");
+    assert!(node.remove_route(&dest, &EndpointId::from("dtn://router1"))?);
+    let routes = node.get_all_routes()?;
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes[0].next_hop, EndpointId::from("dtn://router2"));
+
+    assert_eq!(node.clear_routes()?, 1);
+    assert!(node.get_all_routes()?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_routes_survive_a_restart_with_routes_path() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let routes_path = temp_dir.path().join("routes.json");
+    let route = |next_hop: &str| RouteEntry {
+        destination: EndpointId::from("dtn://dest"),
+        next_hop: EndpointId::from(next_hop),
+        cla_type: "tcp".to_string(),
+        cost: 10,
+        is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
+    };
+
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?
+        .with_routes_path(&routes_path)?;
+    node.add_route(route("dtn://router1"))?;
+    node.add_route(route("dtn://router2"))?;
+    node.remove_route(
+        &EndpointId::from("dtn://dest"),
+        &EndpointId::from("dtn://router1"),
+    )?;
+    drop(node);
+
+    let restarted = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?
+        .with_routes_path(&routes_path)?;
+    assert_eq!(restarted.get_all_routes()?, vec![route("dtn://router2")]);
+
+    restarted.clear_routes()?;
+    let restarted = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?
+        .with_routes_path(&routes_path)?;
+    assert!(restarted.get_all_routes()?.is_empty());
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_find_best_route() -> anyhow::Result<()> {
     let temp_dir = TempDir::new()?;
@@ -270,6 +722,10 @@ async fn test_find_best_route() -> anyhow::Result<()> {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     let route2 = RouteEntry {
@@ -278,6 +734,10 @@ async fn test_find_best_route() -> anyhow::Result<()> {
         cla_type: "ble".to_string(),
         cost: 5,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     node.add_route(route1)?;
@@ -290,6 +750,74 @@ async fn test_find_best_route() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_find_best_route_with_ecmp() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic).with_ecmp(true),
+    )?;
+    let dest = EndpointId::from("dtn://dest");
+    for next_hop in ["dtn://router1", "dtn://router2"] {
+        node.add_route(RouteEntry {
+            destination: dest.clone(),
+            next_hop: EndpointId::from(next_hop),
+            cla_type: "tcp".to_string(),
+            cost: 5,
+            is_active: true,
+            valid_until: None,
+            latency_ms: None,
+            data_rate_bps: None,
+            reliability: None,
+        })?;
+    }
+
+    let first = node.find_best_route(&dest)?.unwrap().next_hop;
+    let second = node.find_best_route(&dest)?.unwrap().next_hop;
+    let third = node.find_best_route(&dest)?.unwrap().next_hop;
+    assert_ne!(first, second);
+    assert_eq!(first, third);
+    assert_eq!(node.find_routes_ranked(&dest)?.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forward_bundle_spreads_over_equal_cost_routes() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic).with_ecmp(true),
+    )?;
+    let routers = ["dtn://router1", "dtn://router2"].map(MockCla::new);
+    let other = MockCla::new("dtn://other");
+    for (router, eid) in routers.iter().zip(["dtn://router1", "dtn://router2"]) {
+        node.register_peer(Box::new(router.clone())).await;
+        node.add_route(RouteEntry {
+            destination: EndpointId::from("dtn://dest"),
+            next_hop: EndpointId::from(eid),
+            cla_type: "tcp".to_string(),
+            cost: 5,
+            is_active: true,
+            valid_until: None,
+            latency_ms: None,
+            data_rate_bps: None,
+            reliability: None,
+        })?;
+    }
+    node.register_peer(Box::new(other.clone())).await;
+
+    for payload in ["first", "second"] {
+        let bundle = Bundle::new("dtn://origin", "dtn://dest", payload.as_bytes().to_vec());
+        assert_eq!(node.forward_bundle(&bundle, None).await?.len(), 2);
+    }
+
+    // Each equal-cost next hop carries one bundle; peers without a route get both
+    assert_eq!(routers[0].received_count(), 1);
+    assert_eq!(routers[1].received_count(), 1);
+    assert_eq!(other.received_count(), 2);
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_find_best_route_no_routes() -> anyhow::Result<()> {
     let temp_dir = TempDir::new()?;
@@ -345,6 +873,10 @@ async fn test_select_routes_for_forwarding_with_routes() -> anyhow::Result<()> {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     node.add_route(RouteEntry {
@@ -353,6 +885,10 @@ async fn test_select_routes_for_forwarding_with_routes() -> anyhow::Result<()> {
         cla_type: "ble".to_string(),
         cost: 5,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     node.insert_bundle("Test message".to_string()).await?;
@@ -378,9 +914,17 @@ async fn test_get_bundle_status_single() -> anyhow::Result<()> {
 
     let status = node.get_bundle_status(Some(bundle_id))?;
     match status {
-        BundleStatus::Single { id, bundle } => {
+        BundleStatus::Single {
+            id,
+            bundle,
+            remaining_lifetime,
+            latency,
+        } => {
             assert_eq!(id, *bundle_id);
             assert_eq!(bundle.payload, b"Test message");
+            assert!(remaining_lifetime > 3590 && remaining_lifetime <= 3600);
+            // Inserted locally, not received
+            assert_eq!(latency, None);
         }
         _ => panic!("Expected Single status"),
     }
@@ -400,8 +944,10 @@ async fn test_get_bundle_status_summary() -> anyhow::Result<()> {
         BundleStatus::Summary {
             active,
             expired,
+            expiring_soon,
             total,
         } => {
+            assert_eq!(expiring_soon, 0);
             assert_eq!(active, 2);
             assert_eq!(expired, 0);
             assert_eq!(total, 2);
@@ -426,6 +972,57 @@ async fn test_cleanup_expired() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_store_stats() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    assert_eq!(node.store_stats()?.bundle_count, 0);
+
+    node.insert_bundle("four".to_string()).await?;
+    node.insert_bundle("eight...".to_string()).await?;
+
+    let stats = node.store_stats()?;
+    assert_eq!(stats.bundle_count, 2);
+    assert_eq!(stats.active_count, 2);
+    assert_eq!(stats.payload_bytes, 12);
+    assert_eq!(stats.average_payload_size(), 6.0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_expired_and_active_bundles() -> anyhow::Result<()> {
+    use crate::store::BundleStore;
+
+    let temp_dir = TempDir::new()?;
+    let path = temp_dir.path().to_str().unwrap();
+    let store = BundleStore::new(path)?;
+    let mut expired_ids = Vec::new();
+    for i in 0..2 {
+        let mut bundle = Bundle::new("dtn://src", "dtn://dst", format!("old {i}").into_bytes());
+        bundle.primary.creation_timestamp = 1_000_000 + i;
+        bundle.primary.lifetime = 60;
+        store.insert(&bundle)?;
+        expired_ids.push(bundle.id().digest());
+    }
+
+    let node = DtnNode::with_store_path(path)?;
+    let mut active_ids = vec![
+        node.insert_bundle("fresh 1".to_string()).await?,
+        node.insert_bundle("fresh 2".to_string()).await?,
+        node.insert_bundle("fresh 3".to_string()).await?,
+    ];
+
+    let mut expired = node.list_expired_bundles()?;
+    let mut active = node.list_active_bundles()?;
+    expired.sort();
+    active.sort();
+    expired_ids.sort();
+    active_ids.sort();
+    assert_eq!(expired, expired_ids);
+    assert_eq!(active, active_ids);
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_routing_with_prophet_algorithm() -> anyhow::Result<()> {
     let temp_dir = TempDir::new()?;
@@ -452,6 +1049,10 @@ async fn test_complex_routing_scenario() -> anyhow::Result<()> {
         cla_type: "tcp".to_string(),
         cost: 100,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     node.add_route(RouteEntry {
@@ -460,6 +1061,10 @@ async fn test_complex_routing_scenario() -> anyhow::Result<()> {
         cla_type: "ble".to_string(),
         cost: 5,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     node.add_route(RouteEntry {
@@ -468,6 +1073,10 @@ async fn test_complex_routing_scenario() -> anyhow::Result<()> {
         cla_type: "lora".to_string(),
         cost: 50,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     // Insert bundle and test routing
@@ -502,13 +1111,17 @@ async fn test_get_routing_table() -> anyhow::Result<()> {
 
     // Add a route through the reference
     {
-        let mut table = routing_table.lock().unwrap();
+        let mut table = routing_table.write();
         table.add_route(RouteEntry {
             destination: EndpointId::from("dtn://test-dest"),
             next_hop: EndpointId::from("dtn://test-router"),
             cla_type: "tcp".to_string(),
             cost: 15,
             is_active: true,
+            valid_until: None,
+            latency_ms: None,
+            data_rate_bps: None,
+            reliability: None,
         });
     }
 
@@ -554,8 +1167,10 @@ async fn test_error_handling_empty_bundle_list() -> anyhow::Result<()> {
         BundleStatus::Summary {
             active,
             expired,
+            expiring_soon,
             total,
         } => {
+            assert_eq!(expiring_soon, 0);
             assert_eq!(active, 0);
             assert_eq!(expired, 0);
             assert_eq!(total, 0);
@@ -592,6 +1207,10 @@ async fn test_find_best_route_multiple_costs() -> anyhow::Result<()> {
         cla_type: "tcp".to_string(),
         cost: 100,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     node.add_route(RouteEntry {
@@ -600,6 +1219,10 @@ async fn test_find_best_route_multiple_costs() -> anyhow::Result<()> {
         cla_type: "ble".to_string(),
         cost: 50,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     node.add_route(RouteEntry {
@@ -608,6 +1231,10 @@ async fn test_find_best_route_multiple_costs() -> anyhow::Result<()> {
         cla_type: "lora".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     })?;
 
     let best_route = node.find_best_route(&dest)?;
@@ -663,8 +1290,10 @@ async fn test_bundle_status_with_partial_failures() -> anyhow::Result<()> {
         BundleStatus::Summary {
             active,
             expired,
+            expiring_soon,
             total,
         } => {
+            assert_eq!(expiring_soon, 0);
             assert_eq!(active, 2);
             assert_eq!(expired, 0);
             assert_eq!(total, 2);
@@ -692,38 +1321,2376 @@ async fn test_cleanup_expired_no_expired_bundles() -> anyhow::Result<()> {
     Ok(())
 }
 
-#[test]
-fn test_dtn_node_add_route_lock_fail() {
-    use crate::api::node::DtnNode;
-    use crate::bpv7::EndpointId;
-    use crate::routing::algorithm::RouteEntry;
-    use std::sync::{Arc, Mutex};
-
-    // DtnNodeのラッパーを作り、Mutex<RoutingTable>を外から注入できるようにする
-    struct _TestNode {
-        node: DtnNode,
-        routing_table: Arc<Mutex<()>>, // ダミー
-    }
-
-    // Poisoned Mutexを作る
-    let m = Arc::new(Mutex::new(()));
-    let m2 = m.clone();
-    let _ = std::thread::spawn(move || {
-        let _lock = m2.lock().unwrap();
-        panic!("poison");
-    })
-    .join();
-
-    // DtnNodeのadd_routeのロック失敗分岐を間接的にテスト（直接private差し替え不可のため、PoisonErrorを模倣）
-    let _entry = RouteEntry {
-        destination: EndpointId::from("dtn://fail"),
-        next_hop: EndpointId::from("dtn://fail"),
+#[tokio::test]
+async fn test_panic_while_holding_routing_table_does_not_break_lookups() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    let route = |next_hop: &str| RouteEntry {
+        destination: EndpointId::from("dtn://dest"),
+        next_hop: EndpointId::from(next_hop),
         cla_type: "tcp".to_string(),
         cost: 1,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
-    // Mutex PoisonErrorの挙動を確認
-    let result = m.lock();
-    assert!(result.is_err());
-    // DtnNode本体の分岐はprivateのため直接は困難だが、PoisonError自体の発生はテストできる
+    node.add_route(route("dtn://router-1"))?;
+
+    // A task panics with the write lock held
+    let table = node.get_routing_table();
+    let result = tokio::spawn(async move {
+        let _guard = table.write();
+        panic!("panic while holding the routing table");
+    })
+    .await;
+    assert!(result.unwrap_err().is_panic());
+
+    let best = node.find_best_route(&EndpointId::from("dtn://dest"))?;
+    assert_eq!(best.unwrap().next_hop, EndpointId::from("dtn://router-1"));
+    node.add_route(route("dtn://router-2"))?;
+    assert_eq!(node.get_all_routes()?.len(), 2);
+    assert_eq!(
+        node.find_routes_ranked(&EndpointId::from("dtn://dest"))?
+            .len(),
+        2
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_insert_bundle_compressed_round_trip() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    let message = "temperature=21.5;pressure=1013;".repeat(200);
+
+    let id = node.insert_bundle_compressed(message.clone(), None).await?;
+
+    // The stored file is smaller than the raw payload alone
+    let stored = std::fs::metadata(temp_dir.path().join(format!("{id}.cbor")))?.len();
+    assert!(stored < message.len() as u64);
+
+    let bundle = node.show_bundle(&id)?;
+    assert_eq!(bundle.payload, message.as_bytes());
+    assert!(!bundle.primary.payload_compressed);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_spawn_cleanup_task_removes_expired_bundle() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    node.insert_bundle("still valid".to_string()).await?;
+
+    let mut expired = Bundle::new("dtn://src", "dtn://dst", b"stale".to_vec());
+    expired.primary.creation_timestamp = 1_000_000;
+    crate::store::BundleStore::new(temp_dir.path())?.insert(&expired)?;
+    assert_eq!(node.list_bundles()?.len(), 2);
+
+    let handle = node.spawn_cleanup_task(std::time::Duration::from_millis(50));
+
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(2);
+    while node.list_bundles()?.len() != 1 {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "expired bundle was not cleaned up in time"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    handle.abort();
+    assert!(handle.await.unwrap_err().is_cancelled());
+
+    let remaining = node.list_bundles()?;
+    assert_eq!(node.show_bundle(&remaining[0])?.payload, b"still valid");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cleanup_task_stops_on_shutdown() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    let shutdown = tokio_util::sync::CancellationToken::new();
+
+    let handle = node
+        .spawn_cleanup_task_with_shutdown(std::time::Duration::from_millis(10), shutdown.clone());
+    shutdown.cancel();
+
+    tokio::time::timeout(std::time::Duration::from_secs(1), handle).await??;
+    Ok(())
+}
+
+/// A bundle with a 100s lifetime that was created 95s ago
+fn nearly_expired_bundle() -> Bundle {
+    let mut bundle = Bundle::new("dtn://src", "dtn://dst", b"nearly gone".to_vec());
+    bundle.primary.creation_timestamp = crate::clock::now() - 95;
+    bundle.primary.lifetime = 100;
+    bundle
+}
+
+#[tokio::test]
+async fn test_list_expiring_soon() -> anyhow::Result<()> {
+    use crate::store::{BundleStorage, MemoryBundleStore};
+
+    let store = Arc::new(MemoryBundleStore::new());
+    let node = DtnNode::with_store(
+        Arc::clone(&store) as Arc<dyn BundleStorage>,
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    );
+    let nearly_expired = nearly_expired_bundle();
+    store.insert(&nearly_expired)?;
+    let mut expired = Bundle::new("dtn://src", "dtn://dst", b"gone".to_vec());
+    expired.primary.creation_timestamp = 1_000_000;
+    store.insert(&expired)?;
+    let fresh = node.insert_bundle("fresh".to_string()).await?;
+
+    let window = std::time::Duration::from_secs(10);
+    assert_eq!(
+        node.list_expiring_soon(window)?,
+        vec![nearly_expired.id().digest()]
+    );
+    assert!(!node.list_expiring_soon(window)?.contains(&fresh));
+    // A window longer than the fresh bundle's lifetime takes it in as well
+    let mut all = node.list_expiring_soon(std::time::Duration::from_secs(7200))?;
+    all.sort();
+    let mut expected = vec![nearly_expired.id().digest(), fresh];
+    expected.sort();
+    assert_eq!(all, expected);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cleanup_task_warns_once_per_expiring_bundle() -> anyhow::Result<()> {
+    use crate::store::{BundleStorage, MemoryBundleStore};
+
+    let store = Arc::new(MemoryBundleStore::new());
+    let node = DtnNode::with_store(
+        Arc::clone(&store) as Arc<dyn BundleStorage>,
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_expiring_soon_window(std::time::Duration::from_secs(10));
+    store.insert(&nearly_expired_bundle())?;
+    node.insert_bundle("fresh".to_string()).await?;
+
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let handle = node
+        .spawn_cleanup_task_with_shutdown(std::time::Duration::from_millis(10), shutdown.clone());
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    shutdown.cancel();
+    handle.await?;
+
+    // Several passes saw the bundle, but it was only warned about once
+    assert_eq!(node.metrics_snapshot().expiry_warnings, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_bundles_paged_sorting_and_windows() -> anyhow::Result<()> {
+    use crate::api::SortKey;
+
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    let store = crate::store::BundleStore::new(temp_dir.path())?;
+
+    for (i, source) in ["dtn://c", "dtn://a", "dtn://d", "dtn://b"]
+        .iter()
+        .enumerate()
+    {
+        let mut bundle = Bundle::new(source, "dtn://dst", vec![b'x'; 10 * (4 - i)]);
+        bundle.primary.creation_timestamp = 1_000 + i as u64;
+        bundle.primary.lifetime = 100 * (i as u64 % 2 + 1) + i as u64;
+        store.insert(&bundle)?;
+    }
+
+    let by_time = node.list_bundles_paged(0, 10, SortKey::CreationTime)?;
+    let times: Vec<u64> = by_time.iter().map(|m| m.creation_timestamp).collect();
+    assert_eq!(times, vec![1_000, 1_001, 1_002, 1_003]);
+
+    let by_source = node.list_bundles_paged(0, 10, SortKey::Source)?;
+    let sources: Vec<&str> = by_source.iter().map(|m| m.source.as_str()).collect();
+    assert_eq!(sources, vec!["dtn://a", "dtn://b", "dtn://c", "dtn://d"]);
+
+    let by_lifetime = node.list_bundles_paged(0, 10, SortKey::Lifetime)?;
+    let lifetimes: Vec<u64> = by_lifetime.iter().map(|m| m.lifetime).collect();
+    assert_eq!(lifetimes, vec![100, 102, 201, 203]);
+
+    let by_size = node.list_bundles_paged(0, 10, SortKey::Size)?;
+    assert!(by_size.windows(2).all(|w| w[0].size <= w[1].size));
+    assert_eq!(by_size[0].source, "dtn://b");
+
+    // Page windows
+    let page = node.list_bundles_paged(1, 2, SortKey::CreationTime)?;
+    assert_eq!(page, by_time[1..3].to_vec());
+    assert!(node
+        .list_bundles_paged(4, 2, SortKey::CreationTime)?
+        .is_empty());
+    assert_eq!(
+        node.list_bundles_paged(3, 10, SortKey::CreationTime)?.len(),
+        1
+    );
+
+    // Metadata IDs match the store IDs
+    let mut ids: Vec<String> = by_time.into_iter().map(|m| m.id).collect();
+    ids.sort();
+    let mut listed = node.list_bundles()?;
+    listed.sort();
+    assert_eq!(ids, listed);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_metrics_snapshot_reflects_activity() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    assert_eq!(node.metrics_snapshot(), crate::api::Metrics::default());
+
+    node.insert_bundle("one".to_string()).await?;
+    let id = node.insert_bundle("two".to_string()).await?;
+    assert_eq!(node.metrics_snapshot().bundles_stored, 2);
+
+    node.dispatch_bundle(&id)?;
+    assert_eq!(node.metrics_snapshot().bundles_forwarded, 1);
+
+    let mut expired = Bundle::new("dtn://src", "dtn://dst", b"stale".to_vec());
+    expired.primary.creation_timestamp = 1_000_000;
+    crate::store::BundleStore::new(temp_dir.path())?.insert(&expired)?;
+    node.cleanup_expired()?;
+
+    let metrics = node.metrics_snapshot();
+    assert_eq!(metrics.bundles_expired, 1);
+    assert_eq!(metrics.bundles_stored, 2);
+    assert_eq!(metrics.receive_failures, 0);
+    Ok(())
+}
+
+#[test]
+fn test_metrics_prometheus_format() {
+    let metrics = crate::api::Metrics {
+        bundles_stored: 5,
+        bundles_forwarded: 3,
+        bundles_delivered: 2,
+        bundles_expired: 1,
+        receive_failures: 4,
+        duplicates_suppressed: 6,
+        expiry_warnings: 7,
+    };
+    let text = metrics.to_prometheus();
+
+    let mut samples = std::collections::HashMap::new();
+    for line in text.lines() {
+        if line.starts_with('#') {
+            assert!(line.starts_with("# HELP sdtn_") || line.starts_with("# TYPE sdtn_"));
+            continue;
+        }
+        let (name, value) = line
+            .split_once(' ')
+            .expect("sample line has name and value");
+        samples.insert(name.to_string(), value.parse::<u64>().unwrap());
+    }
+
+    assert_eq!(samples["sdtn_bundles_stored_total"], 5);
+    assert_eq!(samples["sdtn_bundles_forwarded_total"], 3);
+    assert_eq!(samples["sdtn_bundles_delivered_total"], 2);
+    assert_eq!(samples["sdtn_bundles_dropped_expired_total"], 1);
+    assert_eq!(samples["sdtn_receive_failures_total"], 4);
+    assert_eq!(samples["sdtn_duplicates_suppressed_total"], 6);
+    assert_eq!(samples["sdtn_expiry_warnings_total"], 7);
+}
+
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn test_metrics_http_endpoint() -> anyhow::Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let metrics = Arc::new(crate::api::NodeMetrics::default());
+    metrics.record_stored();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let server = tokio::spawn(crate::api::metrics::serve_metrics(
+        metrics,
+        listener,
+        shutdown.clone(),
+    ));
+
+    let get = |path: &'static str| async move {
+        let mut stream = tokio::net::TcpStream::connect(addr).await?;
+        stream
+            .write_all(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes())
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        anyhow::Ok(response)
+    };
+
+    let response = get("/metrics").await?;
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("sdtn_bundles_stored_total 1\n"));
+    assert!(get("/other").await?.starts_with("HTTP/1.1 404"));
+
+    shutdown.cancel();
+    server.await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_selected_peers_keep_their_cla_type() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?;
+    let rover = MockCla::new("dtn://rover").with_cla_type("ble");
+    node.register_peer(Box::new(rover.clone())).await;
+
+    let bundle = Bundle::new("dtn://origin", "dtn://dest", b"over ble".to_vec());
+    for peers in [
+        node.select_peers_for_forwarding(&bundle).await?,
+        node.select_peers_for_forwarding_async(&bundle).await?,
+    ] {
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].get_cla_type(), "ble");
+        assert_eq!(peers[0].get_connection_address(), "ble:dtn://rover");
+
+        // The clone sends through the original peer's transport
+        peers[0].send_bundle(&bundle).await?;
+    }
+    assert_eq!(rover.received_count(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forward_bundle_skips_previous_hop() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?;
+    let upstream = MockCla::new("dtn://upstream");
+    let downstream = MockCla::new("dtn://downstream");
+    node.register_peer(Box::new(upstream.clone())).await;
+    node.register_peer(Box::new(downstream.clone())).await;
+
+    let bundle = Bundle::new("dtn://origin", "dtn://dest", b"relay me".to_vec());
+    let delivered = node
+        .forward_bundle(&bundle, Some(EndpointId::from("dtn://upstream")))
+        .await?;
+
+    assert_eq!(delivered, vec![EndpointId::from("dtn://downstream")]);
+    assert_eq!(upstream.received_count(), 0);
+    assert_eq!(downstream.received_count(), 1);
+    assert_eq!(node.metrics_snapshot().bundles_forwarded, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_relay_mode_forwards_received_bundles() -> anyhow::Result<()> {
+    use crate::cla::tcp::client::send_bundle;
+
+    let temp_dir = TempDir::new()?;
+    let node = Arc::new(
+        DtnNode::with_routing_algorithm(
+            temp_dir.path().to_str().unwrap(),
+            RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+        )?
+        .with_relay_mode(true),
+    );
+    // The previous hop is identified by the bundle's source endpoint
+    let origin = MockCla::new("dtn://origin");
+    let next_hop = MockCla::new("dtn://next-hop");
+    node.register_peer(Box::new(origin.clone())).await;
+    node.register_peer(Box::new(next_hop.clone())).await;
+
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let listener_node = Arc::clone(&node);
+    let handle = tokio::spawn(async move {
+        listener_node
+            .start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = tokio::net::TcpStream::connect(addr).await {
+            stream = Some(s);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let mut stream = stream.expect("listener did not start");
+
+    let bundle = Bundle::new("dtn://origin", "dtn://far-away", b"relayed".to_vec());
+    send_bundle(&mut stream, &bundle).await?;
+    // A duplicate is stored once and not relayed again
+    send_bundle(&mut stream, &bundle).await?;
+
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(2);
+    while next_hop.received_count() == 0 {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "bundle was not relayed"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    assert_eq!(next_hop.received_count(), 1);
+    assert_eq!(next_hop.received()[0].payload, b"relayed");
+    assert_eq!(origin.received_count(), 0);
+    assert_eq!(node.list_bundles()?.len(), 1);
+    // Stored for relaying, not delivered to this node
+    assert_eq!(node.metrics_snapshot().bundles_delivered, 0);
+
+    shutdown.cancel();
+    handle.await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_node_with_memory_store() -> anyhow::Result<()> {
+    use crate::store::{BundleStorage, MemoryBundleStore};
+
+    let store = Arc::new(MemoryBundleStore::new());
+    let node = DtnNode::with_store(
+        store.clone(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    );
+
+    let first = node.insert_bundle("in memory".to_string()).await?;
+    let second = node.insert_bundle("also in memory".to_string()).await?;
+
+    let mut ids = node.list_bundles()?;
+    ids.sort();
+    let mut expected = vec![first.clone(), second.clone()];
+    expected.sort();
+    assert_eq!(ids, expected);
+    assert_eq!(node.show_bundle(&first[..8])?.payload, b"in memory");
+
+    // The store is shared: bundles are visible to every holder of the handle
+    assert_eq!(store.list()?.len(), 2);
+    let other = DtnNode::with_store(
+        store.clone(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    );
+    assert_eq!(other.list_bundles()?.len(), 2);
+
+    node.dispatch_bundle(&first)?;
+    assert_eq!(store.dispatched(), vec![first]);
+    node.delete_bundle(&second)?;
+    assert!(node.list_bundles()?.is_empty());
+    assert!(node.delete_bundle(&second).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unreachable_bundle_moves_to_failed_after_max_attempts() -> anyhow::Result<()> {
+    use crate::bpv7::{StatusReport, StatusReportReason};
+    use crate::store::BundleStore;
+
+    let temp_dir = TempDir::new()?;
+    let path = temp_dir.path().to_str().unwrap();
+    let new_node = || -> anyhow::Result<DtnNode> {
+        Ok(DtnNode::with_routing_algorithm(
+            path,
+            RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+        )?
+        .with_max_forwarding_attempts(3)
+        // The persisted backoff would otherwise hold the bundle back across restarts
+        .with_retry_backoff(std::time::Duration::ZERO, std::time::Duration::ZERO))
+    };
+
+    let mut bundle = Bundle::new("dtn://origin", "dtn://nowhere", b"stuck".to_vec());
+    bundle.primary.report_to = "dtn://origin/reports".to_string();
+    let id = bundle.id().digest();
+    let store = BundleStore::new(path)?;
+    store.insert(&bundle)?;
+
+    // No peers are registered, so every pass fails; the count survives a restart
+    assert_eq!(new_node()?.forward_stored_bundles().await?, 0);
+    assert_eq!(new_node()?.forward_stored_bundles().await?, 0);
+    assert_eq!(store.forwarding_attempts(&id)?, 2);
+    assert!(store.contains(&bundle));
+
+    new_node()?.forward_stored_bundles().await?;
+    assert!(!store.contains(&bundle));
+    assert!(store.failed_dir().join(format!("{id}.cbor")).exists());
+    assert_eq!(store.forwarding_attempts(&id)?, 0);
+
+    // The only bundle left is the deletion report addressed to report_to
+    let remaining = store.list()?;
+    assert_eq!(remaining.len(), 1);
+    let report_bundle = store.load(&remaining[0])?;
+    assert_eq!(report_bundle.primary.destination, "dtn://origin/reports");
+    let report = StatusReport::from_bundle(&report_bundle)?;
+    assert!(report.deleted);
+    assert_eq!(report.reason, StatusReportReason::NoTimelyContact);
+    assert_eq!(report.subject, bundle.id().to_string());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deletion_report_for_expedited_bundle_is_forwarded_first() -> anyhow::Result<()> {
+    use crate::bpv7::ReportPriorityPolicy;
+    use crate::store::{BundleStorage, ForwardingQueue, MemoryBundleStore};
+
+    let store = Arc::new(MemoryBundleStore::new());
+    let new_node = |policy| {
+        DtnNode::with_store(
+            store.clone(),
+            RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+        )
+        .with_max_forwarding_attempts(1)
+        .with_report_priority_policy(policy)
+    };
+
+    let mut urgent = Bundle::new("dtn://origin", "dtn://nowhere", b"urgent".to_vec());
+    urgent.primary.priority = Priority::Expedited;
+    urgent.primary.report_to = "dtn://origin/reports".to_string();
+    store.insert(&urgent)?;
+    new_node(ReportPriorityPolicy::Inherit)
+        .forward_stored_bundles()
+        .await?;
+
+    let ids = store.list()?;
+    assert_eq!(ids.len(), 1);
+    let report = store.load(&ids[0])?;
+    assert!(report.primary.is_admin_record());
+    assert_eq!(report.primary.priority, Priority::Expedited);
+
+    // The scheduler takes the report ahead of older bulk traffic
+    let mut bulk = Bundle::new("dtn://origin", "dtn://ground", b"bulk".to_vec());
+    bulk.primary.priority = Priority::Bulk;
+    bulk.primary.creation_timestamp -= 60;
+    store.insert(&bulk)?;
+    let mut queue = ForwardingQueue::from_store(store.as_ref())?;
+    assert_eq!(queue.pop(0).unwrap().id, ids[0]);
+
+    // With the `normal` policy the report does not inherit the priority
+    store.remove(&ids[0])?;
+    store.remove(&bulk.id().digest())?;
+    let mut urgent = urgent.clone();
+    urgent.primary.sequence_number += 1;
+    store.insert(&urgent)?;
+    new_node(ReportPriorityPolicy::Normal)
+        .forward_stored_bundles()
+        .await?;
+    let report = store.load(&store.list()?[0])?;
+    assert_eq!(report.primary.priority, Priority::Normal);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forward_stored_bundles_backs_off_after_failures() -> anyhow::Result<()> {
+    use crate::store::{BundleStorage, MemoryBundleStore};
+    use std::time::Duration;
+
+    let store = Arc::new(MemoryBundleStore::new());
+    let node = DtnNode::with_store(
+        store.clone(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_retry_backoff(Duration::from_secs(10), Duration::from_secs(300));
+    let id = node.insert_bundle("unreachable".to_string()).await?;
+    assert_eq!(node.next_retry_at(&id), 0);
+
+    let before = crate::config::generate_creation_timestamp();
+    node.forward_stored_bundles().await?;
+    assert_eq!(store.forwarding_attempts(&id)?, 1);
+    let retry_at = node.next_retry_at(&id);
+    assert!(
+        (before + 20..=before + 21).contains(&retry_at),
+        "{retry_at}"
+    );
+
+    // The backoff has not elapsed, so the next pass skips the bundle even with a peer
+    let peer = MockCla::new("dtn://next-hop");
+    node.register_peer(Box::new(peer.clone())).await;
+    assert_eq!(node.forward_stored_bundles().await?, 0);
+    assert_eq!(peer.received_count(), 0);
+    assert_eq!(store.forwarding_attempts(&id)?, 1);
+    assert_eq!(node.next_retry_at(&id), retry_at);
+
+    // The schedule is kept in the store, so a restarted node does not retry early
+    assert_eq!(store.next_retry_at(&id)?, retry_at);
+    let restarted = DtnNode::with_store(
+        store.clone(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    );
+    restarted.register_peer(Box::new(peer.clone())).await;
+    assert_eq!(restarted.forward_stored_bundles().await?, 0);
+    assert_eq!(peer.received_count(), 0);
+    assert_eq!(restarted.next_retry_at(&id), retry_at);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forward_stored_bundles_dispatches_delivered() -> anyhow::Result<()> {
+    use crate::store::{BundleStorage, MemoryBundleStore};
+
+    let store = Arc::new(MemoryBundleStore::new());
+    let node = DtnNode::with_store(
+        store.clone(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_max_forwarding_attempts(1);
+    let id = node.insert_bundle("never delivered".to_string()).await?;
+
+    // Without peers a single failed pass exhausts the limit and queues a report
+    node.forward_stored_bundles().await?;
+    assert_eq!(store.failed(), vec![id]);
+    let reports = store.list()?;
+    assert_eq!(reports.len(), 1);
+
+    // Once a peer is reachable the queued report is delivered and dispatched
+    let peer = MockCla::new("dtn://next-hop");
+    node.register_peer(Box::new(peer.clone())).await;
+    assert_eq!(node.forward_stored_bundles().await?, 1);
+    assert_eq!(peer.received_count(), 1);
+    assert_eq!(store.dispatched(), reports);
+    assert!(store.list()?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forwarding_history_records_each_dispatch() -> anyhow::Result<()> {
+    use crate::store::HistoryOutcome;
+
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?;
+    let store = crate::store::BundleStore::new(temp_dir.path())?;
+    let to_ground = Bundle::new("dtn://sat", "dtn://ground", b"one".to_vec());
+    let to_moon = Bundle::new("dtn://sat", "dtn://moon", b"two".to_vec());
+    store.insert(&to_ground)?;
+    store.insert(&to_moon)?;
+    assert!(node.forwarding_history()?.is_empty());
+
+    let peer = MockCla::new("dtn://next-hop");
+    node.register_peer(Box::new(peer.clone())).await;
+    assert_eq!(node.forward_stored_bundles().await?, 2);
+
+    let mut history = node.forwarding_history()?;
+    assert_eq!(history.len(), 2);
+    history.sort_by(|a, b| a.destination.cmp(&b.destination));
+    for (entry, bundle) in history.iter().zip([&to_ground, &to_moon]) {
+        assert_eq!(entry.bundle_id, bundle.id().digest());
+        assert_eq!(entry.destination, bundle.primary.destination);
+        assert_eq!(entry.next_hop.as_deref(), Some("dtn://next-hop"));
+        assert_eq!(entry.outcome, HistoryOutcome::Sent);
+        assert!(entry.timestamp > 0);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_node_contact_header_uses_configured_wire_format() -> anyhow::Result<()> {
+    use crate::cla::tcp::wire::WireFormat;
+    use crate::config::{ClaConfig, ClaRole, ClaType};
+
+    let temp_dir = TempDir::new()?;
+    let mut config = crate::config::Config::test_config();
+    config.cla = vec![ClaConfig {
+        tcpcl_v4: true,
+        wire_format: WireFormat::Json,
+        ..ClaConfig::new(ClaType::Tcp, ClaRole::Listener, "127.0.0.1:4556")
+    }];
+    let node = DtnNode::from_config(temp_dir.path().to_str().unwrap(), config)?;
+    let header = node.contact_header().expect("TCPCL v4 is enabled");
+    assert_eq!(header.wire_format(), WireFormat::Json);
+
+    let node = node.with_wire_format(WireFormat::Cbor);
+    assert_eq!(
+        node.contact_header().unwrap().wire_format(),
+        WireFormat::Cbor
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_node_from_config_uses_configured_endpoint_id() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let mut config = crate::config::Config::test_config();
+    config.endpoints.node_eid = Some("dtn://ground-station".to_string());
+    let node = DtnNode::from_config(temp_dir.path().to_str().unwrap(), config)?;
+    assert_eq!(
+        node.endpoint_id(),
+        &EndpointId::from("dtn://ground-station")
+    );
+
+    let id = node.insert_bundle("from the ground".to_string()).await?;
+    assert_eq!(
+        node.show_bundle(&id)?.primary.source,
+        "dtn://ground-station"
+    );
+    let ids = node.insert_bundles(vec!["batched".to_string()]).await?;
+    assert_eq!(
+        node.show_bundle(&ids[0])?.primary.source,
+        "dtn://ground-station"
+    );
+
+    // Without a node EID the configured source is used
+    let other_dir = TempDir::new()?;
+    let node = DtnNode::from_config(
+        other_dir.path().to_str().unwrap(),
+        crate::config::Config::test_config(),
+    )?;
+    assert!(node.endpoint_id().is_null());
+    let id = node.insert_bundle("from the default".to_string()).await?;
+    assert_eq!(node.show_bundle(&id)?.primary.source, "dtn://src");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_allowed_schemes_reject_out_of_scheme_inserts() -> anyhow::Result<()> {
+    use crate::store::MemoryBundleStore;
+
+    let new_node = || {
+        DtnNode::with_store(
+            Arc::new(MemoryBundleStore::new()),
+            RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+        )
+        .with_node_eid(EndpointId::from("http://example.com"))
+    };
+
+    // Off by default: the misconfigured source goes through
+    let id = new_node().insert_bundle("legacy".to_string()).await?;
+    assert!(!id.is_empty());
+
+    let node = new_node().with_allowed_schemes(vec!["dtn".to_string(), "IPN".to_string()]);
+    let err = node
+        .insert_bundle("rejected".to_string())
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("source 'http://example.com' does not use an allowed scheme (dtn, ipn)"),
+        "{err}"
+    );
+    assert!(node
+        .insert_bundles(vec!["rejected".to_string()])
+        .await
+        .is_err());
+    assert!(node.list_bundles()?.is_empty());
+
+    // A scheme left out of the list is refused for file destinations too
+    let temp_dir = TempDir::new()?;
+    let path = temp_dir.path().join("payload.bin");
+    std::fs::write(&path, b"payload")?;
+    let node = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_allowed_schemes(vec!["dtn".to_string()]);
+    assert!(node.insert_file(&path, "dtn://ground").await.is_ok());
+    let err = node.insert_file(&path, "ipn:2.1").await.unwrap_err();
+    assert!(err.to_string().contains("destination 'ipn:2.1'"), "{err}");
+    Ok(())
+}
+
+/// Backend whose `dispatch` fails once for the bundle with store ID `broken`
+#[derive(Default)]
+struct FlakyDispatchStore {
+    store: crate::store::MemoryBundleStore,
+    broken: std::sync::Mutex<Option<String>>,
+}
+
+impl crate::store::BundleStorage for FlakyDispatchStore {
+    fn insert(&self, bundle: &Bundle) -> crate::store::error::Result<crate::store::InsertOutcome> {
+        self.store.insert(bundle)
+    }
+    fn contains(&self, bundle: &Bundle) -> bool {
+        self.store.contains(bundle)
+    }
+    fn load(&self, id: &str) -> crate::store::error::Result<Bundle> {
+        self.store.load(id)
+    }
+    fn list(&self) -> crate::store::error::Result<Vec<String>> {
+        self.store.list()
+    }
+    fn remove(&self, id: &str) -> crate::store::error::Result<()> {
+        self.store.remove(id)
+    }
+    fn dispatch(&self, bundle: &Bundle) -> crate::store::error::Result<()> {
+        let mut broken = self.broken.lock().unwrap();
+        if broken.as_deref() == Some(bundle.id().digest().as_str()) {
+            *broken = None;
+            return Err(crate::store::StoreError::Corrupt("disk hiccup".to_string()));
+        }
+        self.store.dispatch(bundle)
+    }
+    fn fail(&self, bundle: &Bundle) -> crate::store::error::Result<()> {
+        self.store.fail(bundle)
+    }
+    fn deliver(&self, bundle: &Bundle) -> crate::store::error::Result<()> {
+        self.store.deliver(bundle)
+    }
+    fn forwarding_attempts(&self, id: &str) -> crate::store::error::Result<u32> {
+        self.store.forwarding_attempts(id)
+    }
+    fn set_forwarding_attempts(&self, id: &str, attempts: u32) -> crate::store::error::Result<()> {
+        self.store.set_forwarding_attempts(id, attempts)
+    }
+    fn record_history(
+        &self,
+        entry: &crate::store::HistoryEntry,
+    ) -> crate::store::error::Result<()> {
+        self.store.record_history(entry)
+    }
+    fn history(&self) -> crate::store::error::Result<Vec<crate::store::HistoryEntry>> {
+        self.store.history()
+    }
+}
+
+#[tokio::test]
+async fn test_forwarding_pass_survives_a_failing_bundle() -> anyhow::Result<()> {
+    use crate::store::BundleStorage;
+
+    let store = Arc::new(FlakyDispatchStore::default());
+    let node = DtnNode::with_store(
+        Arc::clone(&store) as Arc<dyn BundleStorage>,
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    );
+    let peer = MockCla::new("dtn://peer");
+    node.register_peer(Box::new(peer.clone())).await;
+
+    let mut first = Bundle::new("dtn://src", "dtn://dest", b"first".to_vec());
+    first.primary.priority = Priority::Expedited;
+    let second = Bundle::new("dtn://src", "dtn://dest", b"second".to_vec());
+    let third = Bundle::new("dtn://src", "dtn://dest", b"third".to_vec());
+    for bundle in [&first, &second, &third] {
+        store.insert(bundle)?;
+    }
+    *store.broken.lock().unwrap() = Some(first.id().digest());
+
+    // The failure is retried later; the bundles popped after it are still forwarded
+    assert_eq!(node.forward_stored_bundles().await?, 2);
+    assert_eq!(node.list_bundles()?, vec![first.id().digest()]);
+    assert!(node.next_retry_at(&first.id().digest()) > 0);
+    Ok(())
+}
+
+/// Peer that takes a while to send and tracks how many sends overlap
+#[derive(Clone)]
+struct SlowPeer {
+    eid: EndpointId,
+    active: Arc<std::sync::atomic::AtomicUsize>,
+    peak: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl crate::cla::ClaPeer for SlowPeer {
+    fn get_peer_endpoint_id(&self) -> EndpointId {
+        self.eid.clone()
+    }
+    async fn is_reachable(&self) -> bool {
+        true
+    }
+    fn get_cla_type(&self) -> &str {
+        "mock"
+    }
+    fn get_connection_address(&self) -> String {
+        self.eid.to_string()
+    }
+    fn clone_box(&self) -> Box<dyn crate::cla::ClaPeer> {
+        Box::new(self.clone())
+    }
+    async fn activate(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn send_bundle(&self, _bundle: &Bundle) -> anyhow::Result<()> {
+        use std::sync::atomic::Ordering;
+
+        let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(active, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_forwarding_respects_max_concurrent_sends() -> anyhow::Result<()> {
+    use crate::store::MemoryBundleStore;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let node = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_max_concurrent_sends(3);
+    let active = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    for i in 0..4 {
+        node.register_peer(Box::new(SlowPeer {
+            eid: EndpointId::from(format!("dtn://peer-{i}").as_str()),
+            active: Arc::clone(&active),
+            peak: Arc::clone(&peak),
+        }))
+        .await;
+    }
+    for i in 0..6 {
+        node.insert_bundle(format!("bundle {i}")).await?;
+    }
+
+    assert_eq!(node.forward_stored_bundles().await?, 6);
+    // Sends overlapped, but never more than the limit at once
+    assert_eq!(peak.load(Ordering::SeqCst), 3);
+    assert_eq!(active.load(Ordering::SeqCst), 0);
+    assert!(node.list_bundles()?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forward_bundle_never_targets_own_endpoint() -> anyhow::Result<()> {
+    use crate::store::MemoryBundleStore;
+
+    let node = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_node_eid(EndpointId::from("dtn://relay"));
+    let own = MockCla::new("dtn://relay/");
+    let next_hop = MockCla::new("dtn://next-hop");
+    node.register_peer(Box::new(own.clone())).await;
+    node.register_peer(Box::new(next_hop.clone())).await;
+
+    let bundle = Bundle::new("dtn://origin", "dtn://dest", b"onward".to_vec());
+    let delivered = node.forward_bundle(&bundle, None).await?;
+    assert_eq!(delivered, vec![EndpointId::from("dtn://next-hop")]);
+    assert_eq!(own.received_count(), 0);
+    assert_eq!(next_hop.received_count(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forwarded_copies_carry_previous_node() -> anyhow::Result<()> {
+    use crate::store::MemoryBundleStore;
+
+    let node = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_node_eid(EndpointId::from("dtn://relay"));
+    let peer_x = MockCla::new("dtn://peerX");
+    let peer_y = MockCla::new("dtn://peerY");
+    node.register_peer(Box::new(peer_x.clone())).await;
+    node.register_peer(Box::new(peer_y.clone())).await;
+
+    // Tagged by peerX on the way in, so only peerY gets a copy
+    let mut bundle = Bundle::new("dtn://origin", "dtn://dest", b"onward".to_vec());
+    bundle.previous_node = Some(EndpointId::from("dtn://peerX"));
+    let delivered = node.forward_bundle(&bundle, None).await?;
+
+    assert_eq!(delivered, vec![EndpointId::from("dtn://peerY")]);
+    assert_eq!(peer_x.received_count(), 0);
+    let sent = peer_y.received();
+    assert_eq!(sent[0].previous_node, Some(EndpointId::from("dtn://relay")));
+    assert_eq!(sent[0].id(), bundle.id());
+    Ok(())
+}
+
+/// Upstream custodian that has forwarded one custody-requested bundle to its only
+/// peer, `dtn://downstream`, through the forwarding loop
+async fn forwarded_under_custody() -> anyhow::Result<(DtnNode, MockCla, Bundle)> {
+    use crate::bpv7::BundleFlags;
+    use crate::store::{BundleStorage, MemoryBundleStore};
+
+    let store = Arc::new(MemoryBundleStore::new());
+    let upstream = DtnNode::with_store(
+        Arc::clone(&store) as Arc<dyn BundleStorage>,
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_node_eid(EndpointId::from("dtn://upstream"))
+    .with_custody_timeout(std::time::Duration::from_secs(120));
+    let link = MockCla::new("dtn://downstream");
+    upstream.register_peer(Box::new(link.clone())).await;
+
+    let mut bundle = Bundle::new("dtn://upstream", "dtn://ground", b"precious".to_vec());
+    bundle
+        .primary
+        .set_flag(BundleFlags::CUSTODY_REQUESTED, true);
+    store.insert(&bundle)?;
+
+    assert_eq!(upstream.forward_stored_bundles().await?, 1);
+    Ok((upstream, link, bundle))
+}
+
+#[tokio::test]
+async fn test_custody_handoff_releases_upstream_copy() -> anyhow::Result<()> {
+    use crate::bpv7::CustodySignal;
+    use crate::cla::tcp::ack::AckStatus;
+    use crate::store::MemoryBundleStore;
+
+    let (upstream, link, bundle) = forwarded_under_custody().await?;
+    let sent = link.received();
+    assert_eq!(sent[0].primary.custodian.as_deref(), Some("dtn://upstream"));
+    // The custodian keeps its copy and waits for the custody signal
+    let digest = bundle.id().digest();
+    assert_eq!(upstream.list_bundles()?, vec![digest.clone()]);
+    assert!(upstream.next_retry_at(&digest) >= crate::clock::now() + 119);
+
+    let downstream = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_node_eid(EndpointId::from("dtn://downstream"));
+    // The downstream node stores the bundle and signals acceptance upstream
+    assert_eq!(downstream.receive_bundle(sent[0].clone()), AckStatus::Ok);
+    let stored = downstream
+        .list_bundles()?
+        .iter()
+        .map(|id| downstream.show_bundle(id))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    assert_eq!(stored.len(), 2);
+    let signal = stored
+        .into_iter()
+        .find(|b| b.primary.is_admin_record())
+        .unwrap();
+    assert_eq!(signal.primary.destination, "dtn://upstream/");
+    assert!(CustodySignal::from_bundle(&signal)?.accepted);
+
+    // Acceptance releases the upstream copy; the signal itself is not stored
+    assert_eq!(upstream.receive_bundle(signal), AckStatus::Ok);
+    assert!(upstream.list_bundles()?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custody_signal_from_another_node_is_ignored() -> anyhow::Result<()> {
+    use crate::bpv7::CustodySignal;
+    use crate::cla::tcp::ack::AckStatus;
+
+    let (upstream, link, bundle) = forwarded_under_custody().await?;
+    let sent = link.received().remove(0);
+    let forged = CustodySignal::acceptance(&sent)
+        .to_bundle(
+            &sent,
+            &EndpointId::from("dtn://mallory"),
+            Default::default(),
+        )?
+        .unwrap();
+    assert_eq!(upstream.receive_bundle(forged), AckStatus::Ok);
+    assert_eq!(upstream.list_bundles()?, vec![bundle.id().digest()]);
+
+    // The peer the bundle went to is still heard
+    let acceptance = CustodySignal::acceptance(&sent)
+        .to_bundle(
+            &sent,
+            &EndpointId::from("dtn://downstream"),
+            Default::default(),
+        )?
+        .unwrap();
+    assert_eq!(upstream.receive_bundle(acceptance), AckStatus::Ok);
+    assert!(upstream.list_bundles()?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custody_refusal_forwards_bundle_again() -> anyhow::Result<()> {
+    use crate::bpv7::{CustodySignal, StatusReportReason};
+    use crate::cla::tcp::ack::AckStatus;
+
+    let (upstream, link, bundle) = forwarded_under_custody().await?;
+    let digest = bundle.id().digest();
+    // Nothing is due again before the custody timeout
+    assert_eq!(upstream.forward_stored_bundles().await?, 0);
+
+    let sent = link.received().remove(0);
+    let refusal = CustodySignal::refusal(&sent, StatusReportReason::DepletedStorage)
+        .to_bundle(
+            &sent,
+            &EndpointId::from("dtn://downstream"),
+            Default::default(),
+        )?
+        .unwrap();
+    assert_eq!(upstream.receive_bundle(refusal), AckStatus::Ok);
+    assert_eq!(upstream.list_bundles()?, vec![digest.clone()]);
+    assert_eq!(upstream.next_retry_at(&digest), 0);
+
+    assert_eq!(upstream.forward_stored_bundles().await?, 1);
+    assert_eq!(link.received_count(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_bundles_by_destination() -> anyhow::Result<()> {
+    use crate::api::BundleFilter;
+
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    let store = crate::store::BundleStore::new(temp_dir.path())?;
+
+    let mut ground_ids = Vec::new();
+    for destination in ["dtn://ground", "dtn://relay", "dtn://ground", "dtn://moon"] {
+        let bundle = Bundle::new("dtn://sat", destination, destination.as_bytes().to_vec());
+        store.insert(&bundle)?;
+        if destination == "dtn://ground" {
+            ground_ids.push(bundle.id().digest());
+        }
+    }
+
+    let mut found = node.query_bundles(BundleFilter::new().with_destination("dtn://ground"))?;
+    found.sort();
+    ground_ids.sort();
+    assert_eq!(found, ground_ids);
+
+    assert!(node
+        .query_bundles(BundleFilter::new().with_destination("dtn://mars"))?
+        .is_empty());
+    assert!(node
+        .query_bundles(BundleFilter::new().with_expired(true))?
+        .is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bundle_for_node_eid_is_delivered_not_forwarded() -> anyhow::Result<()> {
+    use crate::cla::tcp::client::send_bundle;
+
+    let temp_dir = TempDir::new()?;
+    let delivered = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = Arc::clone(&delivered);
+    let mut node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?
+    .with_relay_mode(true)
+    .with_node_eid(EndpointId::from("dtn://ground"))
+    .with_delivered_archive(true);
+    node.set_delivery_handler(move |bundle| sink.lock().unwrap().push(bundle));
+    let node = Arc::new(node);
+    let next_hop = MockCla::new("dtn://next-hop");
+    node.register_peer(Box::new(next_hop.clone())).await;
+
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let listener_node = Arc::clone(&node);
+    let handle = tokio::spawn(async move {
+        listener_node
+            .start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = tokio::net::TcpStream::connect(addr).await {
+            stream = Some(s);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let mut stream = stream.expect("listener did not start");
+
+    // The destination matches the node EID after normalization
+    let bundle = Bundle::new("dtn://sat", "DTN://ground/", b"for the ground".to_vec());
+    send_bundle(&mut stream, &bundle).await?;
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    assert_eq!(delivered.lock().unwrap().len(), 1);
+    assert_eq!(delivered.lock().unwrap()[0].payload, b"for the ground");
+    assert_eq!(next_hop.received_count(), 0);
+    assert_eq!(node.metrics_snapshot().bundles_forwarded, 0);
+    assert_eq!(node.metrics_snapshot().bundles_delivered, 1);
+
+    // Archived out of the active store, so the forwarding loop never sees it
+    assert!(node.list_bundles()?.is_empty());
+    assert!(temp_dir
+        .path()
+        .join("delivered")
+        .join(format!("{}.cbor", bundle.id().digest()))
+        .exists());
+    assert_eq!(node.forward_stored_bundles().await?, 0);
+    assert_eq!(next_hop.received_count(), 0);
+
+    shutdown.cancel();
+    handle.await??;
+    Ok(())
+}
+
+#[test]
+fn test_node_eid_registers_admin_endpoint() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    assert!(node.registered_endpoints().is_empty());
+    assert_eq!(node.admin_endpoint(), None);
+
+    let node = node.with_node_eid(EndpointId::from("ipn:7.1"));
+    assert_eq!(node.admin_endpoint(), Some(EndpointId::from("ipn:7.0")));
+    assert!(node.is_registered(&EndpointId::from("ipn:7.1")));
+    assert!(node.is_registered(&EndpointId::from("ipn:7.0")));
+
+    // Replacing the node EID drops the previous node and admin registrations
+    let node = node.with_node_eid(EndpointId::from("ipn:8.1"));
+    assert_eq!(
+        node.registered_endpoints(),
+        vec![EndpointId::from("ipn:8.0"), EndpointId::from("ipn:8.1")]
+    );
+
+    assert!(node.register_endpoint(EndpointId::from("dtn://ground/telemetry")));
+    assert!(!node.register_endpoint(EndpointId::from("DTN://Ground/telemetry/")));
+    assert!(!node.register_endpoint(EndpointId::from("dtn:none")));
+    assert!(node.unregister_endpoint(&EndpointId::from("dtn://ground/telemetry")));
+    assert!(!node.unregister_endpoint(&EndpointId::from("dtn://ground/telemetry")));
+    assert!(!node.is_registered(&EndpointId::from("dtn://ground/telemetry")));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_registered_endpoints_are_delivered_locally() -> anyhow::Result<()> {
+    use crate::cla::tcp::client::send_bundle;
+
+    let temp_dir = TempDir::new()?;
+    let delivered = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = Arc::clone(&delivered);
+    let mut node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?
+    .with_relay_mode(true);
+    node.set_delivery_handler(move |bundle: Bundle| {
+        sink.lock().unwrap().push(bundle.primary.destination)
+    });
+    assert!(node.register_endpoint(EndpointId::from("dtn://ground/telemetry")));
+    assert!(node.register_endpoint(EndpointId::from("dtn://ground/commands")));
+    let node = Arc::new(node);
+    let next_hop = MockCla::new("dtn://next-hop");
+    node.register_peer(Box::new(next_hop.clone())).await;
+
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let listener_node = Arc::clone(&node);
+    let handle = tokio::spawn(async move {
+        listener_node
+            .start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = tokio::net::TcpStream::connect(addr).await {
+            stream = Some(s);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let mut stream = stream.expect("listener did not start");
+
+    for (source, destination) in [
+        ("dtn://sat1", "dtn://ground/telemetry"),
+        ("dtn://sat2", "dtn://ground/commands"),
+        ("dtn://sat3", "dtn://ground/other"),
+    ] {
+        let bundle = Bundle::new(source, destination, b"payload".to_vec());
+        send_bundle(&mut stream, &bundle).await?;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let mut destinations = delivered.lock().unwrap().clone();
+    destinations.sort();
+    assert_eq!(
+        destinations,
+        vec!["dtn://ground/commands", "dtn://ground/telemetry"]
+    );
+    // Only the unregistered destination is relayed to the next hop
+    assert_eq!(next_hop.received_count(), 1);
+    assert_eq!(node.metrics_snapshot().bundles_forwarded, 1);
+
+    // Once unregistered, an endpoint is forwarded like any other destination
+    node.unregister_endpoint(&EndpointId::from("dtn://ground/commands"));
+    let bundle = Bundle::new("dtn://sat4", "dtn://ground/commands", b"late".to_vec());
+    send_bundle(&mut stream, &bundle).await?;
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert_eq!(delivered.lock().unwrap().len(), 2);
+    assert_eq!(next_hop.received_count(), 2);
+
+    shutdown.cancel();
+    handle.await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forward_stored_bundles_skips_local_destination() -> anyhow::Result<()> {
+    use crate::store::MemoryBundleStore;
+
+    let node = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_node_eid(EndpointId::from("dtn://dest"));
+    let peer = MockCla::new("dtn://next-hop");
+    node.register_peer(Box::new(peer.clone())).await;
+
+    // Inserted bundles are addressed to the configured destination, dtn://dest
+    node.insert_bundle("stay here".to_string()).await?;
+    assert_eq!(node.forward_stored_bundles().await?, 0);
+    assert_eq!(peer.received_count(), 0);
+    assert_eq!(node.list_bundles()?.len(), 1);
+    assert_eq!(node.node_eid(), &EndpointId::from("dtn://dest"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_require_signed_drops_unverified_bundles() -> anyhow::Result<()> {
+    use crate::bpv7::SigningKey;
+    use crate::cla::tcp::client::send_bundle;
+
+    let key = SigningKey::from_bytes(&[9u8; 32]);
+    let temp_dir = TempDir::new()?;
+    let node = Arc::new(
+        DtnNode::with_routing_algorithm(
+            temp_dir.path().to_str().unwrap(),
+            RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+        )?
+        .with_verifying_key(key.verifying_key())
+        .with_require_signed(true),
+    );
+
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let listener_node = Arc::clone(&node);
+    let handle = tokio::spawn(async move {
+        listener_node
+            .start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = tokio::net::TcpStream::connect(addr).await {
+            stream = Some(s);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let mut stream = stream.expect("listener did not start");
+
+    let unsigned = Bundle::new("dtn://sat", "dtn://ground", b"unsigned".to_vec());
+    let mut wrong_key = Bundle::new("dtn://sat", "dtn://ground", b"wrong key".to_vec());
+    wrong_key.sign(&SigningKey::from_bytes(&[1u8; 32]))?;
+    let mut signed = Bundle::new("dtn://sat", "dtn://ground", b"signed".to_vec());
+    signed.sign(&key)?;
+    // Bundles failing verification are answered with BAD
+    for bundle in [&unsigned, &wrong_key] {
+        let err = send_bundle(&mut stream, bundle).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<crate::cla::tcp::ack::AckError>(),
+            Some(&crate::cla::tcp::ack::AckError::Rejected)
+        );
+    }
+    send_bundle(&mut stream, &signed).await?;
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let stored = node.list_bundles()?;
+    assert_eq!(stored, vec![signed.id().digest()]);
+    assert!(node.show_bundle(&stored[0])?.verify(&key.verifying_key()));
+    assert_eq!(node.metrics_snapshot().receive_failures, 2);
+
+    shutdown.cancel();
+    handle.await??;
+    Ok(())
+}
+
+/// Routing algorithm that floods like epidemic routing and records delivery confirmations
+struct SpyRouting {
+    confirmed: Arc<std::sync::Mutex<Vec<(String, EndpointId)>>>,
+}
+
+#[async_trait::async_trait]
+impl crate::routing::algorithm::RoutingAlgorithm for SpyRouting {
+    fn notify_new_bundle(&mut self, _descriptor: &crate::store::BundleDescriptor) {}
+
+    fn select_peers_for_forwarding<'a>(
+        &self,
+        _descriptor: &crate::store::BundleDescriptor,
+        all_peers: &'a [Box<dyn crate::cla::ClaPeer>],
+    ) -> Vec<&'a dyn crate::cla::ClaPeer> {
+        all_peers.iter().map(|peer| &**peer).collect()
+    }
+
+    fn select_routes_for_forwarding(
+        &self,
+        _descriptor: &crate::store::BundleDescriptor,
+        _routing_table: &crate::routing::algorithm::RoutingTable,
+    ) -> Vec<RouteEntry> {
+        Vec::new()
+    }
+
+    fn notify_delivery_confirmed(
+        &mut self,
+        descriptor: &crate::store::BundleDescriptor,
+        peer: &EndpointId,
+    ) {
+        self.confirmed
+            .lock()
+            .unwrap()
+            .push((descriptor.bundle.id().digest(), peer.clone()));
+    }
+}
+
+#[tokio::test]
+async fn test_delivery_confirmation_reaches_routing_algorithm() -> anyhow::Result<()> {
+    use crate::store::MemoryBundleStore;
+
+    let confirmed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let node = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_custom_routing_algorithm(Box::new(SpyRouting {
+        confirmed: Arc::clone(&confirmed),
+    }));
+    let peer = MockCla::new("dtn://next-hop");
+    node.register_peer(Box::new(peer.clone())).await;
+
+    let bundle = Bundle::new("dtn://origin", "dtn://dest", b"confirm me".to_vec());
+    node.forward_bundle(&bundle, None).await?;
+
+    assert_eq!(peer.received_count(), 1);
+    assert_eq!(
+        *confirmed.lock().unwrap(),
+        vec![(bundle.id().digest(), EndpointId::from("dtn://next-hop"))]
+    );
+    Ok(())
+}
+
+/// Routing algorithm whose only state is the number of bundles it has been told about
+#[derive(Default)]
+struct CountingRouting {
+    seen: u32,
+}
+
+#[async_trait::async_trait]
+impl crate::routing::algorithm::RoutingAlgorithm for CountingRouting {
+    fn notify_new_bundle(&mut self, _descriptor: &crate::store::BundleDescriptor) {
+        self.seen += 1;
+    }
+
+    fn select_peers_for_forwarding<'a>(
+        &self,
+        _descriptor: &crate::store::BundleDescriptor,
+        _all_peers: &'a [Box<dyn crate::cla::ClaPeer>],
+    ) -> Vec<&'a dyn crate::cla::ClaPeer> {
+        Vec::new()
+    }
+
+    fn select_routes_for_forwarding(
+        &self,
+        _descriptor: &crate::store::BundleDescriptor,
+        _routing_table: &crate::routing::algorithm::RoutingTable,
+    ) -> Vec<RouteEntry> {
+        Vec::new()
+    }
+
+    fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.seen.to_be_bytes().to_vec())
+    }
+
+    fn load_state(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        self.seen = u32::from_be_bytes(bytes.try_into()?);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_routing_state_survives_restart() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let state_path = temp_dir.path().join(crate::consts::ROUTING_STATE_FILE);
+    let build = || -> anyhow::Result<DtnNode> {
+        Ok(DtnNode::with_routing_algorithm(
+            temp_dir.path().to_str().unwrap(),
+            RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+        )?
+        .with_routing_state_path(&state_path)
+        .with_custom_routing_algorithm(Box::new(CountingRouting::default())))
+    };
+
+    let node = build()?;
+    node.insert_bundles(vec!["one".to_string(), "two".to_string()])
+        .await?;
+    node.insert_bundle("three".to_string()).await?;
+    node.save_routing_state().await?;
+    drop(node);
+
+    // A reconstructed node picks up where the first one left off
+    let node = build()?;
+    node.insert_bundle("four".to_string()).await?;
+    node.save_routing_state().await?;
+    assert_eq!(std::fs::read(&state_path)?, 4u32.to_be_bytes());
+
+    // Stateless algorithms save nothing and leave the file untouched
+    let epidemic = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?
+    .with_routing_state_path(&state_path);
+    epidemic.save_routing_state().await?;
+    assert_eq!(std::fs::read(&state_path)?, 4u32.to_be_bytes());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_corrupt_routing_state_is_ignored() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let state_path = temp_dir.path().join(crate::consts::ROUTING_STATE_FILE);
+    std::fs::write(&state_path, b"garbage!")?;
+
+    let node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?
+    .with_routing_state_path(&state_path)
+    .with_custom_routing_algorithm(Box::new(CountingRouting::default()));
+    node.insert_bundle("fresh".to_string()).await?;
+    node.save_routing_state().await?;
+    assert_eq!(std::fs::read(&state_path)?, 1u32.to_be_bytes());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_incoming_bundles_stream_yields_received_bundles_in_order() -> anyhow::Result<()> {
+    use crate::cla::tcp::client::send_bundle;
+    use futures::StreamExt;
+
+    let temp_dir = TempDir::new()?;
+    let node = Arc::new(DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?);
+    let mut first = node.incoming_bundles();
+    let mut second = node.incoming_bundles();
+    // A dropped subscriber does not hold up the others
+    drop(node.incoming_bundles());
+
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let listener_node = Arc::clone(&node);
+    let handle = tokio::spawn(async move {
+        listener_node
+            .start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = tokio::net::TcpStream::connect(addr).await {
+            stream = Some(s);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let mut stream = stream.expect("listener did not start");
+
+    for payload in ["one", "two"] {
+        let bundle = Bundle::new("dtn://sat", "dtn://ground", payload.as_bytes().to_vec());
+        send_bundle(&mut stream, &bundle).await?;
+    }
+
+    for subscriber in [&mut first, &mut second] {
+        let mut payloads = Vec::new();
+        for _ in 0..2 {
+            let bundle = tokio::time::timeout(std::time::Duration::from_secs(1), subscriber.next())
+                .await?
+                .expect("stream open");
+            payloads.push(String::from_utf8(bundle.payload)?);
+        }
+        assert_eq!(payloads, ["one", "two"]);
+    }
+
+    shutdown.cancel();
+    handle.await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_lagging_incoming_stream_skips_oldest_bundles() -> anyhow::Result<()> {
+    use super::incoming::IncomingSubscribers;
+    use futures::StreamExt;
+
+    let subscribers = IncomingSubscribers::with_capacity(2);
+    let mut stream = subscribers.subscribe();
+    for payload in ["one", "two", "three"] {
+        subscribers.publish(&Bundle::new(
+            "dtn://sat",
+            "dtn://ground",
+            payload.as_bytes().to_vec(),
+        ));
+    }
+
+    // Only the newest bundles that fit in the buffer are left for the slow reader
+    for expected in ["two", "three"] {
+        let bundle = stream.next().await.expect("stream open");
+        assert_eq!(bundle.payload, expected.as_bytes());
+    }
+    drop(subscribers);
+    assert!(stream.next().await.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mailboxes_receive_only_their_bundles() -> anyhow::Result<()> {
+    use crate::cla::tcp::client::send_bundle;
+
+    let temp_dir = TempDir::new()?;
+    let node = Arc::new(DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?);
+    let mut telemetry = node.register_mailbox(EndpointId::from("dtn://ground/telemetry"));
+    let mut commands = node.register_mailbox(EndpointId::from("dtn://ground/commands"));
+    assert!(telemetry.try_recv().is_none());
+
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let listener_node = Arc::clone(&node);
+    let handle = tokio::spawn(async move {
+        listener_node
+            .start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = tokio::net::TcpStream::connect(addr).await {
+            stream = Some(s);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let mut stream = stream.expect("listener did not start");
+
+    for (destination, payload) in [
+        ("dtn://ground/telemetry", "t1"),
+        ("dtn://ground/commands", "c1"),
+        ("dtn://ground/telemetry", "t2"),
+        ("dtn://elsewhere", "other"),
+    ] {
+        let bundle = Bundle::new("dtn://sat", destination, payload.as_bytes().to_vec());
+        send_bundle(&mut stream, &bundle).await?;
+    }
+
+    let recv = |mailbox: &mut crate::api::Mailbox| {
+        let bundle = mailbox.try_recv().expect("bundle queued");
+        String::from_utf8(bundle.payload).unwrap()
+    };
+    assert_eq!(recv(&mut telemetry), "t1");
+    assert_eq!(recv(&mut telemetry), "t2");
+    assert!(telemetry.try_recv().is_none());
+    let command = tokio::time::timeout(std::time::Duration::from_secs(1), commands.recv())
+        .await?
+        .expect("mailbox open");
+    assert_eq!(command.payload, b"c1");
+    assert!(commands.try_recv().is_none());
+
+    // Only the bundle without a mailbox reached the store
+    let stored = node.list_bundles()?;
+    assert_eq!(stored.len(), 1);
+    assert_eq!(node.show_bundle(&stored[0])?.payload, b"other");
+
+    shutdown.cancel();
+    handle.await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_explain_forwarding_lists_rejected_peers() -> anyhow::Result<()> {
+    use crate::cla::TcpPeer;
+    use crate::routing::explain::RejectionReason;
+    use crate::store::MemoryBundleStore;
+
+    let node = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    );
+    let upstream = MockCla::new("dtn://upstream");
+    let downstream = MockCla::new("dtn://downstream");
+    node.register_peer(Box::new(upstream.clone())).await;
+    node.register_peer(Box::new(downstream.clone())).await;
+    let closed_addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+    node.register_peer(Box::new(TcpPeer::new(
+        EndpointId::from("dtn://offline"),
+        closed_addr.to_string(),
+    )))
+    .await;
+
+    let bundle = Bundle::new("dtn://origin", "dtn://dest", b"explain me".to_vec());
+    let explanation = node
+        .explain_forwarding_from(&bundle, Some(EndpointId::from("dtn://upstream")))
+        .await;
+
+    assert_eq!(explanation.bundle_id, bundle.id().to_string());
+    assert_eq!(explanation.peers.len(), 3);
+    let reason = |eid: &str| explanation.peer(&EndpointId::from(eid)).unwrap().rejection;
+    assert_eq!(reason("dtn://upstream"), Some(RejectionReason::AlreadySent));
+    assert_eq!(reason("dtn://offline"), Some(RejectionReason::Unreachable));
+    assert_eq!(reason("dtn://downstream"), None);
+    assert_eq!(
+        explanation.selected_peers().collect::<Vec<_>>(),
+        vec![&EndpointId::from("dtn://downstream")]
+    );
+
+    // A dry run sends nothing
+    assert_eq!(upstream.received_count() + downstream.received_count(), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_insert_bundles_batch() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?;
+    let messages: Vec<String> = (0..100).map(|i| format!("part {i}")).collect();
+
+    let ids = node.insert_bundles(messages.clone()).await?;
+    assert_eq!(ids.len(), 100);
+    assert_eq!(
+        ids.iter().collect::<std::collections::HashSet<_>>().len(),
+        100,
+        "bundles created in the same second need distinct IDs"
+    );
+
+    let listed = node.list_bundles()?;
+    assert_eq!(listed.len(), 100);
+    for (id, message) in ids.iter().zip(&messages) {
+        assert!(listed.contains(id));
+        assert_eq!(node.show_bundle(id)?.payload, message.as_bytes());
+    }
+    assert_eq!(node.metrics_snapshot().bundles_stored, 100);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_beacon_nodes_discover_each_other() -> anyhow::Result<()> {
+    use crate::cla::BeaconConfig;
+    use crate::store::MemoryBundleStore;
+    use std::net::{Ipv4Addr, SocketAddrV4};
+
+    // Both nodes share one beacon port, as they would on a real mesh
+    let port = std::net::UdpSocket::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port();
+    let config = BeaconConfig::new(
+        SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port),
+        std::time::Duration::from_millis(50),
+    )
+    .with_group(Ipv4Addr::new(239, 255, 45, 56), Ipv4Addr::LOCALHOST)
+    .with_ttl(std::time::Duration::from_secs(1));
+
+    let node = |eid: &str, address: &str| {
+        DtnNode::with_store(
+            Arc::new(MemoryBundleStore::new()),
+            RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+        )
+        .with_node_eid(EndpointId::from(eid))
+        .with_advertised_address(address.to_string())
+    };
+    let alpha = node("dtn://alpha", "127.0.0.1:14556");
+    let beta = node("dtn://beta", "127.0.0.1:24556");
+
+    let alpha_shutdown = tokio_util::sync::CancellationToken::new();
+    let beta_shutdown = tokio_util::sync::CancellationToken::new();
+    let alpha_beacon = alpha.start_beacon_with_config(config.clone(), alpha_shutdown.clone())?;
+    let beta_beacon = beta.start_beacon_with_config(config, beta_shutdown.clone())?;
+
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(3);
+    loop {
+        let (alpha_peers, beta_peers) = (alpha.list_peers().await, beta.list_peers().await);
+        if alpha_peers == vec![EndpointId::from("dtn://beta")]
+            && beta_peers == vec![EndpointId::from("dtn://alpha")]
+        {
+            break;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "nodes did not discover each other: {alpha_peers:?} / {beta_peers:?}"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    // Repeat announcements do not register the neighbor twice
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert_eq!(alpha.list_peers().await.len(), 1);
+
+    // Once beta falls silent, alpha forgets it after the announced TTL
+    beta_shutdown.cancel();
+    beta_beacon.await??;
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(3);
+    while !alpha.list_peers().await.is_empty() {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "silent neighbor was not expired"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+
+    alpha_shutdown.cancel();
+    alpha_beacon.await??;
+    Ok(())
+}
+
+#[test]
+fn test_beacon_requires_node_eid() {
+    let temp_dir = TempDir::new().unwrap();
+    let node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .unwrap()
+    .with_advertised_address("127.0.0.1:4556".to_string());
+    let err = node
+        .start_beacon("0.0.0.0:0", std::time::Duration::from_secs(1))
+        .unwrap_err();
+    assert!(err.to_string().contains("node EID"));
+}
+
+/// Listener that hands one bundle to the node, then idles until shutdown
+struct MockListener {
+    cla_type: String,
+    address: String,
+    receive_callback: crate::cla::ReceiveCallback,
+}
+
+#[async_trait::async_trait]
+impl crate::cla::ClaListener for MockListener {
+    fn cla_type(&self) -> &str {
+        &self.cla_type
+    }
+    fn address(&self) -> String {
+        self.address.clone()
+    }
+    async fn run(&self, shutdown: tokio_util::sync::CancellationToken) -> anyhow::Result<()> {
+        (self.receive_callback)(Bundle::new(
+            "dtn://neighbor",
+            "dtn://dest",
+            format!("via {}", self.address).into_bytes(),
+        ));
+        shutdown.cancelled().await;
+        Ok(())
+    }
+}
+
+/// Factory recording which CLAs it was asked for instead of opening sockets
+#[derive(Default)]
+struct MockClaFactory {
+    created: std::sync::Mutex<Vec<(crate::config::ClaType, crate::config::ClaRole)>>,
+}
+
+impl crate::cla::ClaFactory for MockClaFactory {
+    fn create_listener(
+        &self,
+        config: &crate::config::ClaConfig,
+        receive_callback: crate::cla::ReceiveCallback,
+    ) -> anyhow::Result<Box<dyn crate::cla::ClaListener>> {
+        self.created
+            .lock()
+            .unwrap()
+            .push((config.cla_type, config.role));
+        Ok(Box::new(MockListener {
+            cla_type: config.cla_type.to_string(),
+            address: config.address.clone(),
+            receive_callback,
+        }))
+    }
+
+    fn create_dialer(
+        &self,
+        config: &crate::config::ClaConfig,
+    ) -> anyhow::Result<Box<dyn crate::cla::ClaPeer>> {
+        self.created
+            .lock()
+            .unwrap()
+            .push((config.cla_type, config.role));
+        Ok(Box::new(MockCla::new(config.peer_eid().as_str())))
+    }
+}
+
+fn parse_cla_entries(toml: &str) -> Vec<crate::config::ClaConfig> {
+    config::Config::builder()
+        .add_source(config::File::from_str(toml, config::FileFormat::Toml))
+        .build()
+        .unwrap()
+        .get("cla")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_start_clas_from_multi_cla_config() -> anyhow::Result<()> {
+    use crate::config::{ClaRole, ClaType};
+
+    let entries = parse_cla_entries(
+        r#"
+        [[cla]]
+        type = "tcp"
+        role = "listener"
+        address = "0.0.0.0:4556"
+
+        [[cla]]
+        type = "ble"
+        role = "listener"
+        address = "sdtn-node"
+
+        [[cla]]
+        type = "tcp"
+        role = "dialer"
+        address = "10.0.0.2:4556"
+        peer = "dtn://relay"
+
+        [[cla]]
+        type = "ws"
+        role = "dialer"
+        address = "ws://10.0.0.3:8080"
+        peer = "dtn://gateway"
+        "#,
+    );
+
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?;
+    let factory = MockClaFactory::default();
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let handles = node
+        .start_clas(&factory, &entries, shutdown.clone())
+        .await?;
+
+    assert_eq!(
+        *factory.created.lock().unwrap(),
+        vec![
+            (ClaType::Tcp, ClaRole::Listener),
+            (ClaType::Ble, ClaRole::Listener),
+            (ClaType::Tcp, ClaRole::Dialer),
+            (ClaType::Ws, ClaRole::Dialer),
+        ]
+    );
+    assert_eq!(handles.len(), 2);
+    let mut peers = node.list_peers().await;
+    peers.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    assert_eq!(
+        peers,
+        vec![
+            EndpointId::from("dtn://gateway"),
+            EndpointId::from("dtn://relay")
+        ]
+    );
+
+    // Both listeners feed the node's receive path
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(2);
+    while node.list_bundles()?.len() < 2 {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "listeners did not deliver"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    shutdown.cancel();
+    for handle in handles {
+        handle.await??;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_start_from_config_rejects_unsupported_cla() -> anyhow::Result<()> {
+    use crate::config::{ClaConfig, ClaRole, ClaType};
+
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?
+    .with_cla_configs(vec![
+        ClaConfig::new(ClaType::Tcp, ClaRole::Dialer, "127.0.0.1:4556"),
+        ClaConfig::new(ClaType::Udp, ClaRole::Listener, "0.0.0.0:4557"),
+    ]);
+
+    let err = node
+        .start_from_config(tokio_util::sync::CancellationToken::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("No udp convergence layer"));
+    // Nothing is started when one entry cannot be built
+    assert!(node.list_peers().await.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_insert_file_roundtrips_binary_payload() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    let contents: Vec<u8> = (0..=255u8).chain([0, 0xff, 0x80]).collect();
+    let path = temp_dir.path().join("image.bin");
+    std::fs::write(&path, &contents)?;
+
+    let ids = node.insert_file(&path, "dtn://ground").await?;
+    assert_eq!(ids.len(), 1);
+    let bundle = node.show_bundle(&ids[0])?;
+    assert_eq!(bundle.payload, contents);
+    assert_eq!(bundle.primary.destination, "dtn://ground");
+    assert!(!bundle.is_fragment());
+    assert_eq!(node.metrics_snapshot().bundles_stored, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_insert_file_fragments_large_files() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node =
+        DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?.with_fragment_threshold(100);
+    let contents: Vec<u8> = (0..250u32).map(|i| (i * 7) as u8).collect();
+    let path = temp_dir.path().join("log.bin");
+    std::fs::write(&path, &contents)?;
+
+    let ids = node.insert_file(&path, "dtn://ground").await?;
+    assert_eq!(ids.len(), 3);
+    let fragments = ids
+        .iter()
+        .map(|id| node.show_bundle(id))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    assert!(fragments.iter().all(|f| f.payload.len() <= 100));
+
+    let reassembled = Bundle::reassemble(&fragments)?;
+    assert_eq!(reassembled.payload, contents);
+    assert_eq!(reassembled.primary.destination, "dtn://ground");
+
+    // Importing the same file again in the same second still stores new fragments
+    let again = node.insert_file(&path, "dtn://ground").await?;
+    assert!(again.iter().all(|id| !ids.contains(id)));
+    assert_eq!(node.list_bundles()?.len(), 6);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_insert_file_rejects_bad_input() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    let path = temp_dir.path().join("data.bin");
+    std::fs::write(&path, b"data")?;
+
+    let err = node.insert_file(&path, "ground").await.unwrap_err();
+    assert!(err.to_string().contains("Invalid destination"), "{err}");
+    let err = node
+        .insert_file(temp_dir.path().join("missing.bin"), "dtn://ground")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Failed to read"), "{err}");
+    assert!(node.list_bundles()?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_export_bundle_cbor_reimports_identically() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    let id = node
+        .insert_bundle_compressed("exported ".repeat(20), Some(120))
+        .await?;
+
+    let cbor = node.export_bundle(&id, ExportFormat::Cbor)?;
+    let decoded: Bundle = serde_cbor::from_slice(&cbor)?;
+    let fresh_dir = TempDir::new()?;
+    let fresh = crate::store::BundleStore::new(fresh_dir.path().to_str().unwrap())?;
+    fresh.insert(&decoded)?;
+
+    let original = crate::store::BundleStore::new(temp_dir.path().to_str().unwrap())?.load(&id)?;
+    let reimported = fresh.load(&id)?;
+    assert!(reimported.content_eq(&original));
+    assert!(reimported.primary.payload_compressed);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_export_bundle_json_and_raw_payload() -> anyhow::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let node = DtnNode::with_store_path(temp_dir.path().to_str().unwrap())?;
+    let message = "raw payload ".repeat(10);
+    let id = node.insert_bundle_compressed(message.clone(), None).await?;
+
+    assert_eq!(
+        node.export_bundle(&id[..8], ExportFormat::RawPayload)?,
+        message.as_bytes()
+    );
+    let json = node.export_bundle(&id, ExportFormat::JsonPretty)?;
+    let decoded: Bundle = serde_json::from_slice(&json)?;
+    assert!(decoded.primary.payload_compressed);
+    assert_eq!(decoded.id().digest(), id);
+
+    assert!(node
+        .export_bundle("nonexistent", ExportFormat::Cbor)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_export_format_parse() {
+    assert_eq!("cbor".parse::<ExportFormat>().unwrap(), ExportFormat::Cbor);
+    assert_eq!(
+        "JSON".parse::<ExportFormat>().unwrap(),
+        ExportFormat::JsonPretty
+    );
+    assert_eq!(
+        "raw".parse::<ExportFormat>().unwrap(),
+        ExportFormat::RawPayload
+    );
+    let err = "xml".parse::<ExportFormat>().unwrap_err();
+    assert!(err.to_string().contains("cbor, json or raw"), "{err}");
+}
+
+#[tokio::test]
+async fn test_bundle_status_reports_remaining_lifetime() -> anyhow::Result<()> {
+    use crate::store::{BundleStorage, MemoryBundleStore};
+
+    let store = Arc::new(MemoryBundleStore::new());
+    let node = DtnNode::with_store(
+        store.clone(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )
+    .with_expiring_soon_window(std::time::Duration::from_secs(120));
+    let now = crate::config::generate_creation_timestamp();
+    let mut aged = Bundle::new("dtn://aged", "dtn://dest", b"aged".to_vec());
+    aged.primary.creation_timestamp = now - 600;
+    aged.primary.lifetime = 3600;
+    let mut closing = Bundle::new("dtn://closing", "dtn://dest", b"closing".to_vec());
+    closing.primary.creation_timestamp = now - 3540;
+    closing.primary.lifetime = 3600;
+    let mut gone = Bundle::new("dtn://gone", "dtn://dest", b"gone".to_vec());
+    gone.primary.creation_timestamp = now - 7200;
+    gone.primary.lifetime = 3600;
+    for bundle in [&aged, &closing, &gone] {
+        store.insert(bundle)?;
+    }
+
+    match node.get_bundle_status(Some(&aged.id().digest()))? {
+        BundleStatus::Single {
+            remaining_lifetime, ..
+        } => assert!(
+            (2995..=3000).contains(&remaining_lifetime),
+            "{remaining_lifetime}"
+        ),
+        _ => panic!("Expected Single status"),
+    }
+    assert_eq!(gone.remaining_lifetime(), 0);
+
+    match node.get_bundle_status(None)? {
+        BundleStatus::Summary {
+            active,
+            expired,
+            expiring_soon,
+            total,
+        } => {
+            assert_eq!(active, 2);
+            assert_eq!(expiring_soon, 1);
+            assert_eq!(expired, 1);
+            assert_eq!(total, 3);
+        }
+        _ => panic!("Expected Summary status"),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_health_check_reports_read_only_store() -> anyhow::Result<()> {
+    use crate::store::BundleStore;
+
+    let temp_dir = TempDir::new()?;
+    let store = BundleStore::new(temp_dir.path())?.with_max_bytes(1024 * 1024);
+    let node = DtnNode::with_store(
+        Arc::new(store),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    );
+    node.register_peer(Box::new(MockCla::new("dtn://next-hop")))
+        .await;
+
+    let health = node.health_check().await;
+    assert!(health.store_writable);
+    assert_eq!(health.reachable_peer_count, 1);
+    assert_eq!(health.store_usage_ratio, Some(0.0));
+    assert!(health.ready);
+
+    let mut permissions = std::fs::metadata(temp_dir.path())?.permissions();
+    permissions.set_readonly(true);
+    std::fs::set_permissions(temp_dir.path(), permissions.clone())?;
+    let health = node.health_check().await;
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    std::fs::set_permissions(temp_dir.path(), permissions)?;
+
+    assert!(!health.store_writable);
+    assert_eq!(health.reachable_peer_count, 1);
+    assert!(!health.ready);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_health_check_without_reachable_peers_is_not_ready() -> anyhow::Result<()> {
+    use crate::store::MemoryBundleStore;
+
+    let node = DtnNode::with_store(
+        Arc::new(MemoryBundleStore::new()),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    );
+    let health = node.health_check().await;
+    assert!(health.store_writable);
+    assert_eq!(health.reachable_peer_count, 0);
+    assert_eq!(health.store_usage_ratio, None);
+    assert!(!health.ready);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_received_bundle_reports_latency() -> anyhow::Result<()> {
+    use crate::cla::tcp::client::send_bundle;
+
+    let temp_dir = TempDir::new()?;
+    let node = Arc::new(DtnNode::with_routing_algorithm(
+        temp_dir.path().to_str().unwrap(),
+        RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+    )?);
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let listener_node = Arc::clone(&node);
+    let handle = tokio::spawn(async move {
+        listener_node
+            .start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(s) = tokio::net::TcpStream::connect(addr).await {
+            stream = Some(s);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let mut stream = stream.expect("listener did not start");
+
+    let bundle = Bundle::new("dtn://sat1", "dtn://ground", b"payload".to_vec());
+    send_bundle(&mut stream, &bundle).await?;
+    let id = bundle.id().digest();
+
+    // Creation timestamps have whole-second resolution, arrivals millisecond
+    let latency = node
+        .bundle_latency(&id)
+        .expect("latency of a received bundle");
+    assert!(latency < std::time::Duration::from_secs(2), "{latency:?}");
+    match node.get_bundle_status(Some(&id))? {
+        BundleStatus::Single {
+            latency: Some(reported),
+            ..
+        } => assert_eq!(reported, latency),
+        status => panic!("Expected Single status with a latency, got {status:?}"),
+    }
+
+    // Bundles inserted locally never arrived, so they have none
+    let local = node.insert_bundle("local".to_string()).await?;
+    assert_eq!(node.bundle_latency(&local), None);
+
+    shutdown.cancel();
+    handle.await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_summary_vectors_only_transfer_missing_bundles() -> anyhow::Result<()> {
+    use crate::cla::TcpPeer;
+    use crate::store::{BundleStorage, MemoryBundleStore};
+
+    let bundles: Vec<Bundle> = (0..4)
+        .map(|i| Bundle::new(&format!("dtn://origin{i}"), "dtn://far", vec![i]))
+        .collect();
+    let sender_store = Arc::new(MemoryBundleStore::new());
+    let receiver_store = Arc::new(MemoryBundleStore::new());
+    for bundle in &bundles {
+        sender_store.insert(bundle)?;
+    }
+    // The receiver already holds the first two
+    for bundle in &bundles[..2] {
+        receiver_store.insert(bundle)?;
+    }
+
+    let routing = || RoutingConfig::new(RoutingAlgorithmType::Epidemic);
+    let sender = DtnNode::with_store(sender_store.clone(), routing())
+        .with_tcpcl_v4(true)
+        .with_summary_vectors(true);
+    let receiver = Arc::new(
+        DtnNode::with_store(receiver_store.clone(), routing())
+            .with_tcpcl_v4(true)
+            .with_summary_vectors(true),
+    );
+    let mut held: Vec<String> = receiver
+        .bundle_id_digest()
+        .iter()
+        .map(|id| id.digest())
+        .collect();
+    held.sort();
+    let mut expected: Vec<String> = bundles[..2].iter().map(|b| b.id().digest()).collect();
+    expected.sort();
+    assert_eq!(held, expected);
+    let summary = receiver.summary_vector();
+    assert_eq!(summary.len(), 2);
+    assert!(bundles[..2].iter().all(|b| summary.contains(&b.id())));
+
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await?
+        .local_addr()?;
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let token = shutdown.clone();
+    let listener_node = Arc::clone(&receiver);
+    let handle = tokio::spawn(async move {
+        listener_node
+            .start_tcp_listener_with_shutdown(addr.to_string(), token)
+            .await
+    });
+    for _ in 0..50 {
+        if tokio::net::TcpStream::connect(addr).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    let peer = TcpPeer::new(EndpointId::from("dtn://receiver"), addr.to_string())
+        .with_contact_header(sender.contact_header().unwrap());
+    sender.register_peer(Box::new(peer)).await;
+    assert_eq!(sender.forward_stored_bundles().await?, 4);
+
+    // Every bundle left the sender, but only the missing two crossed the link
+    assert_eq!(sender_store.dispatched().len(), 4);
+    assert_eq!(receiver_store.list()?.len(), 4);
+    let metrics = receiver.metrics_snapshot();
+    assert_eq!(metrics.bundles_stored, 2);
+    assert_eq!(metrics.duplicates_suppressed, 0);
+
+    shutdown.cancel();
+    handle.await??;
+    Ok(())
 }
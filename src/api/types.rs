@@ -1,14 +1,75 @@
 use crate::bpv7::bundle::Bundle;
+use serde::Serialize;
+use std::time::Duration;
 
 /// Bundle status information
 #[derive(Debug)]
+// `Single` carries the bundle by value, as it always has, so matching callers keep working
+#[allow(clippy::large_enum_variant)]
 pub enum BundleStatus {
     /// Status of a single bundle
-    Single { id: String, bundle: Bundle },
+    Single {
+        id: String,
+        bundle: Bundle,
+        /// Seconds until the bundle expires; 0 once it has
+        remaining_lifetime: u64,
+        /// Arrival minus creation time for a received bundle, see
+        /// [`DtnNode::bundle_latency`](super::DtnNode::bundle_latency)
+        latency: Option<Duration>,
+    },
     /// Summary status of all bundles
     Summary {
         active: usize,
         expired: usize,
+        /// Active bundles that expire within the node's expiring-soon window
+        expiring_soon: usize,
         total: usize,
     },
 }
+
+/// Result of [`DtnNode::health_check`](super::DtnNode::health_check)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthStatus {
+    /// A probe file could be written to the bundle store
+    pub store_writable: bool,
+    /// Registered peers that answered a reachability check
+    pub reachable_peer_count: usize,
+    /// Fraction of the store's size limit in use; `None` for an unbounded store
+    pub store_usage_ratio: Option<f64>,
+    /// The store is writable and below its limit, and at least one peer is reachable
+    pub ready: bool,
+}
+
+/// Encoding used by [`DtnNode::export_bundle`](super::DtnNode::export_bundle)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// The stored bundle as deterministic CBOR, decodable with `serde_cbor::from_slice`
+    Cbor,
+    /// The stored bundle as pretty-printed JSON
+    JsonPretty,
+    /// Only the payload bytes, decompressed if the bundle was compressed
+    RawPayload,
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::Cbor => write!(f, "cbor"),
+            ExportFormat::JsonPretty => write!(f, "json"),
+            ExportFormat::RawPayload => write!(f, "raw"),
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cbor" => Ok(ExportFormat::Cbor),
+            "json" => Ok(ExportFormat::JsonPretty),
+            "raw" | "payload" => Ok(ExportFormat::RawPayload),
+            _ => anyhow::bail!("Unknown export format '{s}' (expected cbor, json or raw)"),
+        }
+    }
+}
@@ -1,21 +1,91 @@
+use base64::Engine;
 use clap::Parser;
-use sdtn::api::DtnNode;
+use sdtn::api::{DtnNode, ExportFormat};
+use sdtn::bpv7::bundle::Bundle;
 use sdtn::bpv7::EndpointId;
+use sdtn::cla::tcp::client::{connect, send_bundle, SendOptions};
 use sdtn::routing::algorithm::RouteEntry;
+use sdtn::routing::explain::RejectionReason;
 
 #[derive(Parser)]
 struct Opts {
+    /// Emit machine-readable JSON instead of decorated text
+    #[clap(long, global = true)]
+    json: bool,
     #[clap(subcommand)]
     cmd: Command,
 }
 
+/// Output format for commands that support scripting-friendly output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn from_json_flag(json: bool) -> Self {
+        if json {
+            OutputFormat::Json
+        } else {
+            OutputFormat::Text
+        }
+    }
+}
+
+/// Which stored bundles `list` shows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListFilter {
+    Active,
+    Expired,
+}
+
+impl std::str::FromStr for ListFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "active" => Ok(ListFilter::Active),
+            "expired" => Ok(ListFilter::Expired),
+            _ => anyhow::bail!("Unknown list filter '{s}' (expected active or expired)"),
+        }
+    }
+}
+
+/// Build the JSON representation of a bundle, with the payload base64-encoded
+pub fn bundle_to_json(id: &str, bundle: &Bundle) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "primary": bundle.primary,
+        "payload": base64::engine::general_purpose::STANDARD.encode(&bundle.payload),
+        "expired": bundle.is_expired(),
+    })
+}
+
 #[derive(Parser)]
 pub enum Command {
     Insert {
         #[clap(short, long)]
         message: String,
+        /// Bundle lifetime in seconds (defaults to the configured lifetime)
+        #[clap(short, long)]
+        lifetime: Option<u64>,
+        /// Gzip the payload before storing it
+        #[clap(long)]
+        compress: bool,
+    },
+    /// Import a file as a bundle, splitting it into fragments if it is large
+    InsertFile {
+        #[clap(short, long)]
+        path: String,
+        #[clap(short, long)]
+        destination: String,
+    },
+    List {
+        /// Only list active or only expired bundles
+        #[clap(long)]
+        filter: Option<ListFilter>,
     },
-    List,
     Show {
         #[clap(short, long)]
         id: String,
@@ -25,12 +95,40 @@ pub enum Command {
         #[clap(short, long)]
         id: Option<String>,
     },
+    /// Delete a bundle by (partial) ID
+    Delete {
+        #[clap(short, long)]
+        id: String,
+    },
+    /// Write a stored bundle to a file as CBOR, JSON or its raw payload
+    Export {
+        #[clap(short, long)]
+        id: String,
+        /// cbor, json or raw
+        #[clap(short, long, default_value = "cbor")]
+        format: ExportFormat,
+        #[clap(short, long)]
+        out: String,
+    },
+    /// Send a stored bundle to a TCP listener and mark it dispatched
+    Send {
+        #[clap(short, long)]
+        id: String,
+        #[clap(short, long)]
+        addr: String,
+    },
+    /// Show the forwarding history of sent and failed bundles
+    History,
     Receive,
     Daemon {
         #[clap(subcommand)]
         cmd: DaemonCmd,
     },
     Cleanup,
+    /// Show bundle count, size, age and expiry of the store
+    Stats,
+    /// Print the node's health as JSON, exiting nonzero unless it is ready
+    Health,
     Route {
         #[clap(subcommand)]
         cmd: RouteCmd,
@@ -47,6 +145,8 @@ pub enum DaemonCmd {
         #[clap(long)]
         addr: String,
     },
+    /// Start every convergence layer listed under [[cla]] in the config
+    Start,
 }
 
 #[derive(Parser)]
@@ -76,24 +176,75 @@ pub enum RouteCmd {
         #[clap(long, default_value = "10")]
         cost: u32,
     },
+    /// Remove the routes to a destination through one next hop
+    Remove {
+        #[clap(long)]
+        destination: String,
+        #[clap(long)]
+        next_hop: String,
+    },
+    /// Remove every route from the routing table
+    Clear,
     /// Test routing with routing table
     TestTable {
         #[clap(short, long)]
         id: String,
     },
+    /// Explain why each peer and route would or would not be used for a bundle
+    Explain {
+        #[clap(short, long)]
+        id: String,
+    },
 }
 
 // Split command handling into separate functions for better testability
-pub async fn handle_insert_command(node: &DtnNode, message: String) -> anyhow::Result<()> {
+pub async fn handle_insert_command(
+    node: &DtnNode,
+    message: String,
+    lifetime: Option<u64>,
+    compress: bool,
+) -> anyhow::Result<()> {
     println!("📦 Inserting bundle: {message}");
-    node.insert_bundle(message).await?;
-    println!("✅ Bundle inserted successfully!");
+    let id = match (compress, lifetime) {
+        (true, lifetime) => node.insert_bundle_compressed(message, lifetime).await?,
+        (false, Some(lifetime)) => node.insert_bundle_with_lifetime(message, lifetime).await?,
+        (false, None) => node.insert_bundle(message).await?,
+    };
+    println!("✅ Bundle inserted successfully! (ID: {id})");
+    Ok(())
+}
+
+pub async fn handle_insert_file_command(
+    node: &DtnNode,
+    path: String,
+    destination: String,
+) -> anyhow::Result<()> {
+    println!("📦 Importing file: {path} -> {destination}");
+    let ids = node.insert_file(&path, &destination).await?;
+    if let [id] = ids.as_slice() {
+        println!("✅ Bundle inserted successfully! (ID: {id})");
+    } else {
+        println!("✅ File split into {} fragments:", ids.len());
+        for id in ids {
+            println!("  {id}");
+        }
+    }
     Ok(())
 }
 
-pub fn handle_list_command(node: &DtnNode) -> anyhow::Result<()> {
-    let bundles = node.list_bundles()?;
-    if bundles.is_empty() {
+pub fn handle_list_command(
+    node: &DtnNode,
+    filter: Option<ListFilter>,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let bundles = match filter {
+        None => node.list_bundles()?,
+        Some(ListFilter::Active) => node.list_active_bundles()?,
+        Some(ListFilter::Expired) => node.list_expired_bundles()?,
+    };
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&bundles)?);
+    } else if bundles.is_empty() {
         println!("📋 No bundles found");
     } else {
         println!("📋 Found {} bundles:", bundles.len());
@@ -104,8 +255,15 @@ pub fn handle_list_command(node: &DtnNode) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn handle_show_command(node: &DtnNode, id: String) -> anyhow::Result<()> {
+pub fn handle_show_command(node: &DtnNode, id: String, format: OutputFormat) -> anyhow::Result<()> {
     let bundle = node.show_bundle(&id)?;
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&bundle_to_json(&id, &bundle))?
+        );
+        return Ok(());
+    }
     println!("📄 Bundle Details:");
     println!("  Source: {}", bundle.primary.source);
     println!("  Destination: {}", bundle.primary.destination);
@@ -116,16 +274,39 @@ pub fn handle_show_command(node: &DtnNode, id: String) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn handle_status_command(node: &DtnNode, id: Option<String>) -> anyhow::Result<()> {
+pub fn handle_status_command(
+    node: &DtnNode,
+    id: Option<String>,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
     match id {
         Some(bundle_id) => {
             let bundle = node.show_bundle(&bundle_id)?;
+            if format == OutputFormat::Json {
+                let mut value = bundle_to_json(&bundle_id, &bundle);
+                value["status"] = if bundle.is_expired() {
+                    "expired".into()
+                } else {
+                    "active".into()
+                };
+                value["remaining_lifetime"] = bundle.remaining_lifetime().into();
+                value["latency_ms"] = node
+                    .bundle_latency(&bundle_id)
+                    .map(|latency| latency.as_millis() as u64)
+                    .into();
+                println!("{}", serde_json::to_string_pretty(&value)?);
+                return Ok(());
+            }
 
             println!("📄 Bundle Status: {bundle_id}");
             println!("  Source: {}", bundle.primary.source);
             println!("  Destination: {}", bundle.primary.destination);
             println!("  Creation Time: {}", bundle.primary.creation_timestamp);
             println!("  Lifetime: {} seconds", bundle.primary.lifetime);
+            println!("  Remaining: {}s", bundle.remaining_lifetime());
+            if let Some(latency) = node.bundle_latency(&bundle_id) {
+                println!("  Latency: {:.3}s", latency.as_secs_f64());
+            }
             println!(
                 "  Status: {}",
                 if bundle.is_expired() {
@@ -143,10 +324,26 @@ pub fn handle_status_command(node: &DtnNode, id: Option<String>) -> anyhow::Resu
                 sdtn::api::BundleStatus::Summary {
                     active,
                     expired,
+                    expiring_soon,
+                    total,
+                } if format == OutputFormat::Json => {
+                    let value = serde_json::json!({
+                        "active": active,
+                        "expired": expired,
+                        "expiring_soon": expiring_soon,
+                        "total": total,
+                    });
+                    println!("{}", serde_json::to_string_pretty(&value)?);
+                }
+                sdtn::api::BundleStatus::Summary {
+                    active,
+                    expired,
+                    expiring_soon,
                     total,
                 } => {
                     println!("📊 Bundle Status Summary:");
                     println!("  ✅ Active: {active}");
+                    println!("  ⌛ Expiring soon: {expiring_soon}");
                     println!("  ⏰ Expired: {expired}");
                     println!("  📦 Total: {total}");
                 }
@@ -157,8 +354,110 @@ pub fn handle_status_command(node: &DtnNode, id: Option<String>) -> anyhow::Resu
     Ok(())
 }
 
+pub fn handle_delete_command(node: &DtnNode, id: String) -> anyhow::Result<()> {
+    node.delete_bundle(&id)?;
+    println!("✅ Bundle deleted successfully!");
+    Ok(())
+}
+
+pub fn handle_export_command(
+    node: &DtnNode,
+    id: String,
+    format: ExportFormat,
+    out: String,
+) -> anyhow::Result<()> {
+    let bytes = node.export_bundle(&id, format)?;
+    std::fs::write(&out, &bytes)?;
+    println!(
+        "✅ Exported bundle {id} as {format} to {out} ({} bytes)",
+        bytes.len()
+    );
+    Ok(())
+}
+
+pub async fn handle_send_command(node: &DtnNode, id: String, addr: String) -> anyhow::Result<()> {
+    let bundle = node.show_bundle(&id)?;
+    println!("📨 Sending bundle {id} to {addr}");
+
+    let sent = async {
+        let options = SendOptions::default();
+        let mut stream = connect(&addr, node.contact_header().as_ref(), &options).await?;
+        send_bundle(&mut stream, &bundle).await
+    }
+    .await;
+    if let Err(e) = sent {
+        node.record_send_failure(&id, &addr, &e)?;
+        return Err(e);
+    }
+
+    node.dispatch_bundle_via(&id, Some(&addr))?;
+    println!("✅ Bundle sent successfully!");
+    Ok(())
+}
+
+pub fn handle_history_command(node: &DtnNode, format: OutputFormat) -> anyhow::Result<()> {
+    let entries = node.forwarding_history()?;
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else if entries.is_empty() {
+        println!("📜 No forwarding history");
+    } else {
+        println!("📜 Forwarding history ({} entries):", entries.len());
+        for entry in entries {
+            let next_hop = entry.next_hop.as_deref().unwrap_or("-");
+            print!(
+                "  {} {} {} -> {} via {next_hop}",
+                entry.timestamp, entry.outcome, entry.bundle_id, entry.destination
+            );
+            match entry.error {
+                Some(error) => println!(" ({error})"),
+                None => println!(),
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn handle_cleanup_command(node: &DtnNode) -> anyhow::Result<()> {
-    node.cleanup_expired()?;
+    let removed = node.cleanup_expired()?;
+    println!("🧹 Removed {removed} expired bundles");
+    Ok(())
+}
+
+pub fn handle_stats_command(node: &DtnNode, format: OutputFormat) -> anyhow::Result<()> {
+    let stats = node.store_stats()?;
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    println!("📊 Store statistics:");
+    println!("  Bundles: {}", stats.bundle_count);
+    println!(
+        "  Active: {}, expired: {}",
+        stats.active_count, stats.expired_count
+    );
+    println!("  Total bytes: {}", stats.total_bytes);
+    println!(
+        "  Average payload size: {:.1} bytes",
+        stats.average_payload_size()
+    );
+    if let (Some(oldest), Some(newest)) = (
+        stats.oldest_creation_timestamp,
+        stats.newest_creation_timestamp,
+    ) {
+        println!("  Oldest created: {oldest}");
+        println!("  Newest created: {newest}");
+    }
+    Ok(())
+}
+
+pub async fn handle_health_command(node: &DtnNode) -> anyhow::Result<()> {
+    node.register_configured_peers().await?;
+    let health = node.health_check().await;
+    println!("{}", serde_json::to_string_pretty(&health)?);
+    if !health.ready {
+        anyhow::bail!("Node is not ready");
+    }
     Ok(())
 }
 
@@ -232,6 +531,11 @@ pub fn handle_route_add_command(
     cla_type: String,
     cost: u32,
 ) -> anyhow::Result<()> {
+    let destination = EndpointId::parse(&destination)
+        .map_err(|e| anyhow::anyhow!("Invalid --destination: {e}"))?;
+    let next_hop =
+        EndpointId::parse(&next_hop).map_err(|e| anyhow::anyhow!("Invalid --next-hop: {e}"))?;
+
     println!("🧭 Adding route to routing table:");
     println!("  Destination: {destination}");
     println!("  Next hop: {next_hop}");
@@ -239,11 +543,15 @@ pub fn handle_route_add_command(
     println!("  Cost: {cost}");
 
     let entry = RouteEntry {
-        destination: EndpointId::from(&destination),
-        next_hop: EndpointId::from(&next_hop),
+        destination,
+        next_hop,
         cla_type,
         cost,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     match node.add_route(entry) {
@@ -253,6 +561,30 @@ pub fn handle_route_add_command(
     Ok(())
 }
 
+pub fn handle_route_remove_command(
+    node: &DtnNode,
+    destination: String,
+    next_hop: String,
+) -> anyhow::Result<()> {
+    let destination = EndpointId::parse(&destination)
+        .map_err(|e| anyhow::anyhow!("Invalid --destination: {e}"))?;
+    let next_hop =
+        EndpointId::parse(&next_hop).map_err(|e| anyhow::anyhow!("Invalid --next-hop: {e}"))?;
+
+    if node.remove_route(&destination, &next_hop)? {
+        println!("✅ Removed route to {destination} via {next_hop}");
+    } else {
+        println!("No route to {destination} via {next_hop}");
+    }
+    Ok(())
+}
+
+pub fn handle_route_clear_command(node: &DtnNode) -> anyhow::Result<()> {
+    let removed = node.clear_routes()?;
+    println!("🧹 Cleared {removed} routes from the routing table");
+    Ok(())
+}
+
 pub async fn handle_route_test_table_command(node: &DtnNode, id: String) -> anyhow::Result<()> {
     let bundle = node.show_bundle(&id)?;
     println!("🧭 Testing routing table for bundle: {id}");
@@ -299,48 +631,113 @@ pub async fn handle_route_test_table_command(node: &DtnNode, id: String) -> anyh
     Ok(())
 }
 
-pub async fn handle_daemon_listener_command(node: &DtnNode, addr: String) -> anyhow::Result<()> {
-    tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .build()?
-        .block_on(async {
-            node.start_tcp_listener(addr).await.unwrap();
-        });
+pub async fn handle_route_explain_command(node: &DtnNode, id: String) -> anyhow::Result<()> {
+    let bundle = node.show_bundle(&id)?;
+    let explanation = node.explain_forwarding(&bundle).await;
+    println!(
+        "🧭 Forwarding decision for bundle: {}",
+        explanation.bundle_id
+    );
+    println!("  Destination: {}", explanation.destination);
+
+    let verdict = |rejection: Option<RejectionReason>| match rejection {
+        None => "✅ selected".to_string(),
+        Some(reason) => format!("❌ {reason}"),
+    };
+    println!("  Peers ({}):", explanation.peers.len());
+    for decision in &explanation.peers {
+        println!(
+            "    {} [{}]: {}",
+            decision.peer,
+            decision.cla_type,
+            verdict(decision.rejection)
+        );
+    }
+    println!("  Routes ({}):", explanation.routes.len());
+    for decision in &explanation.routes {
+        let route = &decision.route;
+        println!(
+            "    {} via {} (cost: {}, cla: {}): {}",
+            route.destination,
+            route.next_hop,
+            route.cost,
+            route.cla_type,
+            verdict(decision.rejection)
+        );
+    }
+    if explanation.selected_peers().next().is_none() {
+        println!("  ⚠️ The bundle would not be forwarded to any peer");
+    }
     Ok(())
 }
 
+/// Token cancelled on Ctrl-C, so daemons stop and save their state
+fn ctrl_c_token() -> tokio_util::sync::CancellationToken {
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let signal_token = shutdown.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            signal_token.cancel();
+        }
+    });
+    shutdown
+}
+
+pub async fn handle_daemon_listener_command(node: &DtnNode, addr: String) -> anyhow::Result<()> {
+    // Stop accepting connections on Ctrl-C
+    node.start_tcp_listener_with_shutdown(addr, ctrl_c_token())
+        .await
+}
+
+pub async fn handle_daemon_start_command(node: &DtnNode) -> anyhow::Result<()> {
+    // Stop every listener on Ctrl-C
+    let handles = node.start_from_config(ctrl_c_token()).await?;
+    println!("🚀 Started {} CLA listener(s)", handles.len());
+    for handle in handles {
+        if let Err(e) = handle.await? {
+            eprintln!("❌ CLA listener failed: {e}");
+        }
+    }
+    node.save_routing_state().await?;
+    node.save_seen_bundles().await
+}
+
 pub async fn handle_daemon_dialer_command(node: &DtnNode, addr: String) -> anyhow::Result<()> {
-    tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .build()?
-        .block_on(async {
-            node.start_tcp_dialer(addr).await.unwrap();
-        });
-    Ok(())
+    node.start_tcp_dialer(addr).await
 }
 
-pub async fn execute_command(node: &DtnNode, cmd: Command) -> anyhow::Result<()> {
+pub async fn execute_command(
+    node: &DtnNode,
+    cmd: Command,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
     match cmd {
-        Command::Insert { message } => handle_insert_command(node, message).await,
-        Command::List => handle_list_command(node),
-        Command::Show { id } => handle_show_command(node, id),
-        Command::Status { id } => handle_status_command(node, id),
+        Command::Insert {
+            message,
+            lifetime,
+            compress,
+        } => handle_insert_command(node, message, lifetime, compress).await,
+        Command::InsertFile { path, destination } => {
+            handle_insert_file_command(node, path, destination).await
+        }
+        Command::List { filter } => handle_list_command(node, filter, format),
+        Command::Show { id } => handle_show_command(node, id, format),
+        Command::Status { id } => handle_status_command(node, id, format),
+        Command::Delete { id } => handle_delete_command(node, id),
+        Command::Export { id, format, out } => handle_export_command(node, id, format, out),
+        Command::Send { id, addr } => handle_send_command(node, id, addr).await,
+        Command::History => handle_history_command(node, format),
         Command::Receive => {
             todo!();
         }
         Command::Daemon { cmd } => match cmd {
-            DaemonCmd::Listener { addr } => {
-                tokio::runtime::Runtime::new()?
-                    .block_on(async { handle_daemon_listener_command(node, addr).await })?;
-                Ok(())
-            }
-            DaemonCmd::Dialer { addr } => {
-                tokio::runtime::Runtime::new()?
-                    .block_on(async { handle_daemon_dialer_command(node, addr).await })?;
-                Ok(())
-            }
+            DaemonCmd::Listener { addr } => handle_daemon_listener_command(node, addr).await,
+            DaemonCmd::Dialer { addr } => handle_daemon_dialer_command(node, addr).await,
+            DaemonCmd::Start => handle_daemon_start_command(node).await,
         },
         Command::Cleanup => handle_cleanup_command(node),
+        Command::Stats => handle_stats_command(node, format),
+        Command::Health => handle_health_command(node).await,
         Command::Route { cmd } => match cmd {
             RouteCmd::Test { id } => handle_route_test_command(node, id).await,
             RouteCmd::Show => handle_route_show_command(),
@@ -352,7 +749,13 @@ pub async fn execute_command(node: &DtnNode, cmd: Command) -> anyhow::Result<()>
                 cla_type,
                 cost,
             } => handle_route_add_command(node, destination, next_hop, cla_type, cost),
+            RouteCmd::Remove {
+                destination,
+                next_hop,
+            } => handle_route_remove_command(node, destination, next_hop),
+            RouteCmd::Clear => handle_route_clear_command(node),
             RouteCmd::TestTable { id } => handle_route_test_table_command(node, id).await,
+            RouteCmd::Explain { id } => handle_route_explain_command(node, id).await,
         },
     }
 }
@@ -360,6 +763,8 @@ pub async fn execute_command(node: &DtnNode, cmd: Command) -> anyhow::Result<()>
 fn main() -> anyhow::Result<()> {
     env_logger::init();
     let opts = Opts::parse();
+    let format = OutputFormat::from_json_flag(opts.json);
     let node = DtnNode::new()?;
-    tokio::runtime::Runtime::new()?.block_on(async { execute_command(&node, opts.cmd).await })
+    tokio::runtime::Runtime::new()?
+        .block_on(async { execute_command(&node, opts.cmd, format).await })
 }
@@ -1,27 +1,157 @@
+use crate::bpv7::flags::BundleFlags;
+use crate::bpv7::signature::{SignatureBlock, SigningKey, VerifyingKey};
+use crate::bpv7::{BundleId, EndpointId};
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use thiserror::Error;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+static SEQUENCE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Next creation sequence number, distinguishing bundles created by this
+/// process within the same second
+pub fn next_sequence_number() -> u64 {
+    SEQUENCE_COUNTER.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Class of service for a bundle; higher priorities are forwarded first
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Priority {
+    Bulk,
+    #[default]
+    Normal,
+    Expedited,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PrimaryBlock {
     pub version: u8,
     pub destination: String,
     pub source: String,
     pub report_to: String,
     pub creation_timestamp: u64,
+    /// Creation timestamp sequence number; defaults to 0 for bundles stored before it existed
+    #[serde(default)]
+    pub sequence_number: u64,
     pub lifetime: u64,
+    /// Defaults to `Normal` so bundles stored before priorities existed still decode
+    #[serde(default)]
+    pub priority: Priority,
+    /// Whether the payload is gzip-compressed; bundles from nodes without compression decode as `false`
+    #[serde(default)]
+    pub payload_compressed: bool,
+    /// Where this fragment's payload sits in the original payload; omitted from
+    /// the encoding for whole bundles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fragment: Option<FragmentInfo>,
+    /// Bundle processing control flags, read through [`PrimaryBlock::flags`]; omitted
+    /// from the encoding while 0 so bundles stored or signed before they existed
+    /// keep their bytes
+    #[serde(default, skip_serializing_if = "no_flags")]
+    pub flags: u64,
+    /// Node currently holding custody of the bundle, for bundles with
+    /// [`BundleFlags::CUSTODY_REQUESTED`]. Rewritten by every custodian that forwards
+    /// the bundle, so it is not covered by the signature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custodian: Option<String>,
+}
+
+fn no_flags(flags: &u64) -> bool {
+    *flags == 0
+}
+
+/// Structural problems found by [`Bundle::validate`]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BundleError {
+    #[error("Unsupported bundle protocol version {0}, expected 7")]
+    UnsupportedVersion(u8),
+    #[error("Bundle has no destination")]
+    EmptyDestination,
+    #[error("Invalid lifetime of {lifetime} seconds from creation time {creation_timestamp}")]
+    InvalidLifetime {
+        lifetime: u64,
+        creation_timestamp: u64,
+    },
+    #[error("Bundle is flagged as a fragment but carries no fragment offset")]
+    MissingFragmentInfo,
+    #[error("Fragment payload {offset}..{end} lies outside the original {total_length} bytes")]
+    FragmentOutOfRange {
+        offset: u64,
+        end: u64,
+        total_length: u64,
+    },
+}
+
+/// Position of a fragment's payload within the payload of the bundle it was split from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FragmentInfo {
+    /// Byte offset of this fragment's payload in the original payload
+    pub offset: u64,
+    /// Length of the original payload
+    pub total_length: u64,
 }
 
+/// Bundles compare and hash by identity (version, endpoints, creation timestamp,
+/// sequence number and fragment offset), matching the store's notion of the same
+/// bundle; use [`Bundle::content_eq`] to also compare payloads.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bundle {
     pub primary: PrimaryBlock,
     pub payload: Vec<u8>,
+    /// Ed25519 signature extension block; omitted from the encoding when unsigned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureBlock>,
+    /// Previous Node extension block (RFC 9171 §4.4.1): the node that forwarded
+    /// this copy, rewritten at every hop and not covered by the signature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_node: Option<EndpointId>,
+}
+
+impl Bundle {
+    fn identity(&self) -> (u8, &str, &str, u64, u64, Option<u64>) {
+        (
+            self.primary.version,
+            &self.primary.source,
+            &self.primary.destination,
+            self.primary.creation_timestamp,
+            self.primary.sequence_number,
+            self.id().fragment_offset,
+        )
+    }
+
+    /// Encode the bundle as deterministic CBOR (RFC 8949 §4.2): map keys sorted
+    /// canonically, definite lengths and shortest-form integers, so the same
+    /// logical bundle always yields the same bytes. Decodes with `serde_cbor::from_slice`.
+    pub fn to_canonical_cbor(&self) -> anyhow::Result<Vec<u8>> {
+        // `Value::Map` is a BTreeMap whose ordering is the canonical key order
+        let value = serde_cbor::value::to_value(self)?;
+        Ok(serde_cbor::to_vec(&value)?)
+    }
+
+    /// Whether both bundles carry the same primary block and payload bytes
+    pub fn content_eq(&self, other: &Bundle) -> bool {
+        self.primary == other.primary && self.payload == other.payload
+    }
+}
+
+impl PartialEq for Bundle {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl Eq for Bundle {}
+
+impl std::hash::Hash for Bundle {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.identity().hash(state);
+    }
 }
 
 impl Bundle {
     pub fn new(source: &str, destination: &str, payload: Vec<u8>) -> Self {
-        let creation_timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let creation_timestamp = crate::clock::now();
 
         Bundle {
             primary: PrimaryBlock {
@@ -30,17 +160,283 @@ impl Bundle {
                 destination: destination.to_string(),
                 report_to: "none".to_string(),
                 creation_timestamp,
+                sequence_number: next_sequence_number(),
                 lifetime: 3600,
+                priority: Priority::Normal,
+                payload_compressed: false,
+                fragment: None,
+                flags: 0,
+                custodian: None,
+            },
+            payload,
+            signature: None,
+            previous_node: None,
+        }
+    }
+
+    /// Gzip the payload in place and mark it as compressed; a no-op if it already is
+    pub fn compress_payload(&mut self) -> anyhow::Result<()> {
+        use std::io::Write;
+
+        if self.primary.payload_compressed {
+            return Ok(());
+        }
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&self.payload)?;
+        self.payload = encoder.finish()?;
+        self.primary.payload_compressed = true;
+        Ok(())
+    }
+
+    /// Restore a compressed payload in place; a no-op for uncompressed bundles
+    pub fn decompress_payload(&mut self) -> anyhow::Result<()> {
+        use std::io::Read;
+
+        if !self.primary.payload_compressed {
+            return Ok(());
+        }
+        let mut payload = Vec::new();
+        flate2::read::GzDecoder::new(self.payload.as_slice()).read_to_end(&mut payload)?;
+        self.payload = payload;
+        self.primary.payload_compressed = false;
+        Ok(())
+    }
+
+    /// Canonical identifier of this bundle; the single source of truth for bundle IDs
+    pub fn id(&self) -> BundleId {
+        BundleId {
+            source: EndpointId::from(self.primary.source.as_str()),
+            timestamp: self.primary.creation_timestamp,
+            sequence: self.primary.sequence_number,
+            fragment_offset: self.primary.fragment.map(|f| f.offset),
+        }
+    }
+
+    pub fn is_fragment(&self) -> bool {
+        self.primary.fragment.is_some()
+    }
+
+    /// Split the bundle into fragments carrying at most `max_payload` payload bytes
+    /// each. A bundle whose payload already fits, or that must not be fragmented, is
+    /// returned unchanged as the only element. Fragments are unsigned, since a
+    /// signature covers the whole payload.
+    pub fn fragment(&self, max_payload: usize) -> Vec<Bundle> {
+        if self.payload.len() <= max_payload
+            || self.is_fragment()
+            || self
+                .primary
+                .flags()
+                .contains(BundleFlags::MUST_NOT_FRAGMENT)
+        {
+            return vec![self.clone()];
+        }
+        let mut flags = self.primary.flags();
+        flags.insert(BundleFlags::IS_FRAGMENT);
+        let total_length = self.payload.len() as u64;
+        self.payload
+            .chunks(max_payload.max(1))
+            .enumerate()
+            .map(|(index, chunk)| Bundle {
+                primary: PrimaryBlock {
+                    fragment: Some(FragmentInfo {
+                        offset: (index * max_payload.max(1)) as u64,
+                        total_length,
+                    }),
+                    flags: flags.bits(),
+                    ..self.primary.clone()
+                },
+                payload: chunk.to_vec(),
+                signature: None,
+                previous_node: self.previous_node.clone(),
+            })
+            .collect()
+    }
+
+    /// Rebuild the original bundle from all of its fragments, in any order
+    pub fn reassemble(fragments: &[Bundle]) -> anyhow::Result<Bundle> {
+        let mut sorted: Vec<&Bundle> = fragments.iter().collect();
+        sorted.sort_by_key(|bundle| bundle.primary.fragment.map(|f| f.offset));
+        let Some(first) = sorted.first() else {
+            anyhow::bail!("No fragments to reassemble");
+        };
+        let Some(FragmentInfo { total_length, .. }) = first.primary.fragment else {
+            anyhow::bail!("Bundle {} is not a fragment", first.id());
+        };
+
+        let mut payload = Vec::with_capacity(total_length as usize);
+        for bundle in &sorted {
+            let Some(fragment) = bundle.primary.fragment else {
+                anyhow::bail!("Bundle {} is not a fragment", bundle.id());
+            };
+            let same_original = bundle.primary.source == first.primary.source
+                && bundle.primary.creation_timestamp == first.primary.creation_timestamp
+                && bundle.primary.sequence_number == first.primary.sequence_number
+                && fragment.total_length == total_length;
+            if !same_original {
+                anyhow::bail!("Fragment {} belongs to a different bundle", bundle.id());
+            }
+            if fragment.offset != payload.len() as u64 {
+                anyhow::bail!(
+                    "Missing payload bytes {}..{} of {}",
+                    payload.len(),
+                    fragment.offset,
+                    first.id()
+                );
+            }
+            payload.extend_from_slice(&bundle.payload);
+        }
+        if payload.len() as u64 != total_length {
+            anyhow::bail!(
+                "Missing payload bytes {}..{} of {}",
+                payload.len(),
+                total_length,
+                first.id()
+            );
+        }
+
+        let mut flags = first.primary.flags();
+        flags.remove(BundleFlags::IS_FRAGMENT);
+        Ok(Bundle {
+            primary: PrimaryBlock {
+                fragment: None,
+                flags: flags.bits(),
+                ..first.primary.clone()
             },
             payload,
+            signature: None,
+            previous_node: first.previous_node.clone(),
+        })
+    }
+
+    /// Sign the canonical bundle bytes, replacing any existing signature block.
+    /// Compress the payload before signing: changing it afterwards invalidates the signature.
+    pub fn sign(&mut self, key: &SigningKey) -> anyhow::Result<()> {
+        use ed25519_dalek::Signer;
+
+        let signature = key.sign(&self.signed_bytes()?);
+        self.signature = Some(SignatureBlock::new(signature));
+        Ok(())
+    }
+
+    /// Whether the bundle carries a valid signature by `key`; unsigned bundles never verify
+    pub fn verify(&self, key: &VerifyingKey) -> bool {
+        let Some(signature) = self.signature.as_ref().and_then(SignatureBlock::signature) else {
+            return false;
+        };
+        match self.signed_bytes() {
+            Ok(bytes) => key.verify_strict(&bytes, &signature).is_ok(),
+            Err(_) => false,
         }
     }
 
+    /// Bytes covered by the signature: the canonical encoding without the signature
+    /// and previous node blocks or the custodian
+    fn signed_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        Bundle {
+            primary: PrimaryBlock {
+                custodian: None,
+                ..self.primary.clone()
+            },
+            payload: self.payload.clone(),
+            signature: None,
+            previous_node: None,
+        }
+        .to_canonical_cbor()
+    }
+
+    /// Check the invariants every bundle must hold before the node processes it:
+    /// BPv7, a destination, a lifetime that is nonzero and does not overflow the
+    /// clock, and a fragment offset that fits the original payload
+    pub fn validate(&self) -> Result<(), BundleError> {
+        let primary = &self.primary;
+        if primary.version != 7 {
+            return Err(BundleError::UnsupportedVersion(primary.version));
+        }
+        if primary.destination.trim().is_empty() {
+            return Err(BundleError::EmptyDestination);
+        }
+        if primary.lifetime == 0
+            || primary
+                .creation_timestamp
+                .checked_add(primary.lifetime)
+                .is_none()
+        {
+            return Err(BundleError::InvalidLifetime {
+                lifetime: primary.lifetime,
+                creation_timestamp: primary.creation_timestamp,
+            });
+        }
+        match primary.fragment {
+            None if primary.flags().contains(BundleFlags::IS_FRAGMENT) => {
+                Err(BundleError::MissingFragmentInfo)
+            }
+            Some(FragmentInfo {
+                offset,
+                total_length,
+            }) => {
+                let end = offset.saturating_add(self.payload.len() as u64);
+                if end > total_length {
+                    return Err(BundleError::FragmentOutOfRange {
+                        offset,
+                        end,
+                        total_length,
+                    });
+                }
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.primary.is_expired()
+    }
+
+    /// Seconds until the bundle expires; 0 once it has
+    pub fn remaining_lifetime(&self) -> u64 {
+        self.primary.remaining_lifetime()
+    }
+}
+
+impl PrimaryBlock {
+    pub fn flags(&self) -> BundleFlags {
+        BundleFlags::from_bits(self.flags)
+    }
+
+    pub fn set_flags(&mut self, flags: BundleFlags) {
+        self.flags = flags.bits();
+    }
+
+    /// Set or clear a single processing control flag
+    pub fn set_flag(&mut self, flag: BundleFlags, value: bool) {
+        let mut flags = self.flags();
+        flags.set(flag, value);
+        self.set_flags(flags);
+    }
+
+    /// Whether the payload is an administrative record such as a status report
+    pub fn is_admin_record(&self) -> bool {
+        self.flags().contains(BundleFlags::IS_ADMIN_RECORD)
+    }
+
+    /// Whether the source asked for custody transfer, so each node that stores the
+    /// bundle signals its previous custodian
+    pub fn custody_requested(&self) -> bool {
+        self.flags().contains(BundleFlags::CUSTODY_REQUESTED)
+    }
+
+    /// Whether the bundle's lifetime has passed since its creation
     pub fn is_expired(&self) -> bool {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        now > self.primary.creation_timestamp + self.primary.lifetime
+        crate::clock::now() > self.creation_timestamp + self.lifetime
+    }
+
+    /// Seconds until the bundle's lifetime passes; 0 once it has
+    pub fn remaining_lifetime(&self) -> u64 {
+        self.remaining_lifetime_at(crate::clock::now())
+    }
+
+    /// Seconds left of the lifetime at unix time `now`
+    pub fn remaining_lifetime_at(&self, now: u64) -> u64 {
+        (self.creation_timestamp + self.lifetime).saturating_sub(now)
     }
 }
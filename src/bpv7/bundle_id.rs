@@ -0,0 +1,71 @@
+use crate::bpv7::EndpointId;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// Canonical bundle identifier: source EID, creation timestamp and sequence number,
+/// plus the fragment offset for bundle fragments.
+///
+/// Rendered as `{source}/{timestamp}.{sequence}` (with `.{fragment_offset}` appended
+/// for fragments), e.g. `dtn://node1/1700000000.0`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BundleId {
+    pub source: EndpointId,
+    pub timestamp: u64,
+    pub sequence: u64,
+    pub fragment_offset: Option<u64>,
+}
+
+impl BundleId {
+    /// Hex SHA-256 of the canonical form; used as the store key and file name
+    pub fn digest(&self) -> String {
+        format!("{:x}", Sha256::digest(self.to_string().as_bytes()))
+    }
+
+    /// Raw SHA-256 of the canonical form, the bytes behind [`BundleId::digest`]
+    pub fn digest_bytes(&self) -> [u8; 32] {
+        Sha256::digest(self.to_string().as_bytes()).into()
+    }
+}
+
+impl fmt::Display for BundleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}.{}", self.source, self.timestamp, self.sequence)?;
+        if let Some(offset) = self.fragment_offset {
+            write!(f, ".{offset}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BundleId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (source, numbers) = s
+            .rsplit_once('/')
+            .ok_or_else(|| anyhow::anyhow!("Invalid bundle ID: {s}"))?;
+        if source.is_empty() {
+            anyhow::bail!("Invalid bundle ID, missing source: {s}");
+        }
+
+        let parts = numbers
+            .split('.')
+            .map(|part| part.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow::anyhow!("Invalid bundle ID {s}: {e}"))?;
+
+        let (timestamp, sequence, fragment_offset) = match parts.as_slice() {
+            [timestamp, sequence] => (*timestamp, *sequence, None),
+            [timestamp, sequence, offset] => (*timestamp, *sequence, Some(*offset)),
+            _ => anyhow::bail!("Invalid bundle ID, expected timestamp.sequence: {s}"),
+        };
+
+        Ok(BundleId {
+            source: EndpointId::from(source),
+            timestamp,
+            sequence,
+            fragment_offset,
+        })
+    }
+}
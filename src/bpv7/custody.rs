@@ -0,0 +1,66 @@
+use crate::bpv7::bundle::Bundle;
+use crate::bpv7::flags::BundleFlags;
+use crate::bpv7::status_report::{ReportPriorityPolicy, StatusReportReason};
+use crate::bpv7::EndpointId;
+use serde::{Deserialize, Serialize};
+
+/// Administrative record telling a bundle's custodian whether the next node took
+/// custody of it. On acceptance the custodian may delete its copy; on refusal it
+/// keeps the bundle and stays responsible for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustodySignal {
+    /// Canonical ID of the bundle the signal is about
+    pub subject: String,
+    pub accepted: bool,
+    /// Why custody was refused; `NoAdditionalInformation` for acceptances
+    pub reason: StatusReportReason,
+    /// Seconds since the UNIX epoch at which custody was accepted or refused
+    pub timestamp: u64,
+}
+
+impl CustodySignal {
+    pub fn acceptance(bundle: &Bundle) -> Self {
+        Self::new(bundle, true, StatusReportReason::NoAdditionalInformation)
+    }
+
+    pub fn refusal(bundle: &Bundle, reason: StatusReportReason) -> Self {
+        Self::new(bundle, false, reason)
+    }
+
+    fn new(bundle: &Bundle, accepted: bool, reason: StatusReportReason) -> Self {
+        Self {
+            subject: bundle.id().to_string(),
+            accepted,
+            reason,
+            timestamp: crate::clock::now(),
+        }
+    }
+
+    /// Wrap the signal in an administrative record bundle from `source` to the
+    /// administrative endpoint of the subject's custodian, or `None` if the subject
+    /// did not request custody transfer or names no custodian
+    pub fn to_bundle(
+        &self,
+        subject: &Bundle,
+        source: &EndpointId,
+        policy: ReportPriorityPolicy,
+    ) -> anyhow::Result<Option<Bundle>> {
+        let Some(custodian) = subject.primary.custodian.as_deref() else {
+            return Ok(None);
+        };
+        if !subject.primary.custody_requested() {
+            return Ok(None);
+        }
+        let custodian = EndpointId::from(custodian);
+        let destination = custodian.admin_endpoint().unwrap_or(custodian);
+        let payload = serde_cbor::to_vec(self)?;
+        let mut bundle = Bundle::new(source.as_str(), destination.as_str(), payload);
+        bundle.primary.set_flag(BundleFlags::IS_ADMIN_RECORD, true);
+        bundle.primary.priority = policy.priority_for(subject.primary.priority);
+        Ok(Some(bundle))
+    }
+
+    pub fn from_bundle(bundle: &Bundle) -> anyhow::Result<Self> {
+        Ok(serde_cbor::from_slice(&bundle.payload)?)
+    }
+}
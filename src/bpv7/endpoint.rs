@@ -1,8 +1,28 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use thiserror::Error;
 
-/// Endpoint Identifier (EID) as defined in BPv7 specification
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Why a string was rejected by [`EndpointId::parse`]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EidError {
+    #[error("EID is empty")]
+    Empty,
+    #[error("EID '{0}' has no scheme; expected dtn: or ipn:")]
+    MissingScheme(String),
+    #[error("EID '{0}' uses an unsupported scheme; expected dtn: or ipn:")]
+    UnsupportedScheme(String),
+    #[error("Malformed dtn EID '{0}'; expected dtn://<node>[/<service>] or dtn:none")]
+    InvalidDtn(String),
+    #[error("Malformed ipn EID '{0}'; expected ipn:<node>.<service>")]
+    InvalidIpn(String),
+}
+
+/// Endpoint Identifier (EID) as defined in BPv7 specification.
+///
+/// Equality and hashing use the [`normalized`](EndpointId::normalized) form, so
+/// `dtn://Node/` and `dtn://node` are the same endpoint. The original spelling is
+/// kept for display and serialization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EndpointId(String);
 
 impl EndpointId {
@@ -16,20 +36,160 @@ impl EndpointId {
         EndpointId(id.to_string())
     }
 
+    /// Create an EndpointId, rejecting strings that are not well-formed `dtn:` or
+    /// `ipn:` EIDs. A trailing `*` (as in `dtn://sat1/*`) is accepted for wildcard
+    /// patterns. Unlike [`EndpointId::from`], malformed input is an error.
+    pub fn parse(id: &str) -> Result<Self, EidError> {
+        if id.is_empty() {
+            return Err(EidError::Empty);
+        }
+        let Some((scheme, rest)) = id.split_once(':') else {
+            return Err(EidError::MissingScheme(id.to_string()));
+        };
+        let wildcard = rest.ends_with('*');
+        let rest = rest.strip_suffix('*').unwrap_or(rest);
+
+        let valid = match scheme.to_ascii_lowercase().as_str() {
+            "dtn" => Self::is_valid_dtn(rest, wildcard),
+            "ipn" => Self::is_valid_ipn(rest, wildcard),
+            _ => return Err(EidError::UnsupportedScheme(id.to_string())),
+        };
+        match (valid, scheme.eq_ignore_ascii_case("dtn")) {
+            (true, _) => Ok(EndpointId(id.to_string())),
+            (false, true) => Err(EidError::InvalidDtn(id.to_string())),
+            (false, false) => Err(EidError::InvalidIpn(id.to_string())),
+        }
+    }
+
+    /// `//node[/service]` or `none`; the node name may only be empty for a wildcard
+    fn is_valid_dtn(rest: &str, wildcard: bool) -> bool {
+        if rest == "none" && !wildcard {
+            return true;
+        }
+        let Some(hierarchical) = rest.strip_prefix("//") else {
+            return false;
+        };
+        let (node, path) = hierarchical.split_once('/').unwrap_or((hierarchical, ""));
+        let node_ok = if node.is_empty() {
+            wildcard && path.is_empty()
+        } else {
+            node.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~'))
+        };
+        node_ok && !path.chars().any(|c| c.is_whitespace() || c.is_control())
+    }
+
+    /// `<node>.<service>` with decimal numbers; the service may be omitted for a wildcard
+    fn is_valid_ipn(rest: &str, wildcard: bool) -> bool {
+        let is_number = |s: &str| s.bytes().all(|b| b.is_ascii_digit()) && s.parse::<u64>().is_ok();
+        match rest.split_once('.') {
+            Some((node, "")) => wildcard && is_number(node),
+            Some((node, service)) => !wildcard && is_number(node) && is_number(service),
+            None => false,
+        }
+    }
+
     /// Get the string representation
     pub fn as_str(&self) -> &str {
         &self.0
     }
 
+    /// Canonical form of this EID:
+    /// - the scheme is lowercased (`DTN:` becomes `dtn:`)
+    /// - for `scheme://authority/path` EIDs the authority is lowercased, while the
+    ///   path keeps its case
+    /// - repeated slashes in the path are collapsed and any trailing slash is
+    ///   removed, so `dtn://node/` and `dtn://node` normalize to `dtn://node`
+    ///
+    /// EIDs without a scheme are returned unchanged.
+    pub fn normalized(&self) -> EndpointId {
+        let Some((scheme, rest)) = self.0.split_once(':') else {
+            return self.clone();
+        };
+        let scheme = scheme.to_ascii_lowercase();
+
+        match rest.strip_prefix("//") {
+            Some(hierarchical) => {
+                let (authority, path) = hierarchical.split_once('/').unwrap_or((hierarchical, ""));
+                let mut normalized = format!("{scheme}://{}", authority.to_ascii_lowercase());
+                for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+                    normalized.push('/');
+                    normalized.push_str(segment);
+                }
+                EndpointId(normalized)
+            }
+            None => EndpointId(format!("{scheme}:{rest}")),
+        }
+    }
+
     /// Check if this is a valid DTN scheme EID
     pub fn is_dtn_scheme(&self) -> bool {
-        self.0.starts_with("dtn://")
+        self.normalized().0.starts_with("dtn://")
+    }
+
+    /// Administrative endpoint of the node this endpoint belongs to, where status
+    /// reports are delivered (RFC 9171 §4.2.5.2): `dtn://<node>/` for the dtn scheme
+    /// and service 0 (`ipn:<node>.0`) for the ipn scheme. `None` for the null
+    /// endpoint, wildcards and EIDs without a node part.
+    pub fn admin_endpoint(&self) -> Option<EndpointId> {
+        if self.is_null() || self.is_wildcard() {
+            return None;
+        }
+        let normalized = self.normalized();
+        let (scheme, rest) = normalized.0.split_once(':')?;
+        match scheme {
+            "dtn" => {
+                let node = rest.strip_prefix("//")?.split('/').next()?;
+                (!node.is_empty()).then(|| EndpointId(format!("dtn://{node}/")))
+            }
+            "ipn" => {
+                let (node, _) = rest.split_once('.')?;
+                (!node.is_empty()).then(|| EndpointId(format!("ipn:{node}.0")))
+            }
+            _ => None,
+        }
     }
 
     /// Check if this is a null endpoint
     pub fn is_null(&self) -> bool {
         self.0 == "dtn:none" || self.0.is_empty()
     }
+
+    /// Check if this is a wildcard pattern (trailing `*`, e.g. `dtn://sat1/*`)
+    pub fn is_wildcard(&self) -> bool {
+        self.0.ends_with('*')
+    }
+
+    /// Check whether this endpoint matches `pattern`.
+    /// A wildcard pattern matches every endpoint starting with its prefix;
+    /// any other pattern must be equal.
+    pub fn matches(&self, pattern: &EndpointId) -> bool {
+        match pattern.0.strip_suffix('*') {
+            Some(prefix) => {
+                // Normalizing drops the trailing slash, which must still bound the prefix
+                let mut prefix_normalized = EndpointId::from(prefix).normalized().0;
+                if prefix.ends_with('/') && !prefix_normalized.ends_with('/') {
+                    prefix_normalized.push('/');
+                }
+                self.normalized().0.starts_with(&prefix_normalized)
+            }
+            None => self == pattern,
+        }
+    }
+}
+
+impl PartialEq for EndpointId {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0 || self.normalized().0 == other.normalized().0
+    }
+}
+
+impl Eq for EndpointId {}
+
+impl std::hash::Hash for EndpointId {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.normalized().0.hash(state);
+    }
 }
 
 impl From<String> for EndpointId {
@@ -0,0 +1,87 @@
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+
+/// Bundle processing control flags of the primary block (RFC 9171 §4.2.3).
+///
+/// A typed view of [`PrimaryBlock::flags`](crate::bpv7::bundle::PrimaryBlock::flags);
+/// bits this implementation does not know are kept as they are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BundleFlags(u64);
+
+impl BundleFlags {
+    /// The bundle is a fragment
+    pub const IS_FRAGMENT: Self = Self(0x0001);
+    /// The payload is an administrative record, such as a status report
+    pub const IS_ADMIN_RECORD: Self = Self(0x0002);
+    /// The bundle must not be fragmented
+    pub const MUST_NOT_FRAGMENT: Self = Self(0x0004);
+    /// Custody transfer is requested; the bit RFC 5050 assigned to it, which
+    /// RFC 9171 leaves reserved
+    pub const CUSTODY_REQUESTED: Self = Self(0x0008);
+    /// The destination application is asked to acknowledge receipt
+    pub const ACK_REQUESTED: Self = Self(0x0020);
+    /// Status reports should carry the time of the reported event
+    pub const STATUS_TIME_REQUESTED: Self = Self(0x0040);
+    pub const REQUEST_RECEPTION_REPORT: Self = Self(0x4000);
+    pub const REQUEST_FORWARDING_REPORT: Self = Self(0x1_0000);
+    pub const REQUEST_DELIVERY_REPORT: Self = Self(0x2_0000);
+    pub const REQUEST_DELETION_REPORT: Self = Self(0x4_0000);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every bit of `flags` is set
+    pub const fn contains(self, flags: Self) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+
+    pub fn insert(&mut self, flags: Self) {
+        self.0 |= flags.0;
+    }
+
+    pub fn remove(&mut self, flags: Self) {
+        self.0 &= !flags.0;
+    }
+
+    /// Set or clear the bits of `flags`
+    pub fn set(&mut self, flags: Self, value: bool) {
+        if value {
+            self.insert(flags);
+        } else {
+            self.remove(flags);
+        }
+    }
+}
+
+impl BitOr for BundleFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for BundleFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.insert(rhs);
+    }
+}
+
+impl fmt::Display for BundleFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
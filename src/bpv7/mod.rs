@@ -1,7 +1,20 @@
 pub mod bundle;
+pub mod bundle_id;
+pub mod custody;
 pub mod endpoint;
+pub mod flags;
+pub mod reassembly;
+pub mod signature;
+pub mod status_report;
 
-pub use endpoint::EndpointId;
+pub use bundle::BundleError;
+pub use bundle_id::BundleId;
+pub use custody::CustodySignal;
+pub use endpoint::{EidError, EndpointId};
+pub use flags::BundleFlags;
+pub use reassembly::ReassemblyBuffer;
+pub use signature::{SignatureBlock, SigningKey, VerifyingKey};
+pub use status_report::{ReportPriorityPolicy, StatusReport, StatusReportReason};
 
 #[cfg(test)]
 mod tests;
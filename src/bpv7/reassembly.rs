@@ -0,0 +1,102 @@
+use crate::bpv7::bundle::Bundle;
+use crate::bpv7::BundleId;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+/// Fragments received so far of one original bundle
+struct PartialBundle {
+    /// Fragments by payload offset
+    fragments: BTreeMap<u64, Bundle>,
+    total_length: u64,
+    /// Unix time the first fragment arrived
+    first_seen: u64,
+}
+
+impl PartialBundle {
+    /// Whether the fragments together cover every payload byte
+    fn is_complete(&self) -> bool {
+        let mut covered = 0;
+        for (offset, fragment) in &self.fragments {
+            if *offset > covered {
+                return false;
+            }
+            covered = covered.max(offset + fragment.payload.len() as u64);
+        }
+        covered >= self.total_length
+    }
+}
+
+/// Holds received fragments, keyed by the ID of the bundle they were split from,
+/// until every payload byte has arrived and the original can be rebuilt. Sets still
+/// incomplete after `timeout` are dropped by [`ReassemblyBuffer::discard_expired`],
+/// so fragments whose siblings never arrive do not pile up.
+pub struct ReassemblyBuffer {
+    timeout: Duration,
+    pending: HashMap<BundleId, PartialBundle>,
+}
+
+impl ReassemblyBuffer {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Add a received bundle. Whole bundles are returned as they are; a fragment is
+    /// held until it completes its set, at which point the reassembled bundle is
+    /// returned and the set forgotten. Fails if the set cannot be reassembled (e.g.
+    /// fragments disagreeing on the payload length), discarding it.
+    pub fn insert(&mut self, bundle: Bundle) -> anyhow::Result<Option<Bundle>> {
+        let Some(fragment) = bundle.primary.fragment else {
+            return Ok(Some(bundle));
+        };
+        let original = BundleId {
+            fragment_offset: None,
+            ..bundle.id()
+        };
+        let partial = self
+            .pending
+            .entry(original.clone())
+            .or_insert_with(|| PartialBundle {
+                fragments: BTreeMap::new(),
+                total_length: fragment.total_length,
+                first_seen: crate::clock::now(),
+            });
+        partial.fragments.insert(fragment.offset, bundle);
+        if !partial.is_complete() {
+            return Ok(None);
+        }
+
+        let Some(partial) = self.pending.remove(&original) else {
+            return Ok(None);
+        };
+        let fragments: Vec<Bundle> = partial.fragments.into_values().collect();
+        Bundle::reassemble(&fragments).map(Some)
+    }
+
+    /// Drop the sets whose first fragment arrived longer than the timeout ago,
+    /// returning the IDs of the bundles given up on
+    pub fn discard_expired(&mut self) -> Vec<BundleId> {
+        let now = crate::clock::now();
+        let timeout = self.timeout.as_secs();
+        let mut discarded = Vec::new();
+        self.pending.retain(|id, partial| {
+            let expired = now.saturating_sub(partial.first_seen) >= timeout;
+            if expired {
+                discarded.push(id.clone());
+            }
+            !expired
+        });
+        discarded
+    }
+
+    /// Number of bundles with fragments waiting for their siblings
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
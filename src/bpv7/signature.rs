@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+/// Block type code of the RFC 9172 Block Integrity Block
+pub const SIGNATURE_BLOCK_TYPE: u64 = 11;
+
+/// Simplified BPSec integrity block: an Ed25519 signature over the bundle's
+/// canonical CBOR encoding with the signature block itself left out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureBlock {
+    pub block_type: u64,
+    pub signature: Vec<u8>,
+}
+
+impl SignatureBlock {
+    pub fn new(signature: ed25519_dalek::Signature) -> Self {
+        Self {
+            block_type: SIGNATURE_BLOCK_TYPE,
+            signature: signature.to_bytes().to_vec(),
+        }
+    }
+
+    /// The stored signature, or `None` if the block is malformed
+    pub fn signature(&self) -> Option<ed25519_dalek::Signature> {
+        if self.block_type != SIGNATURE_BLOCK_TYPE {
+            return None;
+        }
+        ed25519_dalek::Signature::from_slice(&self.signature).ok()
+    }
+}
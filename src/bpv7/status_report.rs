@@ -0,0 +1,114 @@
+use crate::bpv7::bundle::{Bundle, Priority};
+use crate::bpv7::flags::BundleFlags;
+use serde::{Deserialize, Serialize};
+
+/// Source used for reports generated by this node; RFC 9171 allows status
+/// reports to be sent anonymously
+pub const ANONYMOUS_SOURCE: &str = "dtn:none";
+
+/// Why a bundle was deleted, using the RFC 9171 status report reason codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusReportReason {
+    NoAdditionalInformation = 0,
+    LifetimeExpired = 1,
+    TransmissionCanceled = 3,
+    DepletedStorage = 4,
+    NoKnownRoute = 6,
+    NoTimelyContact = 7,
+}
+
+/// Priority given to the bundle carrying a status report.
+///
+/// Reports compete with regular traffic in the forwarding queue, so by default a
+/// report is never less urgent than the bundle it reports on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportPriorityPolicy {
+    /// The subject's priority, raised to `Normal` for `Bulk` subjects
+    #[default]
+    Inherit,
+    /// Always `Expedited`
+    Expedited,
+    /// Always `Normal`, whatever the subject's priority
+    Normal,
+}
+
+impl ReportPriorityPolicy {
+    /// Priority of a report about a bundle of priority `subject`
+    pub fn priority_for(&self, subject: Priority) -> Priority {
+        match self {
+            ReportPriorityPolicy::Inherit => subject.max(Priority::Normal),
+            ReportPriorityPolicy::Expedited => Priority::Expedited,
+            ReportPriorityPolicy::Normal => Priority::Normal,
+        }
+    }
+}
+
+impl std::fmt::Display for ReportPriorityPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportPriorityPolicy::Inherit => write!(f, "inherit"),
+            ReportPriorityPolicy::Expedited => write!(f, "expedited"),
+            ReportPriorityPolicy::Normal => write!(f, "normal"),
+        }
+    }
+}
+
+/// Simplified bundle status report announcing that a bundle was deleted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusReport {
+    /// Canonical ID of the bundle the report is about
+    pub subject: String,
+    pub deleted: bool,
+    pub reason: StatusReportReason,
+    /// Seconds since the UNIX epoch at which the deletion happened
+    pub timestamp: u64,
+}
+
+impl StatusReport {
+    pub fn deletion(bundle: &Bundle, reason: StatusReportReason) -> Self {
+        Self {
+            subject: bundle.id().to_string(),
+            deleted: true,
+            reason,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    /// Wrap the report in an administrative record bundle addressed to the subject's
+    /// `report_to` endpoint, or `None` if the subject did not ask for reports. Reports
+    /// are never generated about administrative records themselves.
+    ///
+    /// The report is at least as urgent as its subject; see [`ReportPriorityPolicy::Inherit`].
+    pub fn to_bundle(&self, subject: &Bundle) -> anyhow::Result<Option<Bundle>> {
+        self.to_bundle_with_policy(subject, ReportPriorityPolicy::default())
+    }
+
+    /// [`StatusReport::to_bundle`] with the report's priority chosen by `policy`
+    pub fn to_bundle_with_policy(
+        &self,
+        subject: &Bundle,
+        policy: ReportPriorityPolicy,
+    ) -> anyhow::Result<Option<Bundle>> {
+        let report_to = subject.primary.report_to.trim();
+        if report_to.is_empty()
+            || report_to == "none"
+            || report_to == ANONYMOUS_SOURCE
+            || subject.primary.is_admin_record()
+        {
+            return Ok(None);
+        }
+        let payload = serde_cbor::to_vec(self)?;
+        let mut bundle = Bundle::new(ANONYMOUS_SOURCE, report_to, payload);
+        bundle.primary.set_flag(BundleFlags::IS_ADMIN_RECORD, true);
+        bundle.primary.priority = policy.priority_for(subject.primary.priority);
+        Ok(Some(bundle))
+    }
+
+    pub fn from_bundle(bundle: &Bundle) -> anyhow::Result<Self> {
+        Ok(serde_cbor::from_slice(&bundle.payload)?)
+    }
+}
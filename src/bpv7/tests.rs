@@ -1,4 +1,4 @@
-use crate::bpv7::bundle::{Bundle, PrimaryBlock};
+use crate::bpv7::bundle::{Bundle, PrimaryBlock, Priority};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[test]
@@ -9,7 +9,13 @@ fn test_primary_block_creation() {
         source: "src://endpoint".to_string(),
         report_to: "none".to_string(),
         creation_timestamp: 1234567890,
+        sequence_number: 0,
         lifetime: 3600,
+        priority: Priority::Normal,
+        payload_compressed: false,
+        fragment: None,
+        flags: 0,
+        custodian: None,
     };
 
     assert_eq!(primary.version, 7);
@@ -133,6 +139,33 @@ fn test_unicode_endpoints() {
     assert_eq!(bundle.primary.destination, destination);
 }
 
+#[test]
+fn test_bundle_default_priority() {
+    let bundle = Bundle::new("src://test", "dst://test", vec![1, 2, 3]);
+    assert_eq!(bundle.primary.priority, Priority::Normal);
+    assert_eq!(Priority::default(), Priority::Normal);
+}
+
+#[test]
+fn test_priority_ordering() {
+    assert!(Priority::Expedited > Priority::Normal);
+    assert!(Priority::Normal > Priority::Bulk);
+}
+
+#[test]
+fn test_deserialize_bundle_without_priority() {
+    // Bundles serialized before priorities existed must still decode
+    let json = r#"{"primary":{"version":7,"destination":"dst://test","source":"src://test","report_to":"none","creation_timestamp":1234567890,"lifetime":3600},"payload":[1,2,3]}"#;
+    let bundle: Bundle = serde_json::from_str(json).unwrap();
+    assert_eq!(bundle.primary.priority, Priority::Normal);
+
+    let mut expedited = bundle.clone();
+    expedited.primary.priority = Priority::Expedited;
+    let encoded = serde_cbor::to_vec(&expedited).unwrap();
+    let decoded: Bundle = serde_cbor::from_slice(&encoded).unwrap();
+    assert_eq!(decoded.primary.priority, Priority::Expedited);
+}
+
 use crate::bpv7::EndpointId;
 
 #[test]
@@ -168,6 +201,181 @@ fn test_null_endpoint() {
     assert!(!eid3.is_null());
 }
 
+#[test]
+fn test_endpoint_matches() {
+    let eid = EndpointId::from("dtn://sat1/camera");
+
+    assert!(eid.matches(&EndpointId::from("dtn://sat1/camera")));
+    assert!(eid.matches(&EndpointId::from("dtn://sat1/*")));
+    assert!(eid.matches(&EndpointId::from("dtn://*")));
+    assert!(!eid.matches(&EndpointId::from("dtn://sat1")));
+    assert!(!eid.matches(&EndpointId::from("dtn://sat2/*")));
+
+    assert!(EndpointId::from("dtn://sat1/*").is_wildcard());
+    assert!(!eid.is_wildcard());
+}
+
+#[test]
+fn test_endpoint_normalized() {
+    assert_eq!(
+        EndpointId::from("DTN://Node1//camera/")
+            .normalized()
+            .as_str(),
+        "dtn://node1/camera"
+    );
+    assert_eq!(
+        EndpointId::from("dtn://node/").normalized().as_str(),
+        "dtn://node"
+    );
+    assert_eq!(
+        EndpointId::from("dtn:none").normalized().as_str(),
+        "dtn:none"
+    );
+    assert_eq!(EndpointId::from("IPN:1.2").normalized().as_str(), "ipn:1.2");
+    // Path segments keep their case
+    assert_eq!(
+        EndpointId::from("dtn://node/Camera").normalized().as_str(),
+        "dtn://node/Camera"
+    );
+}
+
+#[test]
+fn test_endpoint_admin_endpoint() {
+    assert_eq!(
+        EndpointId::from("DTN://Sat1/camera")
+            .admin_endpoint()
+            .unwrap()
+            .as_str(),
+        "dtn://sat1/"
+    );
+    // The administrative endpoint is the node ID itself
+    assert_eq!(
+        EndpointId::from("dtn://sat1").admin_endpoint(),
+        Some(EndpointId::from("dtn://sat1"))
+    );
+    assert_eq!(
+        EndpointId::from("ipn:42.7")
+            .admin_endpoint()
+            .unwrap()
+            .as_str(),
+        "ipn:42.0"
+    );
+    assert_eq!(EndpointId::from("dtn:none").admin_endpoint(), None);
+    assert_eq!(EndpointId::from("dtn://sat1/*").admin_endpoint(), None);
+    assert_eq!(EndpointId::from("no-scheme").admin_endpoint(), None);
+}
+
+#[test]
+fn test_endpoint_equality_uses_normalized_form() {
+    use std::collections::HashSet;
+    use std::hash::{BuildHasher, RandomState};
+
+    let plain = EndpointId::from("dtn://node");
+    let variants = ["dtn://node/", "DTN://NODE", "dtn://Node//"];
+    let hasher = RandomState::new();
+    for variant in variants {
+        let eid = EndpointId::from(variant);
+        assert_eq!(eid, plain, "{variant}");
+        assert_eq!(hasher.hash_one(&eid), hasher.hash_one(&plain), "{variant}");
+    }
+    assert_ne!(
+        EndpointId::from("dtn://node/a"),
+        EndpointId::from("dtn://node/A")
+    );
+
+    let set: HashSet<EndpointId> = variants.into_iter().map(EndpointId::from).collect();
+    assert_eq!(set.len(), 1);
+
+    // The original spelling is preserved for display
+    assert_eq!(EndpointId::from("dtn://node/").to_string(), "dtn://node/");
+}
+
+#[test]
+fn test_already_sent_ignores_trailing_slash() {
+    use crate::store::BundleDescriptor;
+
+    let bundle = Bundle::new("dtn://src", "dtn://dst", b"x".to_vec());
+    let mut descriptor = BundleDescriptor::new(bundle);
+    descriptor.mark_sent(EndpointId::from("dtn://router/"));
+    assert!(descriptor.has_been_sent_to(&EndpointId::from("dtn://router")));
+}
+
+#[test]
+fn test_endpoint_wildcard_prefix_keeps_segment_boundary() {
+    assert!(EndpointId::from("dtn://SAT1/camera").matches(&EndpointId::from("dtn://sat1/*")));
+    assert!(!EndpointId::from("dtn://sat10/camera").matches(&EndpointId::from("dtn://sat1/*")));
+}
+
+#[test]
+fn test_endpoint_parse_accepts_dtn_and_ipn() {
+    for eid in [
+        "dtn://node1",
+        "dtn://router/",
+        "DTN://Ground-Station.example/telemetry/Camera",
+        "dtn:none",
+        "dtn://sat1/*",
+        "dtn://*",
+        "ipn:1.2",
+        "IPN:977000.1",
+        "ipn:5.*",
+    ] {
+        let parsed = EndpointId::parse(eid).unwrap_or_else(|e| panic!("{eid}: {e}"));
+        // The original spelling is kept, as with `from`
+        assert_eq!(parsed.as_str(), eid);
+    }
+}
+
+#[test]
+fn test_endpoint_parse_rejects_malformed() {
+    use crate::bpv7::EidError;
+
+    assert_eq!(EndpointId::parse(""), Err(EidError::Empty));
+    assert_eq!(
+        EndpointId::parse("invalid-endpoint"),
+        Err(EidError::MissingScheme("invalid-endpoint".to_string()))
+    );
+    assert_eq!(
+        EndpointId::parse("http://example"),
+        Err(EidError::UnsupportedScheme("http://example".to_string()))
+    );
+    for eid in [
+        "dtn:",
+        "dtn:node",
+        "dtn://",
+        "dtn:///service",
+        "dtn://no de",
+        "dtn://node/a b",
+        "dtn://node@host",
+        "dtn:none*",
+    ] {
+        assert_eq!(
+            EndpointId::parse(eid),
+            Err(EidError::InvalidDtn(eid.to_string())),
+            "{eid}"
+        );
+    }
+    for eid in [
+        "ipn:",
+        "ipn:1",
+        "ipn:1.",
+        "ipn:a.b",
+        "ipn:1.2.3",
+        "ipn:+1.2",
+        "ipn:1.2*",
+    ] {
+        assert_eq!(
+            EndpointId::parse(eid),
+            Err(EidError::InvalidIpn(eid.to_string())),
+            "{eid}"
+        );
+    }
+    // The lenient constructor still accepts anything
+    assert_eq!(
+        EndpointId::from("invalid-endpoint").as_str(),
+        "invalid-endpoint"
+    );
+}
+
 #[test]
 fn test_display() {
     let eid = EndpointId::from("dtn://test");
@@ -181,3 +389,666 @@ fn test_serialization() {
     let deserialized: EndpointId = serde_json::from_str(&json).unwrap();
     assert_eq!(eid, deserialized);
 }
+
+#[test]
+fn test_bundle_id_display_and_parse() {
+    use crate::bpv7::BundleId;
+
+    let id = BundleId {
+        source: EndpointId::from("dtn://node1/"),
+        timestamp: 1700000000,
+        sequence: 3,
+        fragment_offset: None,
+    };
+    assert_eq!(id.to_string(), "dtn://node1//1700000000.3");
+    assert_eq!(id.to_string().parse::<BundleId>().unwrap(), id);
+
+    let fragment = BundleId {
+        fragment_offset: Some(4096),
+        ..id.clone()
+    };
+    assert_eq!(fragment.to_string(), "dtn://node1//1700000000.3.4096");
+    assert_eq!(fragment.to_string().parse::<BundleId>().unwrap(), fragment);
+    assert_ne!(fragment.digest(), id.digest());
+}
+
+#[test]
+fn test_bundle_id_parse_invalid() {
+    use crate::bpv7::BundleId;
+
+    assert!("no-separator".parse::<BundleId>().is_err());
+    assert!("/1700000000.0".parse::<BundleId>().is_err());
+    assert!("dtn://node/1700000000".parse::<BundleId>().is_err());
+    assert!("dtn://node/abc.0".parse::<BundleId>().is_err());
+    assert!("dtn://node/1.2.3.4".parse::<BundleId>().is_err());
+}
+
+#[test]
+fn test_bundle_id_from_bundle() {
+    let bundle1 = Bundle::new("dtn://src", "dtn://dest", b"a".to_vec());
+    let bundle2 = Bundle::new("dtn://src", "dtn://dest", b"b".to_vec());
+
+    let id = bundle1.id();
+    assert_eq!(id.source, EndpointId::from("dtn://src"));
+    assert_eq!(id.timestamp, bundle1.primary.creation_timestamp);
+    assert_eq!(id.sequence, bundle1.primary.sequence_number);
+
+    // Bundles created back to back get distinct sequence numbers
+    assert_ne!(bundle1.id(), bundle2.id());
+}
+
+#[test]
+fn test_compress_payload_round_trip() {
+    let payload = b"abcabcabcabc".repeat(100);
+    let mut bundle = Bundle::new("dtn://src", "dtn://dst", payload.clone());
+
+    bundle.compress_payload().unwrap();
+    assert!(bundle.primary.payload_compressed);
+    assert!(bundle.payload.len() < payload.len());
+
+    // Compressing twice must not double-encode
+    let compressed = bundle.payload.clone();
+    bundle.compress_payload().unwrap();
+    assert_eq!(bundle.payload, compressed);
+
+    bundle.decompress_payload().unwrap();
+    assert!(!bundle.primary.payload_compressed);
+    assert_eq!(bundle.payload, payload);
+}
+
+#[test]
+fn test_bundle_without_compression_flag_decodes_uncompressed() {
+    // Bundles written before payload compression existed carry no flag
+    #[derive(serde::Serialize)]
+    struct LegacyPrimaryBlock {
+        version: u8,
+        destination: String,
+        source: String,
+        report_to: String,
+        creation_timestamp: u64,
+        lifetime: u64,
+    }
+    #[derive(serde::Serialize)]
+    struct LegacyBundle {
+        primary: LegacyPrimaryBlock,
+        payload: Vec<u8>,
+    }
+
+    let legacy = LegacyBundle {
+        primary: LegacyPrimaryBlock {
+            version: 7,
+            destination: "dtn://dst".to_string(),
+            source: "dtn://src".to_string(),
+            report_to: "none".to_string(),
+            creation_timestamp: 1,
+            lifetime: 3600,
+        },
+        payload: b"plain".to_vec(),
+    };
+    let encoded = serde_cbor::to_vec(&legacy).unwrap();
+
+    let mut bundle: Bundle = serde_cbor::from_slice(&encoded).unwrap();
+    assert!(!bundle.primary.payload_compressed);
+    bundle.decompress_payload().unwrap();
+    assert_eq!(bundle.payload, b"plain");
+}
+
+#[test]
+fn test_bundle_identity_equality_and_hash() {
+    use std::collections::HashSet;
+    use std::hash::{BuildHasher, RandomState};
+
+    let a = Bundle::new("dtn://src", "dtn://dst", b"first copy".to_vec());
+    let mut b = a.clone();
+
+    let hasher = RandomState::new();
+    assert_eq!(a, b);
+    assert_eq!(hasher.hash_one(&a), hasher.hash_one(&b));
+    assert!(a.content_eq(&b));
+
+    // A different payload does not change identity
+    b.payload = b"second copy".to_vec();
+    b.primary.lifetime = 60;
+    assert_eq!(a, b);
+    assert_eq!(hasher.hash_one(&a), hasher.hash_one(&b));
+    assert!(!a.content_eq(&b));
+
+    let set: HashSet<Bundle> = [a.clone(), b].into_iter().collect();
+    assert_eq!(set.len(), 1);
+
+    // Any identity field tells bundles apart
+    let mut other = a.clone();
+    other.primary.sequence_number += 1;
+    assert_ne!(a, other);
+    let mut other = a.clone();
+    other.primary.destination = "dtn://elsewhere".to_string();
+    assert_ne!(a, other);
+    let mut other = a.clone();
+    other.primary.creation_timestamp += 1;
+    assert_ne!(a, other);
+}
+
+#[test]
+fn test_canonical_cbor_is_deterministic() {
+    let bundle = Bundle::new("dtn://src", "dtn://dst", b"canonical".to_vec());
+
+    let first = bundle.to_canonical_cbor().unwrap();
+    let second = bundle.to_canonical_cbor().unwrap();
+    let cloned = bundle.clone().to_canonical_cbor().unwrap();
+    assert_eq!(first, second);
+    assert_eq!(first, cloned);
+
+    // Round-trips through the regular decoder
+    let decoded: Bundle = serde_cbor::from_slice(&first).unwrap();
+    assert!(decoded.content_eq(&bundle));
+}
+
+#[test]
+fn test_canonical_cbor_sorts_map_keys() {
+    let bundle = Bundle::new("dtn://src", "dtn://dst", b"x".to_vec());
+    let encoded = bundle.to_canonical_cbor().unwrap();
+
+    let value: serde_cbor::Value = serde_cbor::from_slice(&encoded).unwrap();
+    let serde_cbor::Value::Map(top) = value else {
+        panic!("bundle encodes as a map");
+    };
+    // Keys of equal length sort lexically
+    let keys: Vec<_> = top.keys().cloned().collect();
+    assert_eq!(
+        keys,
+        vec![
+            serde_cbor::Value::Text("payload".to_string()),
+            serde_cbor::Value::Text("primary".to_string())
+        ]
+    );
+
+    // The encoded bytes list "payload" before "primary", unlike declaration order
+    let position = |needle: &[u8]| encoded.windows(needle.len()).position(|w| w == needle);
+    assert!(position(b"payload") < position(b"primary"));
+    // Within the primary block, "source" (6 bytes) precedes "version" (7 bytes)
+    assert!(position(b"source") < position(b"version"));
+}
+
+#[test]
+fn test_sign_then_verify() {
+    use crate::bpv7::SigningKey;
+
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let mut bundle = Bundle::new("dtn://src", "dtn://dst", b"signed".to_vec());
+    assert!(!bundle.verify(&key.verifying_key()));
+
+    bundle.sign(&key).unwrap();
+    assert!(bundle.verify(&key.verifying_key()));
+
+    // The signature block survives encoding
+    let decoded: Bundle = serde_cbor::from_slice(&serde_cbor::to_vec(&bundle).unwrap()).unwrap();
+    assert_eq!(decoded.signature, bundle.signature);
+    assert!(decoded.verify(&key.verifying_key()));
+}
+
+#[test]
+fn test_tampered_bundle_fails_verification() {
+    use crate::bpv7::SigningKey;
+
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let mut bundle = Bundle::new("dtn://src", "dtn://dst", b"signed".to_vec());
+    bundle.sign(&key).unwrap();
+
+    let mut tampered = bundle.clone();
+    tampered.payload = b"forged".to_vec();
+    assert!(!tampered.verify(&key.verifying_key()));
+
+    let mut redirected = bundle.clone();
+    redirected.primary.destination = "dtn://elsewhere".to_string();
+    assert!(!redirected.verify(&key.verifying_key()));
+
+    let mut truncated = bundle;
+    truncated.signature.as_mut().unwrap().signature.pop();
+    assert!(!truncated.verify(&key.verifying_key()));
+}
+
+#[test]
+fn test_wrong_key_fails_verification() {
+    use crate::bpv7::SigningKey;
+
+    let mut bundle = Bundle::new("dtn://src", "dtn://dst", b"signed".to_vec());
+    bundle.sign(&SigningKey::from_bytes(&[7u8; 32])).unwrap();
+
+    let other = SigningKey::from_bytes(&[8u8; 32]);
+    assert!(!bundle.verify(&other.verifying_key()));
+}
+
+#[test]
+fn test_unsigned_bundle_encoding_has_no_signature_key() {
+    let bundle = Bundle::new("dtn://src", "dtn://dst", b"x".to_vec());
+    let encoded = bundle.to_canonical_cbor().unwrap();
+    assert!(!encoded
+        .windows(b"signature".len())
+        .any(|w| w == b"signature"));
+}
+
+#[test]
+fn test_fragment_and_reassemble() {
+    let payload: Vec<u8> = (0..25).collect();
+    let bundle = Bundle::new("dtn://node1", "dtn://node2", payload.clone());
+
+    let fragments = bundle.fragment(10);
+    assert_eq!(fragments.len(), 3);
+    let offsets: Vec<u64> = fragments
+        .iter()
+        .map(|f| f.primary.fragment.unwrap().offset)
+        .collect();
+    assert_eq!(offsets, vec![0, 10, 20]);
+    assert!(fragments
+        .iter()
+        .all(|f| f.primary.fragment.unwrap().total_length == 25));
+    assert_eq!(fragments[2].payload, payload[20..]);
+    assert_eq!(fragments[1].id().fragment_offset, Some(10));
+    assert_ne!(fragments[0], bundle);
+
+    let mut shuffled = fragments.clone();
+    shuffled.reverse();
+    let reassembled = Bundle::reassemble(&shuffled).unwrap();
+    assert!(reassembled.content_eq(&bundle));
+    assert!(!reassembled.is_fragment());
+}
+
+#[test]
+fn test_fragment_leaves_small_bundles_whole() {
+    let bundle = Bundle::new("dtn://node1", "dtn://node2", vec![1, 2, 3]);
+    let fragments = bundle.fragment(3);
+    assert_eq!(fragments.len(), 1);
+    assert!(fragments[0].content_eq(&bundle));
+
+    // Whole bundles encode exactly as before fragments existed
+    let encoded = serde_cbor::to_vec(&bundle).unwrap();
+    let value: serde_cbor::Value = serde_cbor::from_slice(&encoded).unwrap();
+    assert!(!format!("{value:?}").contains("fragment"));
+}
+
+#[test]
+fn test_reassemble_rejects_incomplete_or_mixed_fragments() {
+    let bundle = Bundle::new("dtn://node1", "dtn://node2", (0..25).collect());
+    let fragments = bundle.fragment(10);
+
+    let err = Bundle::reassemble(&[fragments[0].clone(), fragments[2].clone()]).unwrap_err();
+    assert!(
+        err.to_string().contains("Missing payload bytes 10..20"),
+        "{err}"
+    );
+    let err = Bundle::reassemble(&fragments[..2]).unwrap_err();
+    assert!(
+        err.to_string().contains("Missing payload bytes 20..25"),
+        "{err}"
+    );
+
+    let other = Bundle::new("dtn://node3", "dtn://node2", (0..25).collect()).fragment(10);
+    let err = Bundle::reassemble(&[fragments[0].clone(), other[1].clone(), fragments[2].clone()])
+        .unwrap_err();
+    assert!(err.to_string().contains("different bundle"), "{err}");
+
+    assert!(Bundle::reassemble(&[bundle]).is_err());
+    assert!(Bundle::reassemble(&[]).is_err());
+}
+
+#[test]
+fn test_reassembly_buffer_rebuilds_complete_set() {
+    use crate::bpv7::ReassemblyBuffer;
+
+    let bundle = Bundle::new("dtn://node1", "dtn://node2", (0..25).collect());
+    let mut buffer = ReassemblyBuffer::new(std::time::Duration::from_secs(60));
+    let mut fragments = bundle.fragment(10).into_iter();
+
+    assert!(buffer.insert(fragments.next().unwrap()).unwrap().is_none());
+    assert!(buffer.insert(fragments.next().unwrap()).unwrap().is_none());
+    assert_eq!(buffer.len(), 1);
+    let reassembled = buffer.insert(fragments.next().unwrap()).unwrap().unwrap();
+    assert!(reassembled.content_eq(&bundle));
+    assert_eq!(reassembled.id(), bundle.id());
+    assert!(buffer.is_empty());
+
+    // Whole bundles pass straight through
+    let whole = Bundle::new("dtn://node1", "dtn://node2", b"whole".to_vec());
+    assert!(buffer
+        .insert(whole.clone())
+        .unwrap()
+        .unwrap()
+        .content_eq(&whole));
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn test_reassembly_buffer_accepts_fragments_out_of_order() {
+    use crate::bpv7::ReassemblyBuffer;
+
+    let first = Bundle::new("dtn://node1", "dtn://node2", (0..25).collect());
+    let mut second = Bundle::new("dtn://node1", "dtn://node2", (100..130).collect());
+    second.primary.sequence_number = first.primary.sequence_number + 1;
+    let first_fragments = first.fragment(10);
+    let second_fragments = second.fragment(10);
+
+    let mut buffer = ReassemblyBuffer::new(std::time::Duration::from_secs(60));
+    // Interleaved and reversed, with a duplicate
+    for fragment in [
+        &first_fragments[2],
+        &second_fragments[1],
+        &first_fragments[2],
+        &second_fragments[0],
+        &first_fragments[0],
+    ] {
+        assert!(buffer.insert(fragment.clone()).unwrap().is_none());
+    }
+    assert_eq!(buffer.len(), 2);
+
+    let reassembled = buffer.insert(first_fragments[1].clone()).unwrap().unwrap();
+    assert!(reassembled.content_eq(&first));
+    let reassembled = buffer.insert(second_fragments[2].clone()).unwrap().unwrap();
+    assert!(reassembled.content_eq(&second));
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn test_reassembly_buffer_discards_incomplete_sets_after_timeout() {
+    use crate::bpv7::ReassemblyBuffer;
+    use crate::clock::MockClock;
+
+    let clock = MockClock::new(1_700_000_000);
+    let _guard = clock.install();
+    let mut buffer = ReassemblyBuffer::new(std::time::Duration::from_secs(60));
+
+    let stale = Bundle::new("dtn://node1", "dtn://node2", (0..25).collect());
+    let stale_fragments = stale.fragment(10);
+    buffer.insert(stale_fragments[0].clone()).unwrap();
+
+    clock.advance(std::time::Duration::from_secs(30));
+    let mut fresh = Bundle::new("dtn://node1", "dtn://node2", (0..25).collect());
+    fresh.primary.sequence_number = stale.primary.sequence_number + 1;
+    buffer.insert(fresh.fragment(10)[0].clone()).unwrap();
+    assert!(buffer.discard_expired().is_empty());
+
+    clock.advance(std::time::Duration::from_secs(30));
+    assert_eq!(buffer.discard_expired(), vec![stale.id()]);
+    assert_eq!(buffer.len(), 1);
+
+    // The late siblings of a discarded set start it over instead of completing it
+    assert!(buffer.insert(stale_fragments[1].clone()).unwrap().is_none());
+    assert!(buffer.insert(stale_fragments[2].clone()).unwrap().is_none());
+    assert_eq!(buffer.len(), 2);
+}
+
+#[test]
+fn test_bundle_flags_set_and_clear_individual_bits() {
+    use crate::bpv7::BundleFlags;
+
+    let mut bundle = Bundle::new("dtn://node1", "dtn://node2", b"flags".to_vec());
+    assert!(bundle.primary.flags().is_empty());
+
+    bundle
+        .primary
+        .set_flag(BundleFlags::REQUEST_DELIVERY_REPORT, true);
+    bundle
+        .primary
+        .set_flag(BundleFlags::MUST_NOT_FRAGMENT, true);
+    assert_eq!(bundle.primary.flags, 0x2_0004);
+    assert!(bundle
+        .primary
+        .flags()
+        .contains(BundleFlags::REQUEST_DELIVERY_REPORT | BundleFlags::MUST_NOT_FRAGMENT));
+    assert!(!bundle.primary.is_admin_record());
+
+    bundle
+        .primary
+        .set_flag(BundleFlags::MUST_NOT_FRAGMENT, false);
+    assert_eq!(bundle.primary.flags(), BundleFlags::REQUEST_DELIVERY_REPORT);
+    assert_eq!(bundle.primary.flags().to_string(), "0x20000");
+}
+
+#[test]
+fn test_bundle_flags_round_trip_serialization() {
+    use crate::bpv7::BundleFlags;
+
+    let mut bundle = Bundle::new("dtn://node1", "dtn://node2", b"flags".to_vec());
+    // Unknown bits survive alongside the ones this node understands
+    bundle
+        .primary
+        .set_flags(BundleFlags::ACK_REQUESTED | BundleFlags::from_bits(1 << 40));
+
+    let decoded: Bundle = serde_cbor::from_slice(&bundle.to_canonical_cbor().unwrap()).unwrap();
+    assert_eq!(decoded.primary.flags, bundle.primary.flags);
+    assert!(decoded.primary.flags().contains(BundleFlags::ACK_REQUESTED));
+
+    // A bundle without flags encodes as it did before they existed, and decodes to 0
+    bundle.primary.set_flags(BundleFlags::empty());
+    let value: serde_cbor::Value =
+        serde_cbor::from_slice(&serde_cbor::to_vec(&bundle).unwrap()).unwrap();
+    assert!(!format!("{value:?}").contains("flags"));
+    let decoded: Bundle = serde_cbor::from_slice(&serde_cbor::to_vec(&bundle).unwrap()).unwrap();
+    assert_eq!(decoded.primary.flags, 0);
+}
+
+#[test]
+fn test_fragmentation_sets_and_clears_fragment_flag() {
+    use crate::bpv7::BundleFlags;
+
+    let mut bundle = Bundle::new("dtn://node1", "dtn://node2", (0..25).collect());
+    bundle
+        .primary
+        .set_flag(BundleFlags::REQUEST_DELETION_REPORT, true);
+    let fragments = bundle.fragment(10);
+    assert!(fragments.iter().all(|f| f
+        .primary
+        .flags()
+        .contains(BundleFlags::IS_FRAGMENT | BundleFlags::REQUEST_DELETION_REPORT)));
+
+    let reassembled = Bundle::reassemble(&fragments).unwrap();
+    assert_eq!(
+        reassembled.primary.flags(),
+        BundleFlags::REQUEST_DELETION_REPORT
+    );
+
+    bundle
+        .primary
+        .set_flag(BundleFlags::MUST_NOT_FRAGMENT, true);
+    let whole = bundle.fragment(10);
+    assert_eq!(whole.len(), 1);
+    assert!(!whole[0].is_fragment());
+}
+
+#[test]
+fn test_status_reports_are_admin_records() {
+    use crate::bpv7::{StatusReport, StatusReportReason};
+
+    let mut subject = Bundle::new("dtn://node1", "dtn://node2", b"lost".to_vec());
+    subject.primary.report_to = "dtn://node1/".to_string();
+    let report = StatusReport::deletion(&subject, StatusReportReason::LifetimeExpired);
+    let report_bundle = report.to_bundle(&subject).unwrap().unwrap();
+    assert!(report_bundle.primary.is_admin_record());
+
+    // No report is ever generated about a report
+    let mut about_report = report_bundle.clone();
+    about_report.primary.report_to = "dtn://node2/".to_string();
+    let report = StatusReport::deletion(&about_report, StatusReportReason::LifetimeExpired);
+    assert!(report.to_bundle(&about_report).unwrap().is_none());
+}
+
+#[test]
+fn test_custody_signals_go_to_the_custodians_admin_endpoint() {
+    use crate::bpv7::{BundleFlags, CustodySignal, EndpointId, StatusReportReason};
+
+    let node2 = EndpointId::from("dtn://node2");
+    let mut subject = Bundle::new("dtn://node1", "dtn://node3", b"held".to_vec());
+    subject.primary.custodian = Some("dtn://node1".to_string());
+    let acceptance = CustodySignal::acceptance(&subject);
+    // Custody was never requested
+    assert!(acceptance
+        .to_bundle(&subject, &node2, Default::default())
+        .unwrap()
+        .is_none());
+
+    subject
+        .primary
+        .set_flag(BundleFlags::CUSTODY_REQUESTED, true);
+    assert!(subject.primary.custody_requested());
+    let signal_bundle = acceptance
+        .to_bundle(&subject, &node2, Default::default())
+        .unwrap()
+        .unwrap();
+    assert!(signal_bundle.primary.is_admin_record());
+    assert_eq!(signal_bundle.primary.source, "dtn://node2");
+    assert_eq!(signal_bundle.primary.destination, "dtn://node1/");
+    let decoded = CustodySignal::from_bundle(&signal_bundle).unwrap();
+    assert!(decoded.accepted);
+    assert_eq!(decoded.subject, subject.id().to_string());
+
+    let refusal = CustodySignal::refusal(&subject, StatusReportReason::DepletedStorage);
+    assert!(!refusal.accepted);
+    assert_eq!(refusal.reason, StatusReportReason::DepletedStorage);
+
+    subject.primary.custodian = None;
+    assert!(acceptance
+        .to_bundle(&subject, &node2, Default::default())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_signature_survives_custodian_change() {
+    use crate::bpv7::SigningKey;
+
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let mut bundle = Bundle::new("dtn://src", "dtn://dst", b"signed".to_vec());
+    bundle.primary.custodian = Some("dtn://src".to_string());
+    bundle.sign(&key).unwrap();
+
+    bundle.primary.custodian = Some("dtn://relay".to_string());
+    assert!(bundle.verify(&key.verifying_key()));
+}
+
+#[test]
+fn test_validate_accepts_well_formed_bundles() {
+    let bundle = Bundle::new("dtn://node1", "dtn://node2", (0..25).collect());
+    assert_eq!(bundle.validate(), Ok(()));
+    for fragment in bundle.fragment(10) {
+        assert_eq!(fragment.validate(), Ok(()));
+    }
+}
+
+#[test]
+fn test_validate_rejects_unsupported_version() {
+    use crate::bpv7::BundleError;
+
+    let mut bundle = Bundle::new("dtn://node1", "dtn://node2", b"v6".to_vec());
+    bundle.primary.version = 6;
+    assert_eq!(bundle.validate(), Err(BundleError::UnsupportedVersion(6)));
+}
+
+#[test]
+fn test_validate_rejects_empty_destination() {
+    use crate::bpv7::BundleError;
+
+    let mut bundle = Bundle::new("dtn://node1", "dtn://node2", b"nowhere".to_vec());
+    bundle.primary.destination = " ".to_string();
+    assert_eq!(bundle.validate(), Err(BundleError::EmptyDestination));
+}
+
+#[test]
+fn test_validate_rejects_zero_or_overflowing_lifetime() {
+    use crate::bpv7::BundleError;
+
+    let mut bundle = Bundle::new("dtn://node1", "dtn://node2", b"ttl".to_vec());
+    bundle.primary.lifetime = 0;
+    assert!(matches!(
+        bundle.validate(),
+        Err(BundleError::InvalidLifetime { lifetime: 0, .. })
+    ));
+
+    bundle.primary.lifetime = u64::MAX;
+    assert!(matches!(
+        bundle.validate(),
+        Err(BundleError::InvalidLifetime { .. })
+    ));
+}
+
+#[test]
+fn test_validate_rejects_fragment_flag_without_fragment_info() {
+    use crate::bpv7::{BundleError, BundleFlags};
+
+    let mut bundle = Bundle::new("dtn://node1", "dtn://node2", b"piece".to_vec());
+    bundle.primary.set_flag(BundleFlags::IS_FRAGMENT, true);
+    assert_eq!(bundle.validate(), Err(BundleError::MissingFragmentInfo));
+}
+
+#[test]
+fn test_validate_rejects_fragment_past_original_length() {
+    use crate::bpv7::bundle::FragmentInfo;
+    use crate::bpv7::BundleError;
+
+    let mut bundle = Bundle::new("dtn://node1", "dtn://node2", vec![0; 10]);
+    bundle.primary.fragment = Some(FragmentInfo {
+        offset: 15,
+        total_length: 20,
+    });
+    assert_eq!(
+        bundle.validate(),
+        Err(BundleError::FragmentOutOfRange {
+            offset: 15,
+            end: 25,
+            total_length: 20
+        })
+    );
+}
+
+#[test]
+fn test_status_report_inherits_subject_priority() {
+    use crate::bpv7::{StatusReport, StatusReportReason};
+
+    let mut subject = Bundle::new("dtn://node1", "dtn://node2", b"urgent".to_vec());
+    subject.primary.report_to = "dtn://node1/".to_string();
+    subject.primary.priority = Priority::Expedited;
+    let report = StatusReport::deletion(&subject, StatusReportReason::NoTimelyContact);
+    let report_bundle = report.to_bundle(&subject).unwrap().unwrap();
+    assert_eq!(report_bundle.primary.priority, Priority::Expedited);
+
+    // Reports about bulk traffic are not starved behind it
+    subject.primary.priority = Priority::Bulk;
+    let report_bundle = report.to_bundle(&subject).unwrap().unwrap();
+    assert_eq!(report_bundle.primary.priority, Priority::Normal);
+}
+
+#[test]
+fn test_report_priority_policies() {
+    use crate::bpv7::{ReportPriorityPolicy, StatusReport, StatusReportReason};
+
+    let mut subject = Bundle::new("dtn://node1", "dtn://node2", b"urgent".to_vec());
+    subject.primary.report_to = "dtn://node1/".to_string();
+    subject.primary.priority = Priority::Expedited;
+    let report = StatusReport::deletion(&subject, StatusReportReason::NoTimelyContact);
+    let priority_with = |policy| {
+        report
+            .to_bundle_with_policy(&subject, policy)
+            .unwrap()
+            .unwrap()
+            .primary
+            .priority
+    };
+    assert_eq!(
+        priority_with(ReportPriorityPolicy::Inherit),
+        Priority::Expedited
+    );
+    assert_eq!(
+        priority_with(ReportPriorityPolicy::Normal),
+        Priority::Normal
+    );
+
+    subject.primary.priority = Priority::Bulk;
+    let report = StatusReport::deletion(&subject, StatusReportReason::NoTimelyContact);
+    assert_eq!(
+        report
+            .to_bundle_with_policy(&subject, ReportPriorityPolicy::Expedited)
+            .unwrap()
+            .unwrap()
+            .primary
+            .priority,
+        Priority::Expedited
+    );
+}
@@ -0,0 +1,286 @@
+use crate::bpv7::EndpointId;
+use crate::cla::manager::ClaManager;
+use crate::cla::tcp::contact::ContactHeader;
+use crate::cla::TcpPeer;
+use crate::consts::beacon::{DEFAULT_GROUP, DEFAULT_PORT, MAX_BEACON_BYTES};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// Neighbor announcement: which endpoint is reachable at which CLA address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Beacon {
+    pub eid: EndpointId,
+    pub cla_type: String,
+    /// Address the announced node's convergence layer listens on
+    pub address: String,
+    /// Seconds after which the neighbor is forgotten unless it announces itself again
+    pub ttl_secs: u32,
+    /// The announced listener expects a TCPCL v4 contact header
+    #[serde(default)]
+    pub tcpcl_v4: bool,
+}
+
+impl Beacon {
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        Ok(serde_cbor::to_vec(self)?)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        Ok(serde_cbor::from_slice(data)?)
+    }
+
+    /// TCP peer for the announced node, speaking the protocol its listener expects
+    pub fn to_peer(&self) -> TcpPeer {
+        let peer = TcpPeer::new(self.eid.clone(), self.address.clone());
+        if self.tcpcl_v4 {
+            peer.with_contact_header(ContactHeader::default())
+        } else {
+            peer
+        }
+    }
+}
+
+/// Where beacons are sent and how often
+#[derive(Debug, Clone)]
+pub struct BeaconConfig {
+    /// Local address to receive beacons on; every node in the mesh uses the same port
+    pub bind: SocketAddrV4,
+    /// Multicast group beacons are sent to
+    pub group: Ipv4Addr,
+    /// Interface to send and join the group on (`0.0.0.0` lets the OS choose)
+    pub interface: Ipv4Addr,
+    pub interval: Duration,
+    /// Lifetime announced in our beacons; defaults to three intervals
+    pub ttl: Duration,
+}
+
+impl BeaconConfig {
+    pub fn new(bind: SocketAddrV4, interval: Duration) -> Self {
+        Self {
+            bind,
+            group: DEFAULT_GROUP,
+            interface: Ipv4Addr::UNSPECIFIED,
+            interval,
+            ttl: interval * 3,
+        }
+    }
+
+    pub fn with_group(mut self, group: Ipv4Addr, interface: Ipv4Addr) -> Self {
+        self.group = group;
+        self.interface = interface;
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// `address` as neighbors can reach it: a loopback or unspecified host is
+    /// replaced by the address of the interface beacons go out on. Host names and
+    /// other addresses are kept as they are.
+    pub fn reachable_address(&self, address: &str) -> String {
+        let Ok(addr) = address.parse::<SocketAddr>() else {
+            return address.to_string();
+        };
+        if !(addr.ip().is_loopback() || addr.ip().is_unspecified()) {
+            return address.to_string();
+        }
+        match self.interface_address() {
+            Some(ip) => SocketAddr::new(IpAddr::V4(ip), addr.port()).to_string(),
+            None => {
+                log::warn!("Announcing {address}, which neighbors may not be able to reach");
+                address.to_string()
+            }
+        }
+    }
+
+    /// Address of the interface beacons are sent on
+    fn interface_address(&self) -> Option<Ipv4Addr> {
+        if !self.interface.is_unspecified() {
+            return Some(self.interface);
+        }
+        // Connecting a UDP socket sends nothing, but makes the OS pick the interface
+        let probe = std::net::UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+        probe.connect((self.group, self.bind.port())).ok()?;
+        match probe.local_addr().ok()?.ip() {
+            IpAddr::V4(ip) if !ip.is_unspecified() => Some(ip),
+            _ => None,
+        }
+    }
+}
+
+impl Default for BeaconConfig {
+    fn default() -> Self {
+        Self::new(
+            SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, DEFAULT_PORT),
+            Duration::from_secs(10),
+        )
+    }
+}
+
+/// A discovered neighbor and when it is forgotten
+struct Neighbor {
+    address: String,
+    tcpcl_v4: bool,
+    expires_at: Instant,
+}
+
+/// Periodically announces this node over UDP multicast and registers the
+/// neighbors it hears as TCP peers of a [`ClaManager`]
+pub struct BeaconService {
+    config: BeaconConfig,
+    local: Beacon,
+    manager: Arc<ClaManager>,
+    neighbors: HashMap<EndpointId, Neighbor>,
+}
+
+impl BeaconService {
+    /// Announce `eid` as reachable over TCP at `address`; a loopback or unspecified
+    /// host is announced as the address of the beacon interface instead
+    pub fn new(
+        config: BeaconConfig,
+        eid: EndpointId,
+        address: String,
+        manager: Arc<ClaManager>,
+    ) -> Self {
+        let local = Beacon {
+            eid,
+            cla_type: "tcp".to_string(),
+            address: config.reachable_address(&address),
+            ttl_secs: config.ttl.as_secs().max(1) as u32,
+            tcpcl_v4: false,
+        };
+        Self {
+            config,
+            local,
+            manager,
+            neighbors: HashMap::new(),
+        }
+    }
+
+    /// Announce that our TCP listener expects a TCPCL v4 contact header
+    pub fn with_tcpcl_v4(mut self, tcpcl_v4: bool) -> Self {
+        self.local.tcpcl_v4 = tcpcl_v4;
+        self
+    }
+
+    /// Bind the beacon socket, then announce and listen until `shutdown` is cancelled
+    pub async fn run(self, shutdown: CancellationToken) -> Result<()> {
+        let socket = bind_multicast(&self.config)?;
+        self.serve(socket, shutdown).await
+    }
+
+    /// Bind the beacon socket now, reporting bind errors to the caller, and run
+    /// the service in a background task
+    pub fn spawn(self, shutdown: CancellationToken) -> Result<JoinHandle<Result<()>>> {
+        let socket = bind_multicast(&self.config)?;
+        Ok(tokio::spawn(self.serve(socket, shutdown)))
+    }
+
+    async fn serve(mut self, socket: UdpSocket, shutdown: CancellationToken) -> Result<()> {
+        let target = SocketAddr::from((self.config.group, self.config.bind.port()));
+        let announcement = self.local.encode()?;
+        let mut ticker = tokio::time::interval(self.config.interval);
+        let mut buf = vec![0u8; MAX_BEACON_BYTES];
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = ticker.tick() => {
+                    if let Err(e) = socket.send_to(&announcement, target).await {
+                        log::warn!("Failed to send beacon to {target}: {e}");
+                    }
+                    self.expire_neighbors().await;
+                }
+                received = socket.recv_from(&mut buf) => {
+                    // Errors such as ICMP reports from earlier sends pass; keep listening
+                    let (len, from) = match received {
+                        Ok(received) => received,
+                        Err(e) => {
+                            log::warn!("Failed to receive beacon: {e}");
+                            continue;
+                        }
+                    };
+                    match Beacon::decode(&buf[..len]) {
+                        Ok(beacon) => self.handle_beacon(beacon).await,
+                        Err(e) => log::debug!("Ignoring malformed beacon from {from}: {e}"),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_beacon(&mut self, beacon: Beacon) {
+        if beacon.eid == self.local.eid {
+            return;
+        }
+        if beacon.cla_type != "tcp" {
+            log::debug!("Ignoring beacon for unsupported CLA '{}'", beacon.cla_type);
+            return;
+        }
+
+        let expires_at = Instant::now() + Duration::from_secs(beacon.ttl_secs.into());
+        match self.neighbors.get_mut(&beacon.eid) {
+            // Repeat announcement: only extend the neighbor's lifetime
+            Some(known) if known.address == beacon.address && known.tcpcl_v4 == beacon.tcpcl_v4 => {
+                known.expires_at = expires_at
+            }
+            known => {
+                if known.is_some() {
+                    self.manager.unregister_peer(beacon.eid.as_str()).await;
+                }
+                log::info!("Discovered neighbor {} at {}", beacon.eid, beacon.address);
+                self.manager.register_peer(Box::new(beacon.to_peer())).await;
+                self.neighbors.insert(
+                    beacon.eid,
+                    Neighbor {
+                        address: beacon.address,
+                        tcpcl_v4: beacon.tcpcl_v4,
+                        expires_at,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Unregister neighbors whose last beacon has outlived its TTL
+    async fn expire_neighbors(&mut self) {
+        let now = Instant::now();
+        let expired: Vec<EndpointId> = self
+            .neighbors
+            .iter()
+            .filter(|(_, neighbor)| neighbor.expires_at <= now)
+            .map(|(eid, _)| eid.clone())
+            .collect();
+        for eid in expired {
+            self.neighbors.remove(&eid);
+            log::info!("Neighbor {eid} timed out");
+            self.manager.unregister_peer(eid.as_str()).await;
+        }
+    }
+}
+
+/// UDP socket joined to the beacon group; the port is shared with other nodes on the host
+fn bind_multicast(config: &BeaconConfig) -> Result<UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.bind(&SocketAddr::V4(config.bind).into())?;
+    socket.join_multicast_v4(&config.group, &config.interface)?;
+    socket.set_multicast_if_v4(&config.interface)?;
+    socket.set_multicast_loop_v4(true)?;
+    socket.set_nonblocking(true)?;
+    Ok(UdpSocket::from_std(socket.into())?)
+}
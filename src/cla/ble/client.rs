@@ -1,11 +1,14 @@
+use crate::bpv7::bundle::Bundle;
 use crate::bpv7::EndpointId;
+use crate::cla::framing::{encode_frame, LENGTH_PREFIX_LEN};
 use crate::cla::peer::ClaPeer;
 use crate::cla::ConvergenceLayer;
-use crate::consts::ble::{ADV_NAME, NOTIFY_CHAR_UUID, WRITE_CHAR_UUID};
+use crate::consts::ble::{ACK, ADV_NAME, DEFAULT_CHUNK_SIZE, NOTIFY_CHAR_UUID, WRITE_CHAR_UUID};
 use async_trait::async_trait;
-use btleplug::api::{Central, Manager as _, Peripheral as _, WriteType};
-use btleplug::platform::Manager;
+use btleplug::api::{Central, Characteristic, Manager as _, Peripheral as _, WriteType};
+use btleplug::platform::{Manager, Peripheral};
 use futures::stream::StreamExt;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time;
 use uuid::Uuid;
@@ -15,7 +18,10 @@ use uuid::Uuid;
 pub struct BlePeer {
     pub peer_id: EndpointId,
     pub device_name: String,
-    pub connection_info: Option<BleConnectionInfo>,
+    /// What the last successful scan found, shared by clones of the peer so a scan
+    /// made through any of them (such as the routing table's) is seen by all
+    connection_info: Arc<Mutex<Option<BleConnectionInfo>>>,
+    pub chunk_size: usize,
 }
 
 /// BLE connection information including device details
@@ -64,17 +70,55 @@ impl BlePeer {
         Self {
             peer_id,
             device_name,
-            connection_info: None,
+            connection_info: Arc::default(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
         }
     }
 
-    pub fn with_connection_info(mut self, info: BleConnectionInfo) -> Self {
-        self.connection_info = Some(info);
+    /// Set the maximum number of bytes written per characteristic write.
+    /// This should not exceed the negotiated ATT MTU minus the 3-byte ATT header.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
         self
     }
 
-    pub fn get_connection_info(&self) -> Option<&BleConnectionInfo> {
-        self.connection_info.as_ref()
+    pub fn with_connection_info(self, info: BleConnectionInfo) -> Self {
+        *self.connection_info.lock().unwrap() = Some(info);
+        self
+    }
+
+    pub fn get_connection_info(&self) -> Option<BleConnectionInfo> {
+        self.connection_info.lock().unwrap().clone()
+    }
+
+    /// Signal strength seen by the most recent successful scan, in dBm
+    pub fn last_rssi(&self) -> Option<i16> {
+        self.connection_info
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|info| info.rssi)
+    }
+
+    /// Scan for the device and keep what was discovered on the peer, so the RSSI,
+    /// TX power and services stay available afterwards for route selection.
+    /// [`ClaPeer::is_reachable`] probes the same way.
+    pub async fn probe(&self) -> bool {
+        let discovered = ble_discover_device(&self.device_name).await.unwrap_or(None);
+        self.record_discovery(discovered)
+    }
+
+    /// Store a discovery result and report whether the device was found.
+    /// A miss keeps the previous info so the last known RSSI is not lost
+    pub fn record_discovery(&self, discovered: Option<BleConnectionInfo>) -> bool {
+        match discovered {
+            Some(info) => {
+                log::debug!("BLE probe of {} found {info:?}", self.device_name);
+                *self.connection_info.lock().unwrap() = Some(info);
+                true
+            }
+            None => false,
+        }
     }
 }
 
@@ -88,7 +132,7 @@ async fn ble_discover_device(device_name: &str) -> anyhow::Result<Option<BleConn
         .next()
         .ok_or_else(|| anyhow::anyhow!("No BLE adapter found"))?;
 
-    println!("🔍 Scanning for BLE device: {device_name}");
+    log::debug!("Scanning for BLE device: {device_name}");
     adapter.start_scan(Default::default()).await?;
     time::sleep(Duration::from_secs(2)).await;
 
@@ -105,6 +149,7 @@ async fn ble_discover_device(device_name: &str) -> anyhow::Result<Option<BleConn
                     if let Some(tx_power) = props.tx_power_level {
                         connection_info.tx_power = Some(tx_power);
                     }
+                    connection_info.services = props.services.clone();
                     connection_info.is_connectable = true;
                     return Ok(Some(connection_info));
                 }
@@ -115,7 +160,7 @@ async fn ble_discover_device(device_name: &str) -> anyhow::Result<Option<BleConn
 }
 
 /// Connect to a BLE device using its connection info
-async fn ble_connect_device(info: &BleConnectionInfo) -> anyhow::Result<()> {
+async fn ble_connect_device(info: &BleConnectionInfo) -> anyhow::Result<Peripheral> {
     let manager = Manager::new().await?;
     let adapter = manager
         .adapters()
@@ -130,11 +175,12 @@ async fn ble_connect_device(info: &BleConnectionInfo) -> anyhow::Result<()> {
                 if name == &info.device_name && props.address.to_string() == info.mac_address {
                     peripheral.connect().await?;
                     peripheral.discover_services().await?;
-                    println!(
+                    log::info!(
                         "Connected to BLE device: {} ({})",
-                        info.device_name, info.mac_address
+                        info.device_name,
+                        info.mac_address
                     );
-                    return Ok(());
+                    return Ok(peripheral);
                 }
             }
         }
@@ -145,15 +191,72 @@ async fn ble_connect_device(info: &BleConnectionInfo) -> anyhow::Result<()> {
     ))
 }
 
+/// Split serialized bundle bytes into chunks of at most `chunk_size` bytes.
+/// The first chunk is prefixed with the 4-byte big-endian total length of `data`
+/// so the receiving peripheral knows when reassembly is complete.
+pub fn split_into_chunks(data: &[u8], chunk_size: usize) -> Vec<Vec<u8>> {
+    let chunk_size = chunk_size.max(LENGTH_PREFIX_LEN + 1);
+    encode_frame(data)
+        .chunks(chunk_size)
+        .map(|c| c.to_vec())
+        .collect()
+}
+
+fn find_characteristic(peripheral: &Peripheral, uuid: &str) -> anyhow::Result<Characteristic> {
+    let uuid = Uuid::parse_str(uuid)?;
+    peripheral
+        .characteristics()
+        .into_iter()
+        .find(|c| c.uuid == uuid)
+        .ok_or_else(|| anyhow::anyhow!("Characteristic not found: {uuid}"))
+}
+
+/// Write data to the peer's write characteristic in chunks and wait for the ACK
+async fn write_chunked(
+    peripheral: &Peripheral,
+    data: &[u8],
+    chunk_size: usize,
+) -> anyhow::Result<()> {
+    let write_char = find_characteristic(peripheral, WRITE_CHAR_UUID)?;
+    let notify_char = find_characteristic(peripheral, NOTIFY_CHAR_UUID)?;
+
+    peripheral.subscribe(&notify_char).await?;
+    let mut notification_stream = peripheral.notifications().await?;
+
+    let chunks = split_into_chunks(data, chunk_size);
+    for (i, chunk) in chunks.iter().enumerate() {
+        peripheral
+            .write(&write_char, chunk, WriteType::WithResponse)
+            .await?;
+        log::debug!("Sent BLE chunk {}/{}", i + 1, chunks.len());
+    }
+
+    match time::timeout(Duration::from_secs(5), notification_stream.next()).await {
+        Ok(Some(notification)) if notification.value == ACK => Ok(()),
+        Ok(Some(notification)) => Err(anyhow::anyhow!(
+            "Unexpected BLE ACK: {:?}",
+            String::from_utf8_lossy(&notification.value)
+        )),
+        Ok(None) => Err(anyhow::anyhow!("BLE notification stream closed before ACK")),
+        Err(_) => Err(anyhow::anyhow!("Timed out waiting for BLE ACK")),
+    }
+}
+
 #[async_trait]
 impl ConvergenceLayer for BlePeer {
     fn address(&self) -> String {
         self.device_name.clone()
     }
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
     async fn activate(&self) -> anyhow::Result<()> {
         if let Some(connection_info) = ble_discover_device(&self.device_name).await? {
-            println!("✅ BLE device found and connection info retrieved:");
-            connection_info.display_info();
+            log::info!("BLE device found: {}", self.device_name);
+            log::debug!("{connection_info:?}");
             // 実際の接続を行う
             ble_connect_device(&connection_info).await?;
             Ok(())
@@ -164,6 +267,21 @@ impl ConvergenceLayer for BlePeer {
             ))
         }
     }
+
+    /// Serialize a bundle and transmit it to this peer in MTU-sized chunks,
+    /// waiting for the ACK notification after the final chunk
+    async fn send_bundle(&self, bundle: &Bundle) -> anyhow::Result<()> {
+        let info = match ble_discover_device(&self.device_name).await? {
+            Some(info) => info,
+            None => anyhow::bail!("BLE device not found: {}", self.device_name),
+        };
+        let peripheral = ble_connect_device(&info).await?;
+
+        let encoded = bundle.to_canonical_cbor()?;
+        let result = write_chunked(&peripheral, &encoded, self.chunk_size).await;
+        peripheral.disconnect().await?;
+        result
+    }
 }
 
 #[async_trait]
@@ -173,10 +291,11 @@ impl ClaPeer for BlePeer {
     }
 
     async fn is_reachable(&self) -> bool {
-        ble_discover_device(&self.device_name)
-            .await
-            .unwrap_or(None)
-            .is_some()
+        self.probe().await
+    }
+
+    fn signal_strength(&self) -> Option<i16> {
+        self.last_rssi()
     }
 
     fn get_cla_type(&self) -> &str {
@@ -184,7 +303,7 @@ impl ClaPeer for BlePeer {
     }
 
     fn get_connection_address(&self) -> String {
-        if let Some(info) = &self.connection_info {
+        if let Some(info) = self.connection_info.lock().unwrap().as_ref() {
             format!("{} ({})", info.device_name, info.mac_address)
         } else {
             self.device_name.clone()
@@ -198,6 +317,10 @@ impl ClaPeer for BlePeer {
     async fn activate(&self) -> anyhow::Result<()> {
         <Self as ConvergenceLayer>::activate(self).await
     }
+
+    async fn send_bundle(&self, bundle: &Bundle) -> anyhow::Result<()> {
+        <Self as ConvergenceLayer>::send_bundle(self, bundle).await
+    }
 }
 
 /// BLE CLA client (for symmetry with TCP)
@@ -218,11 +341,14 @@ impl BleClaClient {
     pub async fn scan_and_store_info(&mut self) -> anyhow::Result<bool> {
         if let Some(info) = ble_discover_device(&self.device_name).await? {
             self.connection_info = Some(info.clone());
-            println!("✅ Device found and connection info stored:");
-            info.display_info();
+            log::info!(
+                "BLE device found and connection info stored: {}",
+                self.device_name
+            );
+            log::debug!("{info:?}");
             Ok(true)
         } else {
-            println!("❌ Device not found: {}", self.device_name);
+            log::warn!("BLE device not found: {}", self.device_name);
             Ok(false)
         }
     }
@@ -253,7 +379,7 @@ async fn _main() -> anyhow::Result<()> {
         .next()
         .expect("No BLE adapter");
 
-    println!("Scanning for peripherals...");
+    log::info!("Scanning for peripherals...");
     adapter.start_scan(Default::default()).await?;
     time::sleep(Duration::from_secs(3)).await;
 
@@ -273,43 +399,20 @@ async fn _main() -> anyhow::Result<()> {
     let peripheral = match maybe_peripheral {
         Some(p) => p,
         None => {
-            println!("No target peripheral found.");
+            log::warn!("No target peripheral found.");
             return Ok(());
         }
     };
 
     peripheral.connect().await?;
     peripheral.discover_services().await?;
-    println!("Connected to peripheral.");
-
-    let chars = peripheral.characteristics();
-    let write_char = chars
-        .iter()
-        .find(|c| c.uuid == Uuid::parse_str(WRITE_CHAR_UUID).unwrap())
-        .expect("Write char not found");
-    let notify_char = chars
-        .iter()
-        .find(|c| c.uuid == Uuid::parse_str(NOTIFY_CHAR_UUID).unwrap())
-        .expect("Notify char not found");
-
-    peripheral.subscribe(notify_char).await?;
+    log::info!("Connected to peripheral.");
 
     let bundle_data = b"HelloBundle".to_vec();
-    peripheral
-        .write(write_char, &bundle_data, WriteType::WithResponse)
-        .await?;
-    println!("Sent bundle.");
-
-    let mut notification_stream = peripheral.notifications().await?;
-    println!("Waiting for ACK...");
-    if let Some(data) = notification_stream.next().await {
-        println!(
-            "Received notify: {:?}",
-            String::from_utf8_lossy(&data.value)
-        );
-    }
+    write_chunked(&peripheral, &bundle_data, DEFAULT_CHUNK_SIZE).await?;
+    log::info!("Sent bundle and received ACK.");
 
     peripheral.disconnect().await?;
-    println!("Disconnected.");
+    log::info!("Disconnected.");
     Ok(())
 }
@@ -1,42 +1,235 @@
-#[cfg(target_os = "linux")]
-use crate::consts::ble::{ADV_NAME, SERVICE_UUID};
-#[cfg(target_os = "linux")]
-use bluer::adv::Advertisement;
-#[cfg(target_os = "linux")]
-use tokio::time::{sleep, Duration};
+use crate::bpv7::bundle::Bundle;
+use crate::cla::ConvergenceLayer;
+use crate::consts::ble::ADV_NAME;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Reassembles chunked characteristic writes into a complete serialized bundle.
+///
+/// The first chunk of a transfer starts with a 4-byte big-endian header holding
+/// the total length of the serialized bundle; every following chunk is raw data.
+#[derive(Debug, Default)]
+pub struct ChunkReassembler {
+    expected_len: Option<usize>,
+    buffer: Vec<u8>,
+}
+
+impl ChunkReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one written chunk. Returns the complete payload once all bytes announced
+    /// by the header have arrived, or `None` while the transfer is still in progress.
+    pub fn push(&mut self, chunk: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        let data = match self.expected_len {
+            Some(_) => chunk,
+            None => {
+                if chunk.len() < 4 {
+                    anyhow::bail!("First BLE chunk too short for length header");
+                }
+                let mut len_buf = [0u8; 4];
+                len_buf.copy_from_slice(&chunk[..4]);
+                self.expected_len = Some(u32::from_be_bytes(len_buf) as usize);
+                &chunk[4..]
+            }
+        };
+
+        self.buffer.extend_from_slice(data);
+        let expected = self.expected_len.unwrap_or_default();
+
+        if self.buffer.len() > expected {
+            self.reset();
+            anyhow::bail!("Received more BLE data than announced ({expected} bytes)");
+        }
+        if self.buffer.len() == expected {
+            self.expected_len = None;
+            return Ok(Some(std::mem::take(&mut self.buffer)));
+        }
+        Ok(None)
+    }
+
+    /// Discard any partially received transfer
+    pub fn reset(&mut self) {
+        self.expected_len = None;
+        self.buffer.clear();
+    }
+
+    /// Whether a transfer is currently in progress
+    pub fn is_in_progress(&self) -> bool {
+        self.expected_len.is_some()
+    }
+}
+
+/// BLE CLA server acting as a GATT peripheral.
+///
+/// Advertises `ADV_NAME`, accepts bundle bytes on `WRITE_CHAR_UUID` and
+/// acknowledges every complete bundle via `NOTIFY_CHAR_UUID`.
+#[derive(Clone)]
+pub struct BleClaServer {
+    pub adv_name: String,
+    pub receive_callback: Arc<dyn Fn(Bundle) + Send + Sync>,
+}
+
+impl BleClaServer {
+    pub fn new(receive_callback: Arc<dyn Fn(Bundle) + Send + Sync>) -> Self {
+        Self {
+            adv_name: ADV_NAME.to_string(),
+            receive_callback,
+        }
+    }
+
+    pub fn with_adv_name(mut self, adv_name: String) -> Self {
+        self.adv_name = adv_name;
+        self
+    }
+}
+
+#[async_trait]
+impl ConvergenceLayer for BleClaServer {
+    fn address(&self) -> String {
+        self.adv_name.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    #[cfg(target_os = "linux")]
+    async fn activate(&self) -> anyhow::Result<()> {
+        linux::serve(self).await
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    async fn activate(&self) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!("BLE server is only supported on Linux"))
+    }
+}
 
 #[cfg(target_os = "linux")]
-#[tokio::main]
-async fn _main() -> anyhow::Result<()> {
-    let session = bluer::Session::new().await?;
-    let adapter = session.default_adapter().await?;
-    adapter.set_powered(true).await?;
-
-    println!("Using Bluetooth adapter: {}", adapter.name());
-
-    // Create a simple advertisement
-    let advertisement = Advertisement {
-        local_name: Some(ADV_NAME.to_string()),
-        service_uuids: vec![SERVICE_UUID.parse().unwrap()].into_iter().collect(),
-        discoverable: Some(true),
-        ..Default::default()
+mod linux {
+    use super::{BleClaServer, ChunkReassembler};
+    use crate::bpv7::bundle::Bundle;
+    use crate::consts::ble::{ACK, NOTIFY_CHAR_UUID, SERVICE_UUID, WRITE_CHAR_UUID};
+    use bluer::adv::Advertisement;
+    use bluer::gatt::local::{
+        Application, Characteristic, CharacteristicNotifier, CharacteristicNotify,
+        CharacteristicNotifyMethod, CharacteristicWrite, CharacteristicWriteMethod, ReqError,
+        Service,
     };
+    use futures::FutureExt;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+    use tokio::time::{sleep, Duration};
+
+    pub(super) async fn serve(server: &BleClaServer) -> anyhow::Result<()> {
+        let session = bluer::Session::new().await?;
+        let adapter = session.default_adapter().await?;
+        adapter.set_powered(true).await?;
+
+        log::info!("Using Bluetooth adapter: {}", adapter.name());
+
+        let advertisement = Advertisement {
+            local_name: Some(server.adv_name.clone()),
+            service_uuids: vec![SERVICE_UUID.parse()?].into_iter().collect(),
+            discoverable: Some(true),
+            ..Default::default()
+        };
+        let _adv_handle = adapter.advertise(advertisement).await?;
+
+        let reassembler = Arc::new(Mutex::new(ChunkReassembler::new()));
+        let notifier: Arc<Mutex<Option<CharacteristicNotifier>>> = Arc::new(Mutex::new(None));
+        let callback = Arc::clone(&server.receive_callback);
+        let write_notifier = Arc::clone(&notifier);
 
-    let _handle = adapter.advertise(advertisement).await?;
-    println!("Advertising BLE Peripheral...");
+        let app = Application {
+            services: vec![Service {
+                uuid: SERVICE_UUID.parse()?,
+                primary: true,
+                characteristics: vec![
+                    Characteristic {
+                        uuid: WRITE_CHAR_UUID.parse()?,
+                        write: Some(CharacteristicWrite {
+                            write: true,
+                            write_without_response: true,
+                            method: CharacteristicWriteMethod::Fun(Box::new(move |chunk, _req| {
+                                let reassembler = Arc::clone(&reassembler);
+                                let notifier = Arc::clone(&write_notifier);
+                                let callback = Arc::clone(&callback);
+                                async move {
+                                    let complete = reassembler.lock().await.push(&chunk);
+                                    match complete {
+                                        Ok(Some(data)) => {
+                                            handle_complete(&data, &callback, &notifier).await
+                                        }
+                                        Ok(None) => Ok(()),
+                                        Err(e) => {
+                                            log::error!("BLE reassembly error: {e}");
+                                            Err(ReqError::InvalidValueLength)
+                                        }
+                                    }
+                                }
+                                .boxed()
+                            })),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                    Characteristic {
+                        uuid: NOTIFY_CHAR_UUID.parse()?,
+                        notify: Some(CharacteristicNotify {
+                            notify: true,
+                            method: CharacteristicNotifyMethod::Fun(Box::new(move |n| {
+                                let notifier = Arc::clone(&notifier);
+                                async move {
+                                    log::info!("BLE central subscribed to notifications");
+                                    *notifier.lock().await = Some(n);
+                                }
+                                .boxed()
+                            })),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
 
-    // For now, we'll just keep the advertising running
-    // The GATT server implementation would need more complex setup
-    // This is a simplified version to get the build working
-    loop {
-        sleep(Duration::from_secs(10)).await;
-        println!("Server running...");
+        let _app_handle = adapter.serve_gatt_application(app).await?;
+        log::info!("BLE CLA Server advertising as {}", server.adv_name);
+
+        loop {
+            sleep(Duration::from_secs(10)).await;
+        }
     }
-}
 
-#[cfg(not(target_os = "linux"))]
-#[tokio::main]
-async fn _main() -> anyhow::Result<()> {
-    println!("BLE server is only supported on Linux");
-    Ok(())
+    async fn handle_complete(
+        data: &[u8],
+        callback: &Arc<dyn Fn(Bundle) + Send + Sync>,
+        notifier: &Mutex<Option<CharacteristicNotifier>>,
+    ) -> Result<(), ReqError> {
+        let bundle = match serde_cbor::from_slice::<Bundle>(data) {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                log::error!("Failed to deserialize BLE bundle: {e}");
+                return Err(ReqError::Failed);
+            }
+        };
+
+        log::info!("Received bundle over BLE ({} bytes)", data.len());
+        callback(bundle);
+
+        if let Some(n) = notifier.lock().await.as_mut() {
+            if let Err(e) = n.notify(ACK.to_vec()).await {
+                log::error!("Failed to send BLE ACK: {e}");
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,183 @@
+use crate::bpv7::bundle::Bundle;
+use crate::cla::manager::ConvergenceLayer;
+use crate::cla::peer::ClaPeer;
+use crate::cla::tcp::ack::AckStatus;
+use crate::cla::tcp::contact::ContactHeader;
+use crate::cla::{
+    BleClaServer, BlePeer, SerialClaListener, SerialPeer, TcpClaListener, TcpPeer, WsClaListener,
+    WsPeer,
+};
+use crate::config::{ClaConfig, ClaType};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Callback a listener hands every received bundle to; TCP listeners send the
+/// returned status back as the bundle's ACK
+pub type ReceiveCallback = Arc<dyn Fn(Bundle) -> AckStatus + Send + Sync>;
+
+/// A convergence layer that accepts bundles until it is shut down
+#[async_trait]
+pub trait ClaListener: Send + Sync {
+    /// CLA type name (e.g., "tcp", "ble")
+    fn cla_type(&self) -> &str;
+
+    /// Address or name the listener accepts bundles on
+    fn address(&self) -> String;
+
+    /// Accept bundles until `shutdown` is cancelled
+    async fn run(&self, shutdown: CancellationToken) -> Result<()>;
+}
+
+#[async_trait]
+impl ClaListener for TcpClaListener {
+    fn cla_type(&self) -> &str {
+        "tcp"
+    }
+
+    fn address(&self) -> String {
+        self.bind_addr.clone()
+    }
+
+    async fn run(&self, shutdown: CancellationToken) -> Result<()> {
+        self.activate_until(shutdown).await
+    }
+}
+
+#[async_trait]
+impl ClaListener for WsClaListener {
+    fn cla_type(&self) -> &str {
+        "ws"
+    }
+
+    fn address(&self) -> String {
+        self.bind_addr.clone()
+    }
+
+    async fn run(&self, shutdown: CancellationToken) -> Result<()> {
+        self.activate_until(shutdown).await
+    }
+}
+
+#[async_trait]
+impl ClaListener for SerialClaListener {
+    fn cla_type(&self) -> &str {
+        "serial"
+    }
+
+    fn address(&self) -> String {
+        self.settings.path.clone()
+    }
+
+    async fn run(&self, shutdown: CancellationToken) -> Result<()> {
+        self.activate_until(shutdown).await
+    }
+}
+
+#[async_trait]
+impl ClaListener for BleClaServer {
+    fn cla_type(&self) -> &str {
+        "ble"
+    }
+
+    fn address(&self) -> String {
+        self.adv_name.clone()
+    }
+
+    async fn run(&self, shutdown: CancellationToken) -> Result<()> {
+        tokio::select! {
+            _ = shutdown.cancelled() => Ok(()),
+            result = self.activate() => result,
+        }
+    }
+}
+
+/// Builds the listeners and dialer peers described by `[[cla]]` config entries
+pub trait ClaFactory: Send + Sync {
+    fn create_listener(
+        &self,
+        config: &ClaConfig,
+        receive_callback: ReceiveCallback,
+    ) -> Result<Box<dyn ClaListener>>;
+
+    fn create_dialer(&self, config: &ClaConfig) -> Result<Box<dyn ClaPeer>>;
+}
+
+/// Factory for the convergence layers built into this crate
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultClaFactory;
+
+impl ClaFactory for DefaultClaFactory {
+    fn create_listener(
+        &self,
+        config: &ClaConfig,
+        receive_callback: ReceiveCallback,
+    ) -> Result<Box<dyn ClaListener>> {
+        let address = config.address.clone();
+        if config.cla_type == ClaType::Tcp {
+            let mut listener = TcpClaListener::with_status_callback(address, receive_callback);
+            if let Some(secs) = config.write_timeout_secs {
+                listener = listener.with_write_timeout(Duration::from_secs(secs));
+            }
+            if config.tcpcl_v4 {
+                listener = listener.with_contact_header(
+                    ContactHeader::default().with_wire_format(config.wire_format),
+                );
+            }
+            return Ok(Box::new(listener));
+        }
+        if config.cla_type == ClaType::Serial {
+            return Ok(Box::new(SerialClaListener::new(
+                config.serial_settings(),
+                receive_callback,
+            )));
+        }
+
+        // WebSocket and BLE acknowledge every decodable bundle alike
+        let callback: Arc<dyn Fn(Bundle) + Send + Sync> = Arc::new(move |bundle| {
+            receive_callback(bundle);
+        });
+        match config.cla_type {
+            ClaType::Ws => Ok(Box::new(WsClaListener::new(address, callback))),
+            ClaType::Ble => Ok(Box::new(BleClaServer::new(callback).with_adv_name(address))),
+            ClaType::Tcp | ClaType::Serial | ClaType::Udp => Err(unsupported(config)),
+        }
+    }
+
+    fn create_dialer(&self, config: &ClaConfig) -> Result<Box<dyn ClaPeer>> {
+        let peer_eid = config.peer_eid();
+        let address = config.address.clone();
+        match config.cla_type {
+            ClaType::Tcp => {
+                let mut peer = TcpPeer::new(peer_eid, address).with_legacy_ack(config.legacy_ack);
+                if let Some(secs) = config.write_timeout_secs {
+                    peer = peer.with_write_timeout(Duration::from_secs(secs));
+                }
+                if config.tcpcl_v4 {
+                    peer = peer.with_contact_header(
+                        ContactHeader::default().with_wire_format(config.wire_format),
+                    );
+                }
+                Ok(Box::new(peer))
+            }
+            ClaType::Ws => Ok(Box::new(WsPeer::new(peer_eid, address))),
+            ClaType::Ble => Ok(Box::new(BlePeer::new(peer_eid, address))),
+            ClaType::Serial => Ok(Box::new(SerialPeer::new(
+                peer_eid,
+                config.serial_settings(),
+            ))),
+            ClaType::Udp => Err(unsupported(config)),
+        }
+    }
+}
+
+fn unsupported(config: &ClaConfig) -> anyhow::Error {
+    anyhow::anyhow!(
+        "No {} convergence layer is available for {} '{}'",
+        config.cla_type,
+        config.role,
+        config.address
+    )
+}
@@ -0,0 +1,151 @@
+//! Length-prefixed bundle frames shared by the stream-based CLAs (TCP and BLE):
+//!
+//! ```text
+//! length (u32 BE) | CBOR bundle
+//! ```
+
+use crate::bpv7::bundle::Bundle;
+use crate::consts::tcp::DEFAULT_READ_TIMEOUT;
+use anyhow::Result;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Size of the big-endian length prefix in front of every frame
+pub const LENGTH_PREFIX_LEN: usize = 4;
+
+/// A bundle frame that cannot be read; the connection is closed after reporting it
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FramingError {
+    #[error("Declared bundle length {len} exceeds limit of {max_bytes} bytes, closing connection")]
+    Oversized { len: usize, max_bytes: usize },
+    #[error("Timed out after {timeout:?} with {received} of {expected} bundle bytes received")]
+    ReadTimeout {
+        expected: usize,
+        received: usize,
+        timeout: Duration,
+    },
+    #[error("Connection closed with {received} of {expected} bundle bytes received")]
+    Truncated { expected: usize, received: usize },
+}
+
+/// A write that did not complete in time, typically because the peer stopped reading
+/// and the link is backed up. Nothing about the bundle is known to have arrived, so
+/// the sender keeps it for a later attempt.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("Timed out after {timeout:?} writing {len} bytes to the peer")]
+pub struct SendTimeout {
+    pub len: usize,
+    pub timeout: Duration,
+}
+
+/// Write all of `data`, failing with [`SendTimeout`] if that takes longer than `timeout`
+pub async fn write_with_timeout<W>(writer: &mut W, data: &[u8], timeout: Duration) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    match tokio::time::timeout(timeout, writer.write_all(data)).await {
+        Ok(written) => Ok(written?),
+        Err(_) => Err(SendTimeout {
+            len: data.len(),
+            timeout,
+        }
+        .into()),
+    }
+}
+
+/// Prefix `data` with its length
+pub fn encode_frame(data: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(LENGTH_PREFIX_LEN + data.len());
+    frame.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    frame.extend_from_slice(data);
+    frame
+}
+
+/// Frame the canonical CBOR of `bundle`
+pub fn encode_bundle(bundle: &Bundle) -> Result<Vec<u8>> {
+    Ok(encode_frame(&bundle.to_canonical_cbor()?))
+}
+
+/// Check an announced frame length against `max_bytes` before anything is allocated for it
+pub fn check_frame_len(len: u32, max_bytes: usize) -> Result<usize, FramingError> {
+    let len = len as usize;
+    if len > max_bytes {
+        return Err(FramingError::Oversized { len, max_bytes });
+    }
+    Ok(len)
+}
+
+/// Fill `data`, failing with [`FramingError::ReadTimeout`] once no bytes arrive for
+/// `timeout` and with [`FramingError::Truncated`] if the stream ends first
+pub async fn read_with_timeout<R>(reader: &mut R, data: &mut [u8], timeout: Duration) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+{
+    let expected = data.len();
+    let mut received = 0;
+    while received < expected {
+        match tokio::time::timeout(timeout, reader.read(&mut data[received..])).await {
+            Ok(Ok(0)) | Ok(Err(_)) => {
+                return Err(FramingError::Truncated { expected, received }.into())
+            }
+            Ok(Ok(n)) => received += n,
+            Err(_) => {
+                return Err(FramingError::ReadTimeout {
+                    expected,
+                    received,
+                    timeout,
+                }
+                .into())
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read the `len` bytes of a frame whose length prefix has been read, checking `len`
+/// against `max_bytes` first and waiting at most `timeout` for each read
+pub async fn read_frame_data<R>(
+    reader: &mut R,
+    len: u32,
+    max_bytes: usize,
+    timeout: Duration,
+) -> Result<Vec<u8>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut data = vec![0u8; check_frame_len(len, max_bytes)?];
+    read_with_timeout(reader, &mut data, timeout).await?;
+    Ok(data)
+}
+
+/// Read one frame of at most `max_bytes`, returning `None` if the stream ends
+/// cleanly before the next frame starts. The wait for the next frame is unbounded;
+/// once it has started, each read of the rest waits at most `timeout`.
+pub async fn read_frame<R>(
+    reader: &mut R,
+    max_bytes: usize,
+    timeout: Duration,
+) -> Result<Option<Vec<u8>>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut len_buf = [0u8; LENGTH_PREFIX_LEN];
+    if reader.read(&mut len_buf[..1]).await? == 0 {
+        return Ok(None);
+    }
+    read_with_timeout(reader, &mut len_buf[1..], timeout).await?;
+    let data = read_frame_data(reader, u32::from_be_bytes(len_buf), max_bytes, timeout).await?;
+    Ok(Some(data))
+}
+
+/// Read one framed CBOR bundle, returning `None` on a clean end of stream
+pub async fn read_framed_bundle<R>(reader: &mut R, max_bytes: usize) -> Result<Option<Bundle>>
+where
+    R: AsyncRead + Unpin,
+{
+    match read_frame(reader, max_bytes, DEFAULT_READ_TIMEOUT).await? {
+        Some(data) => Ok(Some(serde_cbor::from_slice(&data)?)),
+        None => Ok(None),
+    }
+}
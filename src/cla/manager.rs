@@ -1,13 +1,40 @@
 use crate::bpv7::bundle::Bundle;
+use crate::bpv7::EndpointId;
 use crate::cla::peer::ClaPeer;
+use crate::cla::rate_limit::RateLimiter;
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 
 #[async_trait]
 pub trait ConvergenceLayer: Send + Sync {
     fn address(&self) -> String;
+
+    /// Clone this convergence layer into a boxed trait object
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer>;
+
+    /// The concrete convergence layer, for downcasting with `downcast_ref`
+    fn as_any(&self) -> &dyn std::any::Any;
+
     async fn activate(&self) -> anyhow::Result<()>;
+
+    /// Transmit a single bundle over this convergence layer.
+    /// Receive-only CLAs (listeners/servers) keep the default, which reports an error.
+    async fn send_bundle(&self, _bundle: &Bundle) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!(
+            "CLA at {} does not support sending bundles",
+            self.address()
+        ))
+    }
+}
+
+/// Enable cloning for boxed ConvergenceLayer trait objects
+impl Clone for Box<dyn ConvergenceLayer> {
+    fn clone(&self) -> Box<dyn ConvergenceLayer> {
+        self.clone_box()
+    }
 }
 
 // TODO: receive_callbackの責任分担を明確にする
@@ -18,9 +45,23 @@ pub struct ClaManager {
     receive_callback: Arc<dyn Fn(Bundle) + Send + Sync>,
 }
 
+/// Per-peer transmission counters
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeerStats {
+    pub address: String,
+    pub bundles_sent: u64,
+    pub bytes_sent: u64,
+    pub send_failures: u64,
+    /// Unix timestamp (seconds) of the last successful send
+    pub last_success: Option<u64>,
+}
+
 #[derive(Default)]
 struct ClaState {
     peers: Vec<Box<dyn ClaPeer>>,
+    stats: HashMap<String, PeerStats>,
+    /// Transmission caps keyed by peer endpoint
+    rate_limits: HashMap<EndpointId, Arc<RateLimiter>>,
 }
 
 impl ClaManager {
@@ -43,15 +84,117 @@ impl ClaManager {
             .iter()
             .any(|p| p.get_peer_endpoint_id() == peer_id)
         {
-            println!("Peer already registered: {peer_id}");
+            log::debug!("Peer already registered: {peer_id}");
             return;
         }
         if let Err(e) = peer.activate().await {
-            println!("Failed to activate peer {peer_id}: {e}");
+            log::warn!("Failed to activate peer {peer_id}: {e}");
         }
         state.peers.push(peer);
     }
 
+    /// Remove a peer by endpoint ID or connection address.
+    /// Returns true if a peer was removed.
+    pub async fn unregister_peer(&self, address: &str) -> bool {
+        let mut state = self.state.write().await;
+        let before = state.peers.len();
+        state.peers.retain(|p| {
+            p.get_peer_endpoint_id().as_str() != address && p.get_connection_address() != address
+        });
+        let removed = state.peers.len() != before;
+        if removed {
+            log::info!("Peer unregistered: {address}");
+        }
+        removed
+    }
+
+    /// Probe every registered peer and drop those that are not reachable
+    pub async fn clear_unreachable(&self) {
+        // Probe without holding the lock, since reachability checks may take seconds
+        let peers = self.list_all_peers().await;
+        let reachable = probe_all(&peers).await;
+        let unreachable: Vec<EndpointId> = peers
+            .iter()
+            .zip(reachable)
+            .filter(|(_, reachable)| !reachable)
+            .map(|(peer, _)| peer.get_peer_endpoint_id())
+            .collect();
+
+        let mut state = self.state.write().await;
+        state
+            .peers
+            .retain(|p| !unreachable.contains(&p.get_peer_endpoint_id()));
+        for peer_id in &unreachable {
+            log::info!("Peer unreachable, removed: {peer_id}");
+        }
+    }
+
+    /// Throttle every bundle sent to `peer_id` through `limiter`
+    pub async fn set_rate_limit(&self, peer_id: EndpointId, limiter: RateLimiter) {
+        let mut state = self.state.write().await;
+        state.rate_limits.insert(peer_id, Arc::new(limiter));
+    }
+
+    /// Deliver a bundle to a registered peer through its convergence layer,
+    /// waiting first if the peer's rate limit has no room for it
+    pub async fn send_bundle(&self, peer_id: &EndpointId, bundle: &Bundle) -> anyhow::Result<()> {
+        let (peer, limiter) = {
+            let st = self.state.read().await;
+            let peer = st
+                .peers
+                .iter()
+                .find(|p| &p.get_peer_endpoint_id() == peer_id)
+                .map(|p| p.clone_box());
+            (peer, st.rate_limits.get(peer_id).cloned())
+        };
+        let peer = match peer {
+            Some(peer) => peer,
+            None => return Err(anyhow::anyhow!("Peer not registered: {peer_id}")),
+        };
+
+        let size = bundle
+            .to_canonical_cbor()
+            .map(|encoded| encoded.len() as u64)
+            .unwrap_or(0);
+        if let Some(limiter) = limiter {
+            limiter.acquire(size).await;
+        }
+        let result = peer.send_bundle(bundle).await;
+
+        let address = peer.get_connection_address();
+        let mut st = self.state.write().await;
+        let stats = st
+            .stats
+            .entry(address.clone())
+            .or_insert_with(|| PeerStats {
+                address,
+                ..Default::default()
+            });
+        match &result {
+            Ok(()) => {
+                stats.bundles_sent += 1;
+                stats.bytes_sent += size;
+                stats.last_success = Some(
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs(),
+                );
+            }
+            Err(_) => stats.send_failures += 1,
+        }
+        result
+    }
+
+    /// Transmission counters for every peer a bundle has been dispatched to,
+    /// sorted by peer address
+    pub async fn peer_stats(&self) -> Vec<PeerStats> {
+        let st = self.state.read().await;
+        let mut stats: Vec<PeerStats> = st.stats.values().cloned().collect();
+        stats.sort_by(|a, b| a.address.cmp(&b.address));
+        stats
+    }
+
     pub fn notify_receive(&self, bundle: Bundle) {
         let cb = Arc::clone(&self.receive_callback);
         tokio::spawn(async move {
@@ -65,16 +208,15 @@ impl ClaManager {
         st.peers.iter().map(|p| p.clone_box()).collect()
     }
 
-    /// List only reachable peers (filtered by is_reachable())
+    /// List only reachable peers (filtered by is_reachable()), probing them concurrently
     pub async fn list_reachable_peers(&self) -> Vec<Box<dyn ClaPeer>> {
-        let st = self.state.read().await;
-        let mut reachable = Vec::new();
-        for peer in &st.peers {
-            if peer.is_reachable().await {
-                reachable.push(peer.clone_box());
-            }
-        }
-        reachable
+        let peers = self.list_all_peers().await;
+        let reachable = probe_all(&peers).await;
+        peers
+            .into_iter()
+            .zip(reachable)
+            .filter_map(|(peer, reachable)| reachable.then_some(peer))
+            .collect()
     }
 
     /// Alias for list_reachable_peers (backward compatibility)
@@ -91,3 +233,8 @@ impl Clone for ClaManager {
         }
     }
 }
+
+/// Probe every peer at once, so the slowest probe rather than their sum bounds the wait
+async fn probe_all(peers: &[Box<dyn ClaPeer>]) -> Vec<bool> {
+    futures::future::join_all(peers.iter().map(|peer| peer.is_reachable())).await
+}
@@ -1,13 +1,29 @@
+pub mod beacon;
 pub mod ble;
+pub mod factory;
+pub mod framing;
 pub mod manager;
 pub mod peer;
+pub mod rate_limit;
+pub mod serial;
 pub mod tcp;
+pub mod ws;
 
+pub use beacon::{Beacon, BeaconConfig, BeaconService};
 pub use ble::client::{BleClaClient, BlePeer};
+pub use ble::server::BleClaServer;
+pub use factory::{ClaFactory, ClaListener, DefaultClaFactory, ReceiveCallback};
 pub use manager::ClaManager;
 pub use manager::ConvergenceLayer;
+pub use manager::PeerStats;
 pub use peer::ClaPeer;
+pub use rate_limit::RateLimiter;
+pub use serial::{
+    client::SerialClaClient, client::SerialPeer, port::SerialParity, port::SerialSettings,
+    server::SerialClaListener,
+};
 pub use tcp::{client::TcpClaClient, client::TcpPeer, server::TcpClaListener};
+pub use ws::{client::WsClaClient, client::WsPeer, server::WsClaListener};
 
 #[cfg(test)]
 mod tests;
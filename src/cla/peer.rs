@@ -1,3 +1,4 @@
+use crate::bpv7::bundle::Bundle;
 use crate::bpv7::EndpointId;
 use async_trait::async_trait;
 
@@ -20,12 +21,28 @@ pub trait ClaPeer: Send + Sync {
     /// Get the connection address/identifier for this peer
     fn get_connection_address(&self) -> String;
 
+    /// Received signal strength in dBm seen by the last successful reachability
+    /// check, for link types that measure one (BLE). Others keep the default.
+    fn signal_strength(&self) -> Option<i16> {
+        None
+    }
+
     /// Clone this peer into a boxed trait object
     fn clone_box(&self) -> Box<dyn ClaPeer>;
 
     /// Activate this peer's convergence layer
     /// This delegates to the underlying ConvergenceLayer implementation
     async fn activate(&self) -> anyhow::Result<()>;
+
+    /// Send a bundle to this peer
+    /// This delegates to the underlying ConvergenceLayer implementation
+    async fn send_bundle(&self, _bundle: &Bundle) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!(
+            "{} peer {} does not support sending bundles",
+            self.get_cla_type(),
+            self.get_peer_endpoint_id()
+        ))
+    }
 }
 
 /// Enable cloning for boxed ClaPeer trait objects
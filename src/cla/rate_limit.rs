@@ -0,0 +1,81 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Token bucket that caps the bytes per second sent over a convergence layer.
+///
+/// The bucket starts full and holds up to `burst` bytes (one second's worth by
+/// default). `acquire` only takes tokens at the moment it returns, so dropping a
+/// waiting `acquire` future never consumes any.
+#[derive(Debug)]
+pub struct RateLimiter {
+    bytes_per_second: u64,
+    burst: u64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Available bytes; negative after a transmission larger than the burst
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Limit transmissions to `bytes_per_second`, which must be greater than 0
+    pub fn new(bytes_per_second: u64) -> Self {
+        assert!(bytes_per_second > 0, "rate limit must be greater than 0");
+        Self {
+            bytes_per_second,
+            burst: bytes_per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: bytes_per_second as f64,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Allow at most `burst` bytes to be sent back to back after an idle period
+    pub fn with_burst(mut self, burst: u64) -> Self {
+        self.burst = burst.max(1);
+        let bucket = self.bucket.get_mut().unwrap();
+        bucket.tokens = bucket.tokens.min(self.burst as f64);
+        self
+    }
+
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bytes_per_second
+    }
+
+    /// Wait until `bytes` may be sent, then take them from the bucket.
+    ///
+    /// A request larger than the burst waits for a full bucket and then drives it
+    /// negative, so later senders make up for it.
+    pub async fn acquire(&self, bytes: u64) {
+        loop {
+            match self.try_acquire(bytes) {
+                None => return,
+                Some(wait) => tokio::time::sleep(wait).await,
+            }
+        }
+    }
+
+    /// Take `bytes` if they are available now, otherwise return how long to wait
+    fn try_acquire(&self, bytes: u64) -> Option<Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens =
+            (bucket.tokens + elapsed * self.bytes_per_second as f64).min(self.burst as f64);
+        bucket.refilled_at = now;
+
+        let needed = bytes.min(self.burst) as f64;
+        if bucket.tokens >= needed {
+            bucket.tokens -= bytes as f64;
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            (needed - bucket.tokens) / self.bytes_per_second as f64,
+        ))
+    }
+}
@@ -0,0 +1,183 @@
+use crate::bpv7::bundle::Bundle;
+use crate::bpv7::EndpointId;
+use crate::cla::peer::ClaPeer;
+use crate::cla::serial::framing::{encode_frame, FrameDecoder};
+use crate::cla::serial::port::SerialSettings;
+use crate::cla::tcp::ack::{AckError, AckStatus};
+use crate::cla::ConvergenceLayer;
+use crate::consts::serial::ACK_TIMEOUT;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Serial CLA client sending bundles over an RS-232/RS-485 line
+#[derive(Clone)]
+pub struct SerialClaClient {
+    pub settings: SerialSettings,
+    /// How long to wait for the listener's ACK frame
+    pub ack_timeout: Duration,
+}
+
+impl SerialClaClient {
+    pub fn new(settings: SerialSettings) -> Self {
+        Self {
+            settings,
+            ack_timeout: ACK_TIMEOUT,
+        }
+    }
+
+    pub fn with_ack_timeout(mut self, ack_timeout: Duration) -> Self {
+        self.ack_timeout = ack_timeout;
+        self
+    }
+}
+
+#[async_trait]
+impl ConvergenceLayer for SerialClaClient {
+    fn address(&self) -> String {
+        self.settings.path.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn activate(&self) -> Result<()> {
+        self.settings.open()?;
+        log::info!(
+            "Serial port {} opened at {} baud",
+            self.settings.path,
+            self.settings.baud_rate
+        );
+        Ok(())
+    }
+
+    async fn send_bundle(&self, bundle: &Bundle) -> Result<()> {
+        let mut port = self.settings.open()?;
+        send_bundle(&mut port, bundle, self.ack_timeout).await
+    }
+}
+
+/// Serial-specific implementation of ClaPeer for routing
+#[derive(Clone)]
+pub struct SerialPeer {
+    pub peer_id: EndpointId,
+    pub settings: SerialSettings,
+}
+
+impl SerialPeer {
+    pub fn new(peer_id: EndpointId, settings: SerialSettings) -> Self {
+        Self { peer_id, settings }
+    }
+}
+
+#[async_trait]
+impl ConvergenceLayer for SerialPeer {
+    fn address(&self) -> String {
+        self.settings.path.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn activate(&self) -> Result<()> {
+        SerialClaClient::new(self.settings.clone()).activate().await
+    }
+
+    async fn send_bundle(&self, bundle: &Bundle) -> Result<()> {
+        SerialClaClient::new(self.settings.clone())
+            .send_bundle(bundle)
+            .await
+    }
+}
+
+#[async_trait]
+impl ClaPeer for SerialPeer {
+    fn get_peer_endpoint_id(&self) -> EndpointId {
+        self.peer_id.clone()
+    }
+
+    /// A serial line has no handshake, so the peer counts as reachable whenever
+    /// the device can be opened
+    async fn is_reachable(&self) -> bool {
+        match self.settings.open() {
+            Ok(_) => true,
+            Err(e) => {
+                log::warn!("{e}");
+                false
+            }
+        }
+    }
+
+    fn get_cla_type(&self) -> &str {
+        "serial"
+    }
+
+    fn get_connection_address(&self) -> String {
+        self.settings.path.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn ClaPeer> {
+        Box::new(self.clone())
+    }
+
+    async fn activate(&self) -> Result<()> {
+        <Self as ConvergenceLayer>::activate(self).await
+    }
+
+    async fn send_bundle(&self, bundle: &Bundle) -> Result<()> {
+        <Self as ConvergenceLayer>::send_bundle(self, bundle).await
+    }
+}
+
+/// Send one bundle frame and wait up to `ack_timeout` for the ACK frame
+pub async fn send_bundle<S>(stream: &mut S, bundle: &Bundle, ack_timeout: Duration) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let encoded = bundle.to_canonical_cbor()?;
+    stream.write_all(&encode_frame(&encoded)).await?;
+    stream.flush().await?;
+
+    let status = tokio::time::timeout(ack_timeout, read_ack(stream))
+        .await
+        .map_err(|_| anyhow::anyhow!("No ACK received within {ack_timeout:?}"))??;
+    status.into_result()?;
+    Ok(())
+}
+
+/// Read frames until one carries an ACK, skipping frames damaged on the line
+pub async fn read_ack<S>(stream: &mut S) -> Result<AckStatus>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut decoder = FrameDecoder::new(64);
+    let mut buf = [0u8; 64];
+    loop {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Err(AckError::Closed.into());
+        }
+        decoder.extend(&buf[..n]);
+        while let Some(frame) = decoder.next_frame() {
+            match frame {
+                Ok(ack) => {
+                    return AckStatus::parse(&ack).ok_or_else(|| {
+                        AckError::Unexpected(String::from_utf8_lossy(&ack).into_owned()).into()
+                    })
+                }
+                Err(e) => log::warn!("Skipping damaged frame while waiting for ACK: {e}"),
+            }
+        }
+    }
+}
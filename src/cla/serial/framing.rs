@@ -0,0 +1,145 @@
+use thiserror::Error;
+
+/// Byte that separates frames on the line; COBS guarantees it never occurs inside one
+pub const FRAME_DELIMITER: u8 = 0;
+
+/// Bytes of the big-endian length prefix inside every frame
+const LENGTH_PREFIX_BYTES: usize = 4;
+
+/// Why bytes received between two delimiters were discarded
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SerialFrameError {
+    #[error("Invalid COBS encoding in a {0}-byte frame")]
+    InvalidEncoding(usize),
+    #[error("Frame announced {announced} bytes but carried {received}")]
+    LengthMismatch { announced: usize, received: usize },
+    #[error("Discarded {0} bytes received without a frame delimiter")]
+    Oversized(usize),
+}
+
+/// Consistent Overhead Byte Stuffing: rewrite `data` so it contains no zero bytes
+pub fn cobs_encode(data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(data.len() + data.len() / 254 + 1);
+    let mut code_index = 0;
+    let mut code = 1u8;
+    encoded.push(0);
+    for &byte in data {
+        if byte != 0 {
+            encoded.push(byte);
+            code += 1;
+        }
+        if byte == 0 || code == 0xFF {
+            encoded[code_index] = code;
+            code_index = encoded.len();
+            encoded.push(0);
+            code = 1;
+        }
+    }
+    encoded[code_index] = code;
+    encoded
+}
+
+/// Undo [`cobs_encode`]; `None` if `encoded` is not valid COBS
+pub fn cobs_decode(encoded: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let code = usize::from(encoded[i]);
+        if code == 0 || i + code > encoded.len() {
+            return None;
+        }
+        let block = &encoded[i + 1..i + code];
+        if block.contains(&0) {
+            return None;
+        }
+        decoded.extend_from_slice(block);
+        i += code;
+        if code < 0xFF && i < encoded.len() {
+            decoded.push(0);
+        }
+    }
+    Some(decoded)
+}
+
+/// Frame `payload` for the line: a leading delimiter that flushes any partial frame
+/// the receiver holds, then the COBS-encoded length prefix and payload, then the
+/// closing delimiter
+pub fn encode_frame(payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(LENGTH_PREFIX_BYTES + payload.len());
+    body.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    body.extend_from_slice(payload);
+    let mut frame = vec![FRAME_DELIMITER];
+    frame.extend(cobs_encode(&body));
+    frame.push(FRAME_DELIMITER);
+    frame
+}
+
+/// Decode the bytes between two delimiters into the payload they carry
+pub fn decode_frame(encoded: &[u8]) -> Result<Vec<u8>, SerialFrameError> {
+    let body = cobs_decode(encoded).ok_or(SerialFrameError::InvalidEncoding(encoded.len()))?;
+    let Some((prefix, payload)) = body.split_first_chunk::<LENGTH_PREFIX_BYTES>() else {
+        return Err(SerialFrameError::LengthMismatch {
+            announced: LENGTH_PREFIX_BYTES,
+            received: body.len(),
+        });
+    };
+    let announced = u32::from_be_bytes(*prefix) as usize;
+    if announced != payload.len() {
+        return Err(SerialFrameError::LengthMismatch {
+            announced,
+            received: payload.len(),
+        });
+    }
+    Ok(payload.to_vec())
+}
+
+/// Splits a received byte stream into frames.
+///
+/// Line noise only costs the frame it lands in: decoding resumes at the next
+/// delimiter, and a run of bytes longer than any valid frame is dropped.
+#[derive(Debug)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    /// Longest encoded frame a payload of the configured maximum size produces
+    max_encoded_bytes: usize,
+}
+
+impl FrameDecoder {
+    /// Accept payloads of up to `max_payload_bytes`
+    pub fn new(max_payload_bytes: usize) -> Self {
+        let body = max_payload_bytes.saturating_add(LENGTH_PREFIX_BYTES);
+        Self {
+            buffer: Vec::new(),
+            max_encoded_bytes: body.saturating_add(body / 254 + 1),
+        }
+    }
+
+    /// Append bytes read from the line
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Next complete frame, or `None` until more bytes arrive. Errors report
+    /// discarded bytes; the decoder can keep being polled after them.
+    pub fn next_frame(&mut self) -> Option<Result<Vec<u8>, SerialFrameError>> {
+        loop {
+            let Some(end) = self.buffer.iter().position(|&b| b == FRAME_DELIMITER) else {
+                if self.buffer.len() > self.max_encoded_bytes {
+                    let discarded = self.buffer.len();
+                    self.buffer.clear();
+                    return Some(Err(SerialFrameError::Oversized(discarded)));
+                }
+                return None;
+            };
+            let encoded: Vec<u8> = self.buffer.drain(..=end).take(end).collect();
+            if encoded.is_empty() {
+                // Back-to-back delimiters separate nothing
+                continue;
+            }
+            if encoded.len() > self.max_encoded_bytes {
+                return Some(Err(SerialFrameError::Oversized(encoded.len())));
+            }
+            return Some(decode_frame(&encoded));
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod client;
+pub mod framing;
+pub mod port;
+pub mod server;
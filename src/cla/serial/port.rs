@@ -0,0 +1,71 @@
+use crate::consts::serial::DEFAULT_BAUD_RATE;
+use serde::{Deserialize, Serialize};
+use tokio_serial::{SerialPortBuilderExt, SerialStream};
+
+/// Parity bit used on a serial line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SerialParity {
+    #[default]
+    None,
+    Odd,
+    Even,
+}
+
+impl std::fmt::Display for SerialParity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SerialParity::None => "none",
+            SerialParity::Odd => "odd",
+            SerialParity::Even => "even",
+        })
+    }
+}
+
+impl From<SerialParity> for tokio_serial::Parity {
+    fn from(parity: SerialParity) -> Self {
+        match parity {
+            SerialParity::None => tokio_serial::Parity::None,
+            SerialParity::Odd => tokio_serial::Parity::Odd,
+            SerialParity::Even => tokio_serial::Parity::Even,
+        }
+    }
+}
+
+/// Device and line settings of a serial port (8 data bits, 1 stop bit)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerialSettings {
+    /// Device path, e.g. `/dev/ttyUSB0` or `COM3`
+    pub path: String,
+    pub baud_rate: u32,
+    pub parity: SerialParity,
+}
+
+impl SerialSettings {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            baud_rate: DEFAULT_BAUD_RATE,
+            parity: SerialParity::None,
+        }
+    }
+
+    pub fn with_baud_rate(mut self, baud_rate: u32) -> Self {
+        self.baud_rate = baud_rate;
+        self
+    }
+
+    pub fn with_parity(mut self, parity: SerialParity) -> Self {
+        self.parity = parity;
+        self
+    }
+
+    /// Open the device for async I/O; must be called from within a Tokio runtime
+    pub fn open(&self) -> anyhow::Result<SerialStream> {
+        let stream = tokio_serial::new(&self.path, self.baud_rate)
+            .parity(self.parity.into())
+            .open_native_async()
+            .map_err(|e| anyhow::anyhow!("Failed to open serial port {}: {e}", self.path))?;
+        Ok(stream)
+    }
+}
@@ -0,0 +1,122 @@
+use crate::bpv7::bundle::Bundle;
+use crate::cla::factory::ReceiveCallback;
+use crate::cla::serial::framing::{encode_frame, FrameDecoder};
+use crate::cla::serial::port::SerialSettings;
+use crate::cla::tcp::ack::AckStatus;
+use crate::cla::ConvergenceLayer;
+use crate::consts::tcp::DEFAULT_MAX_BUNDLE_BYTES;
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::sync::CancellationToken;
+
+/// Serial CLA listener.
+///
+/// Each frame carries one CBOR-encoded bundle and is answered with a frame holding
+/// the callback's ACK status. Frames damaged on the line are dropped unanswered,
+/// leaving the sender to time out and retry.
+#[derive(Clone)]
+pub struct SerialClaListener {
+    pub settings: SerialSettings,
+    pub receive_callback: ReceiveCallback,
+    /// Frames carrying more bundle bytes than this are discarded
+    pub max_bundle_bytes: usize,
+}
+
+impl SerialClaListener {
+    pub fn new(settings: SerialSettings, receive_callback: ReceiveCallback) -> Self {
+        Self {
+            settings,
+            receive_callback,
+            max_bundle_bytes: DEFAULT_MAX_BUNDLE_BYTES,
+        }
+    }
+
+    pub fn with_max_bundle_bytes(mut self, max_bundle_bytes: usize) -> Self {
+        self.max_bundle_bytes = max_bundle_bytes;
+        self
+    }
+
+    /// Open the device and receive bundles until `shutdown` is cancelled.
+    pub async fn activate_until(&self, shutdown: CancellationToken) -> Result<()> {
+        let port = self.settings.open()?;
+        log::info!(
+            "Serial CLA Listener reading {} at {} baud",
+            self.settings.path,
+            self.settings.baud_rate
+        );
+        self.serve(port, shutdown).await?;
+        log::info!("Serial CLA Listener on {} shut down", self.settings.path);
+        Ok(())
+    }
+
+    /// Receive bundles from an already opened line until it closes or `shutdown`
+    /// is cancelled
+    pub async fn serve<S>(&self, mut stream: S, shutdown: CancellationToken) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut decoder = FrameDecoder::new(self.max_bundle_bytes);
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                read = stream.read(&mut buf) => read?,
+            };
+            if n == 0 {
+                break;
+            }
+            decoder.extend(&buf[..n]);
+            while let Some(frame) = decoder.next_frame() {
+                let data = match frame {
+                    Ok(data) => data,
+                    Err(e) => {
+                        log::warn!("Resynchronizing serial line: {e}");
+                        continue;
+                    }
+                };
+                let status = match serde_cbor::from_slice::<Bundle>(&data) {
+                    Ok(bundle) => {
+                        // Same as the TCP listener: store on the blocking pool, then acknowledge
+                        let callback = Arc::clone(&self.receive_callback);
+                        match tokio::task::spawn_blocking(move || callback(bundle)).await {
+                            Ok(status) => status,
+                            Err(e) => {
+                                log::error!("Receive callback failed: {e}");
+                                AckStatus::Error
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("Failed to deserialize bundle: {e}");
+                        AckStatus::Bad
+                    }
+                };
+                stream
+                    .write_all(&encode_frame(status.as_str().as_bytes()))
+                    .await?;
+                stream.flush().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ConvergenceLayer for SerialClaListener {
+    fn address(&self) -> String {
+        self.settings.path.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn activate(&self) -> Result<()> {
+        self.activate_until(CancellationToken::new()).await
+    }
+}
@@ -0,0 +1,205 @@
+use crate::cla::framing::LENGTH_PREFIX_LEN;
+use crate::cla::tcp::server::KEEPALIVE_SENTINEL_LEN;
+use crate::consts::tcp::{ACK, BAD, ERROR, FULL, OK, RECEIVED, SUCCESS};
+use anyhow::Result;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Replies that legacy listeners send for a bundle they accepted
+const LEGACY_POSITIVE: [&str; 4] = [OK, ACK, SUCCESS, RECEIVED];
+
+/// Status a listener reports for each received bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckStatus {
+    /// The bundle was stored (or was already present)
+    Ok,
+    /// The receiver's store has no room for the bundle
+    Full,
+    /// The bundle could not be decoded or was rejected as invalid
+    Bad,
+    /// Any other failure while handling the bundle
+    Error,
+}
+
+impl AckStatus {
+    const ALL: [AckStatus; 4] = [
+        AckStatus::Ok,
+        AckStatus::Full,
+        AckStatus::Bad,
+        AckStatus::Error,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AckStatus::Ok => OK,
+            AckStatus::Full => FULL,
+            AckStatus::Bad => BAD,
+            AckStatus::Error => ERROR,
+        }
+    }
+
+    pub fn parse(ack: &[u8]) -> Option<AckStatus> {
+        Self::ALL.into_iter().find(|s| s.as_str().as_bytes() == ack)
+    }
+
+    /// Parse a reply from a listener that predates the status codes: any of
+    /// `OK`, `ACK`, `SUCCESS` or `RECEIVED` (surrounding whitespace ignored)
+    /// confirms the bundle, and the status codes keep their usual meaning
+    pub fn parse_legacy(ack: &[u8]) -> Option<AckStatus> {
+        let ack = ack.trim_ascii();
+        if LEGACY_POSITIVE.iter().any(|p| p.as_bytes() == ack) {
+            return Some(AckStatus::Ok);
+        }
+        Self::parse(ack)
+    }
+
+    /// Map the status to the error `send_bundle` reports for it
+    pub fn into_result(self) -> std::result::Result<(), AckError> {
+        match self {
+            AckStatus::Ok => Ok(()),
+            AckStatus::Full => Err(AckError::Full),
+            AckStatus::Bad => Err(AckError::Rejected),
+            AckStatus::Error => Err(AckError::Failed),
+        }
+    }
+}
+
+/// Why an application refused a received bundle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RejectReason {
+    /// No room for the bundle right now; the sender keeps it and retries (`FULL`)
+    Full,
+    /// The bundle breaks the receiver's policy; the sender gives up on it (`BAD`)
+    Policy(String),
+    /// Handling the bundle failed; the sender keeps it and retries (`ERROR`)
+    Failed(String),
+}
+
+/// Whether an application accepts a received bundle. A rejected bundle is not
+/// acknowledged with `OK`, so the sender keeps it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiveDecision {
+    Accept,
+    Reject(RejectReason),
+}
+
+impl From<ReceiveDecision> for AckStatus {
+    fn from(decision: ReceiveDecision) -> Self {
+        match decision {
+            ReceiveDecision::Accept => AckStatus::Ok,
+            ReceiveDecision::Reject(RejectReason::Full) => AckStatus::Full,
+            ReceiveDecision::Reject(RejectReason::Policy(_)) => AckStatus::Bad,
+            ReceiveDecision::Reject(RejectReason::Failed(_)) => AckStatus::Error,
+        }
+    }
+}
+
+/// Why a sent bundle was not acknowledged with `OK`
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AckError {
+    #[error("Peer store is full")]
+    Full,
+    #[error("Peer rejected the bundle as invalid")]
+    Rejected,
+    #[error("Peer failed to handle the bundle")]
+    Failed,
+    #[error("Unexpected ACK \"{0}\"")]
+    Unexpected(String),
+    #[error("Connection closed before ACK was received")]
+    Closed,
+    #[error("No ACK received within {0:?}")]
+    TimedOut(std::time::Duration),
+}
+
+impl AckError {
+    /// Whether sending the same bundle again may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, AckError::Full | AckError::Failed)
+    }
+}
+
+/// Drop the keepalive frames at the start of `received`, since a listener probing
+/// an idle connection may PING ahead of the next ACK. Returns false while a frame
+/// has only partly arrived.
+fn skip_keepalive_frames(received: &mut Vec<u8>) -> bool {
+    let sentinel = KEEPALIVE_SENTINEL_LEN.to_be_bytes();
+    loop {
+        let prefix = &received[..received.len().min(LENGTH_PREFIX_LEN)];
+        if prefix.is_empty() {
+            return false;
+        }
+        if !sentinel.starts_with(prefix) {
+            return true;
+        }
+        if received.len() <= LENGTH_PREFIX_LEN {
+            return false;
+        }
+        log::debug!(
+            "Skipping keepalive control byte {:#04x} ahead of an ACK",
+            received[LENGTH_PREFIX_LEN]
+        );
+        received.drain(..=LENGTH_PREFIX_LEN);
+    }
+}
+
+/// Read one ACK, continuing while the bytes received so far are the start of a known
+/// status. Keepalive frames ahead of it are skipped.
+pub async fn read_ack<S>(stream: &mut S) -> Result<AckStatus>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut ack = Vec::new();
+    let mut buf = [0u8; 16];
+    loop {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Err(AckError::Closed.into());
+        }
+        ack.extend_from_slice(&buf[..n]);
+        if !skip_keepalive_frames(&mut ack) {
+            continue;
+        }
+        if let Some(status) = AckStatus::parse(&ack) {
+            return Ok(status);
+        }
+        let partial = AckStatus::ALL
+            .iter()
+            .any(|s| s.as_str().as_bytes().starts_with(&ack));
+        if !partial {
+            return Err(AckError::Unexpected(String::from_utf8_lossy(&ack).into_owned()).into());
+        }
+    }
+}
+
+/// Read one reply from a listener that predates the status codes. Only a
+/// positive reply confirms the bundle; anything else is reported as an
+/// [`AckError`] so the bundle is kept for a later retry.
+pub async fn read_legacy_ack<S>(stream: &mut S) -> Result<AckStatus>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut ack = Vec::new();
+    let mut buf = [0u8; 16];
+    loop {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Err(AckError::Closed.into());
+        }
+        ack.extend_from_slice(&buf[..n]);
+        if !skip_keepalive_frames(&mut ack) {
+            continue;
+        }
+        if let Some(status) = AckStatus::parse_legacy(&ack) {
+            return Ok(status);
+        }
+        let received = ack.trim_ascii_start();
+        let partial = LEGACY_POSITIVE
+            .iter()
+            .copied()
+            .chain(AckStatus::ALL.iter().map(AckStatus::as_str))
+            .any(|s| s.as_bytes().starts_with(received));
+        if !partial {
+            return Err(AckError::Unexpected(String::from_utf8_lossy(&ack).into_owned()).into());
+        }
+    }
+}
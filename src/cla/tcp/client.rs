@@ -1,11 +1,21 @@
 use crate::bpv7::EndpointId;
+use crate::cla::framing::{encode_frame, write_with_timeout, SendTimeout};
 use crate::cla::peer::ClaPeer;
-use crate::consts::{BUNDLES_DIR, DISPATCHED_DIR};
+use crate::cla::tcp::ack::{read_ack, read_legacy_ack, AckError, AckStatus};
+use crate::cla::tcp::contact::{handshake_as_initiator, ContactHeader};
+use crate::cla::tcp::summary::SummaryVector;
+use crate::cla::tcp::wire::WireFormat;
+use crate::consts::tcp::{
+    ACK_RETRIES, ACK_RETRY_DELAY, DEFAULT_CONNECT_TIMEOUT, DEFAULT_READ_TIMEOUT,
+    DEFAULT_WRITE_TIMEOUT, MAX_SUMMARY_VECTOR_IDS,
+};
+use crate::consts::BUNDLES_DIR;
 use crate::store::file::BundleStore;
 use crate::{bpv7::bundle::Bundle, cla::ConvergenceLayer};
 use anyhow::Result;
 use async_trait::async_trait;
 use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
 
 /// TCP connection information including connection details
@@ -65,6 +75,17 @@ impl TcpConnectionInfo {
 pub struct TcpClaClient {
     pub target_addr: String,
     pub connection_info: Option<TcpConnectionInfo>,
+    /// Send a TCPCL v4 contact header on connect and require a matching reply
+    pub contact_header: Option<ContactHeader>,
+    /// Accept free-text ACKs (`OK`, `ACK`, `SUCCESS`, `RECEIVED`) from listeners
+    /// that predate the status codes
+    pub legacy_ack: bool,
+    /// Longest a bundle write may take before the send fails with a
+    /// [`SendTimeout`](crate::cla::framing::SendTimeout)
+    pub write_timeout: Duration,
+    /// Longest wait for the listener's contact header or a bundle's ACK before the
+    /// send fails and the contact ends
+    pub read_timeout: Duration,
 }
 
 /// TCP-specific implementation of ClaPeer for routing
@@ -73,6 +94,19 @@ pub struct TcpPeer {
     pub peer_id: EndpointId,
     pub address: String,
     pub connection_info: Option<TcpConnectionInfo>,
+    /// Send a TCPCL v4 contact header on connect and require a matching reply
+    pub contact_header: Option<ContactHeader>,
+    /// Accept free-text ACKs (`OK`, `ACK`, `SUCCESS`, `RECEIVED`) from listeners
+    /// that predate the status codes
+    pub legacy_ack: bool,
+    /// How long [`ClaPeer::is_reachable`] and sends wait for the connection to open
+    pub connect_timeout: Duration,
+    /// Longest a bundle write may take before the send fails with a
+    /// [`SendTimeout`](crate::cla::framing::SendTimeout)
+    pub write_timeout: Duration,
+    /// Longest wait for the listener's contact header or a bundle's ACK before the
+    /// send fails and the contact ends
+    pub read_timeout: Duration,
 }
 
 impl TcpPeer {
@@ -81,17 +115,18 @@ impl TcpPeer {
             peer_id,
             address,
             connection_info: None,
+            contact_header: None,
+            legacy_ack: false,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            write_timeout: DEFAULT_WRITE_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
         }
     }
 
     /// Create TcpPeer from endpoint ID (assumes endpoint ID is the address)
     pub fn from_endpoint_id(peer_id: EndpointId) -> Self {
         let address = peer_id.as_str().to_string();
-        Self {
-            peer_id,
-            address,
-            connection_info: None,
-        }
+        Self::new(peer_id, address)
     }
 
     /// Create TcpPeer for testing (uses endpoint ID as address)
@@ -105,9 +140,54 @@ impl TcpPeer {
         self
     }
 
+    /// Exchange TCPCL v4 contact headers before sending bundles
+    pub fn with_contact_header(mut self, contact_header: ContactHeader) -> Self {
+        self.contact_header = Some(contact_header);
+        self
+    }
+
+    /// Accept the free-text positive replies of older listeners instead of requiring `OK`
+    pub fn with_legacy_ack(mut self, legacy_ack: bool) -> Self {
+        self.legacy_ack = legacy_ack;
+        self
+    }
+
+    /// Give up on reachability probes, activation and sends after `connect_timeout`
+    /// instead of the default 3 seconds
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Fail a send whose bundle cannot be written within `write_timeout` (default
+    /// 30 seconds), e.g. because the peer stopped reading, so the bundle is retried later
+    pub fn with_write_timeout(mut self, write_timeout: Duration) -> Self {
+        self.write_timeout = write_timeout;
+        self
+    }
+
+    /// Fail a send whose ACK does not arrive within `read_timeout` (default 30
+    /// seconds), e.g. because the peer read the bundle but never replied; the bundle
+    /// is kept for a later contact
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
     pub fn get_connection_info(&self) -> Option<&TcpConnectionInfo> {
         self.connection_info.as_ref()
     }
+
+    fn send_options(&self) -> SendOptions {
+        SendOptions {
+            legacy_ack: self.legacy_ack,
+            wire_format: wire_format(self.contact_header.as_ref()),
+            connect_timeout: self.connect_timeout,
+            write_timeout: self.write_timeout,
+            read_timeout: self.read_timeout,
+            ..SendOptions::default()
+        }
+    }
 }
 
 #[async_trait]
@@ -115,15 +195,37 @@ impl ConvergenceLayer for TcpPeer {
     fn address(&self) -> String {
         self.address.clone()
     }
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
     async fn activate(&self) -> anyhow::Result<()> {
-        if let Some(connection_info) = tcp_connect_and_collect_info(&self.address).await? {
-            println!("✅ TCP connection established and info collected:");
-            connection_info.display_info();
+        if let Some(connection_info) =
+            tcp_connect_and_collect_info(&self.address, self.connect_timeout).await?
+        {
+            log::info!("TCP connection established: {}", self.address);
+            log::debug!("{connection_info:?}");
             Ok(())
         } else {
             Err(anyhow::anyhow!("TCP connection failed: {}", self.address))
         }
     }
+    async fn send_bundle(&self, bundle: &Bundle) -> anyhow::Result<()> {
+        let options = self.send_options();
+        let (mut stream, summary) =
+            connect_with_summary(&self.address, self.contact_header.as_ref(), &options).await?;
+        if summary.is_some_and(|summary| summary.contains(&bundle.id())) {
+            log::info!(
+                "Peer {} already holds bundle {}, not sending it",
+                self.peer_id,
+                bundle.id()
+            );
+            return Ok(());
+        }
+        send_bundle_with_options(&mut stream, bundle, &options).await
+    }
 }
 
 #[async_trait]
@@ -133,7 +235,7 @@ impl ClaPeer for TcpPeer {
     }
 
     async fn is_reachable(&self) -> bool {
-        tcp_connect_and_collect_info(&self.address)
+        tcp_connect_and_collect_info(&self.address, self.connect_timeout)
             .await
             .unwrap_or(None)
             .map(|info| info.is_reachable)
@@ -159,17 +261,24 @@ impl ClaPeer for TcpPeer {
     async fn activate(&self) -> anyhow::Result<()> {
         <Self as ConvergenceLayer>::activate(self).await
     }
+
+    async fn send_bundle(&self, bundle: &Bundle) -> anyhow::Result<()> {
+        <Self as ConvergenceLayer>::send_bundle(self, bundle).await
+    }
 }
 
 /// TCP-specific connectivity check with detailed connection information
-async fn tcp_connect_and_collect_info(address: &str) -> anyhow::Result<Option<TcpConnectionInfo>> {
+async fn tcp_connect_and_collect_info(
+    address: &str,
+    connect_timeout: Duration,
+) -> anyhow::Result<Option<TcpConnectionInfo>> {
     let mut connection_info = TcpConnectionInfo::new(address.to_string());
 
-    println!("🔍 Attempting TCP connection to: {address}");
+    log::debug!("Attempting TCP connection to: {address}");
 
     let start_time = Instant::now();
 
-    match tokio::time::timeout(Duration::from_secs(3), TcpStream::connect(address)).await {
+    match tokio::time::timeout(connect_timeout, TcpStream::connect(address)).await {
         Ok(Ok(stream)) => {
             let connection_time = start_time.elapsed();
             connection_info.connection_time = Some(connection_time);
@@ -189,16 +298,16 @@ async fn tcp_connect_and_collect_info(address: &str) -> anyhow::Result<Option<Tc
             // For now, we'll just use the connection time as a rough latency indicator
             connection_info.latency = Some(ping_start.elapsed());
 
-            println!("✅ TCP connection successful to: {address}");
+            log::info!("TCP connection successful to: {address}");
             Ok(Some(connection_info))
         }
         Ok(Err(e)) => {
-            println!("❌ TCP connection failed to {address}: {e}");
+            log::warn!("TCP connection failed to {address}: {e}");
             connection_info.is_reachable = false;
             Ok(Some(connection_info))
         }
         Err(_) => {
-            println!("❌ TCP connection timeout to: {address}");
+            log::warn!("TCP connection timeout to: {address}");
             connection_info.is_reachable = false;
             Ok(Some(connection_info))
         }
@@ -210,27 +319,73 @@ impl TcpClaClient {
         Self {
             target_addr,
             connection_info: None,
+            contact_header: None,
+            legacy_ack: false,
+            write_timeout: DEFAULT_WRITE_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
         }
     }
 
     /// Connect to the target and store connection information
     pub async fn connect_and_store_info(&mut self) -> anyhow::Result<bool> {
-        if let Some(info) = tcp_connect_and_collect_info(&self.target_addr).await? {
+        if let Some(info) =
+            tcp_connect_and_collect_info(&self.target_addr, DEFAULT_CONNECT_TIMEOUT).await?
+        {
             self.connection_info = Some(info.clone());
-            println!("✅ TCP connection established and info stored:");
-            info.display_info();
+            log::info!(
+                "TCP connection established and info stored: {}",
+                self.target_addr
+            );
+            log::debug!("{info:?}");
             Ok(info.is_reachable)
         } else {
-            println!("❌ TCP connection failed: {}", self.target_addr);
+            log::warn!("TCP connection failed: {}", self.target_addr);
             Ok(false)
         }
     }
 
+    /// Exchange TCPCL v4 contact headers before sending bundles
+    pub fn with_contact_header(mut self, contact_header: ContactHeader) -> Self {
+        self.contact_header = Some(contact_header);
+        self
+    }
+
+    /// Accept the free-text positive replies of older listeners instead of requiring `OK`
+    pub fn with_legacy_ack(mut self, legacy_ack: bool) -> Self {
+        self.legacy_ack = legacy_ack;
+        self
+    }
+
+    /// Fail a send whose bundle cannot be written within `write_timeout` (default
+    /// 30 seconds), so a stalled peer does not hold up the remaining bundles
+    pub fn with_write_timeout(mut self, write_timeout: Duration) -> Self {
+        self.write_timeout = write_timeout;
+        self
+    }
+
+    /// Fail a send whose ACK does not arrive within `read_timeout` (default 30
+    /// seconds), e.g. because the peer read the bundle but never replied; the bundle
+    /// is kept for a later contact
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
     /// Get stored connection information
     pub fn get_connection_info(&self) -> Option<&TcpConnectionInfo> {
         self.connection_info.as_ref()
     }
 
+    fn send_options(&self) -> SendOptions {
+        SendOptions {
+            legacy_ack: self.legacy_ack,
+            wire_format: wire_format(self.contact_header.as_ref()),
+            write_timeout: self.write_timeout,
+            read_timeout: self.read_timeout,
+            ..SendOptions::default()
+        }
+    }
+
     /// Display stored connection information
     pub fn display_stored_info(&self) {
         if let Some(info) = &self.connection_info {
@@ -242,50 +397,369 @@ impl TcpClaClient {
     }
 }
 
+impl TcpClaClient {
+    /// Connect to the target and send all stored bundles, retrying the connection
+    /// with exponential backoff (plus jitter) up to `max_retries` times. Bundles named
+    /// in the peer's summary vector are dispatched without being sent.
+    pub async fn activate_with_retry(&self, max_retries: u32, base_delay: Duration) -> Result<()> {
+        let (mut stream, summary) = self
+            .connect_with_summary_and_retry(max_retries, base_delay)
+            .await?;
+        log::info!("Connected to {}", self.target_addr);
+
+        let store = BundleStore::new(BUNDLES_DIR)?;
+        self.send_stored_bundles(&mut stream, summary.as_ref(), &store)
+            .await
+    }
+
+    /// Send every bundle in `store` over `stream`, highest priority first. A bundle
+    /// is dispatched only once the peer confirms it with a positive ACK (or already
+    /// holds it); unconfirmed bundles stay in the store for the next contact. A write
+    /// timeout or I/O error ends the contact: the stream is shut down and the error
+    /// returned without trying the remaining bundles. A bundle that cannot be loaded
+    /// or dispatched is logged and left in the store; the rest are still sent.
+    pub(crate) async fn send_stored_bundles<S>(
+        &self,
+        stream: &mut S,
+        summary: Option<&SummaryVector>,
+        store: &BundleStore,
+    ) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + std::fmt::Debug,
+    {
+        let dispatched_dir = store.dispatched_dir();
+        let options = self.send_options();
+
+        for id in store.list_by_priority()? {
+            // One unreadable bundle must not hold up the rest of the contact
+            let bundle = match store.load(&id) {
+                Ok(bundle) => bundle,
+                Err(e) => {
+                    log::warn!("Skipping stored bundle {id}: {e}");
+                    continue;
+                }
+            };
+            if summary.is_some_and(|summary| summary.contains(&bundle.id())) {
+                log::info!("Peer already holds bundle {id}, not sending it");
+                dispatch(store, &bundle, &dispatched_dir);
+                continue;
+            }
+            log::debug!("Sending bundle: {id} bundle: {bundle:?} stream: {stream:?}");
+            match send_bundle_with_options(stream, &bundle, &options).await {
+                Ok(()) => dispatch(store, &bundle, &dispatched_dir),
+                Err(e) if breaks_connection(&e) => {
+                    log::error!("Connection lost sending bundle {id}, ending the contact: {e}");
+                    let _ = stream.shutdown().await;
+                    return Err(e);
+                }
+                Err(e) => log::error!("Failed to send bundle {id}, keeping it for retry: {e}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Open a TCP stream to the target, retrying with exponential backoff.
+    /// Only sleeps and connects are awaited, so dropping the future (e.g. from
+    /// a `tokio::select!` branch) cancels cleanly without leaking a connection.
+    pub async fn connect_with_retry(
+        &self,
+        max_retries: u32,
+        base_delay: Duration,
+    ) -> Result<TcpStream> {
+        let (stream, _) = self
+            .connect_with_summary_and_retry(max_retries, base_delay)
+            .await?;
+        Ok(stream)
+    }
+
+    /// Same as [`TcpClaClient::connect_with_retry`], also returning the summary
+    /// vector the peer sent, if one was negotiated
+    async fn connect_with_summary_and_retry(
+        &self,
+        max_retries: u32,
+        base_delay: Duration,
+    ) -> Result<(TcpStream, Option<SummaryVector>)> {
+        let mut errors = Vec::new();
+
+        for attempt in 0..=max_retries {
+            if attempt > 0 {
+                let delay = backoff_delay(base_delay, attempt);
+                log::info!(
+                    "Retrying connection to {} in {delay:?} (attempt {}/{})",
+                    self.target_addr,
+                    attempt + 1,
+                    max_retries + 1
+                );
+                tokio::time::sleep(delay).await;
+            }
+
+            match connect_with_summary(
+                &self.target_addr,
+                self.contact_header.as_ref(),
+                &self.send_options(),
+            )
+            .await
+            {
+                Ok(connected) => return Ok(connected),
+                Err(e) => {
+                    log::warn!(
+                        "Connection attempt {} to {} failed: {e}",
+                        attempt + 1,
+                        self.target_addr
+                    );
+                    errors.push(format!("attempt {}: {e}", attempt + 1));
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Failed to connect to {} after {} attempts: [{}]",
+            self.target_addr,
+            max_retries + 1,
+            errors.join("; ")
+        ))
+    }
+}
+
+/// Move a sent bundle out of the active store, logging instead of failing so the
+/// remaining bundles are still sent; a bundle left behind is sent again next contact
+fn dispatch(store: &BundleStore, bundle: &Bundle, dispatched_dir: &std::path::Path) {
+    if let Err(e) = store.dispatch_one(bundle, dispatched_dir) {
+        log::warn!("Failed to dispatch sent bundle {}: {e}", bundle.id());
+    }
+}
+
+/// Backoff before the given retry attempt (1-based): `base * 2^(attempt - 1)`
+/// plus up to 25% random jitter so that multiple dialers don't retry in lockstep
+pub fn backoff_delay(base_delay: Duration, attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    let delay = base_delay.saturating_mul(1u32 << exponent);
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    let jitter = delay.mul_f64(f64::from(nanos % 1000) / 4000.0);
+
+    delay + jitter
+}
+
 #[async_trait::async_trait]
 impl ConvergenceLayer for TcpClaClient {
     fn address(&self) -> String {
         self.target_addr.clone()
     }
 
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
     async fn activate(&self) -> Result<()> {
-        let mut stream = TcpStream::connect(&self.target_addr).await?;
-        println!("Connected to {}", self.target_addr);
+        self.activate_with_retry(0, Duration::ZERO).await
+    }
 
-        let store = BundleStore::new(BUNDLES_DIR)?;
-        let dispatched_dir = std::path::Path::new(DISPATCHED_DIR);
-
-        for id in store.list()? {
-            let bundle = store.load_by_partial_id(&id)?;
-            println!("📨 Sending bundle: {id} bundle: {bundle:?} stream: {stream:?}");
-            if send_bundle(&mut stream, &bundle).await.is_ok() {
-                store.dispatch_one(&bundle, dispatched_dir)?;
-            } else {
-                eprintln!("❌ Failed to send bundle: {id}");
-            }
-        }
+    async fn send_bundle(&self, bundle: &Bundle) -> Result<()> {
+        let options = self.send_options();
+        let mut stream = connect(&self.target_addr, self.contact_header.as_ref(), &options).await?;
+        send_bundle_with_options(&mut stream, bundle, &options).await
+    }
+}
 
-        Ok(())
+/// Open a connection, exchanging contact headers first when `contact_header` is set.
+/// Fails if the connection does not open within `options.connect_timeout`, or if the
+/// peer announces a different wire format than `contact_header`.
+pub async fn connect(
+    address: &str,
+    contact_header: Option<&ContactHeader>,
+    options: &SendOptions,
+) -> Result<TcpStream> {
+    let (stream, _) = connect_with_summary(address, contact_header, options).await?;
+    Ok(stream)
+}
+
+/// Same as [`connect`], also returning the peer's summary vector when both
+/// contact headers announce support for it
+pub async fn connect_with_summary(
+    address: &str,
+    contact_header: Option<&ContactHeader>,
+    options: &SendOptions,
+) -> Result<(TcpStream, Option<SummaryVector>)> {
+    let timeout = options.connect_timeout;
+    let mut stream = match tokio::time::timeout(timeout, TcpStream::connect(address)).await {
+        Ok(connected) => connected?,
+        Err(_) => anyhow::bail!("Timed out after {timeout:?} connecting to {address}"),
+    };
+    let mut summary = None;
+    if let Some(local) = contact_header {
+        let peer = handshake_as_initiator(
+            &mut stream,
+            local,
+            options.read_timeout,
+            options.write_timeout,
+        )
+        .await?;
+        local.negotiated_wire_format(&peer)?;
+        log::debug!("TCPCL contact header accepted by {address}: {peer:?}");
+        if local.negotiated_summary_vector(&peer) {
+            let received =
+                SummaryVector::read_from(&mut stream, MAX_SUMMARY_VECTOR_IDS, options.read_timeout)
+                    .await?;
+            log::debug!("{address} holds {} bundles", received.len());
+            summary = Some(received);
+        }
     }
+    Ok((stream, summary))
+}
+
+/// Bundle encoding of a connection opened with `contact_header`; without a
+/// contact header exchange bundles are always CBOR
+pub fn wire_format(contact_header: Option<&ContactHeader>) -> WireFormat {
+    contact_header
+        .map(ContactHeader::wire_format)
+        .unwrap_or_default()
 }
 
 pub fn create_bundle(source: &str, destination: &str, payload: Vec<u8>) -> Bundle {
     Bundle::new(source, destination, payload)
 }
 
-pub async fn send_bundle(stream: &mut TcpStream, bundle: &Bundle) -> Result<()> {
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+/// How a dialer opens a connection ([`connect`]) and how
+/// [`send_bundle_with_options`] sends a bundle and waits for its ACK
+#[derive(Clone, Copy, Debug)]
+pub struct SendOptions {
+    /// Accept free-text ACKs (`OK`, `ACK`, `SUCCESS`, `RECEIVED`) from listeners
+    /// that predate the status codes
+    pub legacy_ack: bool,
+    /// Times a bundle answered with `FULL` or `ERROR` is resent on the same connection
+    pub retries: u32,
+    /// Encoding of the bundle on this connection
+    pub wire_format: WireFormat,
+    /// Longest the connection may take to open
+    pub connect_timeout: Duration,
+    /// Longest wait for the next bytes of the peer's contact header, and for the
+    /// whole ACK of each bundle
+    pub read_timeout: Duration,
+    /// Longest the bundle write may take before the send fails with a [`SendTimeout`]
+    pub write_timeout: Duration,
+}
 
-    let encoded = serde_cbor::to_vec(bundle)?;
-    let len = encoded.len() as u32;
-    stream.write_all(&len.to_be_bytes()).await?;
-    stream.write_all(&encoded).await?;
+impl Default for SendOptions {
+    fn default() -> Self {
+        Self {
+            legacy_ack: false,
+            retries: ACK_RETRIES,
+            wire_format: WireFormat::Cbor,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            write_timeout: DEFAULT_WRITE_TIMEOUT,
+        }
+    }
+}
 
-    let mut buf = [0u8; 16];
-    let n = stream.read(&mut buf).await?;
-    println!("📨 Received n: {n}");
-    let ack = std::str::from_utf8(&buf[..n])?;
-    println!("📨 Received ACK: \"{ack}\"");
+/// Send a bundle and wait for an `OK` ACK; `FULL`, `BAD` and `ERROR` replies are
+/// reported as the matching [`AckError`]
+pub async fn send_bundle<S>(stream: &mut S, bundle: &Bundle) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let options = SendOptions {
+        retries: 0,
+        ..SendOptions::default()
+    };
+    send_bundle_with_options(stream, bundle, &options).await
+}
+
+/// Send a bundle, resending it on the same connection with backoff while the peer
+/// answers `FULL` or `ERROR`, up to `options.retries` times. `BAD` fails
+/// immediately, and so does a write that takes longer than the write timeout,
+/// with a [`SendTimeout`].
+///
+/// With `legacy_ack`, the listener is one that predates the status codes and
+/// replies with free text: `OK`, `ACK`, `SUCCESS` and `RECEIVED` confirm the
+/// bundle, and any other reply is an error, so the bundle is not treated as
+/// delivered. Legacy replies are never retried.
+pub async fn send_bundle_with_options<S>(
+    stream: &mut S,
+    bundle: &Bundle,
+    options: &SendOptions,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    if options.legacy_ack {
+        return send_bundle_legacy(stream, bundle, options).await;
+    }
+    let mut attempt = 0;
+    loop {
+        match send_bundle_once(stream, bundle, options).await {
+            Err(e)
+                if attempt < options.retries
+                    && e.downcast_ref::<AckError>()
+                        .is_some_and(AckError::is_retryable) =>
+            {
+                attempt += 1;
+                let delay = backoff_delay(ACK_RETRY_DELAY, attempt);
+                log::warn!(
+                    "Bundle {} not accepted ({e}), resending in {delay:?}",
+                    bundle.id()
+                );
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+async fn send_bundle_once<S>(stream: &mut S, bundle: &Bundle, options: &SendOptions) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    write_bundle(stream, bundle, options).await?;
+    let status = with_ack_timeout(read_ack(stream), options.read_timeout).await?;
+    log::debug!("Received ACK: \"{}\"", status.as_str());
+    Ok(status.into_result()?)
+}
+
+async fn send_bundle_legacy<S>(stream: &mut S, bundle: &Bundle, options: &SendOptions) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    write_bundle(stream, bundle, options).await?;
+    let status = with_ack_timeout(read_legacy_ack(stream), options.read_timeout).await?;
+    log::debug!("Received legacy ACK: \"{}\"", status.as_str());
+    Ok(status.into_result()?)
+}
+
+/// Fail with [`AckError::TimedOut`] if the ACK does not arrive within `timeout`
+async fn with_ack_timeout(
+    ack: impl std::future::Future<Output = Result<AckStatus>>,
+    timeout: Duration,
+) -> Result<AckStatus> {
+    tokio::time::timeout(timeout, ack)
+        .await
+        .map_err(|_| AckError::TimedOut(timeout))?
+}
+
+/// Whether a send error leaves the stream unusable, rather than the peer turning
+/// down one bundle
+fn breaks_connection(error: &anyhow::Error) -> bool {
+    error.is::<SendTimeout>()
+        || error.is::<std::io::Error>()
+        || matches!(
+            error.downcast_ref::<AckError>(),
+            Some(AckError::Closed | AckError::TimedOut(_))
+        )
+}
 
-    Ok(())
+async fn write_bundle<S>(stream: &mut S, bundle: &Bundle, options: &SendOptions) -> Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let frame = encode_frame(&options.wire_format.encode(bundle)?);
+    write_with_timeout(stream, &frame, options.write_timeout).await
 }
@@ -0,0 +1,173 @@
+use crate::cla::framing::{read_with_timeout, write_with_timeout};
+use crate::cla::tcp::wire::{WireFormat, WireFormatError};
+use anyhow::Result;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Magic bytes opening every contact header
+pub const CONTACT_MAGIC: [u8; 4] = *b"dtn!";
+/// TCPCL protocol version spoken by this implementation (RFC 9174)
+pub const TCPCL_VERSION: u8 = 4;
+/// Contact header flag announcing that the sender can negotiate TLS
+pub const FLAG_CAN_TLS: u8 = 0x01;
+/// Contact header flag announcing that the sender encodes bundles as JSON instead of CBOR
+pub const FLAG_JSON_BUNDLES: u8 = 0x02;
+/// Contact header flag announcing summary vector support: the accepting side sends
+/// the IDs of the bundles it holds, and the connecting side skips those bundles
+pub const FLAG_SUMMARY_VECTOR: u8 = 0x04;
+/// Encoded length: magic, version, flags and a 16-bit keepalive interval
+pub const CONTACT_HEADER_LEN: usize = 8;
+
+/// TCPCL v4-style contact header exchanged when a connection is opened.
+///
+/// Like RFC 9174 it starts with the magic "dtn!", the version and a flags byte;
+/// the keepalive interval (seconds, 0 disables keepalives) is carried here
+/// instead of in a separate session initialization message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContactHeader {
+    pub version: u8,
+    pub flags: u8,
+    pub keepalive_interval: u16,
+}
+
+impl Default for ContactHeader {
+    fn default() -> Self {
+        Self::new(30)
+    }
+}
+
+impl ContactHeader {
+    pub fn new(keepalive_interval: u16) -> Self {
+        Self {
+            version: TCPCL_VERSION,
+            flags: 0,
+            keepalive_interval,
+        }
+    }
+
+    pub fn encode(&self) -> [u8; CONTACT_HEADER_LEN] {
+        let mut buf = [0u8; CONTACT_HEADER_LEN];
+        buf[..4].copy_from_slice(&CONTACT_MAGIC);
+        buf[4] = self.version;
+        buf[5] = self.flags;
+        buf[6..].copy_from_slice(&self.keepalive_interval.to_be_bytes());
+        buf
+    }
+
+    /// Decode a peer's header, rejecting a wrong magic or an unsupported version
+    pub fn decode(buf: &[u8; CONTACT_HEADER_LEN]) -> Result<Self> {
+        if buf[..4] != CONTACT_MAGIC {
+            anyhow::bail!("Invalid contact header magic: {:02x?}", &buf[..4]);
+        }
+        if buf[4] != TCPCL_VERSION {
+            anyhow::bail!(
+                "Unsupported TCPCL version {} (expected {TCPCL_VERSION})",
+                buf[4]
+            );
+        }
+        Ok(Self {
+            version: buf[4],
+            flags: buf[5],
+            keepalive_interval: u16::from_be_bytes([buf[6], buf[7]]),
+        })
+    }
+
+    /// Announce `format` as the encoding of the bundles this side sends and expects
+    pub fn with_wire_format(mut self, format: WireFormat) -> Self {
+        match format {
+            WireFormat::Cbor => self.flags &= !FLAG_JSON_BUNDLES,
+            WireFormat::Json => self.flags |= FLAG_JSON_BUNDLES,
+        }
+        self
+    }
+
+    /// Bundle encoding announced by this header
+    pub fn wire_format(&self) -> WireFormat {
+        if self.flags & FLAG_JSON_BUNDLES != 0 {
+            WireFormat::Json
+        } else {
+            WireFormat::Cbor
+        }
+    }
+
+    /// Bundle encoding for the connection; both sides must announce the same one
+    pub fn negotiated_wire_format(
+        &self,
+        peer: &ContactHeader,
+    ) -> std::result::Result<WireFormat, WireFormatError> {
+        let (local, peer) = (self.wire_format(), peer.wire_format());
+        if local == peer {
+            Ok(local)
+        } else {
+            Err(WireFormatError::Negotiation { local, peer })
+        }
+    }
+
+    /// Announce support for the summary vector exchange
+    pub fn with_summary_vector(mut self, enabled: bool) -> Self {
+        if enabled {
+            self.flags |= FLAG_SUMMARY_VECTOR;
+        } else {
+            self.flags &= !FLAG_SUMMARY_VECTOR;
+        }
+        self
+    }
+
+    /// Whether this header announces summary vector support
+    pub fn summary_vector(&self) -> bool {
+        self.flags & FLAG_SUMMARY_VECTOR != 0
+    }
+
+    /// The accepting side sends a summary vector only when both sides announce support
+    pub fn negotiated_summary_vector(&self, peer: &ContactHeader) -> bool {
+        self.summary_vector() && peer.summary_vector()
+    }
+
+    /// Keepalive interval both sides agree on: the smaller of the two, where 0 disables it
+    pub fn negotiated_keepalive(&self, peer: &ContactHeader) -> u16 {
+        self.keepalive_interval.min(peer.keepalive_interval)
+    }
+}
+
+/// Send our contact header and validate the peer's reply (connecting side). A peer
+/// that sends nothing for `read_timeout`, or stops reading for `write_timeout`, fails
+/// the handshake with a [`FramingError::ReadTimeout`](crate::cla::framing::FramingError)
+/// or [`SendTimeout`](crate::cla::framing::SendTimeout).
+pub async fn handshake_as_initiator<S>(
+    stream: &mut S,
+    local: &ContactHeader,
+    read_timeout: Duration,
+    write_timeout: Duration,
+) -> Result<ContactHeader>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    write_with_timeout(stream, &local.encode(), write_timeout).await?;
+    read_contact_header(stream, read_timeout).await
+}
+
+/// Validate the peer's contact header, then answer with ours (accepting side).
+/// A peer with a mismatched magic or version is rejected without a reply. The
+/// timeouts apply as in [`handshake_as_initiator`].
+pub async fn handshake_as_acceptor<S>(
+    stream: &mut S,
+    local: &ContactHeader,
+    read_timeout: Duration,
+    write_timeout: Duration,
+) -> Result<ContactHeader>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let peer = read_contact_header(stream, read_timeout).await?;
+    write_with_timeout(stream, &local.encode(), write_timeout).await?;
+    Ok(peer)
+}
+
+async fn read_contact_header<S>(stream: &mut S, read_timeout: Duration) -> Result<ContactHeader>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut buf = [0u8; CONTACT_HEADER_LEN];
+    read_with_timeout(stream, &mut buf, read_timeout).await?;
+    ContactHeader::decode(&buf)
+}
@@ -1,2 +1,6 @@
+pub mod ack;
 pub mod client;
+pub mod contact;
 pub mod server;
+pub mod summary;
+pub mod wire;
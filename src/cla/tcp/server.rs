@@ -1,9 +1,109 @@
 use crate::bpv7::bundle::Bundle;
+pub use crate::cla::framing::FramingError;
+use crate::cla::framing::{
+    read_frame_data, read_with_timeout, write_with_timeout, SendTimeout, LENGTH_PREFIX_LEN,
+};
+use crate::cla::tcp::ack::{AckStatus, ReceiveDecision, RejectReason};
+use crate::cla::tcp::contact::{handshake_as_acceptor, ContactHeader};
+use crate::cla::tcp::summary::SummaryVector;
+use crate::cla::tcp::wire::{WireFormat, WireFormatError};
 use crate::cla::ConvergenceLayer;
+use crate::consts::tcp::{DEFAULT_MAX_BUNDLE_BYTES, DEFAULT_READ_TIMEOUT, DEFAULT_WRITE_TIMEOUT};
 use anyhow::Result;
-use serde_cbor;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+/// Reserved length prefix announcing a keepalive control frame instead of a bundle
+pub const KEEPALIVE_SENTINEL_LEN: u32 = 0;
+/// Control byte following the sentinel length for a keepalive request
+pub const KEEPALIVE_PING: u8 = 0x01;
+/// Control byte following the sentinel length for a keepalive response
+pub const KEEPALIVE_PONG: u8 = 0x02;
+
+/// Receive callback that reports how the bundle was handled; the status is sent
+/// back to the peer as the bundle's ACK
+pub type StatusCallback = Arc<dyn Fn(Bundle) -> AckStatus + Send + Sync>;
+
+/// Receive callback that accepts or rejects each bundle; the decision is sent back
+/// to the peer as the bundle's ACK
+pub type DecisionCallback = Arc<dyn Fn(Bundle) -> ReceiveDecision + Send + Sync>;
+
+/// Supplies the summary vector of the bundles held when a peer connects
+pub type SummaryProvider = Arc<dyn Fn() -> SummaryVector + Send + Sync>;
+
+/// Status callback that acknowledges every bundle with `OK` after running `callback`
+fn always_ok(callback: Arc<dyn Fn(Bundle) + Send + Sync>) -> StatusCallback {
+    Arc::new(move |bundle| {
+        callback(bundle);
+        AckStatus::Ok
+    })
+}
+
+/// Status callback that acknowledges each bundle according to `callback`'s decision,
+/// logging why rejected bundles were refused
+fn decided(callback: DecisionCallback) -> StatusCallback {
+    Arc::new(move |bundle| {
+        let id = bundle.id();
+        let decision = callback(bundle);
+        match &decision {
+            ReceiveDecision::Accept => {}
+            ReceiveDecision::Reject(RejectReason::Full) => {
+                log::warn!("Rejected bundle {id}: no room");
+            }
+            ReceiveDecision::Reject(
+                RejectReason::Policy(reason) | RejectReason::Failed(reason),
+            ) => log::warn!("Rejected bundle {id}: {reason}"),
+        }
+        decision.into()
+    })
+}
+
+/// Keepalive settings for an accepted connection
+#[derive(Clone, Copy, Debug)]
+pub struct KeepaliveConfig {
+    /// Idle time after which a PING is sent to the peer
+    pub interval: Duration,
+    /// Number of consecutive unanswered PINGs before the connection is closed
+    pub max_missed: u32,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            max_missed: 3,
+        }
+    }
+}
+
+/// How [`handle_connection_with_options`] treats an accepted connection
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectionOptions {
+    /// Probe an idle peer with PING frames
+    pub keepalive: Option<KeepaliveConfig>,
+    /// Connections declaring a bundle larger than this are closed before allocating
+    pub max_bundle_bytes: usize,
+    /// Longest wait for the next bytes of a partially received bundle
+    pub read_timeout: Duration,
+    /// Longest an ACK write may take before the connection is closed
+    pub write_timeout: Duration,
+    /// Encoding of the bundles on this connection
+    pub wire_format: WireFormat,
+}
+
+impl Default for ConnectionOptions {
+    fn default() -> Self {
+        Self {
+            keepalive: None,
+            max_bundle_bytes: DEFAULT_MAX_BUNDLE_BYTES,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            write_timeout: DEFAULT_WRITE_TIMEOUT,
+            wire_format: WireFormat::Cbor,
+        }
+    }
+}
 
 // TODO: receive_callbackがClaManagerとTcpClaListenerの両方で保持されている
 // 設計を見直して、コールバックの責任を一箇所に集約する必要がある
@@ -11,73 +111,325 @@ use tokio::net::TcpListener;
 #[derive(Clone)]
 pub struct TcpClaListener {
     pub bind_addr: String,
-    pub receive_callback: Arc<dyn Fn(Bundle) + Send + Sync>,
+    pub receive_callback: StatusCallback,
+    pub keepalive: Option<KeepaliveConfig>,
+    /// Connections declaring a bundle larger than this are closed before allocating
+    pub max_bundle_bytes: usize,
+    /// Require a TCPCL v4 contact header exchange before bundles flow
+    pub contact_header: Option<ContactHeader>,
+    /// Longest wait for the next bytes of the peer's contact header or of a
+    /// partially received bundle
+    pub read_timeout: Duration,
+    /// Longest an ACK or contact header write may take before the connection is closed
+    pub write_timeout: Duration,
+    /// Offer peers a summary vector of held bundles during the contact header exchange
+    pub summary_provider: Option<SummaryProvider>,
 }
 
-#[async_trait::async_trait]
-impl ConvergenceLayer for TcpClaListener {
-    fn address(&self) -> String {
-        self.bind_addr.clone()
+impl TcpClaListener {
+    /// Listener that acknowledges every decodable bundle with `OK`
+    pub fn new(bind_addr: String, receive_callback: Arc<dyn Fn(Bundle) + Send + Sync>) -> Self {
+        Self::with_status_callback(bind_addr, always_ok(receive_callback))
     }
 
-    async fn activate(&self) -> Result<()> {
+    /// Listener whose callback accepts each bundle or rejects it with a reason; rejected
+    /// bundles are answered with `FULL`, `BAD` or `ERROR` instead of `OK`
+    pub fn with_decision_callback(bind_addr: String, receive_callback: DecisionCallback) -> Self {
+        Self::with_status_callback(bind_addr, decided(receive_callback))
+    }
+
+    /// Listener whose callback decides the ACK (`OK`, `FULL`, `BAD` or `ERROR`) of each bundle
+    pub fn with_status_callback(bind_addr: String, receive_callback: StatusCallback) -> Self {
+        Self {
+            bind_addr,
+            receive_callback,
+            keepalive: None,
+            max_bundle_bytes: DEFAULT_MAX_BUNDLE_BYTES,
+            contact_header: None,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            write_timeout: DEFAULT_WRITE_TIMEOUT,
+            summary_provider: None,
+        }
+    }
+
+    pub fn with_keepalive(mut self, keepalive: KeepaliveConfig) -> Self {
+        self.keepalive = Some(keepalive);
+        self
+    }
+
+    pub fn with_max_bundle_bytes(mut self, max_bundle_bytes: usize) -> Self {
+        self.max_bundle_bytes = max_bundle_bytes;
+        self
+    }
+
+    /// Close connections whose peer stalls for `read_timeout` in the middle of a
+    /// bundle, or before completing its contact header
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    /// Close connections whose peer stops reading, so that an ACK cannot be written
+    /// within `write_timeout`
+    pub fn with_write_timeout(mut self, write_timeout: Duration) -> Self {
+        self.write_timeout = write_timeout;
+        self
+    }
+
+    /// Exchange TCPCL v4 contact headers on every accepted connection, closing
+    /// connections from peers whose magic or version does not match
+    pub fn with_contact_header(mut self, contact_header: ContactHeader) -> Self {
+        self.contact_header = Some(contact_header);
+        self
+    }
+
+    /// Send connecting peers that also announce support the summary vector returned by
+    /// `provider`, so they skip bundles this side already holds. Only applies to
+    /// connections exchanging contact headers.
+    pub fn with_summary_vector(mut self, provider: SummaryProvider) -> Self {
+        self.summary_provider = Some(provider);
+        self
+    }
+
+    /// Accept connections until `shutdown` is cancelled.
+    ///
+    /// On cancellation the listening socket is closed and no new connections are
+    /// accepted; connections already being handled run to completion on their own tasks.
+    pub async fn activate_until(&self, shutdown: CancellationToken) -> Result<()> {
         let listener = TcpListener::bind(&self.bind_addr).await?;
-        println!("TCP CLA Listener listening on {}", self.bind_addr);
+        log::info!("TCP CLA Listener listening on {}", self.bind_addr);
 
         loop {
-            let (stream, addr) = listener.accept().await?;
-            println!("📨 New connection from: {addr}");
+            let (mut stream, addr) = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                accepted = listener.accept() => accepted?,
+            };
+            log::info!("New connection from: {addr}");
 
             let callback = Arc::clone(&self.receive_callback);
+            let mut options = ConnectionOptions {
+                keepalive: self.keepalive,
+                max_bundle_bytes: self.max_bundle_bytes,
+                read_timeout: self.read_timeout,
+                write_timeout: self.write_timeout,
+                wire_format: WireFormat::Cbor,
+            };
+            let summary_provider = self.summary_provider.clone();
+            let contact_header = self
+                .contact_header
+                .map(|header| header.with_summary_vector(summary_provider.is_some()));
             tokio::spawn(async move {
-                if let Err(e) = handle_connection(stream, callback).await {
-                    eprintln!("❌ Error handling connection: {e}");
+                if let Some(local) = contact_header {
+                    let negotiated = handshake_as_acceptor(
+                        &mut stream,
+                        &local,
+                        options.read_timeout,
+                        options.write_timeout,
+                    )
+                    .await
+                    .and_then(|peer| Ok((peer, local.negotiated_wire_format(&peer)?)));
+                    match negotiated {
+                        Ok((peer, negotiated_format)) => {
+                            options.wire_format = negotiated_format;
+                            let interval = local.negotiated_keepalive(&peer);
+                            if let Some(config) =
+                                options.keepalive.as_mut().filter(|_| interval > 0)
+                            {
+                                config.interval = Duration::from_secs(interval.into());
+                            }
+                            if let Some(provider) =
+                                summary_provider.filter(|_| local.negotiated_summary_vector(&peer))
+                            {
+                                if let Err(e) = send_summary_vector(
+                                    &mut stream,
+                                    provider,
+                                    options.write_timeout,
+                                )
+                                .await
+                                {
+                                    log::warn!("Failed to send summary vector to {addr}: {e}");
+                                    return;
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            log::warn!("Rejected connection from {addr}: {e}");
+                            return;
+                        }
+                    }
+                }
+                if let Err(e) = handle_connection_with_options(stream, callback, &options).await {
+                    log::error!("Error handling connection: {e}");
                 }
             });
         }
+
+        log::info!("TCP CLA Listener on {} shut down", self.bind_addr);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ConvergenceLayer for TcpClaListener {
+    fn address(&self) -> String {
+        self.bind_addr.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn activate(&self) -> Result<()> {
+        self.activate_until(CancellationToken::new()).await
     }
 }
 
+/// Handle an accepted connection with the default [`ConnectionOptions`],
+/// acknowledging every decodable bundle with `OK` after running `callback`
 pub async fn handle_connection<S>(
-    mut stream: S,
+    stream: S,
     callback: Arc<dyn Fn(Bundle) + Send + Sync>,
 ) -> Result<()>
 where
     S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
 {
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    handle_connection_with_options(stream, always_ok(callback), &ConnectionOptions::default()).await
+}
+
+/// Handle an accepted connection, acknowledging each bundle with the status returned
+/// by `callback`. Bundles that cannot be decoded, or were sent in the other wire
+/// format, are answered with `BAD` and the connection stays open. A declared length
+/// over the limit, a bundle whose data stalls for the read timeout or an ACK that
+/// cannot be written within the write timeout closes the connection with a
+/// [`FramingError`] or [`SendTimeout`].
+///
+/// A keepalive frame is a zero length prefix followed by a single control byte,
+/// so peers that never send a zero length are unaffected. Its control byte and the
+/// PING and PONG replies are bounded by the same read and write timeouts.
+/// [`read_ack`](crate::cla::tcp::ack::read_ack) skips PINGs that reach a sender
+/// ahead of an ACK.
+pub async fn handle_connection_with_options<S>(
+    mut stream: S,
+    callback: StatusCallback,
+    options: &ConnectionOptions,
+) -> Result<()>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    let ConnectionOptions {
+        keepalive,
+        max_bundle_bytes,
+        read_timeout,
+        write_timeout,
+        wire_format: format,
+    } = *options;
+
+    let mut missed = 0u32;
 
     loop {
-        // Read length
-        let mut len_buf = [0u8; 4];
-        match stream.read_exact(&mut len_buf).await {
+        // Wait for the next frame, probing an idle peer if keepalive is on
+        let mut len_buf = [0u8; LENGTH_PREFIX_LEN];
+        let read = match keepalive {
+            Some(config) => {
+                match tokio::time::timeout(config.interval, stream.read(&mut len_buf[..1])).await {
+                    Ok(read) => read,
+                    Err(_) => {
+                        // Idle: count the previous unanswered PING and probe again
+                        if missed >= config.max_missed {
+                            anyhow::bail!(
+                                "Peer missed {missed} keepalive responses, closing connection"
+                            );
+                        }
+                        missed += 1;
+                        send_keepalive(&mut stream, KEEPALIVE_PING, write_timeout).await?;
+                        continue;
+                    }
+                }
+            }
+            None => stream.read(&mut len_buf[..1]).await,
+        };
+        match read {
+            Ok(0) | Err(_) => return Ok(()), // EOF or connection closed
             Ok(_) => {}
-            Err(_) => break, // EOF or connection closed
         }
+        read_with_timeout(&mut stream, &mut len_buf[1..], read_timeout).await?;
 
-        let len = u32::from_be_bytes(len_buf) as usize;
-
-        // Read bundle data
-        let mut data = vec![0u8; len];
-        if stream.read_exact(&mut data).await.is_err() {
-            break;
+        let len = u32::from_be_bytes(len_buf);
+        if len == KEEPALIVE_SENTINEL_LEN {
+            let mut control = [0u8; 1];
+            read_with_timeout(&mut stream, &mut control, read_timeout).await?;
+            match control[0] {
+                KEEPALIVE_PING => {
+                    send_keepalive(&mut stream, KEEPALIVE_PONG, write_timeout).await?
+                }
+                KEEPALIVE_PONG => missed = 0,
+                other => log::warn!("Unknown keepalive control byte: {other:#04x}"),
+            }
+            continue;
         }
+        missed = 0;
+        let data = read_frame_data(&mut stream, len, max_bundle_bytes, read_timeout).await?;
 
         // Deserialize bundle
-        match serde_cbor::from_slice::<Bundle>(&data) {
+        let status = match format.decode(&data) {
             Ok(bundle) => {
-                // Call the callback
-                callback(bundle);
-
-                // Send OK response
-                let _ = stream.write_all(b"OK").await;
+                // The callback typically stores the bundle, so it runs on the blocking
+                // pool; the ACK is only sent once it has returned
+                let callback = Arc::clone(&callback);
+                tokio::task::spawn_blocking(move || callback(bundle))
+                    .await
+                    .unwrap_or_else(|e| {
+                        log::error!("Receive callback failed: {e}");
+                        AckStatus::Error
+                    })
+            }
+            Err(e @ WireFormatError::Mismatch { .. }) => {
+                log::warn!("Rejected bundle: {e}");
+                AckStatus::Bad
             }
             Err(e) => {
-                eprintln!("❌ Failed to deserialize bundle: {e}");
-                let _ = stream.write_all(b"ERROR").await;
+                log::error!("Failed to deserialize bundle: {e}");
+                AckStatus::Bad
+            }
+        };
+        // A peer that has gone away is noticed by the next read; one that stopped
+        // reading would otherwise block the connection forever
+        if let Err(e) =
+            write_with_timeout(&mut stream, status.as_str().as_bytes(), write_timeout).await
+        {
+            if e.is::<SendTimeout>() {
+                return Err(e);
             }
         }
     }
+}
+
+/// Build the summary vector on the blocking pool, since providers typically read
+/// the store, and send it
+async fn send_summary_vector<S>(
+    stream: &mut S,
+    provider: SummaryProvider,
+    timeout: Duration,
+) -> Result<()>
+where
+    S: tokio::io::AsyncWrite + Unpin,
+{
+    let summary = tokio::task::spawn_blocking(move || provider()).await?;
+    log::debug!("Sending summary vector of {} bundles", summary.len());
+    summary.write_to(stream, timeout).await
+}
 
-    Ok(())
+async fn send_keepalive<S>(stream: &mut S, control: u8, timeout: Duration) -> Result<()>
+where
+    S: tokio::io::AsyncWrite + Unpin,
+{
+    let mut frame = KEEPALIVE_SENTINEL_LEN.to_be_bytes().to_vec();
+    frame.push(control);
+    write_with_timeout(stream, &frame, timeout).await
 }
@@ -0,0 +1,121 @@
+use crate::bpv7::BundleId;
+use crate::cla::framing::{read_with_timeout, write_with_timeout};
+use crate::consts::tcp::MAX_SUMMARY_VECTOR_IDS;
+use anyhow::Result;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Bytes of a SHA-256 bundle ID digest on the wire
+const DIGEST_LEN: usize = 32;
+
+/// IDs of the bundles a node holds, sent by the accepting side right after the
+/// contact header exchange so the connecting side only sends what is missing.
+///
+/// On the wire it is a 32-bit big-endian count followed by the raw SHA-256 digest
+/// of each [`BundleId`], the same digest the stores use as bundle keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SummaryVector {
+    digests: HashSet<[u8; DIGEST_LEN]>,
+}
+
+impl SummaryVector {
+    pub fn from_ids<'a>(ids: impl IntoIterator<Item = &'a BundleId>) -> Self {
+        Self {
+            digests: ids.into_iter().map(BundleId::digest_bytes).collect(),
+        }
+    }
+
+    /// Build a vector from the hex digests that stores use as bundle keys, so the
+    /// bundles need not be loaded. Strings that are not such a digest are skipped.
+    pub fn from_digests<'a>(digests: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            digests: digests.into_iter().filter_map(parse_digest).collect(),
+        }
+    }
+
+    /// Whether the peer that sent this vector already holds `id`
+    pub fn contains(&self, id: &BundleId) -> bool {
+        self.digests.contains(&id.digest_bytes())
+    }
+
+    pub fn len(&self) -> usize {
+        self.digests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+
+    /// Write the vector, leaving out digests beyond the [`MAX_SUMMARY_VECTOR_IDS`]
+    /// a peer accepts; the bundles behind them are simply sent again. Fails with
+    /// [`SendTimeout`](crate::cla::framing::SendTimeout) if the peer stops reading
+    /// for `timeout`.
+    pub async fn write_to<S>(&self, stream: &mut S, timeout: Duration) -> Result<()>
+    where
+        S: AsyncWrite + Unpin,
+    {
+        self.write_at_most(stream, MAX_SUMMARY_VECTOR_IDS, timeout)
+            .await
+    }
+
+    pub(crate) async fn write_at_most<S>(
+        &self,
+        stream: &mut S,
+        max_ids: usize,
+        timeout: Duration,
+    ) -> Result<()>
+    where
+        S: AsyncWrite + Unpin,
+    {
+        let count = self.digests.len().min(max_ids);
+        if count < self.digests.len() {
+            log::warn!(
+                "Summary vector truncated to {count} of {} bundle IDs",
+                self.digests.len()
+            );
+        }
+        let mut frame = Vec::with_capacity(4 + count * DIGEST_LEN);
+        frame.extend_from_slice(&(count as u32).to_be_bytes());
+        for digest in self.digests.iter().take(count) {
+            frame.extend_from_slice(digest);
+        }
+        write_with_timeout(stream, &frame, timeout).await
+    }
+
+    /// Read a vector of at most `max_ids` digests, rejecting larger ones before
+    /// allocating for them. Fails with
+    /// [`FramingError::ReadTimeout`](crate::cla::framing::FramingError::ReadTimeout)
+    /// if no bytes arrive for `timeout`.
+    pub async fn read_from<S>(stream: &mut S, max_ids: usize, timeout: Duration) -> Result<Self>
+    where
+        S: AsyncRead + Unpin,
+    {
+        let mut count = [0u8; 4];
+        read_with_timeout(stream, &mut count, timeout).await?;
+        let count = u32::from_be_bytes(count) as usize;
+        if count > max_ids {
+            anyhow::bail!("Summary vector of {count} bundle IDs exceeds limit of {max_ids}");
+        }
+        let mut data = vec![0u8; count * DIGEST_LEN];
+        read_with_timeout(stream, &mut data, timeout).await?;
+        Ok(Self {
+            digests: data
+                .chunks_exact(DIGEST_LEN)
+                .map(|chunk| chunk.try_into().expect("chunks are DIGEST_LEN long"))
+                .collect(),
+        })
+    }
+}
+
+/// Decode a lowercase or uppercase hex SHA-256 digest
+fn parse_digest(hex: &str) -> Option<[u8; DIGEST_LEN]> {
+    if hex.len() != DIGEST_LEN * 2 || !hex.is_ascii() {
+        return None;
+    }
+    let mut digest = [0u8; DIGEST_LEN];
+    for (byte, pair) in digest.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(digest)
+}
@@ -0,0 +1,80 @@
+use crate::bpv7::bundle::Bundle;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Encoding of bundles on a TCP connection.
+///
+/// CBOR is the default; JSON lets peers that only speak JSON bundles interoperate.
+/// The format is negotiated in the contact header exchange, so connections using
+/// the legacy framing without a contact header always carry CBOR.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireFormat {
+    #[default]
+    Cbor,
+    Json,
+}
+
+/// Why a received frame could not be decoded as a bundle
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WireFormatError {
+    #[error("Peer sent a {received} bundle on a connection using {expected}")]
+    Mismatch {
+        expected: WireFormat,
+        received: WireFormat,
+    },
+    #[error("Invalid {format} bundle: {reason}")]
+    Invalid { format: WireFormat, reason: String },
+    #[error("Peer uses the {peer} wire format, but this side uses {local}")]
+    Negotiation { local: WireFormat, peer: WireFormat },
+}
+
+impl WireFormat {
+    pub fn encode(&self, bundle: &Bundle) -> anyhow::Result<Vec<u8>> {
+        match self {
+            WireFormat::Cbor => bundle.to_canonical_cbor(),
+            WireFormat::Json => Ok(serde_json::to_vec(bundle)?),
+        }
+    }
+
+    /// Decode a received frame. A frame that only decodes in the other format is
+    /// reported as a [`WireFormatError::Mismatch`].
+    pub fn decode(&self, data: &[u8]) -> Result<Bundle, WireFormatError> {
+        self.try_decode(data).map_err(|reason| {
+            let other = self.other();
+            match other.try_decode(data) {
+                Ok(_) => WireFormatError::Mismatch {
+                    expected: *self,
+                    received: other,
+                },
+                Err(_) => WireFormatError::Invalid {
+                    format: *self,
+                    reason,
+                },
+            }
+        })
+    }
+
+    fn try_decode(&self, data: &[u8]) -> Result<Bundle, String> {
+        match self {
+            WireFormat::Cbor => serde_cbor::from_slice(data).map_err(|e| e.to_string()),
+            WireFormat::Json => serde_json::from_slice(data).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn other(&self) -> WireFormat {
+        match self {
+            WireFormat::Cbor => WireFormat::Json,
+            WireFormat::Json => WireFormat::Cbor,
+        }
+    }
+}
+
+impl std::fmt::Display for WireFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WireFormat::Cbor => write!(f, "cbor"),
+            WireFormat::Json => write!(f, "json"),
+        }
+    }
+}
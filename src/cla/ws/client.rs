@@ -0,0 +1,160 @@
+use crate::bpv7::EndpointId;
+use crate::cla::peer::ClaPeer;
+use crate::consts::ws::OK;
+use crate::{bpv7::bundle::Bundle, cla::ConvergenceLayer};
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// WebSocket CLA client sending bundles to a `ws://` URL
+#[derive(Clone)]
+pub struct WsClaClient {
+    pub target_url: String,
+}
+
+impl WsClaClient {
+    pub fn new(target_url: String) -> Self {
+        Self { target_url }
+    }
+}
+
+#[async_trait]
+impl ConvergenceLayer for WsClaClient {
+    fn address(&self) -> String {
+        self.target_url.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn activate(&self) -> Result<()> {
+        let (mut stream, _) = connect_async(self.target_url.as_str()).await?;
+        log::info!("WebSocket connection established: {}", self.target_url);
+        let _ = stream.close(None).await;
+        Ok(())
+    }
+
+    async fn send_bundle(&self, bundle: &Bundle) -> Result<()> {
+        send_bundle_to(&self.target_url, bundle).await
+    }
+}
+
+/// WebSocket-specific implementation of ClaPeer for routing
+#[derive(Clone)]
+pub struct WsPeer {
+    pub peer_id: EndpointId,
+    pub url: String,
+}
+
+impl WsPeer {
+    pub fn new(peer_id: EndpointId, url: String) -> Self {
+        Self { peer_id, url }
+    }
+}
+
+#[async_trait]
+impl ConvergenceLayer for WsPeer {
+    fn address(&self) -> String {
+        self.url.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn activate(&self) -> Result<()> {
+        WsClaClient::new(self.url.clone()).activate().await
+    }
+
+    async fn send_bundle(&self, bundle: &Bundle) -> Result<()> {
+        send_bundle_to(&self.url, bundle).await
+    }
+}
+
+#[async_trait]
+impl ClaPeer for WsPeer {
+    fn get_peer_endpoint_id(&self) -> EndpointId {
+        self.peer_id.clone()
+    }
+
+    async fn is_reachable(&self) -> bool {
+        match tokio::time::timeout(Duration::from_secs(3), connect_async(self.url.as_str())).await {
+            Ok(Ok((mut stream, _))) => {
+                let _ = stream.close(None).await;
+                true
+            }
+            Ok(Err(e)) => {
+                log::warn!("WebSocket connection failed to {}: {e}", self.url);
+                false
+            }
+            Err(_) => {
+                log::warn!("WebSocket connection timeout to: {}", self.url);
+                false
+            }
+        }
+    }
+
+    fn get_cla_type(&self) -> &str {
+        "ws"
+    }
+
+    fn get_connection_address(&self) -> String {
+        self.url.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn ClaPeer> {
+        Box::new(self.clone())
+    }
+
+    async fn activate(&self) -> Result<()> {
+        <Self as ConvergenceLayer>::activate(self).await
+    }
+
+    async fn send_bundle(&self, bundle: &Bundle) -> Result<()> {
+        <Self as ConvergenceLayer>::send_bundle(self, bundle).await
+    }
+}
+
+/// Connect to `url`, send one bundle and wait for its ACK
+pub async fn send_bundle_to(url: &str, bundle: &Bundle) -> Result<()> {
+    let (mut stream, _) = connect_async(url).await?;
+    let result = send_bundle(&mut stream, bundle).await;
+    let _ = stream.close(None).await;
+    result
+}
+
+/// Send a bundle as a single binary message and wait for a text "OK" ACK
+pub async fn send_bundle(stream: &mut WsStream, bundle: &Bundle) -> Result<()> {
+    let encoded = bundle.to_canonical_cbor()?;
+    stream.send(Message::Binary(encoded.into())).await?;
+
+    while let Some(message) = stream.next().await {
+        match message? {
+            Message::Text(ack) if ack.as_str() == OK => {
+                log::debug!("Received ACK: \"{ack}\"");
+                return Ok(());
+            }
+            Message::Text(reply) => anyhow::bail!("Peer rejected bundle: {reply}"),
+            Message::Close(_) => break,
+            // Ping/pong frames are answered by tungstenite itself
+            _ => continue,
+        }
+    }
+
+    anyhow::bail!("Connection closed before ACK was received")
+}
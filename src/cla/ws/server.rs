@@ -0,0 +1,132 @@
+use crate::bpv7::bundle::Bundle;
+use crate::cla::ConvergenceLayer;
+use crate::consts::tcp::DEFAULT_MAX_BUNDLE_BYTES;
+use crate::consts::ws::{ERROR, OK};
+use anyhow::Result;
+use futures::{SinkExt, StreamExt};
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_util::sync::CancellationToken;
+
+/// WebSocket CLA listener.
+///
+/// Each binary message carries one CBOR-encoded bundle and is answered with a
+/// text "OK", or "ERROR" if the message could not be decoded.
+#[derive(Clone)]
+pub struct WsClaListener {
+    pub bind_addr: String,
+    pub receive_callback: Arc<dyn Fn(Bundle) + Send + Sync>,
+    /// Messages larger than this are rejected and the connection is closed
+    pub max_bundle_bytes: usize,
+}
+
+impl WsClaListener {
+    pub fn new(bind_addr: String, receive_callback: Arc<dyn Fn(Bundle) + Send + Sync>) -> Self {
+        Self {
+            bind_addr,
+            receive_callback,
+            max_bundle_bytes: DEFAULT_MAX_BUNDLE_BYTES,
+        }
+    }
+
+    pub fn with_max_bundle_bytes(mut self, max_bundle_bytes: usize) -> Self {
+        self.max_bundle_bytes = max_bundle_bytes;
+        self
+    }
+
+    /// Bind `bind_addr` and accept connections until `shutdown` is cancelled.
+    pub async fn activate_until(&self, shutdown: CancellationToken) -> Result<()> {
+        let listener = TcpListener::bind(&self.bind_addr).await?;
+        self.serve(listener, shutdown).await
+    }
+
+    /// Accept connections on an already bound listener until `shutdown` is cancelled.
+    ///
+    /// Connections already being handled run to completion on their own tasks.
+    pub async fn serve(&self, listener: TcpListener, shutdown: CancellationToken) -> Result<()> {
+        let local_addr = listener.local_addr()?;
+        log::info!("WebSocket CLA Listener listening on {local_addr}");
+
+        loop {
+            let (stream, addr) = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                accepted = listener.accept() => accepted?,
+            };
+            log::info!("New WebSocket connection from: {addr}");
+
+            let callback = Arc::clone(&self.receive_callback);
+            let max_bundle_bytes = self.max_bundle_bytes;
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(stream, callback, max_bundle_bytes).await {
+                    log::error!("Error handling WebSocket connection: {e}");
+                }
+            });
+        }
+
+        log::info!("WebSocket CLA Listener on {local_addr} shut down");
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ConvergenceLayer for WsClaListener {
+    fn address(&self) -> String {
+        self.bind_addr.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn ConvergenceLayer> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn activate(&self) -> Result<()> {
+        self.activate_until(CancellationToken::new()).await
+    }
+}
+
+/// Complete the WebSocket handshake and receive bundles until the peer closes.
+pub async fn handle_connection(
+    stream: TcpStream,
+    callback: Arc<dyn Fn(Bundle) + Send + Sync>,
+    max_bundle_bytes: usize,
+) -> Result<()> {
+    let config = WebSocketConfig::default()
+        .max_message_size(Some(max_bundle_bytes))
+        .max_frame_size(Some(max_bundle_bytes));
+    let mut ws = tokio_tungstenite::accept_async_with_config(stream, Some(config)).await?;
+
+    while let Some(message) = ws.next().await {
+        match message? {
+            Message::Binary(data) => match serde_cbor::from_slice::<Bundle>(&data) {
+                Ok(bundle) => {
+                    // Same as the TCP listener: store on the blocking pool, then acknowledge
+                    let callback = Arc::clone(&callback);
+                    let reply = match tokio::task::spawn_blocking(move || callback(bundle)).await {
+                        Ok(()) => OK,
+                        Err(e) => {
+                            log::error!("Receive callback failed: {e}");
+                            ERROR
+                        }
+                    };
+                    ws.send(Message::text(reply)).await?;
+                }
+                Err(e) => {
+                    log::error!("Failed to deserialize bundle: {e}");
+                    ws.send(Message::text(ERROR)).await?;
+                }
+            },
+            Message::Close(_) => break,
+            Message::Text(_) => {
+                log::warn!("Ignoring text message on WebSocket CLA");
+            }
+            _ => continue,
+        }
+    }
+
+    Ok(())
+}
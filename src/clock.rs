@@ -0,0 +1,154 @@
+//! Source of the current time for bundle creation timestamps and lifetime checks.
+//!
+//! Production code always reads the system clock. Tests can install a
+//! [`MockClock`] on their thread and advance it instead of sleeping across
+//! expiration boundaries.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Unix time in seconds
+pub trait Clock: Send + Sync {
+    fn now(&self) -> u64;
+}
+
+/// The operating system's wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+}
+
+/// Current unix time in seconds: the mock installed on this thread in tests,
+/// the system clock otherwise
+pub fn now() -> u64 {
+    #[cfg(test)]
+    if let Some(now) = mock::installed_now() {
+        return now;
+    }
+    SystemClock.now()
+}
+
+#[cfg(test)]
+pub use mock::{MockClock, MockClockGuard};
+
+#[cfg(test)]
+mod mock {
+    use super::Clock;
+    use std::cell::RefCell;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    thread_local! {
+        static INSTALLED: RefCell<Option<MockClock>> = const { RefCell::new(None) };
+    }
+
+    pub(super) fn installed_now() -> Option<u64> {
+        INSTALLED.with(|installed| installed.borrow().as_ref().map(Clock::now))
+    }
+
+    /// Clock that only moves when told to; clones share the same time
+    #[derive(Debug, Clone)]
+    pub struct MockClock {
+        now: Arc<AtomicU64>,
+    }
+
+    impl MockClock {
+        /// Start at unix time `now` (seconds)
+        pub fn new(now: u64) -> Self {
+            Self {
+                now: Arc::new(AtomicU64::new(now)),
+            }
+        }
+
+        /// Start at the current system time
+        pub fn starting_now() -> Self {
+            Self::new(super::SystemClock.now())
+        }
+
+        pub fn set(&self, now: u64) {
+            self.now.store(now, Ordering::SeqCst);
+        }
+
+        pub fn advance(&self, by: Duration) {
+            self.now.fetch_add(by.as_secs(), Ordering::SeqCst);
+        }
+
+        /// Make [`super::now`] read this clock on the current thread until the
+        /// returned guard is dropped
+        pub fn install(&self) -> MockClockGuard {
+            let previous = INSTALLED.with(|installed| installed.replace(Some(self.clone())));
+            MockClockGuard { previous }
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> u64 {
+            self.now.load(Ordering::SeqCst)
+        }
+    }
+
+    /// Restores the previously installed clock when dropped
+    pub struct MockClockGuard {
+        previous: Option<MockClock>,
+    }
+
+    impl Drop for MockClockGuard {
+        fn drop(&mut self) {
+            INSTALLED.with(|installed| *installed.borrow_mut() = self.previous.take());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bpv7::bundle::Bundle;
+    use std::time::Duration;
+
+    #[test]
+    fn test_mock_clock_only_moves_when_advanced() {
+        let clock = MockClock::new(1_700_000_000);
+        let _guard = clock.install();
+        assert_eq!(now(), 1_700_000_000);
+        assert_eq!(crate::config::generate_creation_timestamp(), 1_700_000_000);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(now(), 1_700_000_090);
+        clock.set(5);
+        assert_eq!(now(), 5);
+    }
+
+    #[test]
+    fn test_mock_clock_guard_restores_system_clock() {
+        let clock = MockClock::new(42);
+        {
+            let _guard = clock.install();
+            assert_eq!(now(), 42);
+        }
+        assert!(now() >= SystemClock.now().saturating_sub(1));
+    }
+
+    #[test]
+    fn test_advancing_mock_clock_expires_bundle() {
+        let clock = MockClock::starting_now();
+        let _guard = clock.install();
+        let mut bundle = Bundle::new("dtn://src", "dtn://dest", b"test".to_vec());
+        bundle.primary.lifetime = 60;
+        assert!(!bundle.is_expired());
+        assert_eq!(bundle.remaining_lifetime(), 60);
+
+        clock.advance(Duration::from_secs(60));
+        assert!(!bundle.is_expired());
+        assert_eq!(bundle.remaining_lifetime(), 0);
+
+        clock.advance(Duration::from_secs(1));
+        assert!(bundle.is_expired());
+    }
+}
@@ -1,4 +1,12 @@
+use crate::bpv7::{EndpointId, ReportPriorityPolicy, VerifyingKey};
+use crate::cla::serial::port::{SerialParity, SerialSettings};
+use crate::cla::tcp::wire::WireFormat;
+use crate::consts::{
+    DEFAULT_FRAGMENT_THRESHOLD, DEFAULT_MAX_CONCURRENT_SENDS, DEFAULT_MAX_FORWARDING_ATTEMPTS,
+    DEFAULT_RETRY_BASE_DELAY, DEFAULT_RETRY_MAX_DELAY,
+};
 use crate::routing::algorithm::RoutingAlgorithmType;
+use crate::store::EvictionPolicy;
 use serde::Deserialize;
 use std::path::Path;
 
@@ -6,6 +14,24 @@ use std::path::Path;
 pub struct BundleConfig {
     pub version: u8,
     pub lifetime: u64,
+    /// Gzip payloads of newly inserted bundles
+    #[serde(default)]
+    pub compress_payloads: bool,
+    /// Payload bytes per fragment when an imported file is too large for one bundle
+    #[serde(default = "default_fragment_threshold")]
+    pub fragment_threshold: u64,
+    /// Priority of the status reports this node generates, relative to the bundle
+    /// they report on
+    #[serde(default)]
+    pub report_priority_policy: ReportPriorityPolicy,
+    /// Reassemble received fragments before storing them, giving up on a set this
+    /// many seconds after its first fragment arrived
+    #[serde(default)]
+    pub reassembly_timeout_secs: Option<u64>,
+}
+
+fn default_fragment_threshold() -> u64 {
+    DEFAULT_FRAGMENT_THRESHOLD
 }
 
 #[derive(Debug, Deserialize)]
@@ -13,17 +39,259 @@ pub struct EndpointsConfig {
     pub destination: String,
     pub source: String,
     pub report_to: String,
+    /// This node's own endpoint: the source of bundles it creates and the
+    /// destination it delivers locally instead of forwarding
+    #[serde(default)]
+    pub node_eid: Option<String>,
+    /// EID schemes (`dtn`, `ipn`) inserted bundles must use for their source and
+    /// destination; empty accepts any scheme
+    #[serde(default)]
+    pub allowed_schemes: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct StorageConfig {
     pub path: String,
     pub max_size: u64,
+    /// Failed forwarding attempts after which a bundle is moved to `failed/`
+    #[serde(default = "default_max_forwarding_attempts")]
+    pub max_forwarding_attempts: u32,
+    /// Base in seconds of the retry backoff: after `n` failed forwarding passes a
+    /// bundle waits `retry_base_delay * 2^n` before it is retried
+    #[serde(default = "default_retry_base_delay")]
+    pub retry_base_delay: u64,
+    /// Upper bound in seconds on the delay between retries of a bundle
+    #[serde(default = "default_retry_max_delay")]
+    pub retry_max_delay: u64,
+    /// Transmissions to peers the forwarding loop keeps in flight at once
+    #[serde(default = "default_max_concurrent_sends")]
+    pub max_concurrent_sends: usize,
+    /// Bundles evicted to make room once `max_size` is reached; without one,
+    /// bundles that do not fit are rejected
+    #[serde(default)]
+    pub eviction_policy: Option<EvictionPolicy>,
+    /// Directory sent bundles are moved into, relative to the store directory unless
+    /// absolute; defaults to `<path>/dispatched`
+    #[serde(default)]
+    pub dispatched_path: Option<String>,
+    /// Seconds dispatched bundles are kept before the cleanup task deletes them;
+    /// without one they are kept forever
+    #[serde(default)]
+    pub dispatched_retention_secs: Option<u64>,
+    /// Most bundles stored for any single destination; unlimited when unset
+    #[serde(default)]
+    pub per_destination_max_bundles: Option<usize>,
+    /// Most bytes stored for any single destination, on top of the store-wide
+    /// `max_size`; unlimited when unset
+    #[serde(default)]
+    pub per_destination_max_bytes: Option<u64>,
+}
+
+fn default_max_forwarding_attempts() -> u32 {
+    DEFAULT_MAX_FORWARDING_ATTEMPTS
+}
+
+fn default_retry_base_delay() -> u64 {
+    DEFAULT_RETRY_BASE_DELAY
+}
+
+fn default_retry_max_delay() -> u64 {
+    DEFAULT_RETRY_MAX_DELAY
+}
+
+fn default_max_concurrent_sends() -> usize {
+    DEFAULT_MAX_CONCURRENT_SENDS
 }
 
 #[derive(Debug, Deserialize)]
 pub struct RoutingConfig {
     pub algorithm: String,
+    /// Balance bundles round-robin across equal-cost routes to a destination
+    #[serde(default)]
+    pub ecmp: bool,
+    /// JSON file the routing table is loaded from at startup and saved to whenever
+    /// a route is added or removed; without one, routes live only in memory
+    #[serde(default)]
+    pub routes_path: Option<String>,
+}
+
+/// Legacy `[cla.tcp_server]` table, read as a single TCP listener entry
+#[derive(Debug, Default, Deserialize)]
+pub struct TcpServerConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub address: Option<String>,
+    /// Exchange TCPCL v4 contact headers instead of using the legacy framing alone
+    #[serde(default)]
+    pub tcpcl_v4: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaType {
+    Tcp,
+    Udp,
+    Ble,
+    Ws,
+    Serial,
+}
+
+impl std::fmt::Display for ClaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ClaType::Tcp => "tcp",
+            ClaType::Udp => "udp",
+            ClaType::Ble => "ble",
+            ClaType::Ws => "ws",
+            ClaType::Serial => "serial",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaRole {
+    /// Accept bundles on `address`
+    Listener,
+    /// Register a peer reachable at `address` for forwarding
+    Dialer,
+}
+
+impl std::fmt::Display for ClaRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ClaRole::Listener => "listener",
+            ClaRole::Dialer => "dialer",
+        })
+    }
+}
+
+/// One `[[cla]]` entry: a convergence layer the node starts with
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ClaConfig {
+    #[serde(rename = "type")]
+    pub cla_type: ClaType,
+    pub role: ClaRole,
+    /// Bind address for listeners; peer address (BLE: device name) for dialers
+    pub address: String,
+    /// Endpoint ID of a dialer's peer (defaults to `dtn://<address>`)
+    #[serde(default)]
+    pub peer: Option<String>,
+    /// Exchange TCPCL v4 contact headers (TCP only)
+    #[serde(default)]
+    pub tcpcl_v4: bool,
+    /// Accept `ACK`, `SUCCESS` or `RECEIVED` as well as `OK` from listeners predating
+    /// the status codes (TCP dialers only)
+    #[serde(default)]
+    pub legacy_ack: bool,
+    /// Cap on the bytes per second sent to a dialer's peer
+    #[serde(default)]
+    pub max_bps: Option<u64>,
+    /// Bundle encoding announced in the contact header (TCP with `tcpcl_v4` only)
+    #[serde(default)]
+    pub wire_format: WireFormat,
+    /// Line speed of the device at `address` (serial only)
+    #[serde(default)]
+    pub baud_rate: Option<u32>,
+    /// Parity bit of the device at `address` (serial only)
+    #[serde(default)]
+    pub parity: SerialParity,
+    /// Seconds a bundle or ACK write may take before the peer is considered stalled
+    /// (TCP only, defaults to 30)
+    #[serde(default)]
+    pub write_timeout_secs: Option<u64>,
+}
+
+impl ClaConfig {
+    pub fn new(cla_type: ClaType, role: ClaRole, address: &str) -> Self {
+        Self {
+            cla_type,
+            role,
+            address: address.to_string(),
+            peer: None,
+            tcpcl_v4: false,
+            legacy_ack: false,
+            max_bps: None,
+            wire_format: WireFormat::Cbor,
+            baud_rate: None,
+            parity: SerialParity::None,
+            write_timeout_secs: None,
+        }
+    }
+
+    /// Device settings of a serial entry, whose `address` is the device path
+    pub fn serial_settings(&self) -> SerialSettings {
+        let settings = SerialSettings::new(self.address.clone()).with_parity(self.parity);
+        match self.baud_rate {
+            Some(baud_rate) => settings.with_baud_rate(baud_rate),
+            None => settings,
+        }
+    }
+
+    /// Endpoint ID a dialer registers its peer under
+    pub fn peer_eid(&self) -> EndpointId {
+        match &self.peer {
+            Some(peer) => EndpointId::from(peer.as_str()),
+            None => EndpointId::from(format!("dtn://{}", self.address).as_str()),
+        }
+    }
+}
+
+/// `cla` accepts either the `[[cla]]` array or the legacy `[cla.tcp_server]` table
+fn deserialize_cla<'de, D>(deserializer: D) -> Result<Vec<ClaConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ClaSection {
+        List(Vec<ClaConfig>),
+        Legacy { tcp_server: TcpServerConfig },
+    }
+
+    Ok(match ClaSection::deserialize(deserializer)? {
+        ClaSection::List(entries) => entries,
+        ClaSection::Legacy { tcp_server } => match tcp_server.address {
+            Some(address) if tcp_server.enabled => vec![ClaConfig {
+                tcpcl_v4: tcp_server.tcpcl_v4,
+                ..ClaConfig::new(ClaType::Tcp, ClaRole::Listener, &address)
+            }],
+            _ => Vec::new(),
+        },
+    })
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SecurityConfig {
+    /// Drop received bundles that are unsigned or fail signature verification
+    #[serde(default)]
+    pub require_signed: bool,
+    /// Base64-encoded Ed25519 public key that received bundles are verified against
+    #[serde(default)]
+    pub verifying_key: Option<String>,
+}
+
+impl SecurityConfig {
+    /// Decode the configured verifying key, if any
+    pub fn verifying_key(&self) -> Result<Option<VerifyingKey>, config::ConfigError> {
+        use base64::Engine;
+
+        let Some(encoded) = &self.verifying_key else {
+            return Ok(None);
+        };
+        let invalid =
+            |e: String| config::ConfigError::Message(format!("security.verifying_key: {e}"));
+        let bytes: [u8; 32] = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| invalid(e.to_string()))?
+            .try_into()
+            .map_err(|_| invalid("expected a 32-byte Ed25519 public key".to_string()))?;
+        VerifyingKey::from_bytes(&bytes)
+            .map(Some)
+            .map_err(|e| invalid(e.to_string()))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,6 +300,10 @@ pub struct Config {
     pub endpoints: EndpointsConfig,
     pub storage: StorageConfig,
     pub routing: RoutingConfig,
+    #[serde(default, deserialize_with = "deserialize_cla")]
+    pub cla: Vec<ClaConfig>,
+    #[serde(default)]
+    pub security: SecurityConfig,
 }
 
 impl Config {
@@ -47,14 +319,159 @@ impl Config {
         settings.try_deserialize()
     }
 
+    /// Check the loaded values, naming the offending field in the error
+    pub fn validate(&self) -> Result<(), config::ConfigError> {
+        let invalid = |message: String| Err(config::ConfigError::Message(message));
+
+        if self.bundle.version != 7 {
+            return invalid(format!(
+                "bundle.version must be 7 (BPv7), got {}",
+                self.bundle.version
+            ));
+        }
+        if self.bundle.lifetime == 0 {
+            return invalid("bundle.lifetime must be greater than 0".to_string());
+        }
+        if self.bundle.fragment_threshold == 0 {
+            return invalid("bundle.fragment_threshold must be greater than 0".to_string());
+        }
+        if self.bundle.reassembly_timeout_secs == Some(0) {
+            return invalid("bundle.reassembly_timeout_secs must be greater than 0".to_string());
+        }
+        for (field, value) in [
+            ("endpoints.source", &self.endpoints.source),
+            ("endpoints.destination", &self.endpoints.destination),
+            ("endpoints.report_to", &self.endpoints.report_to),
+        ] {
+            if value.trim().is_empty() {
+                return invalid(format!("{field} must not be empty"));
+            }
+        }
+        if let Some(node_eid) = &self.endpoints.node_eid {
+            if let Err(e) = EndpointId::parse(node_eid) {
+                return invalid(format!("endpoints.node_eid: {e}"));
+            }
+        }
+        if let Some(scheme) = self
+            .endpoints
+            .allowed_schemes
+            .iter()
+            .find(|s| !matches!(s.to_ascii_lowercase().as_str(), "dtn" | "ipn"))
+        {
+            return invalid(format!(
+                "endpoints.allowed_schemes: '{scheme}' is not a DTN scheme (expected 'dtn' or 'ipn')"
+            ));
+        }
+        if self.storage.max_size == 0 {
+            return invalid("storage.max_size must be greater than 0".to_string());
+        }
+        if self.storage.dispatched_retention_secs == Some(0) {
+            return invalid("storage.dispatched_retention_secs must be greater than 0".to_string());
+        }
+        if self.storage.per_destination_max_bundles == Some(0) {
+            return invalid(
+                "storage.per_destination_max_bundles must be greater than 0".to_string(),
+            );
+        }
+        if self.storage.per_destination_max_bytes == Some(0) {
+            return invalid("storage.per_destination_max_bytes must be greater than 0".to_string());
+        }
+        if self.storage.max_forwarding_attempts == 0 {
+            return invalid("storage.max_forwarding_attempts must be greater than 0".to_string());
+        }
+        if self.storage.max_concurrent_sends == 0 {
+            return invalid("storage.max_concurrent_sends must be greater than 0".to_string());
+        }
+        if self.storage.retry_max_delay < self.storage.retry_base_delay {
+            return invalid(format!(
+                "storage.retry_max_delay ({}) must not be less than storage.retry_base_delay ({})",
+                self.storage.retry_max_delay, self.storage.retry_base_delay
+            ));
+        }
+        if let Some(i) = self.cla.iter().position(|c| c.address.trim().is_empty()) {
+            return invalid(format!("cla[{i}].address must not be empty"));
+        }
+        if let Some(i) = self.cla.iter().position(|c| c.max_bps == Some(0)) {
+            return invalid(format!("cla[{i}].max_bps must be greater than 0"));
+        }
+        if let Some(i) = self
+            .cla
+            .iter()
+            .position(|c| c.write_timeout_secs == Some(0))
+        {
+            return invalid(format!(
+                "cla[{i}].write_timeout_secs must be greater than 0"
+            ));
+        }
+        if let Some(i) = self.cla.iter().position(|c| c.baud_rate == Some(0)) {
+            return invalid(format!("cla[{i}].baud_rate must be greater than 0"));
+        }
+        if let Some(i) = self.cla.iter().position(|c| {
+            (c.baud_rate.is_some() || c.parity != SerialParity::None)
+                && c.cla_type != ClaType::Serial
+        }) {
+            return invalid(format!(
+                "cla[{i}].baud_rate and cla[{i}].parity only apply to serial entries"
+            ));
+        }
+        // A serial device can only be held open by one side, so a listener and a
+        // dialer on the same line would lock each other out
+        let serial: Vec<(usize, &ClaConfig)> = self
+            .cla
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.cla_type == ClaType::Serial)
+            .collect();
+        for (n, (j, later)) in serial.iter().enumerate() {
+            if let Some((i, _)) = serial[..n].iter().find(|(_, c)| c.address == later.address) {
+                return invalid(format!(
+                    "cla[{j}] opens serial device {} already used by cla[{i}]; use one entry per device",
+                    later.address
+                ));
+            }
+        }
+        if let Some(i) = self.cla.iter().position(|c| {
+            c.wire_format != WireFormat::Cbor && !(c.cla_type == ClaType::Tcp && c.tcpcl_v4)
+        }) {
+            return invalid(format!(
+                "cla[{i}].wire_format = \"{}\" requires a tcp entry with tcpcl_v4 = true",
+                self.cla[i].wire_format
+            ));
+        }
+        if self.security.verifying_key()?.is_none() && self.security.require_signed {
+            return invalid(
+                "security.require_signed needs security.verifying_key to be set".to_string(),
+            );
+        }
+        if !matches!(
+            self.routing.algorithm.to_lowercase().as_str(),
+            "epidemic" | "destination_aware_epidemic" | "prophet"
+        ) {
+            return invalid(format!(
+                "routing.algorithm '{}' is not recognized (expected 'epidemic', 'destination_aware_epidemic' or 'prophet')",
+                self.routing.algorithm
+            ));
+        }
+        Ok(())
+    }
+
+    /// First TCP listener entry, whose address and contact header settings the
+    /// node uses when a single listener is started
+    pub fn tcp_listener(&self) -> Option<&ClaConfig> {
+        self.cla
+            .iter()
+            .find(|c| c.cla_type == ClaType::Tcp && c.role == ClaRole::Listener)
+    }
+
     pub fn get_routing_algorithm_type(&self) -> RoutingAlgorithmType {
         match self.routing.algorithm.to_lowercase().as_str() {
             "epidemic" => RoutingAlgorithmType::Epidemic,
+            "destination_aware_epidemic" => RoutingAlgorithmType::DestinationAwareEpidemic,
             "prophet" => RoutingAlgorithmType::Prophet,
             // "sprayandwait" => RoutingAlgorithmType::SprayAndWait,
             _ => {
-                eprintln!(
-                    "Warning: Unknown routing algorithm '{alg}', falling back to epidemic",
+                log::warn!(
+                    "Unknown routing algorithm '{alg}', falling back to epidemic",
                     alg = self.routing.algorithm
                 );
                 RoutingAlgorithmType::Epidemic
@@ -62,34 +479,55 @@ impl Config {
         }
     }
 
+    /// The configured `endpoints.node_eid`, if any
+    pub fn node_eid(&self) -> Option<EndpointId> {
+        self.endpoints.node_eid.as_deref().map(EndpointId::from)
+    }
+
     #[cfg(test)]
     pub fn test_config() -> Self {
         Config {
             bundle: BundleConfig {
                 version: 7,
                 lifetime: 3600,
+                compress_payloads: false,
+                fragment_threshold: DEFAULT_FRAGMENT_THRESHOLD,
+                report_priority_policy: ReportPriorityPolicy::Inherit,
+                reassembly_timeout_secs: None,
             },
             endpoints: EndpointsConfig {
                 destination: "dtn://dest".to_string(),
                 source: "dtn://src".to_string(),
                 report_to: "dtn://report".to_string(),
+                node_eid: None,
+                allowed_schemes: Vec::new(),
             },
             storage: StorageConfig {
                 path: "bundles".to_string(),
                 max_size: 1024,
+                max_forwarding_attempts: DEFAULT_MAX_FORWARDING_ATTEMPTS,
+                retry_base_delay: DEFAULT_RETRY_BASE_DELAY,
+                retry_max_delay: DEFAULT_RETRY_MAX_DELAY,
+                max_concurrent_sends: DEFAULT_MAX_CONCURRENT_SENDS,
+                eviction_policy: None,
+                dispatched_path: None,
+                dispatched_retention_secs: None,
+                per_destination_max_bundles: None,
+                per_destination_max_bytes: None,
             },
             routing: RoutingConfig {
                 algorithm: "epidemic".to_string(),
+                ecmp: false,
+                routes_path: None,
             },
+            cla: Vec::new(),
+            security: SecurityConfig::default(),
         }
     }
 }
 
 pub fn generate_creation_timestamp() -> u64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+    crate::clock::now()
 }
 
 #[cfg(test)]
@@ -145,19 +583,38 @@ mod tests {
             bundle: BundleConfig {
                 version: 7,
                 lifetime: 3600,
+                compress_payloads: false,
+                fragment_threshold: DEFAULT_FRAGMENT_THRESHOLD,
+                report_priority_policy: ReportPriorityPolicy::Inherit,
+                reassembly_timeout_secs: None,
             },
             endpoints: EndpointsConfig {
                 destination: "dtn://dest".to_string(),
                 source: "dtn://src".to_string(),
                 report_to: "dtn://report".to_string(),
+                node_eid: None,
+                allowed_schemes: Vec::new(),
             },
             storage: StorageConfig {
                 path: "bundles".to_string(),
                 max_size: 1024,
+                max_forwarding_attempts: DEFAULT_MAX_FORWARDING_ATTEMPTS,
+                retry_base_delay: DEFAULT_RETRY_BASE_DELAY,
+                retry_max_delay: DEFAULT_RETRY_MAX_DELAY,
+                max_concurrent_sends: DEFAULT_MAX_CONCURRENT_SENDS,
+                eviction_policy: None,
+                dispatched_path: None,
+                dispatched_retention_secs: None,
+                per_destination_max_bundles: None,
+                per_destination_max_bytes: None,
             },
             routing: RoutingConfig {
                 algorithm: "prophet".to_string(),
+                ecmp: false,
+                routes_path: None,
             },
+            cla: Vec::new(),
+            security: SecurityConfig::default(),
         };
 
         let algorithm_type = config.get_routing_algorithm_type();
@@ -170,19 +627,38 @@ mod tests {
             bundle: BundleConfig {
                 version: 7,
                 lifetime: 3600,
+                compress_payloads: false,
+                fragment_threshold: DEFAULT_FRAGMENT_THRESHOLD,
+                report_priority_policy: ReportPriorityPolicy::Inherit,
+                reassembly_timeout_secs: None,
             },
             endpoints: EndpointsConfig {
                 destination: "dtn://dest".to_string(),
                 source: "dtn://src".to_string(),
                 report_to: "dtn://report".to_string(),
+                node_eid: None,
+                allowed_schemes: Vec::new(),
             },
             storage: StorageConfig {
                 path: "bundles".to_string(),
                 max_size: 1024,
+                max_forwarding_attempts: DEFAULT_MAX_FORWARDING_ATTEMPTS,
+                retry_base_delay: DEFAULT_RETRY_BASE_DELAY,
+                retry_max_delay: DEFAULT_RETRY_MAX_DELAY,
+                max_concurrent_sends: DEFAULT_MAX_CONCURRENT_SENDS,
+                eviction_policy: None,
+                dispatched_path: None,
+                dispatched_retention_secs: None,
+                per_destination_max_bundles: None,
+                per_destination_max_bytes: None,
             },
             routing: RoutingConfig {
                 algorithm: "EPIDEMIC".to_string(),
+                ecmp: false,
+                routes_path: None,
             },
+            cla: Vec::new(),
+            security: SecurityConfig::default(),
         };
 
         let algorithm_type = config.get_routing_algorithm_type();
@@ -195,19 +671,38 @@ mod tests {
             bundle: BundleConfig {
                 version: 7,
                 lifetime: 3600,
+                compress_payloads: false,
+                fragment_threshold: DEFAULT_FRAGMENT_THRESHOLD,
+                report_priority_policy: ReportPriorityPolicy::Inherit,
+                reassembly_timeout_secs: None,
             },
             endpoints: EndpointsConfig {
                 destination: "dtn://dest".to_string(),
                 source: "dtn://src".to_string(),
                 report_to: "dtn://report".to_string(),
+                node_eid: None,
+                allowed_schemes: Vec::new(),
             },
             storage: StorageConfig {
                 path: "bundles".to_string(),
                 max_size: 1024,
+                max_forwarding_attempts: DEFAULT_MAX_FORWARDING_ATTEMPTS,
+                retry_base_delay: DEFAULT_RETRY_BASE_DELAY,
+                retry_max_delay: DEFAULT_RETRY_MAX_DELAY,
+                max_concurrent_sends: DEFAULT_MAX_CONCURRENT_SENDS,
+                eviction_policy: None,
+                dispatched_path: None,
+                dispatched_retention_secs: None,
+                per_destination_max_bundles: None,
+                per_destination_max_bytes: None,
             },
             routing: RoutingConfig {
                 algorithm: "unknown_algorithm".to_string(),
+                ecmp: false,
+                routes_path: None,
             },
+            cla: Vec::new(),
+            security: SecurityConfig::default(),
         };
 
         let algorithm_type = config.get_routing_algorithm_type();
@@ -219,6 +714,10 @@ mod tests {
         let bundle_config = BundleConfig {
             version: 7,
             lifetime: 3600,
+            compress_payloads: false,
+            fragment_threshold: DEFAULT_FRAGMENT_THRESHOLD,
+            report_priority_policy: ReportPriorityPolicy::Inherit,
+            reassembly_timeout_secs: None,
         };
 
         let debug_str = format!("{bundle_config:?}");
@@ -233,6 +732,8 @@ mod tests {
             destination: "dtn://dest".to_string(),
             source: "dtn://src".to_string(),
             report_to: "dtn://report".to_string(),
+            node_eid: None,
+            allowed_schemes: Vec::new(),
         };
 
         let debug_str = format!("{endpoints_config:?}");
@@ -247,6 +748,15 @@ mod tests {
         let storage_config = StorageConfig {
             path: "test_bundles".to_string(),
             max_size: 2048,
+            max_forwarding_attempts: DEFAULT_MAX_FORWARDING_ATTEMPTS,
+            retry_base_delay: DEFAULT_RETRY_BASE_DELAY,
+            retry_max_delay: DEFAULT_RETRY_MAX_DELAY,
+            max_concurrent_sends: DEFAULT_MAX_CONCURRENT_SENDS,
+            eviction_policy: None,
+            dispatched_path: None,
+            dispatched_retention_secs: None,
+            per_destination_max_bundles: None,
+            per_destination_max_bytes: None,
         };
 
         let debug_str = format!("{storage_config:?}");
@@ -259,6 +769,8 @@ mod tests {
     fn test_routing_config_debug() {
         let routing_config = RoutingConfig {
             algorithm: "epidemic".to_string(),
+            ecmp: false,
+            routes_path: None,
         };
 
         let debug_str = format!("{routing_config:?}");
@@ -313,4 +825,577 @@ mod tests {
         assert_eq!(config.storage.max_size, 1024);
         assert_eq!(config.routing.algorithm, "epidemic");
     }
+
+    fn assert_invalid(config: Config, field: &str) {
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains(field), "{message}");
+    }
+
+    #[test]
+    fn test_validate_accepts_test_config() {
+        assert!(Config::test_config().validate().is_ok());
+
+        let mut config = Config::test_config();
+        config.routing.algorithm = "Prophet".to_string();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_get_routing_algorithm_type_destination_aware_epidemic() {
+        let mut config = Config::test_config();
+        config.routing.algorithm = "destination_aware_epidemic".to_string();
+        assert!(config.validate().is_ok());
+        assert!(matches!(
+            config.get_routing_algorithm_type(),
+            RoutingAlgorithmType::DestinationAwareEpidemic
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_wrong_version() {
+        let mut config = Config::test_config();
+        config.bundle.version = 6;
+        assert_invalid(config, "bundle.version must be 7 (BPv7), got 6");
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_lifetime() {
+        let mut config = Config::test_config();
+        config.bundle.lifetime = 0;
+        assert_invalid(config, "bundle.lifetime");
+    }
+
+    #[test]
+    fn test_fragment_threshold_defaults_and_must_be_positive() {
+        assert_eq!(
+            parse("").bundle.fragment_threshold,
+            DEFAULT_FRAGMENT_THRESHOLD
+        );
+
+        let mut config = Config::test_config();
+        config.bundle.fragment_threshold = 0;
+        assert_invalid(config, "bundle.fragment_threshold must be greater than 0");
+    }
+
+    #[test]
+    fn test_reassembly_timeout_secs() {
+        assert_eq!(parse("").bundle.reassembly_timeout_secs, None);
+
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                &BASE_TOML.replace(
+                    "lifetime = 3600",
+                    "lifetime = 3600\nreassembly_timeout_secs = 300",
+                ),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<Config>()
+            .unwrap();
+        assert_eq!(config.bundle.reassembly_timeout_secs, Some(300));
+
+        let mut config = Config::test_config();
+        config.bundle.reassembly_timeout_secs = Some(0);
+        assert_invalid(
+            config,
+            "bundle.reassembly_timeout_secs must be greater than 0",
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_endpoints() {
+        let mut config = Config::test_config();
+        config.endpoints.source = String::new();
+        assert_invalid(config, "endpoints.source must not be empty");
+
+        let mut config = Config::test_config();
+        config.endpoints.destination = "  ".to_string();
+        assert_invalid(config, "endpoints.destination must not be empty");
+
+        let mut config = Config::test_config();
+        config.endpoints.report_to = String::new();
+        assert_invalid(config, "endpoints.report_to must not be empty");
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_max_size() {
+        let mut config = Config::test_config();
+        config.storage.max_size = 0;
+        assert_invalid(config, "storage.max_size");
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_max_forwarding_attempts() {
+        let mut config = Config::test_config();
+        config.storage.max_forwarding_attempts = 0;
+        assert_invalid(config, "storage.max_forwarding_attempts");
+    }
+
+    #[test]
+    fn test_max_concurrent_sends_defaults_and_must_be_positive() {
+        assert_eq!(
+            parse("").storage.max_concurrent_sends,
+            DEFAULT_MAX_CONCURRENT_SENDS
+        );
+        let mut config = Config::test_config();
+        config.storage.max_concurrent_sends = 0;
+        assert_invalid(
+            config,
+            "storage.max_concurrent_sends must be greater than 0",
+        );
+    }
+
+    #[test]
+    fn test_retry_delays_default_and_must_be_ordered() {
+        let config = parse("");
+        assert_eq!(config.storage.retry_base_delay, DEFAULT_RETRY_BASE_DELAY);
+        assert_eq!(config.storage.retry_max_delay, DEFAULT_RETRY_MAX_DELAY);
+
+        let mut config = Config::test_config();
+        config.storage.retry_base_delay = 60;
+        config.storage.retry_max_delay = 30;
+        assert_invalid(
+            config,
+            "storage.retry_max_delay (30) must not be less than storage.retry_base_delay (60)",
+        );
+    }
+
+    #[test]
+    fn test_eviction_policy_defaults_to_rejecting() {
+        assert_eq!(parse("").storage.eviction_policy, None);
+        let policy: EvictionPolicy = serde_json::from_str("\"lowest_priority\"").unwrap();
+        assert_eq!(policy, EvictionPolicy::LowestPriority);
+        let policy: EvictionPolicy =
+            serde_json::from_str("\"shortest_remaining_lifetime\"").unwrap();
+        assert_eq!(policy, EvictionPolicy::ShortestRemainingLifetime);
+    }
+
+    #[test]
+    fn test_dispatched_path_and_retention() {
+        let config = parse("");
+        assert_eq!(config.storage.dispatched_path, None);
+        assert_eq!(config.storage.dispatched_retention_secs, None);
+
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                &BASE_TOML.replace(
+                    "max_size = 1024",
+                    "max_size = 1024\ndispatched_path = \"/var/lib/sdtn/sent\"\ndispatched_retention_secs = 3600",
+                ),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<Config>()
+            .unwrap();
+        assert_eq!(
+            config.storage.dispatched_path.as_deref(),
+            Some("/var/lib/sdtn/sent")
+        );
+        assert_eq!(config.storage.dispatched_retention_secs, Some(3600));
+
+        let mut config = Config::test_config();
+        config.storage.dispatched_retention_secs = Some(0);
+        assert_invalid(
+            config,
+            "storage.dispatched_retention_secs must be greater than 0",
+        );
+    }
+
+    #[test]
+    fn test_per_destination_quotas() {
+        let defaults = parse("");
+        assert_eq!(defaults.storage.per_destination_max_bundles, None);
+        assert_eq!(defaults.storage.per_destination_max_bytes, None);
+
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                &BASE_TOML.replace(
+                    "max_size = 1024",
+                    "max_size = 1024\nper_destination_max_bundles = 100\nper_destination_max_bytes = 1048576",
+                ),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<Config>()
+            .unwrap();
+        assert_eq!(config.storage.per_destination_max_bundles, Some(100));
+        assert_eq!(config.storage.per_destination_max_bytes, Some(1_048_576));
+
+        let mut config = Config::test_config();
+        config.storage.per_destination_max_bundles = Some(0);
+        assert_invalid(
+            config,
+            "storage.per_destination_max_bundles must be greater than 0",
+        );
+        let mut config = Config::test_config();
+        config.storage.per_destination_max_bytes = Some(0);
+        assert_invalid(
+            config,
+            "storage.per_destination_max_bytes must be greater than 0",
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_routing_algorithm() {
+        let mut config = Config::test_config();
+        config.routing.algorithm = "flooding".to_string();
+        assert_invalid(config, "routing.algorithm 'flooding' is not recognized");
+    }
+
+    #[test]
+    fn test_validate_security_verifying_key() {
+        use base64::Engine;
+
+        let mut config = Config::test_config();
+        config.security.require_signed = true;
+        assert_invalid(
+            config,
+            "security.require_signed needs security.verifying_key",
+        );
+
+        let mut config = Config::test_config();
+        config.security.verifying_key = Some("not base64!".to_string());
+        assert_invalid(config, "security.verifying_key");
+
+        let key = crate::bpv7::SigningKey::from_bytes(&[5u8; 32]).verifying_key();
+        let mut config = Config::test_config();
+        config.security.require_signed = true;
+        config.security.verifying_key =
+            Some(base64::engine::general_purpose::STANDARD.encode(key.as_bytes()));
+        assert!(config.validate().is_ok());
+        assert_eq!(config.security.verifying_key().unwrap(), Some(key));
+    }
+
+    const BASE_TOML: &str = r#"
+        [bundle]
+        version = 7
+        lifetime = 3600
+
+        [endpoints]
+        destination = "dtn://dest"
+        source = "dtn://src"
+        report_to = "dtn://report"
+
+        [storage]
+        path = "bundles"
+        max_size = 1024
+
+        [routing]
+        algorithm = "epidemic"
+    "#;
+
+    fn parse(extra: &str) -> Config {
+        config::Config::builder()
+            .add_source(config::File::from_str(
+                &format!("{BASE_TOML}\n{extra}"),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_routing_ecmp() {
+        assert!(!parse("").routing.ecmp);
+
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                &BASE_TOML.replace(
+                    "algorithm = \"epidemic\"",
+                    "algorithm = \"epidemic\"\necmp = true",
+                ),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<Config>()
+            .unwrap();
+        assert!(config.routing.ecmp);
+        assert_eq!(config.routing.routes_path, None);
+    }
+
+    #[test]
+    fn test_parse_routing_routes_path() {
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                &BASE_TOML.replace(
+                    "algorithm = \"epidemic\"",
+                    "algorithm = \"epidemic\"\nroutes_path = \"/var/lib/sdtn/routes.json\"",
+                ),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<Config>()
+            .unwrap();
+        assert_eq!(
+            config.routing.routes_path.as_deref(),
+            Some("/var/lib/sdtn/routes.json")
+        );
+    }
+
+    #[test]
+    fn test_parse_report_priority_policy() {
+        let config = parse("");
+        assert_eq!(
+            config.bundle.report_priority_policy,
+            ReportPriorityPolicy::Inherit
+        );
+
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                &BASE_TOML.replace(
+                    "lifetime = 3600",
+                    "lifetime = 3600\nreport_priority_policy = \"expedited\"",
+                ),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<Config>()
+            .unwrap();
+        assert_eq!(
+            config.bundle.report_priority_policy,
+            ReportPriorityPolicy::Expedited
+        );
+    }
+
+    #[test]
+    fn test_parse_multiple_clas() {
+        let config = parse(
+            r#"
+            [[cla]]
+            type = "tcp"
+            role = "listener"
+            address = "0.0.0.0:4556"
+            tcpcl_v4 = true
+
+            [[cla]]
+            type = "tcp"
+            role = "dialer"
+            address = "10.0.0.2:4556"
+            peer = "dtn://relay"
+
+            [[cla]]
+            type = "ble"
+            role = "dialer"
+            address = "sdtn-peer"
+            max_bps = 2000
+
+            [[cla]]
+            type = "udp"
+            role = "listener"
+            address = "0.0.0.0:4557"
+            "#,
+        );
+
+        let types: Vec<(ClaType, ClaRole)> =
+            config.cla.iter().map(|c| (c.cla_type, c.role)).collect();
+        assert_eq!(
+            types,
+            vec![
+                (ClaType::Tcp, ClaRole::Listener),
+                (ClaType::Tcp, ClaRole::Dialer),
+                (ClaType::Ble, ClaRole::Dialer),
+                (ClaType::Udp, ClaRole::Listener),
+            ]
+        );
+        assert!(config.cla[0].tcpcl_v4);
+        assert_eq!(config.cla[1].peer_eid(), EndpointId::from("dtn://relay"));
+        assert_eq!(
+            config.cla[2].peer_eid(),
+            EndpointId::from("dtn://sdtn-peer")
+        );
+        assert_eq!(config.cla[1].max_bps, None);
+        assert_eq!(config.cla[2].max_bps, Some(2000));
+        assert_eq!(config.tcp_listener().unwrap().address, "0.0.0.0:4556");
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_parse_legacy_tcp_server_table() {
+        let config = parse(
+            r#"
+            [cla.tcp_server]
+            enabled = true
+            address = "127.0.0.1:4556"
+            tcpcl_v4 = true
+            "#,
+        );
+        let listener = config.tcp_listener().unwrap();
+        assert_eq!(listener.address, "127.0.0.1:4556");
+        assert!(listener.tcpcl_v4);
+        assert_eq!(config.cla.len(), 1);
+
+        let config = parse(
+            r#"
+            [cla.tcp_server]
+            enabled = false
+            address = "127.0.0.1:4556"
+            "#,
+        );
+        assert!(config.cla.is_empty());
+
+        assert!(parse("").cla.is_empty());
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_cla_address() {
+        let mut config = Config::test_config();
+        config.cla = vec![ClaConfig::new(ClaType::Tcp, ClaRole::Dialer, " ")];
+        assert_invalid(config, "cla[0].address must not be empty");
+    }
+
+    #[test]
+    fn test_node_eid_is_validated() {
+        let mut config = parse("");
+        assert_eq!(config.node_eid(), None);
+
+        config.endpoints.node_eid = Some("dtn://relay-1".to_string());
+        assert_eq!(config.node_eid(), Some(EndpointId::from("dtn://relay-1")));
+        assert!(config.validate().is_ok());
+
+        config.endpoints.node_eid = Some("relay-1".to_string());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("endpoints.node_eid"), "{err}");
+    }
+
+    #[test]
+    fn test_allowed_schemes() {
+        let config = parse("");
+        assert!(config.endpoints.allowed_schemes.is_empty());
+
+        let config: Config = config::Config::builder()
+            .add_source(config::File::from_str(
+                &BASE_TOML.replace(
+                    "report_to = \"dtn://report\"",
+                    "report_to = \"dtn://report\"\nallowed_schemes = [\"dtn\", \"IPN\"]",
+                ),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(config.endpoints.allowed_schemes, ["dtn", "IPN"]);
+        assert!(config.validate().is_ok());
+
+        let mut config = Config::test_config();
+        config.endpoints.allowed_schemes = vec!["dtn".to_string(), "http".to_string()];
+        assert_invalid(
+            config,
+            "endpoints.allowed_schemes: 'http' is not a DTN scheme",
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_max_bps() {
+        let mut config = Config::test_config();
+        config.cla = vec![ClaConfig {
+            max_bps: Some(0),
+            ..ClaConfig::new(ClaType::Ble, ClaRole::Dialer, "sdtn-peer")
+        }];
+        assert_invalid(config, "cla[0].max_bps must be greater than 0");
+    }
+
+    #[test]
+    fn test_cla_wire_format() {
+        let config = parse(
+            r#"
+            [[cla]]
+            type = "tcp"
+            role = "dialer"
+            address = "127.0.0.1:4556"
+            tcpcl_v4 = true
+            wire_format = "json"
+            "#,
+        );
+        assert_eq!(config.cla[0].wire_format, WireFormat::Json);
+        assert!(config.validate().is_ok());
+
+        let mut config = Config::test_config();
+        config.cla = vec![ClaConfig {
+            wire_format: WireFormat::Json,
+            ..ClaConfig::new(ClaType::Tcp, ClaRole::Listener, "127.0.0.1:4556")
+        }];
+        assert_invalid(
+            config,
+            "cla[0].wire_format = \"json\" requires a tcp entry with tcpcl_v4 = true",
+        );
+    }
+
+    #[test]
+    fn test_cla_serial_settings() {
+        let config = parse(
+            r#"
+            [[cla]]
+            type = "serial"
+            role = "dialer"
+            address = "/dev/ttyUSB0"
+            peer = "dtn://modem"
+            baud_rate = 9600
+            parity = "even"
+            "#,
+        );
+        assert!(config.validate().is_ok());
+        let settings = config.cla[0].serial_settings();
+        assert_eq!(settings.path, "/dev/ttyUSB0");
+        assert_eq!(settings.baud_rate, 9600);
+        assert_eq!(settings.parity, SerialParity::Even);
+
+        let defaults = ClaConfig::new(ClaType::Serial, ClaRole::Listener, "COM3").serial_settings();
+        assert_eq!(defaults.baud_rate, crate::consts::serial::DEFAULT_BAUD_RATE);
+        assert_eq!(defaults.parity, SerialParity::None);
+
+        let mut config = Config::test_config();
+        config.cla = vec![ClaConfig {
+            baud_rate: Some(9600),
+            ..ClaConfig::new(ClaType::Tcp, ClaRole::Dialer, "127.0.0.1:4556")
+        }];
+        assert_invalid(config, "cla[0].baud_rate and cla[0].parity only apply");
+    }
+
+    #[test]
+    fn test_cla_serial_device_used_twice_is_invalid() {
+        let mut config = Config::test_config();
+        config.cla = vec![
+            ClaConfig::new(ClaType::Serial, ClaRole::Listener, "/dev/ttyUSB0"),
+            ClaConfig::new(ClaType::Serial, ClaRole::Listener, "/dev/ttyUSB1"),
+            ClaConfig {
+                peer: Some("dtn://modem".to_string()),
+                ..ClaConfig::new(ClaType::Serial, ClaRole::Dialer, "/dev/ttyUSB0")
+            },
+        ];
+        assert_invalid(
+            config,
+            "cla[2] opens serial device /dev/ttyUSB0 already used by cla[0]",
+        );
+    }
+
+    #[test]
+    fn test_cla_write_timeout() {
+        let config = parse(
+            r#"
+            [[cla]]
+            type = "tcp"
+            role = "dialer"
+            address = "127.0.0.1:4556"
+            write_timeout_secs = 10
+            "#,
+        );
+        assert!(config.validate().is_ok());
+        assert_eq!(config.cla[0].write_timeout_secs, Some(10));
+
+        let mut config = Config::test_config();
+        config.cla = vec![ClaConfig {
+            write_timeout_secs: Some(0),
+            ..ClaConfig::new(ClaType::Tcp, ClaRole::Listener, "127.0.0.1:4556")
+        }];
+        assert_invalid(config, "cla[0].write_timeout_secs must be greater than 0");
+    }
 }
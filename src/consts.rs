@@ -3,6 +3,28 @@ pub const DEFAULT_LIFETIME: u64 = 3600;
 pub const DEFAULT_REPORT_TO: &str = "none";
 pub const BUNDLES_DIR: &str = "./bundles";
 pub const DISPATCHED_DIR: &str = "./bundles/dispatched";
+/// Failed forwarding attempts before a bundle is given up on
+pub const DEFAULT_MAX_FORWARDING_ATTEMPTS: u32 = 10;
+/// Base (seconds) of the forwarding retry backoff: after `n` failed passes a
+/// bundle waits `base * 2^n` before it is retried
+pub const DEFAULT_RETRY_BASE_DELAY: u64 = 5;
+/// Upper bound (seconds) on the delay between forwarding retries of a bundle
+pub const DEFAULT_RETRY_MAX_DELAY: u64 = 300;
+/// Bundle transmissions the forwarding loop keeps in flight at once
+pub const DEFAULT_MAX_CONCURRENT_SENDS: usize = 8;
+/// File in the store directory holding the routing algorithm's saved state
+pub const ROUTING_STATE_FILE: &str = "routing_state.bin";
+/// File in the store directory remembering the IDs of bundles already received
+pub const SEEN_BUNDLES_FILE: &str = "seen_bundles.log";
+/// Remaining lifetime (seconds) under which a bundle counts as expiring soon
+pub const DEFAULT_EXPIRING_SOON_WINDOW: u64 = 300;
+/// Payload size above which imported files are split into fragments (1 MiB)
+pub const DEFAULT_FRAGMENT_THRESHOLD: u64 = 1024 * 1024;
+/// Seconds a custodian waits for a custody signal before sending the bundle again
+pub const DEFAULT_CUSTODY_TIMEOUT: u64 = 600;
+/// Received bundles an `incoming_bundles` stream may fall behind by before it skips
+/// the oldest ones
+pub const INCOMING_STREAM_CAPACITY: usize = 1024;
 
 // Bundle subdirectories
 pub const BUNDLES_BASIC_DIR: &str = "./bundles/basic";
@@ -15,6 +37,7 @@ pub mod ble {
     pub const NOTIFY_CHAR_UUID: &str = "12345678-1234-5678-1234-56789abcdef2";
     pub const ADV_NAME: &str = "spacearth-dtn-ble";
     pub const ACK: &[u8] = b"ACK\n";
+    pub const DEFAULT_CHUNK_SIZE: usize = 180;
 }
 
 pub mod tcp {
@@ -22,6 +45,47 @@ pub mod tcp {
     pub const OK: &str = "OK";
     pub const SUCCESS: &str = "SUCCESS";
     pub const RECEIVED: &str = "RECEIVED";
+    /// Reply when the receiver's store has no room for the bundle
+    pub const FULL: &str = "FULL";
+    /// Reply when a received bundle cannot be decoded or is rejected
+    pub const BAD: &str = "BAD";
+    /// Reply for any other failure while handling a received bundle
+    pub const ERROR: &str = "ERROR";
+    /// Times a bundle answered with `FULL` or `ERROR` is sent again
+    pub const ACK_RETRIES: u32 = 2;
+    /// Base backoff before resending a bundle that was not acknowledged with `OK`
+    pub const ACK_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+    /// Largest declared bundle length a listener will accept (16 MiB)
+    pub const DEFAULT_MAX_BUNDLE_BYTES: usize = 16 * 1024 * 1024;
+    /// Longest a listener waits for the next bytes of a bundle it has started receiving
+    pub const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+    /// Longest a bundle or ACK write may take before the peer is considered stalled
+    pub const DEFAULT_WRITE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+    /// Most bundle IDs accepted in a peer's summary vector (32 MiB of digests)
+    pub const MAX_SUMMARY_VECTOR_IDS: usize = 1 << 20;
+    /// How long a reachability probe or a send waits for the TCP connection to open
+    pub const DEFAULT_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+}
+
+pub mod ws {
+    /// Text message acknowledging a received bundle
+    pub const OK: &str = "OK";
+    /// Text message sent when a received binary message is not a valid bundle
+    pub const ERROR: &str = "ERROR";
+}
+
+pub mod serial {
+    pub const DEFAULT_BAUD_RATE: u32 = 115_200;
+    /// How long a dialer waits for the ACK frame after sending a bundle
+    pub const ACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+}
+
+pub mod beacon {
+    /// Administratively scoped multicast group neighbor beacons are sent to
+    pub const DEFAULT_GROUP: std::net::Ipv4Addr = std::net::Ipv4Addr::new(239, 255, 45, 56);
+    pub const DEFAULT_PORT: u16 = 4557;
+    /// Largest encoded beacon accepted
+    pub const MAX_BEACON_BYTES: usize = 1024;
 }
 
 #[cfg(test)]
@@ -8,16 +8,19 @@
 pub mod api;
 pub mod bpv7;
 pub mod cla;
+pub mod clock;
 pub mod config;
 pub mod routing;
 pub mod store;
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
 
 // Constants and utilities
 pub mod consts;
 
 // Re-export commonly used types for convenience
 pub use api::{node::DtnNode, BundleStatus};
-pub use bpv7::{bundle::Bundle, EndpointId};
+pub use bpv7::{bundle::Bundle, bundle::Priority, BundleId, EndpointId};
 
 // Re-export convenience functions for easy access
 pub use api::convenience;
@@ -1,46 +1,197 @@
 use crate::bpv7::EndpointId;
 use crate::cla::peer::ClaPeer;
+use crate::config::generate_creation_timestamp;
+use crate::routing::contact::ContactPlan;
+use crate::routing::explain::{PeerDecision, RejectionReason, RouteDecision};
 use crate::store::bundle_descriptor::BundleDescriptor;
+use crate::store::file::write_atomically;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Represents a route entry in the routing table
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RouteEntry {
     pub destination: EndpointId,
     pub next_hop: EndpointId,
     pub cla_type: String,
     pub cost: u32,
     pub is_active: bool,
+    /// Unix timestamp (seconds) after which the route is no longer valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<u64>,
+    /// Expected one-way latency of the link in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u32>,
+    /// Expected data rate of the link in bits per second
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_rate_bps: Option<u64>,
+    /// Fraction of transmissions expected to succeed, from 0.0 to 1.0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reliability: Option<f32>,
+}
+
+/// On-disk form of a routing table written by [`RoutingTable::save`]
+#[derive(Serialize, Deserialize)]
+struct SavedRoutes {
+    routes: Vec<RouteEntry>,
+}
+
+impl RouteEntry {
+    /// Check whether the route's validity window has passed at `now` (unix seconds)
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.valid_until.is_some_and(|until| until < now)
+    }
+}
+
+/// Scores a route for selection; lower scores are preferred
+pub type RouteScorer = fn(&RouteEntry) -> u64;
+
+/// Default [`RouteScorer`]: the route's configured cost
+pub fn cost_score(route: &RouteEntry) -> u64 {
+    u64::from(route.cost)
 }
 
 /// Routing table that maps destinations to next hops and CLAs
 #[derive(Debug, Default)]
 pub struct RoutingTable {
     routes: HashMap<EndpointId, Vec<RouteEntry>>,
+    /// Spread traffic over equal-cost routes instead of always using the first
+    ecmp: bool,
+    /// Per destination pattern, how many selections [`RoutingTable::select_route`] has
+    /// made among its routes; atomic so selecting only needs a shared reference
+    ecmp_counters: HashMap<EndpointId, AtomicUsize>,
+    /// Ranks routes in place of [`cost_score`] when set
+    scorer: Option<RouteScorer>,
 }
 
 impl RoutingTable {
     pub fn new() -> Self {
-        Self {
-            routes: HashMap::new(),
-        }
+        Self::default()
+    }
+
+    /// Rotate [`RoutingTable::select_route`] round-robin over the lowest-cost routes
+    pub fn with_ecmp(mut self, ecmp: bool) -> Self {
+        self.ecmp = ecmp;
+        self
+    }
+
+    pub fn ecmp(&self) -> bool {
+        self.ecmp
+    }
+
+    /// Rank routes with `scorer` instead of by bare cost
+    pub fn with_scorer(mut self, scorer: RouteScorer) -> Self {
+        self.scorer = Some(scorer);
+        self
+    }
+
+    /// Score of `route` under this table's scorer; lower is better
+    pub fn route_score(&self, route: &RouteEntry) -> u64 {
+        self.scorer.unwrap_or(cost_score)(route)
     }
 
     pub fn add_route(&mut self, entry: RouteEntry) {
+        self.ecmp_counters
+            .entry(entry.destination.clone())
+            .or_default();
         self.routes
             .entry(entry.destination.clone())
             .or_default()
             .push(entry);
     }
 
+    /// Remove the routes to exactly `destination` (not wildcard matches) through
+    /// `next_hop`, keeping other routes to the same destination. Returns whether
+    /// any route was removed.
+    pub fn remove_route(&mut self, destination: &EndpointId, next_hop: &EndpointId) -> bool {
+        let Some(routes) = self.routes.get_mut(destination) else {
+            return false;
+        };
+        let before = routes.len();
+        routes.retain(|r| r.next_hop != *next_hop);
+        let removed = routes.len() != before;
+        if routes.is_empty() {
+            self.routes.remove(destination);
+            self.ecmp_counters.remove(destination);
+        }
+        removed
+    }
+
+    /// Remove every route, returning how many there were
+    pub fn clear(&mut self) -> usize {
+        let removed = self.entries().count();
+        self.routes.clear();
+        self.ecmp_counters.clear();
+        removed
+    }
+
+    /// Write every route, including inactive and expired ones, to `path` as JSON
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let mut destinations: Vec<&EndpointId> = self.routes.keys().collect();
+        destinations.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        let routes = destinations
+            .into_iter()
+            .flat_map(|destination| self.routes[destination].iter().cloned())
+            .collect();
+        let json = serde_json::to_vec_pretty(&SavedRoutes { routes })?;
+        write_atomically(path, &json)?;
+        Ok(())
+    }
+
+    /// Replace the routes with those saved at `path`, returning how many were
+    /// loaded. A missing file loads no routes and leaves the table unchanged.
+    pub fn load(&mut self, path: &Path) -> anyhow::Result<usize> {
+        let json = match std::fs::read(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let saved: SavedRoutes = serde_json::from_slice(&json)
+            .map_err(|e| anyhow::anyhow!("Invalid routes file {}: {e}", path.display()))?;
+        self.clear();
+        let loaded = saved.routes.len();
+        for route in saved.routes {
+            self.add_route(route);
+        }
+        Ok(loaded)
+    }
+
+    /// Get the active, unexpired routes for the most specific destination pattern matching `destination`.
+    /// An exact match wins over any wildcard; among wildcards the longest prefix wins.
     pub fn get_routes_for_destination(&self, destination: &EndpointId) -> Vec<&RouteEntry> {
+        let now = generate_creation_timestamp();
         self.routes
-            .get(destination)
-            .map(|routes| routes.iter().filter(|r| r.is_active).collect())
+            .iter()
+            .filter(|(pattern, _)| destination.matches(pattern))
+            .map(|(pattern, routes)| {
+                let active: Vec<&RouteEntry> = routes
+                    .iter()
+                    .filter(|r| r.is_active && !r.is_expired_at(now))
+                    .collect();
+                (Self::specificity(pattern), active)
+            })
+            .filter(|(_, active)| !active.is_empty())
+            .max_by_key(|(specificity, _)| *specificity)
+            .map(|(_, active)| active)
             .unwrap_or_default()
     }
 
+    fn specificity(pattern: &EndpointId) -> usize {
+        if pattern.is_wildcard() {
+            pattern.as_str().len() - 1
+        } else {
+            usize::MAX
+        }
+    }
+
+    /// Every route in the table, including inactive and expired ones
+    pub fn entries(&self) -> impl Iterator<Item = &RouteEntry> {
+        self.routes.values().flatten()
+    }
+
     pub fn get_all_routes(&self) -> Vec<&RouteEntry> {
         self.routes
             .values()
@@ -49,11 +200,99 @@ impl RoutingTable {
             .collect()
     }
 
+    /// Remove routes whose `valid_until` is before `now`, returning how many were removed
+    pub fn prune_expired(&mut self, now: u64) -> usize {
+        let mut pruned = 0;
+        self.routes.retain(|_, routes| {
+            let before = routes.len();
+            routes.retain(|r| !r.is_expired_at(now));
+            pruned += before - routes.len();
+            !routes.is_empty()
+        });
+        let routes = &self.routes;
+        self.ecmp_counters
+            .retain(|destination, _| routes.contains_key(destination));
+        pruned
+    }
+
+    /// Sync routes with a contact plan: each link in the plan gets a direct route to its
+    /// peer that is active only while one of the link's contact windows includes `now`.
+    /// Routes not described by the plan are left untouched.
+    pub fn apply_contact_plan(&mut self, plan: &ContactPlan, now: u64) {
+        for contact in &plan.contacts {
+            let is_active = plan
+                .active_at(now)
+                .any(|c| c.to == contact.to && c.cla_type == contact.cla_type);
+
+            self.ecmp_counters.entry(contact.to.clone()).or_default();
+            let routes = self.routes.entry(contact.to.clone()).or_default();
+            match routes
+                .iter_mut()
+                .find(|r| r.next_hop == contact.to && r.cla_type == contact.cla_type)
+            {
+                Some(route) => route.is_active = is_active,
+                None => routes.push(RouteEntry {
+                    destination: contact.to.clone(),
+                    next_hop: contact.to.clone(),
+                    cla_type: contact.cla_type.clone(),
+                    cost: 1,
+                    is_active,
+                    valid_until: None,
+                    latency_ms: None,
+                    data_rate_bps: None,
+                    reliability: None,
+                }),
+            }
+        }
+    }
+
     /// Find the best route for a destination
     pub fn find_best_route(&self, destination: &EndpointId) -> Option<&RouteEntry> {
         self.get_routes_for_destination(destination)
             .into_iter()
-            .min_by_key(|route| route.cost)
+            .min_by_key(|route| self.route_score(route))
+    }
+
+    /// Every usable route for a destination, best score first; routes of equal score
+    /// keep the order they were added in
+    pub fn find_routes_ranked(&self, destination: &EndpointId) -> Vec<RouteEntry> {
+        let mut routes: Vec<RouteEntry> = self
+            .get_routes_for_destination(destination)
+            .into_iter()
+            .cloned()
+            .collect();
+        routes.sort_by_key(|route| self.route_score(route));
+        routes
+    }
+
+    /// Route to use for the next bundle to `destination`. Without ECMP this is
+    /// [`RoutingTable::find_best_route`]; with it, successive calls cycle through
+    /// all routes sharing the lowest score.
+    pub fn select_route(&self, destination: &EndpointId) -> Option<RouteEntry> {
+        self.select_route_where(destination, |_| true)
+    }
+
+    /// [`RoutingTable::select_route`] among only the routes `usable` accepts, e.g.
+    /// those whose next hop is currently reachable
+    pub fn select_route_where(
+        &self,
+        destination: &EndpointId,
+        usable: impl Fn(&RouteEntry) -> bool,
+    ) -> Option<RouteEntry> {
+        let mut ranked = self.find_routes_ranked(destination);
+        ranked.retain(|route| usable(route));
+        let lowest = self.route_score(ranked.first()?);
+        ranked.retain(|route| self.route_score(route) == lowest);
+        if !self.ecmp || ranked.len() == 1 {
+            return ranked.into_iter().next();
+        }
+        // All matching routes come from one destination pattern, which owns the counter
+        let index = self
+            .ecmp_counters
+            .get(&ranked[0].destination)
+            .map_or(0, |counter| counter.fetch_add(1, Ordering::Relaxed));
+        let index = index % ranked.len();
+        Some(ranked.swap_remove(index))
     }
 }
 
@@ -82,31 +321,152 @@ pub trait RoutingAlgorithm: Send + Sync {
         descriptor: &BundleDescriptor,
         routing_table: &RoutingTable,
     ) -> Vec<RouteEntry>;
+
+    /// Called by the forwarding loop once `peer` has acknowledged receipt of the bundle,
+    /// so algorithms can learn from successful deliveries (e.g. PROPHET predictabilities
+    /// or Spray-and-Wait copy counts). Does nothing by default.
+    fn notify_delivery_confirmed(&mut self, _descriptor: &BundleDescriptor, _peer: &EndpointId) {}
+
+    /// Serialize learned state (e.g. PROPHET predictabilities or Spray-and-Wait copy
+    /// counts) so it survives a restart. Stateless algorithms return an empty blob,
+    /// which is not persisted.
+    fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    /// Restore state produced by `save_state`. Does nothing by default.
+    fn load_state(&mut self, _bytes: &[u8]) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Explain `select_peers_for_forwarding`: one decision per peer in `peers`, with the
+    /// reason it was passed over. Algorithms with their own criteria can override this
+    /// to report them.
+    fn explain_peer_selection(
+        &self,
+        descriptor: &BundleDescriptor,
+        peers: &[Box<dyn ClaPeer>],
+    ) -> Vec<PeerDecision> {
+        let selected: Vec<EndpointId> = self
+            .select_peers_for_forwarding(descriptor, peers)
+            .into_iter()
+            .map(|peer| peer.get_peer_endpoint_id())
+            .collect();
+        peers
+            .iter()
+            .map(|peer| {
+                let eid = peer.get_peer_endpoint_id();
+                let rejection = if selected.contains(&eid) {
+                    None
+                } else if descriptor.has_been_sent_to(&eid) {
+                    Some(RejectionReason::AlreadySent)
+                } else if descriptor.is_previous_hop(&eid) {
+                    Some(RejectionReason::PreviousHop)
+                } else {
+                    Some(RejectionReason::NotSelected)
+                };
+                PeerDecision {
+                    peer: eid,
+                    cla_type: peer.get_cla_type().to_string(),
+                    rejection,
+                }
+            })
+            .collect()
+    }
+
+    /// Explain `select_routes_for_forwarding`: one decision per entry in the routing table
+    fn explain_route_selection(
+        &self,
+        descriptor: &BundleDescriptor,
+        routing_table: &RoutingTable,
+    ) -> Vec<RouteDecision> {
+        let selected = self.select_routes_for_forwarding(descriptor, routing_table);
+        let destination = EndpointId::from(descriptor.bundle.primary.destination.as_str());
+        let now = generate_creation_timestamp();
+        routing_table
+            .entries()
+            .map(|route| {
+                let rejection = if !destination.matches(&route.destination) {
+                    Some(RejectionReason::WrongDestination)
+                } else if !route.is_active || route.is_expired_at(now) {
+                    Some(RejectionReason::InactiveRoute)
+                } else if selected.iter().any(|s| {
+                    s.destination == route.destination
+                        && s.next_hop == route.next_hop
+                        && s.cla_type == route.cla_type
+                }) {
+                    None
+                } else if descriptor.has_been_sent_to(&route.next_hop) {
+                    Some(RejectionReason::AlreadySent)
+                } else if descriptor.is_previous_hop(&route.next_hop) {
+                    Some(RejectionReason::PreviousHop)
+                } else {
+                    Some(RejectionReason::NotSelected)
+                };
+                RouteDecision {
+                    route: route.clone(),
+                    rejection,
+                }
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug)]
 pub enum RoutingAlgorithmType {
     Epidemic,
+    /// Epidemic flooding that only selects routes towards the bundle's destination
+    DestinationAwareEpidemic,
     Prophet,
     // SprayAndWait,
 }
 
 pub struct RoutingConfig {
     pub algorithm_type: RoutingAlgorithmType,
+    /// Balance traffic across equal-cost routes round-robin
+    pub ecmp: bool,
+    /// Custom route ranking; routes are ranked by cost when unset
+    pub route_scorer: Option<RouteScorer>,
 }
 
 impl RoutingConfig {
     pub fn new(algorithm_type: RoutingAlgorithmType) -> Self {
-        Self { algorithm_type }
+        Self {
+            algorithm_type,
+            ecmp: false,
+            route_scorer: None,
+        }
+    }
+
+    pub fn with_ecmp(mut self, ecmp: bool) -> Self {
+        self.ecmp = ecmp;
+        self
+    }
+
+    pub fn with_route_scorer(mut self, scorer: RouteScorer) -> Self {
+        self.route_scorer = Some(scorer);
+        self
+    }
+
+    /// Empty routing table honouring the `ecmp` and `route_scorer` settings
+    pub fn create_routing_table(&self) -> RoutingTable {
+        let table = RoutingTable::new().with_ecmp(self.ecmp);
+        match self.route_scorer {
+            Some(scorer) => table.with_scorer(scorer),
+            None => table,
+        }
     }
 
     pub fn create_algorithm(&self) -> Box<dyn RoutingAlgorithm> {
         match self.algorithm_type {
             RoutingAlgorithmType::Epidemic => Box::new(crate::routing::epidemic::EpidemicRouting),
+            RoutingAlgorithmType::DestinationAwareEpidemic => {
+                Box::new(crate::routing::epidemic::DestinationAwareEpidemicRouting)
+            }
             RoutingAlgorithmType::Prophet => {
                 // TODO: Implement Prophet routing algorithm
                 // For now, fall back to epidemic
-                eprintln!("Warning: Prophet routing not yet implemented, falling back to epidemic");
+                log::warn!("Prophet routing not yet implemented, falling back to epidemic");
                 Box::new(crate::routing::epidemic::EpidemicRouting)
             }
         }
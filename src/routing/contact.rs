@@ -0,0 +1,52 @@
+use crate::bpv7::EndpointId;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A scheduled communication window between two nodes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+    pub from: EndpointId,
+    pub to: EndpointId,
+    /// Unix timestamp (seconds) when the link becomes available
+    pub start: u64,
+    /// Unix timestamp (seconds) when the link goes away
+    pub end: u64,
+    pub cla_type: String,
+}
+
+impl Contact {
+    /// Check whether the contact window `[start, end)` includes `now`
+    pub fn is_active_at(&self, now: u64) -> bool {
+        self.start <= now && now < self.end
+    }
+}
+
+/// Predicted link availability, e.g. ground station passes of a satellite
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactPlan {
+    #[serde(default)]
+    pub contacts: Vec<Contact>,
+}
+
+impl ContactPlan {
+    pub fn new(contacts: Vec<Contact>) -> Self {
+        Self { contacts }
+    }
+
+    /// Load a contact plan from a TOML file containing `[[contacts]]` tables
+    pub fn from_toml<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let settings = config::Config::builder()
+            .add_source(config::File::new(
+                &path.to_string_lossy(),
+                config::FileFormat::Toml,
+            ))
+            .build()?;
+        Ok(settings.try_deserialize()?)
+    }
+
+    /// Contacts whose window includes `now`
+    pub fn active_at(&self, now: u64) -> impl Iterator<Item = &Contact> {
+        self.contacts.iter().filter(move |c| c.is_active_at(now))
+    }
+}
@@ -1,4 +1,6 @@
+use crate::bpv7::EndpointId;
 use crate::cla::peer::ClaPeer;
+use crate::config::generate_creation_timestamp;
 use crate::routing::algorithm::{RouteEntry, RoutingAlgorithm, RoutingTable};
 use crate::store::bundle_descriptor::BundleDescriptor;
 use async_trait::async_trait;
@@ -26,14 +28,18 @@ impl RoutingAlgorithm for EpidemicRouting {
         descriptor: &BundleDescriptor,
         all_peers: &'a [Box<dyn ClaPeer>],
     ) -> Vec<&'a dyn ClaPeer> {
-        // Epidemic routing: forward to ALL available peers (except those already sent to)
+        // Epidemic routing: forward to ALL available peers (except those already sent to
+        // and the one the bundle came from)
         // This is the core of epidemic routing - no routing decisions, just flood to everyone
         let mut seen_eids = HashSet::new();
         let mut result = Vec::new();
 
         for peer in all_peers {
             let eid = peer.get_peer_endpoint_id();
-            if !descriptor.has_been_sent_to(&eid) && seen_eids.insert(eid.clone()) {
+            if !descriptor.has_been_sent_to(&eid)
+                && !descriptor.is_previous_hop(&eid)
+                && seen_eids.insert(eid.clone())
+            {
                 result.push(&**peer);
             }
         }
@@ -47,21 +53,17 @@ impl RoutingAlgorithm for EpidemicRouting {
         descriptor: &BundleDescriptor,
         all_peers: &'a [Box<dyn ClaPeer>],
     ) -> Vec<&'a dyn ClaPeer> {
-        // Epidemic routing with connectivity check: forward to ALL reachable peers
-        let mut seen_eids = HashSet::new();
-        let mut result = Vec::new();
+        // Epidemic routing with connectivity check: forward to ALL reachable peers.
+        // Candidates are probed concurrently so one slow peer does not hold up the rest.
+        let candidates = self.select_peers_for_forwarding(descriptor, all_peers);
+        let reachable =
+            futures::future::join_all(candidates.iter().map(|peer| peer.is_reachable())).await;
 
-        for peer in all_peers {
-            let eid = peer.get_peer_endpoint_id();
-            if !descriptor.has_been_sent_to(&eid)
-                && seen_eids.insert(eid.clone())
-                && peer.is_reachable().await
-            {
-                result.push(&**peer);
-            }
-        }
-
-        result
+        candidates
+            .into_iter()
+            .zip(reachable)
+            .filter_map(|(peer, reachable)| reachable.then_some(peer))
+            .collect()
     }
 
     fn select_routes_for_forwarding(
@@ -74,3 +76,62 @@ impl RoutingAlgorithm for EpidemicRouting {
         Vec::new()
     }
 }
+
+/// Epidemic routing that only uses routes towards the bundle's own destination
+///
+/// Peers are flooded exactly as with [`EpidemicRouting`]. From the routing table,
+/// every active route whose destination (exact or wildcard prefix) matches the
+/// bundle's destination is selected, once per next hop, skipping next hops that
+/// already have the bundle or sent it to us.
+#[derive(Default)]
+pub struct DestinationAwareEpidemicRouting;
+
+#[async_trait]
+impl RoutingAlgorithm for DestinationAwareEpidemicRouting {
+    fn notify_new_bundle(&mut self, _descriptor: &BundleDescriptor) {}
+
+    fn select_peers_for_forwarding<'a>(
+        &self,
+        descriptor: &BundleDescriptor,
+        all_peers: &'a [Box<dyn ClaPeer>],
+    ) -> Vec<&'a dyn ClaPeer> {
+        EpidemicRouting.select_peers_for_forwarding(descriptor, all_peers)
+    }
+
+    async fn select_peers_for_forwarding_async<'a>(
+        &self,
+        descriptor: &BundleDescriptor,
+        all_peers: &'a [Box<dyn ClaPeer>],
+    ) -> Vec<&'a dyn ClaPeer> {
+        EpidemicRouting
+            .select_peers_for_forwarding_async(descriptor, all_peers)
+            .await
+    }
+
+    fn select_routes_for_forwarding(
+        &self,
+        descriptor: &BundleDescriptor,
+        routing_table: &RoutingTable,
+    ) -> Vec<RouteEntry> {
+        let destination = EndpointId::from(descriptor.bundle.primary.destination.as_str());
+        let now = generate_creation_timestamp();
+        let mut routes: Vec<&RouteEntry> = routing_table
+            .entries()
+            .filter(|route| {
+                route.is_active
+                    && !route.is_expired_at(now)
+                    && destination.matches(&route.destination)
+                    && !descriptor.has_been_sent_to(&route.next_hop)
+                    && !descriptor.is_previous_hop(&route.next_hop)
+            })
+            .collect();
+        routes.sort_by_key(|route| routing_table.route_score(route));
+
+        let mut seen_next_hops = HashSet::new();
+        routes
+            .into_iter()
+            .filter(|route| seen_next_hops.insert(route.next_hop.clone()))
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,88 @@
+use crate::bpv7::EndpointId;
+use crate::routing::algorithm::RouteEntry;
+use std::fmt;
+
+/// Why a candidate peer or route was not chosen for forwarding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+    /// The bundle was already sent to (or received from) this peer
+    AlreadySent,
+    /// The bundle's previous node block names this peer as the hop it came from
+    PreviousHop,
+    /// The peer's convergence layer reported it as unreachable
+    Unreachable,
+    /// The route is disabled or its validity window has passed
+    InactiveRoute,
+    /// The route's destination pattern does not match the bundle's destination
+    WrongDestination,
+    /// The routing algorithm passed over the candidate, e.g. for a more specific or cheaper one
+    NotSelected,
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            RejectionReason::AlreadySent => "already sent",
+            RejectionReason::PreviousHop => "previous hop",
+            RejectionReason::Unreachable => "unreachable",
+            RejectionReason::InactiveRoute => "inactive route",
+            RejectionReason::WrongDestination => "wrong destination",
+            RejectionReason::NotSelected => "not selected by the routing algorithm",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// Forwarding decision for one registered peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerDecision {
+    pub peer: EndpointId,
+    pub cla_type: String,
+    /// `None` if the peer was selected
+    pub rejection: Option<RejectionReason>,
+}
+
+impl PeerDecision {
+    pub fn is_selected(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+/// Forwarding decision for one routing table entry
+#[derive(Debug, Clone)]
+pub struct RouteDecision {
+    pub route: RouteEntry,
+    /// `None` if the route was selected
+    pub rejection: Option<RejectionReason>,
+}
+
+impl RouteDecision {
+    pub fn is_selected(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+/// Dry-run of route selection for a bundle: every candidate peer and route,
+/// with the reason it would not be used
+#[derive(Debug, Clone)]
+pub struct ForwardingExplanation {
+    pub bundle_id: String,
+    pub destination: EndpointId,
+    pub peers: Vec<PeerDecision>,
+    pub routes: Vec<RouteDecision>,
+}
+
+impl ForwardingExplanation {
+    /// Peers the bundle would be sent to
+    pub fn selected_peers(&self) -> impl Iterator<Item = &EndpointId> {
+        self.peers
+            .iter()
+            .filter(|d| d.is_selected())
+            .map(|d| &d.peer)
+    }
+
+    /// The decision recorded for `peer`, if it is registered
+    pub fn peer(&self, peer: &EndpointId) -> Option<&PeerDecision> {
+        self.peers.iter().find(|d| &d.peer == peer)
+    }
+}
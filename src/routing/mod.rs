@@ -1,5 +1,7 @@
 pub mod algorithm;
+pub mod contact;
 pub mod epidemic;
+pub mod explain;
 
 #[cfg(test)]
 mod tests;
@@ -5,7 +5,8 @@ use crate::cla::TcpPeer;
 use crate::routing::algorithm::{
     RouteEntry, RoutingAlgorithm, RoutingAlgorithmType, RoutingConfig, RoutingTable,
 };
-use crate::routing::epidemic::EpidemicRouting;
+use crate::routing::contact::{Contact, ContactPlan};
+use crate::routing::epidemic::{DestinationAwareEpidemicRouting, EpidemicRouting};
 use crate::store::bundle_descriptor::BundleDescriptor;
 
 #[test]
@@ -30,6 +31,10 @@ fn test_route_entry_creation() {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     assert_eq!(entry.destination.as_str(), "dtn://dest");
@@ -47,6 +52,10 @@ fn test_route_entry_clone() {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     let cloned = entry.clone();
@@ -78,6 +87,10 @@ fn test_routing_table_add_route() {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     table.add_route(entry.clone());
@@ -97,6 +110,10 @@ fn test_routing_table_multiple_routes_same_destination() {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     let entry2 = RouteEntry {
@@ -105,6 +122,10 @@ fn test_routing_table_multiple_routes_same_destination() {
         cla_type: "ble".to_string(),
         cost: 5,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     table.add_route(entry1);
@@ -125,6 +146,10 @@ fn test_routing_table_inactive_routes() {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: false,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     table.add_route(entry);
@@ -144,6 +169,10 @@ fn test_routing_table_find_best_route() {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     let entry2 = RouteEntry {
@@ -152,6 +181,10 @@ fn test_routing_table_find_best_route() {
         cla_type: "ble".to_string(),
         cost: 5,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     table.add_route(entry1);
@@ -162,6 +195,350 @@ fn test_routing_table_find_best_route() {
     assert_eq!(best.unwrap().cost, 5); // Should return the route with lowest cost
 }
 
+fn route(destination: &str, next_hop: &str, cost: u32) -> RouteEntry {
+    RouteEntry {
+        destination: EndpointId::from(destination),
+        next_hop: EndpointId::from(next_hop),
+        cla_type: "tcp".to_string(),
+        cost,
+        is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
+    }
+}
+
+#[test]
+fn test_routing_table_remove_route_matches_next_hop() {
+    let mut table = RoutingTable::new();
+    table.add_route(route("dtn://ground", "dtn://relay1", 10));
+    table.add_route(route("dtn://ground", "dtn://relay2", 20));
+
+    let ground = EndpointId::from("dtn://ground");
+    assert!(table.remove_route(&ground, &EndpointId::from("dtn://relay1")));
+    assert!(!table.remove_route(&ground, &EndpointId::from("dtn://relay1")));
+    assert!(!table.remove_route(
+        &EndpointId::from("dtn://moon"),
+        &EndpointId::from("dtn://relay2")
+    ));
+
+    let remaining = table.get_routes_for_destination(&ground);
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].next_hop, EndpointId::from("dtn://relay2"));
+
+    assert!(table.remove_route(&ground, &EndpointId::from("dtn://relay2")));
+    assert_eq!(table.entries().count(), 0);
+}
+
+#[test]
+fn test_routing_table_clear() {
+    let mut table = RoutingTable::new().with_ecmp(true);
+    table.add_route(route("dtn://ground", "dtn://relay1", 10));
+    table.add_route(route("dtn://ground", "dtn://relay2", 10));
+    table.add_route(route("dtn://sat1/*", "dtn://relay3", 10));
+    table.select_route(&EndpointId::from("dtn://ground"));
+
+    assert_eq!(table.clear(), 3);
+    assert_eq!(table.entries().count(), 0);
+    assert!(table
+        .find_best_route(&EndpointId::from("dtn://ground"))
+        .is_none());
+    assert_eq!(table.clear(), 0);
+}
+
+#[test]
+fn test_routing_table_save_and_load_round_trip() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let path = temp_dir.path().join("routes.json");
+
+    let mut table = RoutingTable::new();
+    let mut scheduled = route("dtn://ground", "dtn://relay2", 20);
+    scheduled.is_active = false;
+    scheduled.valid_until = Some(1_700_000_000);
+    scheduled.latency_ms = Some(1200);
+    scheduled.data_rate_bps = Some(9600);
+    scheduled.reliability = Some(0.5);
+    let originals = vec![
+        route("dtn://ground", "dtn://relay1", 10),
+        scheduled,
+        route("dtn://sat1/*", "dtn://relay3", 5),
+    ];
+    for route in &originals {
+        table.add_route(route.clone());
+    }
+    table.save(&path)?;
+
+    let mut reloaded = RoutingTable::new();
+    reloaded.add_route(route("dtn://stale", "dtn://gone", 1));
+    assert_eq!(reloaded.load(&path)?, 3);
+    let mut routes: Vec<RouteEntry> = reloaded.entries().cloned().collect();
+    routes.sort_by(|a, b| (a.destination.as_str(), a.cost).cmp(&(b.destination.as_str(), b.cost)));
+    assert_eq!(routes, originals);
+    Ok(())
+}
+
+#[test]
+fn test_routing_table_load_missing_or_corrupt_file() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let mut table = RoutingTable::new();
+    table.add_route(route("dtn://ground", "dtn://relay1", 10));
+
+    assert_eq!(table.load(&temp_dir.path().join("missing.json"))?, 0);
+    assert_eq!(table.entries().count(), 1);
+
+    let corrupt = temp_dir.path().join("routes.json");
+    std::fs::write(&corrupt, b"not json")?;
+    let err = table.load(&corrupt).unwrap_err();
+    assert!(err.to_string().contains("Invalid routes file"), "{err}");
+    assert_eq!(table.entries().count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_routing_table_wildcard_fallback() {
+    let mut table = RoutingTable::new();
+    table.add_route(route("dtn://sat1/*", "dtn://router-default", 10));
+    table.add_route(route("dtn://sat1/camera", "dtn://router-camera", 10));
+
+    // Specific route wins at equal cost
+    let best = table
+        .find_best_route(&EndpointId::from("dtn://sat1/camera"))
+        .unwrap();
+    assert_eq!(best.next_hop, EndpointId::from("dtn://router-camera"));
+
+    // Other endpoints under the prefix fall back to the wildcard
+    let best = table
+        .find_best_route(&EndpointId::from("dtn://sat1/telemetry"))
+        .unwrap();
+    assert_eq!(best.next_hop, EndpointId::from("dtn://router-default"));
+
+    // Endpoints outside the prefix have no route
+    assert!(table
+        .find_best_route(&EndpointId::from("dtn://sat2/camera"))
+        .is_none());
+}
+
+#[test]
+fn test_routing_table_longest_prefix_wins() {
+    let mut table = RoutingTable::new();
+    table.add_route(route("dtn://*", "dtn://router-any", 1));
+    table.add_route(route("dtn://sat1/*", "dtn://router-sat1", 20));
+    table.add_route(route("dtn://sat1/payload/*", "dtn://router-payload", 50));
+
+    let routes = table.get_routes_for_destination(&EndpointId::from("dtn://sat1/payload/img"));
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes[0].next_hop, EndpointId::from("dtn://router-payload"));
+
+    let best = table
+        .find_best_route(&EndpointId::from("dtn://sat1/status"))
+        .unwrap();
+    assert_eq!(best.next_hop, EndpointId::from("dtn://router-sat1"));
+
+    let best = table
+        .find_best_route(&EndpointId::from("dtn://ground"))
+        .unwrap();
+    assert_eq!(best.next_hop, EndpointId::from("dtn://router-any"));
+}
+
+#[test]
+fn test_routing_table_inactive_exact_falls_back_to_wildcard() {
+    let mut table = RoutingTable::new();
+    table.add_route(route("dtn://sat1/*", "dtn://router-default", 10));
+    let mut exact = route("dtn://sat1/camera", "dtn://router-camera", 1);
+    exact.is_active = false;
+    table.add_route(exact);
+
+    let best = table
+        .find_best_route(&EndpointId::from("dtn://sat1/camera"))
+        .unwrap();
+    assert_eq!(best.next_hop, EndpointId::from("dtn://router-default"));
+}
+
+#[test]
+fn test_routing_table_valid_until() {
+    let now = crate::config::generate_creation_timestamp();
+    let mut table = RoutingTable::new();
+
+    let mut past = route("dtn://dest", "dtn://router-past", 1);
+    past.valid_until = Some(now - 60);
+    let mut future = route("dtn://dest", "dtn://router-future", 5);
+    future.valid_until = Some(now + 3600);
+    let absent = route("dtn://dest", "dtn://router-forever", 10);
+    table.add_route(past);
+    table.add_route(future);
+    table.add_route(absent);
+
+    // The expired route is ignored even though it is the cheapest
+    let dest = EndpointId::from("dtn://dest");
+    assert_eq!(table.get_routes_for_destination(&dest).len(), 2);
+    let best = table.find_best_route(&dest).unwrap();
+    assert_eq!(best.next_hop, EndpointId::from("dtn://router-future"));
+}
+
+#[test]
+fn test_routing_table_prune_expired() {
+    let now = 1_000_000;
+    let mut table = RoutingTable::new();
+
+    let mut past = route("dtn://a", "dtn://router-past", 1);
+    past.valid_until = Some(now - 1);
+    let mut future = route("dtn://a", "dtn://router-future", 1);
+    future.valid_until = Some(now + 1);
+    let mut only_past = route("dtn://b", "dtn://router-b", 1);
+    only_past.valid_until = Some(now - 100);
+    table.add_route(past);
+    table.add_route(future);
+    table.add_route(only_past);
+    table.add_route(route("dtn://c", "dtn://router-c", 1));
+
+    assert_eq!(table.prune_expired(now), 2);
+    let remaining: Vec<String> = {
+        let mut hops: Vec<String> = table
+            .get_all_routes()
+            .iter()
+            .map(|r| r.next_hop.to_string())
+            .collect();
+        hops.sort();
+        hops
+    };
+    assert_eq!(remaining, vec!["dtn://router-c", "dtn://router-future"]);
+
+    // Nothing left to prune
+    assert_eq!(table.prune_expired(now), 0);
+}
+
+#[test]
+fn test_routing_table_find_routes_ranked() {
+    let mut table = RoutingTable::new();
+    table.add_route(route("dtn://dest", "dtn://slow", 20));
+    table.add_route(route("dtn://dest", "dtn://fast-a", 5));
+    table.add_route(route("dtn://dest", "dtn://medium", 10));
+    table.add_route(route("dtn://dest", "dtn://fast-b", 5));
+    table.add_route(RouteEntry {
+        is_active: false,
+        ..route("dtn://dest", "dtn://down", 1)
+    });
+
+    let hops: Vec<String> = table
+        .find_routes_ranked(&EndpointId::from("dtn://dest"))
+        .iter()
+        .map(|r| r.next_hop.to_string())
+        .collect();
+    assert_eq!(
+        hops,
+        vec!["dtn://fast-a", "dtn://fast-b", "dtn://medium", "dtn://slow"]
+    );
+    assert!(table
+        .find_routes_ranked(&EndpointId::from("dtn://other"))
+        .is_empty());
+}
+
+#[test]
+fn test_routing_table_ecmp_alternates_equal_cost_routes() {
+    let dest = EndpointId::from("dtn://dest");
+    let mut table = RoutingConfig::new(RoutingAlgorithmType::Epidemic)
+        .with_ecmp(true)
+        .create_routing_table();
+    assert!(table.ecmp());
+    table.add_route(route("dtn://dest", "dtn://relay-a", 5));
+    table.add_route(route("dtn://dest", "dtn://relay-b", 5));
+    table.add_route(route("dtn://dest", "dtn://relay-c", 9));
+
+    let hops: Vec<String> = (0..4)
+        .map(|_| table.select_route(&dest).unwrap().next_hop.to_string())
+        .collect();
+    assert_eq!(
+        hops,
+        vec![
+            "dtn://relay-a",
+            "dtn://relay-b",
+            "dtn://relay-a",
+            "dtn://relay-b"
+        ]
+    );
+}
+
+#[test]
+fn test_routing_table_without_ecmp_always_selects_first_route() {
+    let dest = EndpointId::from("dtn://dest");
+    let mut table = RoutingTable::new();
+    table.add_route(route("dtn://dest", "dtn://relay-a", 5));
+    table.add_route(route("dtn://dest", "dtn://relay-b", 5));
+
+    for _ in 0..3 {
+        let selected = table.select_route(&dest).unwrap();
+        assert_eq!(selected.next_hop, EndpointId::from("dtn://relay-a"));
+        assert_eq!(
+            table.find_best_route(&dest).unwrap().next_hop,
+            selected.next_hop
+        );
+    }
+    assert!(table
+        .select_route(&EndpointId::from("dtn://other"))
+        .is_none());
+}
+
+/// Seconds to move one megabyte over the route, unknown rates ranked last
+fn transfer_time_score(route: &RouteEntry) -> u64 {
+    route
+        .data_rate_bps
+        .map_or(u64::MAX, |rate| 8_000_000 / rate.max(1))
+}
+
+#[test]
+fn test_routing_table_default_score_is_cost() {
+    let table = RoutingTable::new();
+    let entry = RouteEntry {
+        data_rate_bps: Some(1_000_000),
+        ..route("dtn://dest", "dtn://relay", 7)
+    };
+    assert_eq!(table.route_score(&entry), 7);
+}
+
+#[test]
+fn test_routing_table_custom_scorer_prefers_higher_rate() {
+    let dest = EndpointId::from("dtn://dest");
+    let slow = RouteEntry {
+        data_rate_bps: Some(9_600),
+        latency_ms: Some(1_300),
+        ..route("dtn://dest", "dtn://uhf-relay", 1)
+    };
+    let fast = RouteEntry {
+        data_rate_bps: Some(10_000_000),
+        reliability: Some(0.99),
+        ..route("dtn://dest", "dtn://ka-band-relay", 50)
+    };
+
+    let mut by_cost = RoutingTable::new();
+    by_cost.add_route(slow.clone());
+    by_cost.add_route(fast.clone());
+    assert_eq!(
+        by_cost.find_best_route(&dest).unwrap().next_hop,
+        EndpointId::from("dtn://uhf-relay")
+    );
+
+    let mut table = RoutingConfig::new(RoutingAlgorithmType::Epidemic)
+        .with_route_scorer(transfer_time_score)
+        .create_routing_table();
+    table.add_route(slow);
+    table.add_route(fast);
+    assert_eq!(
+        table.find_best_route(&dest).unwrap().next_hop,
+        EndpointId::from("dtn://ka-band-relay")
+    );
+    let hops: Vec<String> = table
+        .find_routes_ranked(&dest)
+        .iter()
+        .map(|r| r.next_hop.to_string())
+        .collect();
+    assert_eq!(hops, vec!["dtn://ka-band-relay", "dtn://uhf-relay"]);
+    assert_eq!(
+        table.select_route(&dest).unwrap().next_hop,
+        EndpointId::from("dtn://ka-band-relay")
+    );
+}
+
 #[test]
 fn test_routing_table_find_best_route_no_routes() {
     let table = RoutingTable::new();
@@ -181,6 +558,10 @@ fn test_routing_table_get_all_routes() {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     let entry2 = RouteEntry {
@@ -189,6 +570,10 @@ fn test_routing_table_get_all_routes() {
         cla_type: "ble".to_string(),
         cost: 5,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     };
 
     table.add_route(entry1);
@@ -344,6 +729,101 @@ fn test_select_peers_for_forwarding_duplicate_endpoints() {
     assert_eq!(selected.len(), 2);
 }
 
+#[test]
+fn test_select_peers_for_forwarding_skips_previous_hop() {
+    let routing = EpidemicRouting;
+    let mut bundle = Bundle::new("dtn://source", "dtn://dest", b"test".to_vec());
+    bundle.previous_node = Some(EndpointId::from("dtn://peerX"));
+    let descriptor = BundleDescriptor::new(bundle);
+
+    let all_peers: Vec<Box<dyn ClaPeer>> = vec![
+        Box::new(TcpPeer::from_endpoint_id(EndpointId::from("dtn://peerX/"))),
+        Box::new(TcpPeer::from_endpoint_id(EndpointId::from("dtn://peerY"))),
+    ];
+    let selected = routing.select_peers_for_forwarding(&descriptor, &all_peers);
+
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].get_peer_endpoint_id().as_str(), "dtn://peerY");
+}
+
+/// Listener whose accept backlog is already full, so further connections hang
+/// until the client gives up. Keep the returned values alive for the test.
+#[cfg(target_os = "linux")]
+fn unresponsive_tcp_address() -> (std::net::TcpListener, std::net::TcpStream, String) {
+    use socket2::{Domain, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket
+        .bind(
+            &"127.0.0.1:0"
+                .parse::<std::net::SocketAddr>()
+                .unwrap()
+                .into(),
+        )
+        .unwrap();
+    socket.listen(0).unwrap();
+    let listener: std::net::TcpListener = socket.into();
+    let address = listener.local_addr().unwrap().to_string();
+    let filler = std::net::TcpStream::connect(&address).unwrap();
+    (listener, filler, address)
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_tcp_peer_send_bundle_gives_up_after_connect_timeout() {
+    let (_listener, _filler, address) = unresponsive_tcp_address();
+    let timeout = std::time::Duration::from_millis(200);
+    let peer = TcpPeer::new(EndpointId::from("dtn://peer"), address).with_connect_timeout(timeout);
+    let bundle = Bundle::new("dtn://source", "dtn://dest", b"test".to_vec());
+
+    let started = std::time::Instant::now();
+    let result = ClaPeer::send_bundle(&peer, &bundle).await;
+    let elapsed = started.elapsed();
+
+    assert!(result.unwrap_err().to_string().contains("Timed out"));
+    assert!(elapsed >= timeout, "{elapsed:?}");
+    assert!(elapsed < timeout * 2, "{elapsed:?}");
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_select_peers_async_probes_unreachable_peers_concurrently() {
+    let (_listener, _filler, address) = unresponsive_tcp_address();
+    let timeout = std::time::Duration::from_millis(300);
+    let all_peers: Vec<Box<dyn ClaPeer>> = (0..4)
+        .map(|i| {
+            Box::new(
+                TcpPeer::new(
+                    EndpointId::from(format!("dtn://peer{i}").as_str()),
+                    address.clone(),
+                )
+                .with_connect_timeout(timeout),
+            ) as Box<dyn ClaPeer>
+        })
+        .collect();
+    let descriptor =
+        BundleDescriptor::new(Bundle::new("dtn://source", "dtn://dest", b"test".to_vec()));
+
+    let started = std::time::Instant::now();
+    let selected = EpidemicRouting
+        .select_peers_for_forwarding_async(&descriptor, &all_peers)
+        .await;
+    let elapsed = started.elapsed();
+
+    assert!(selected.is_empty());
+    // Four probes in turn would take four timeouts
+    assert!(elapsed >= timeout, "{elapsed:?}");
+    assert!(elapsed < timeout * 2, "{elapsed:?}");
+}
+
+#[test]
+fn test_tcp_peer_connect_timeout_defaults_to_three_seconds() {
+    let peer = TcpPeer::from_endpoint_id(EndpointId::from("dtn://peer1"));
+    assert_eq!(peer.connect_timeout, std::time::Duration::from_secs(3));
+    let peer = peer.with_connect_timeout(std::time::Duration::from_millis(250));
+    assert_eq!(peer.connect_timeout, std::time::Duration::from_millis(250));
+}
+
 #[test]
 fn test_select_routes_for_forwarding_empty_table() {
     let routing = EpidemicRouting;
@@ -369,6 +849,10 @@ fn test_select_routes_for_forwarding_with_routes() {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     });
 
     routing_table.add_route(RouteEntry {
@@ -377,6 +861,10 @@ fn test_select_routes_for_forwarding_with_routes() {
         cla_type: "ble".to_string(),
         cost: 5,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     });
 
     let selected = routing.select_routes_for_forwarding(&descriptor, &routing_table);
@@ -400,6 +888,10 @@ fn test_select_routes_for_forwarding_with_already_sent() {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     });
 
     routing_table.add_route(RouteEntry {
@@ -408,6 +900,10 @@ fn test_select_routes_for_forwarding_with_already_sent() {
         cla_type: "ble".to_string(),
         cost: 5,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     });
 
     let selected = routing.select_routes_for_forwarding(&descriptor, &routing_table);
@@ -428,6 +924,10 @@ fn test_select_routes_for_forwarding_inactive_routes() {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: false,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     });
 
     let selected = routing.select_routes_for_forwarding(&descriptor, &routing_table);
@@ -448,6 +948,10 @@ fn test_select_routes_for_forwarding_duplicate_next_hops() {
         cla_type: "tcp".to_string(),
         cost: 10,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     });
 
     routing_table.add_route(RouteEntry {
@@ -456,12 +960,83 @@ fn test_select_routes_for_forwarding_duplicate_next_hops() {
         cla_type: "ble".to_string(),
         cost: 5,
         is_active: true,
+        valid_until: None,
+        latency_ms: None,
+        data_rate_bps: None,
+        reliability: None,
     });
 
     let selected = routing.select_routes_for_forwarding(&descriptor, &routing_table);
     assert_eq!(selected.len(), 0); // Epidemic routing does not use routing table
 }
 
+#[test]
+fn test_destination_aware_epidemic_selects_only_matching_routes() {
+    let mut routing_table = RoutingTable::new();
+    routing_table.add_route(route("dtn://dest", "dtn://router1", 10));
+    routing_table.add_route(route("dtn://dest", "dtn://router2", 5));
+    routing_table.add_route(route("dtn://other", "dtn://router3", 1));
+    routing_table.add_route(route("dtn://other", "dtn://router1", 1));
+
+    let bundle = Bundle::new("dtn://source", "dtn://dest", b"test".to_vec());
+    let selected = DestinationAwareEpidemicRouting
+        .select_routes_for_forwarding(&BundleDescriptor::new(bundle), &routing_table);
+    let next_hops: Vec<&str> = selected.iter().map(|r| r.next_hop.as_str()).collect();
+    assert_eq!(next_hops, ["dtn://router2", "dtn://router1"]);
+    assert!(selected
+        .iter()
+        .all(|r| r.destination == EndpointId::from("dtn://dest")));
+
+    let bundle = Bundle::new("dtn://source", "dtn://other", b"test".to_vec());
+    let selected = DestinationAwareEpidemicRouting
+        .select_routes_for_forwarding(&BundleDescriptor::new(bundle), &routing_table);
+    assert_eq!(selected.len(), 2);
+    assert!(selected
+        .iter()
+        .all(|r| r.destination == EndpointId::from("dtn://other")));
+}
+
+#[test]
+fn test_destination_aware_epidemic_floods_over_prefix_routes() {
+    let mut routing_table = RoutingTable::new();
+    routing_table.add_route(route("dtn://ground/*", "dtn://relay1", 10));
+    routing_table.add_route(route("dtn://ground/station1", "dtn://relay2", 1));
+    routing_table.add_route(route("dtn://ground/station1", "dtn://relay3", 1));
+    routing_table.add_route(route("dtn://space/*", "dtn://relay4", 1));
+    let mut inactive = route("dtn://ground/*", "dtn://relay5", 1);
+    inactive.is_active = false;
+    routing_table.add_route(inactive);
+
+    let bundle = Bundle::new("dtn://source", "dtn://ground/station1", b"test".to_vec());
+    let mut descriptor = BundleDescriptor::new(bundle);
+    descriptor.mark_sent(EndpointId::from("dtn://relay3"));
+
+    // Unlike route lookup, flooding uses every matching pattern, not only the most specific
+    let selected =
+        DestinationAwareEpidemicRouting.select_routes_for_forwarding(&descriptor, &routing_table);
+    let next_hops: Vec<&str> = selected.iter().map(|r| r.next_hop.as_str()).collect();
+    assert_eq!(next_hops, ["dtn://relay2", "dtn://relay1"]);
+}
+
+#[test]
+fn test_destination_aware_epidemic_is_created_from_config() {
+    let routing_table = {
+        let mut table = RoutingTable::new();
+        table.add_route(route("dtn://dest", "dtn://router1", 1));
+        table
+    };
+    let descriptor =
+        BundleDescriptor::new(Bundle::new("dtn://source", "dtn://dest", b"test".to_vec()));
+    let algorithm =
+        RoutingConfig::new(RoutingAlgorithmType::DestinationAwareEpidemic).create_algorithm();
+    assert_eq!(
+        algorithm
+            .select_routes_for_forwarding(&descriptor, &routing_table)
+            .len(),
+        1
+    );
+}
+
 #[tokio::test]
 async fn routing_test_select_peers_for_forwarding_async() {
     use crate::bpv7::EndpointId;
@@ -518,3 +1093,198 @@ async fn routing_test_select_peers_for_forwarding_async() {
     assert_eq!(selected.len(), 1);
     assert_eq!(selected[0].get_peer_endpoint_id().as_str(), "dtn://peer1");
 }
+
+fn contact(to: &str, start: u64, end: u64) -> Contact {
+    Contact {
+        from: EndpointId::from("dtn://ground"),
+        to: EndpointId::from(to),
+        start,
+        end,
+        cla_type: "tcp".to_string(),
+    }
+}
+
+#[test]
+fn test_contact_is_active_at() {
+    let c = contact("dtn://sat1", 100, 200);
+    assert!(!c.is_active_at(99));
+    assert!(c.is_active_at(100));
+    assert!(c.is_active_at(199));
+    assert!(!c.is_active_at(200));
+}
+
+#[test]
+fn test_apply_contact_plan() {
+    let now = 1_000;
+    let plan = ContactPlan::new(vec![
+        contact("dtn://sat1", 900, 1_100),
+        contact("dtn://sat2", 2_000, 2_500),
+    ]);
+
+    let mut table = RoutingTable::new();
+    table.apply_contact_plan(&plan, now);
+
+    let best = table.find_best_route(&EndpointId::from("dtn://sat1"));
+    assert_eq!(best.unwrap().next_hop, EndpointId::from("dtn://sat1"));
+    assert!(table
+        .find_best_route(&EndpointId::from("dtn://sat2"))
+        .is_none());
+
+    // Later, sat1 has set and sat2 is in view
+    table.apply_contact_plan(&plan, 2_100);
+    assert!(table
+        .find_best_route(&EndpointId::from("dtn://sat1"))
+        .is_none());
+    assert!(table
+        .find_best_route(&EndpointId::from("dtn://sat2"))
+        .is_some());
+}
+
+#[test]
+fn test_apply_contact_plan_repeated_passes() {
+    let plan = ContactPlan::new(vec![
+        contact("dtn://sat1", 100, 200),
+        contact("dtn://sat1", 300, 400),
+    ]);
+    let mut table = RoutingTable::new();
+    table.apply_contact_plan(&plan, 350);
+
+    // One route per link, active during the second pass
+    assert_eq!(table.get_all_routes().len(), 1);
+    assert!(table
+        .find_best_route(&EndpointId::from("dtn://sat1"))
+        .is_some());
+}
+
+#[test]
+fn test_apply_contact_plan_keeps_other_routes() {
+    let mut table = RoutingTable::new();
+    table.add_route(route("dtn://other", "dtn://router", 5));
+    table.apply_contact_plan(&ContactPlan::new(vec![contact("dtn://sat1", 0, 10)]), 100);
+
+    assert!(table
+        .find_best_route(&EndpointId::from("dtn://other"))
+        .is_some());
+}
+
+#[test]
+fn test_contact_plan_from_toml() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let path = temp_dir.path().join("contacts.toml");
+    std::fs::write(
+        &path,
+        r#"
+[[contacts]]
+from = "dtn://ground"
+to = "dtn://sat1"
+start = 100
+end = 200
+cla_type = "tcp"
+
+[[contacts]]
+from = "dtn://ground"
+to = "dtn://sat2"
+start = 300
+end = 400
+cla_type = "ble"
+"#,
+    )?;
+
+    let plan = ContactPlan::from_toml(&path)?;
+    assert_eq!(plan.contacts.len(), 2);
+    assert_eq!(plan.contacts[0], contact("dtn://sat1", 100, 200));
+    assert_eq!(plan.contacts[1].cla_type, "ble");
+    assert_eq!(plan.active_at(350).count(), 1);
+
+    assert!(ContactPlan::from_toml(temp_dir.path().join("missing.toml")).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_explain_peer_selection_reports_already_sent() {
+    use crate::routing::explain::RejectionReason;
+
+    let routing = EpidemicRouting;
+    let mut descriptor =
+        BundleDescriptor::new(Bundle::new("dtn://source", "dtn://dest", b"test".to_vec()));
+    descriptor.mark_sent(EndpointId::from("dtn://peer1"));
+
+    let all_peers: Vec<Box<dyn ClaPeer>> = vec![
+        Box::new(TcpPeer::from_endpoint_id(EndpointId::from("dtn://peer1"))),
+        Box::new(TcpPeer::from_endpoint_id(EndpointId::from("dtn://peer2"))),
+    ];
+    let decisions = routing.explain_peer_selection(&descriptor, &all_peers);
+
+    assert_eq!(decisions.len(), 2);
+    assert_eq!(decisions[0].peer, EndpointId::from("dtn://peer1"));
+    assert_eq!(decisions[0].rejection, Some(RejectionReason::AlreadySent));
+    assert_eq!(decisions[0].cla_type, "tcp");
+    assert!(decisions[1].is_selected());
+}
+
+#[test]
+fn test_explain_peer_selection_reports_previous_hop() {
+    use crate::routing::explain::RejectionReason;
+
+    let routing = EpidemicRouting;
+    let mut bundle = Bundle::new("dtn://source", "dtn://dest", b"test".to_vec());
+    bundle.previous_node = Some(EndpointId::from("dtn://peerX"));
+    let descriptor = BundleDescriptor::new(bundle);
+
+    let all_peers: Vec<Box<dyn ClaPeer>> = vec![
+        Box::new(TcpPeer::from_endpoint_id(EndpointId::from("dtn://peerX"))),
+        Box::new(TcpPeer::from_endpoint_id(EndpointId::from("dtn://peerY"))),
+    ];
+    let decisions = routing.explain_peer_selection(&descriptor, &all_peers);
+
+    assert_eq!(decisions[0].rejection, Some(RejectionReason::PreviousHop));
+    assert_eq!(decisions[0].rejection.unwrap().to_string(), "previous hop");
+    assert!(decisions[1].is_selected());
+}
+
+#[test]
+fn test_explain_route_selection_reasons() {
+    use crate::routing::explain::RejectionReason;
+
+    let routing = EpidemicRouting;
+    let mut table = RoutingTable::new();
+    table.add_route(route("dtn://dest", "dtn://router", 1));
+    table.add_route(route("dtn://other", "dtn://router", 1));
+    let mut inactive = route("dtn://dest", "dtn://down", 1);
+    inactive.is_active = false;
+    table.add_route(inactive);
+    table.add_route(route("dtn://dest", "dtn://origin", 1));
+
+    let mut descriptor =
+        BundleDescriptor::new(Bundle::new("dtn://source", "dtn://dest", b"test".to_vec()));
+    descriptor.mark_sent(EndpointId::from("dtn://origin"));
+    let decisions = routing.explain_route_selection(&descriptor, &table);
+
+    let reason_for = |destination: &str, next_hop: &str| {
+        decisions
+            .iter()
+            .find(|d| {
+                d.route.destination.as_str() == destination && d.route.next_hop.as_str() == next_hop
+            })
+            .map(|d| d.rejection)
+            .expect("route is explained")
+    };
+    assert_eq!(decisions.len(), 4);
+    // Epidemic routing floods to peers and never picks routing table entries
+    assert_eq!(
+        reason_for("dtn://dest", "dtn://router"),
+        Some(RejectionReason::NotSelected)
+    );
+    assert_eq!(
+        reason_for("dtn://other", "dtn://router"),
+        Some(RejectionReason::WrongDestination)
+    );
+    assert_eq!(
+        reason_for("dtn://dest", "dtn://down"),
+        Some(RejectionReason::InactiveRoute)
+    );
+    assert_eq!(
+        reason_for("dtn://dest", "dtn://origin"),
+        Some(RejectionReason::AlreadySent)
+    );
+}
@@ -1,7 +1,7 @@
 use crate::bpv7::bundle::Bundle;
 use crate::bpv7::endpoint::EndpointId;
 use std::collections::HashSet;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
 /// BundleDescriptor manages the forwarding state of a bundle
 /// It tracks which endpoints have already received this bundle to prevent duplicates
@@ -11,20 +11,21 @@ pub struct BundleDescriptor {
     pub already_sent: HashSet<EndpointId>,
     pub forwarding_attempts: u32,
     pub created_at: u64,
+    /// Unix timestamp (seconds) before which the forwarding loop skips this
+    /// bundle; 0 when it may be forwarded right away
+    pub next_retry_at: u64,
 }
 
 impl BundleDescriptor {
     pub fn new(bundle: Bundle) -> Self {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = crate::clock::now();
 
         Self {
             bundle,
             already_sent: HashSet::new(),
             forwarding_attempts: 0,
             created_at: now,
+            next_retry_at: 0,
         }
     }
 
@@ -41,6 +42,17 @@ impl BundleDescriptor {
         self.already_sent.contains(eid)
     }
 
+    /// Node the bundle arrived from, as recorded in its previous node block
+    pub fn previous_hop(&self) -> Option<&EndpointId> {
+        self.bundle.previous_node.as_ref()
+    }
+
+    /// Whether forwarding to `eid` would echo the bundle back to the node it came from
+    pub fn is_previous_hop(&self, eid: &EndpointId) -> bool {
+        self.previous_hop()
+            .is_some_and(|hop| hop.normalized() == eid.normalized())
+    }
+
     /// Increment the forwarding attempt counter
     pub fn increment_forwarding_attempts(&mut self) {
         self.forwarding_attempts += 1;
@@ -51,17 +63,30 @@ impl BundleDescriptor {
         self.forwarding_attempts
     }
 
+    /// Backoff before the next retry: `base` doubled for every failed attempt, capped at `max`
+    pub fn retry_delay(&self, base: Duration, max: Duration) -> Duration {
+        let exponent = self.forwarding_attempts.min(16);
+        base.saturating_mul(1u32 << exponent).min(max)
+    }
+
+    /// Set `next_retry_at` from the failed attempts so far, counting from `now`
+    pub fn schedule_retry(&mut self, base: Duration, max: Duration, now: u64) {
+        self.next_retry_at = now + self.retry_delay(base, max).as_secs();
+    }
+
+    /// Whether the retry backoff has elapsed at `now` (unix seconds)
+    pub fn is_retry_due(&self, now: u64) -> bool {
+        now >= self.next_retry_at
+    }
+
     /// Check if this bundle is ready for forwarding (not expired and not over limit)
     pub fn is_ready_for_forwarding(&self, max_attempts: u32) -> bool {
         !self.bundle.is_expired() && self.forwarding_attempts < max_attempts
     }
 
-    /// Get a unique identifier for this bundle
+    /// Get a unique identifier for this bundle (the same ID the store uses)
     pub fn get_bundle_id(&self) -> String {
-        format!(
-            "{}-{}",
-            self.bundle.primary.source, self.bundle.primary.creation_timestamp
-        )
+        self.bundle.id().digest()
     }
 }
 
@@ -69,7 +94,7 @@ impl BundleDescriptor {
 mod tests {
     use super::*;
     use crate::bpv7::bundle::Bundle;
-    use std::thread;
+    use crate::clock::MockClock;
     use std::time::Duration;
 
     #[test]
@@ -207,28 +232,51 @@ mod tests {
 
     #[test]
     fn test_is_ready_for_forwarding_expired_bundle() {
+        let clock = MockClock::starting_now();
+        let _guard = clock.install();
+
         // Create a bundle with very short lifetime
         let mut bundle = Bundle::new("dtn://src", "dtn://dest", b"test".to_vec());
         bundle.primary.lifetime = 1; // 1 second
 
         let descriptor = BundleDescriptor::new(bundle);
+        assert!(descriptor.is_ready_for_forwarding(5));
 
-        // Wait for bundle to expire
-        thread::sleep(Duration::from_secs(2));
+        // Move past the lifetime without sleeping
+        clock.advance(Duration::from_secs(2));
 
         assert!(!descriptor.is_ready_for_forwarding(5));
     }
 
+    #[test]
+    fn test_retry_backoff_doubles_per_failure() {
+        let bundle = Bundle::new("dtn://src", "dtn://dest", b"test".to_vec());
+        let mut descriptor = BundleDescriptor::new(bundle);
+        let base = Duration::from_secs(10);
+        let max = Duration::from_secs(300);
+        let now = 1_700_000_000;
+        assert!(descriptor.is_retry_due(now));
+
+        descriptor.increment_forwarding_attempts();
+        descriptor.increment_forwarding_attempts();
+        descriptor.schedule_retry(base, max, now);
+        assert_eq!(descriptor.next_retry_at, now + 40);
+        assert!(!descriptor.is_retry_due(now + 39));
+        assert!(descriptor.is_retry_due(now + 40));
+
+        for _ in 0..10 {
+            descriptor.increment_forwarding_attempts();
+        }
+        assert_eq!(descriptor.retry_delay(base, max), max);
+    }
+
     #[test]
     fn test_get_bundle_id() {
         let bundle = Bundle::new("dtn://src", "dtn://dest", b"test".to_vec());
         let descriptor = BundleDescriptor::new(bundle.clone());
 
         let bundle_id = descriptor.get_bundle_id();
-        let expected_id = format!(
-            "{}-{}",
-            bundle.primary.source, bundle.primary.creation_timestamp
-        );
+        let expected_id = bundle.id().digest();
 
         assert_eq!(bundle_id, expected_id);
     }
@@ -277,15 +325,19 @@ mod tests {
 
     #[test]
     fn test_created_at_timestamp() {
+        let clock = MockClock::new(1_700_000_000);
+        let _guard = clock.install();
+
         let bundle1 = Bundle::new("dtn://src", "dtn://dest", b"test1".to_vec());
         let descriptor1 = BundleDescriptor::new(bundle1);
 
-        thread::sleep(Duration::from_millis(10));
+        clock.advance(Duration::from_secs(5));
 
         let bundle2 = Bundle::new("dtn://src", "dtn://dest", b"test2".to_vec());
         let descriptor2 = BundleDescriptor::new(bundle2);
 
-        assert!(descriptor2.created_at >= descriptor1.created_at);
+        assert_eq!(descriptor1.created_at, 1_700_000_000);
+        assert_eq!(descriptor2.created_at, 1_700_000_005);
     }
 
     #[test]
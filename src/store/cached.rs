@@ -0,0 +1,293 @@
+use crate::bpv7::bundle::Bundle;
+use crate::store::error::Result;
+use crate::store::file::InsertOutcome;
+use crate::store::history::HistoryEntry;
+use crate::store::meta::{BundleFilter, BundleMeta, SortKey, StoreStats};
+use crate::store::storage::BundleStorage;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Number of bundles a [`CachedBundleStore`] keeps unless configured otherwise
+pub const DEFAULT_CACHE_ENTRIES: usize = 256;
+
+/// Wraps a storage backend with an LRU cache of decoded bundles, so repeatedly
+/// loading the same bundles does not re-read and re-decode them.
+///
+/// Entries are dropped when their bundle is inserted, removed, dispatched, failed
+/// or delivered through this store, or evicted by the inner store to make room for
+/// an insert. Changes made to the inner store directly are not seen by the cache.
+pub struct CachedBundleStore<S> {
+    inner: S,
+    max_entries: usize,
+    max_bytes: Option<u64>,
+    cache: Mutex<LruCache>,
+}
+
+#[derive(Default)]
+struct LruCache {
+    /// Cached bundles with their approximate size and last use
+    entries: HashMap<String, CacheEntry>,
+    /// IDs by last use, least recently used first
+    order: BTreeMap<u64, String>,
+    bytes: u64,
+    tick: u64,
+}
+
+struct CacheEntry {
+    bundle: Bundle,
+    size: u64,
+    used: u64,
+}
+
+impl LruCache {
+    fn get(&mut self, id: &str) -> Option<Bundle> {
+        self.tick += 1;
+        let entry = self.entries.get_mut(id)?;
+        self.order.remove(&entry.used);
+        entry.used = self.tick;
+        self.order.insert(entry.used, id.to_string());
+        Some(entry.bundle.clone())
+    }
+
+    fn put(&mut self, id: &str, bundle: Bundle) {
+        self.remove(id);
+        self.tick += 1;
+        let size = approximate_size(&bundle);
+        self.bytes += size;
+        self.order.insert(self.tick, id.to_string());
+        self.entries.insert(
+            id.to_string(),
+            CacheEntry {
+                bundle,
+                size,
+                used: self.tick,
+            },
+        );
+    }
+
+    fn remove(&mut self, id: &str) {
+        if let Some(entry) = self.entries.remove(id) {
+            self.order.remove(&entry.used);
+            self.bytes -= entry.size;
+        }
+    }
+
+    /// Evict least recently used entries until both limits are met
+    fn shrink(&mut self, max_entries: usize, max_bytes: Option<u64>) {
+        while self.entries.len() > max_entries || max_bytes.is_some_and(|max| self.bytes > max) {
+            let Some((_, id)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&id) {
+                self.bytes -= entry.size;
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.bytes = 0;
+    }
+}
+
+/// Memory held by a decoded bundle: its payload and endpoint strings
+fn approximate_size(bundle: &Bundle) -> u64 {
+    let primary = &bundle.primary;
+    (bundle.payload.len()
+        + primary.source.len()
+        + primary.destination.len()
+        + primary.report_to.len()) as u64
+}
+
+impl<S: BundleStorage> CachedBundleStore<S> {
+    /// Cache up to [`DEFAULT_CACHE_ENTRIES`] bundles loaded from `inner`
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            max_entries: DEFAULT_CACHE_ENTRIES,
+            max_bytes: None,
+            cache: Mutex::new(LruCache::default()),
+        }
+    }
+
+    /// Keep at most `max_entries` bundles cached
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Keep at most about `max_bytes` of payload and endpoint data cached
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// The wrapped storage backend
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Number of bundles currently cached
+    pub fn cached_len(&self) -> usize {
+        self.cache.lock().unwrap().entries.len()
+    }
+
+    fn invalidate(&self, id: &str) {
+        self.cache.lock().unwrap().remove(id);
+    }
+
+    /// Drop the cached bundles the inner store no longer holds, after an insert
+    /// that may have evicted some
+    fn forget_evicted(&self) {
+        if !self.inner.evicts_on_insert() {
+            return;
+        }
+        let mut cache = self.cache.lock().unwrap();
+        let evicted: Vec<String> = cache
+            .entries
+            .iter()
+            .filter(|(_, entry)| !self.inner.contains(&entry.bundle))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in evicted {
+            cache.remove(&id);
+        }
+    }
+}
+
+impl<S: BundleStorage> BundleStorage for CachedBundleStore<S> {
+    fn insert(&self, bundle: &Bundle) -> Result<InsertOutcome> {
+        self.invalidate(&bundle.id().digest());
+        let outcome = self.inner.insert(bundle);
+        self.forget_evicted();
+        outcome
+    }
+
+    fn contains(&self, bundle: &Bundle) -> bool {
+        self.inner.contains(bundle)
+    }
+
+    fn insert_batch(&self, bundles: &[Bundle]) -> Result<Vec<String>> {
+        for bundle in bundles {
+            self.invalidate(&bundle.id().digest());
+        }
+        let ids = self.inner.insert_batch(bundles);
+        self.forget_evicted();
+        ids
+    }
+
+    fn load(&self, id: &str) -> Result<Bundle> {
+        if let Some(bundle) = self.cache.lock().unwrap().get(id) {
+            return Ok(bundle);
+        }
+        let bundle = self.inner.load(id)?;
+        let mut cache = self.cache.lock().unwrap();
+        cache.put(id, bundle.clone());
+        cache.shrink(self.max_entries, self.max_bytes);
+        Ok(bundle)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        self.inner.list()
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        self.invalidate(id);
+        self.inner.remove(id)
+    }
+
+    fn dispatch(&self, bundle: &Bundle) -> Result<()> {
+        self.invalidate(&bundle.id().digest());
+        self.inner.dispatch(bundle)
+    }
+
+    fn fail(&self, bundle: &Bundle) -> Result<()> {
+        self.invalidate(&bundle.id().digest());
+        self.inner.fail(bundle)
+    }
+
+    fn deliver(&self, bundle: &Bundle) -> Result<()> {
+        self.invalidate(&bundle.id().digest());
+        self.inner.deliver(bundle)
+    }
+
+    fn forwarding_attempts(&self, id: &str) -> Result<u32> {
+        self.inner.forwarding_attempts(id)
+    }
+
+    fn set_forwarding_attempts(&self, id: &str, attempts: u32) -> Result<()> {
+        self.inner.set_forwarding_attempts(id, attempts)
+    }
+
+    fn next_retry_at(&self, id: &str) -> Result<u64> {
+        self.inner.next_retry_at(id)
+    }
+
+    fn set_next_retry_at(&self, id: &str, retry_at: u64) -> Result<()> {
+        self.inner.set_next_retry_at(id, retry_at)
+    }
+
+    fn arrival_time(&self, id: &str) -> Result<Option<SystemTime>> {
+        self.inner.arrival_time(id)
+    }
+
+    fn set_arrival_time(&self, id: &str, arrived_at: SystemTime) -> Result<()> {
+        self.inner.set_arrival_time(id, arrived_at)
+    }
+
+    fn record_history(&self, entry: &HistoryEntry) -> Result<()> {
+        self.inner.record_history(entry)
+    }
+
+    fn history(&self) -> Result<Vec<HistoryEntry>> {
+        self.inner.history()
+    }
+
+    fn load_meta(&self, id: &str) -> Result<BundleMeta> {
+        self.inner.load_meta(id)
+    }
+
+    fn find_by_partial_id(&self, partial: &str) -> Option<String> {
+        self.inner.find_by_partial_id(partial)
+    }
+
+    fn list_by_priority(&self) -> Result<Vec<String>> {
+        self.inner.list_by_priority()
+    }
+
+    fn list_meta(&self, offset: usize, limit: usize, sort: SortKey) -> Result<Vec<BundleMeta>> {
+        self.inner.list_meta(offset, limit, sort)
+    }
+
+    fn find(&self, filter: &BundleFilter) -> Result<Vec<String>> {
+        self.inner.find(filter)
+    }
+
+    fn stats(&self) -> Result<StoreStats> {
+        self.inner.stats()
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        self.inner.check_writable()
+    }
+
+    fn usage_ratio(&self) -> Result<Option<f64>> {
+        self.inner.usage_ratio()
+    }
+
+    fn evicts_on_insert(&self) -> bool {
+        self.inner.evicts_on_insert()
+    }
+
+    /// Expired bundles are removed by the inner store, so the whole cache is dropped
+    /// whenever any were
+    fn cleanup_expired(&self) -> Result<usize> {
+        let removed = self.inner.cleanup_expired()?;
+        if removed > 0 {
+            self.cache.lock().unwrap().clear();
+        }
+        Ok(removed)
+    }
+}
@@ -0,0 +1,207 @@
+use crate::bpv7::bundle::Bundle;
+use crate::store::error::{Result, StoreError};
+use crate::store::file::{write_atomically, BundleStore, InsertOutcome};
+use crate::store::history::HistoryEntry;
+use crate::store::storage::{encode_bundle, BundleStorage};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use std::time::SystemTime;
+use std::{fs, path::PathBuf};
+
+/// Length of the AES-GCM nonce stored in front of every ciphertext
+pub const NONCE_LEN: usize = 12;
+
+/// Bundle store that keeps every bundle encrypted at rest with AES-256-GCM.
+///
+/// Each `.cbor` file holds a random nonce followed by the ciphertext of the
+/// CBOR-encoded bundle. File names are derived from the plaintext bundle ID,
+/// so IDs are identical to those of a plain `BundleStore`.
+pub struct EncryptedBundleStore {
+    store: BundleStore,
+    cipher: Aes256Gcm,
+}
+
+impl EncryptedBundleStore {
+    pub fn new<P: Into<PathBuf>>(path: P, key: &[u8; 32]) -> Result<Self> {
+        Ok(Self {
+            store: BundleStore::new(path)?,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        })
+    }
+
+    /// Path of the stored bundle, keyed by the digest of its plaintext `BundleId`
+    pub fn filename_for(&self, bundle: &Bundle) -> PathBuf {
+        self.store.filename_for(bundle)
+    }
+
+    /// Whether a bundle with the same ID is already stored
+    pub fn contains(&self, bundle: &Bundle) -> bool {
+        self.store.contains(bundle)
+    }
+
+    pub fn insert(&self, bundle: &Bundle) -> Result<InsertOutcome> {
+        let path = self.filename_for(bundle);
+        let encoded = encode_bundle(bundle)?;
+
+        // Nonces are random, so compare against the decrypted copy instead of the file bytes
+        if let Ok(existing) = fs::read(&path) {
+            if self.decrypt(&existing).ok().as_deref() == Some(encoded.as_slice()) {
+                log::info!(
+                    "Bundle already stored (ID: {})",
+                    path.file_stem().unwrap().to_string_lossy()
+                );
+                return Ok(InsertOutcome::AlreadyPresent);
+            }
+        }
+
+        write_atomically(&path, &self.encrypt(&encoded)?)?;
+        log::info!(
+            "Encrypted bundle saved to {} (ID: {})",
+            path.display(),
+            path.file_stem().unwrap().to_string_lossy()
+        );
+        Ok(InsertOutcome::Inserted)
+    }
+
+    pub fn load(&self, id_hash: &str) -> Result<Bundle> {
+        let path = self.store.dir.join(format!("{id_hash}.cbor"));
+        let data = fs::read(path).map_err(StoreError::from_bundle_io)?;
+        let plaintext = self.decrypt(&data).map_err(|e| match e {
+            StoreError::Corrupt(reason) => {
+                StoreError::Corrupt(format!("Bundle {id_hash}: {reason}"))
+            }
+            other => other,
+        })?;
+        let bundle: Bundle = serde_cbor::from_slice(&plaintext)?;
+        bundle
+            .validate()
+            .map_err(|e| StoreError::Corrupt(format!("Bundle {id_hash}: {e}")))?;
+        Ok(bundle)
+    }
+
+    pub fn load_by_partial_id(&self, partial: &str) -> Result<Bundle> {
+        if let Some(full_id) = self.find_by_partial_id(partial) {
+            self.load(&full_id)
+        } else {
+            Err(StoreError::NotFound)
+        }
+    }
+
+    pub fn find_by_partial_id(&self, partial: &str) -> Option<String> {
+        self.store.find_by_partial_id(partial)
+    }
+
+    pub fn list(&self) -> Result<Vec<String>> {
+        self.store.list()
+    }
+
+    /// Delete the bundle with the given full ID, failing if it does not exist
+    pub fn remove(&self, id: &str) -> Result<()> {
+        self.store.remove(id)
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| StoreError::Serialize("Failed to encrypt bundle".to_string()))?;
+
+        let mut data = nonce.to_vec();
+        data.extend_from_slice(&ciphertext);
+        Ok(data)
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < NONCE_LEN {
+            return Err(StoreError::Corrupt(
+                "Failed to decrypt bundle: file too short to hold a nonce".to_string(),
+            ));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                StoreError::Corrupt(
+                    "Failed to decrypt bundle: wrong key or tampered data".to_string(),
+                )
+            })
+    }
+}
+
+impl BundleStorage for EncryptedBundleStore {
+    fn insert(&self, bundle: &Bundle) -> Result<InsertOutcome> {
+        EncryptedBundleStore::insert(self, bundle)
+    }
+
+    fn contains(&self, bundle: &Bundle) -> bool {
+        EncryptedBundleStore::contains(self, bundle)
+    }
+
+    fn load(&self, id: &str) -> Result<Bundle> {
+        EncryptedBundleStore::load(self, id)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        EncryptedBundleStore::list(self)
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        EncryptedBundleStore::remove(self, id)
+    }
+
+    fn dispatch(&self, bundle: &Bundle) -> Result<()> {
+        self.store
+            .dispatch_one(bundle, &self.store.dispatched_dir())
+    }
+
+    fn fail(&self, bundle: &Bundle) -> Result<()> {
+        self.store.fail_one(bundle, &self.store.failed_dir())
+    }
+
+    fn deliver(&self, bundle: &Bundle) -> Result<()> {
+        self.store.move_out(bundle, &self.store.delivered_dir())?;
+        Ok(())
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        self.store.check_writable()
+    }
+
+    fn usage_ratio(&self) -> Result<Option<f64>> {
+        self.store.usage_ratio()
+    }
+
+    fn forwarding_attempts(&self, id: &str) -> Result<u32> {
+        self.store.forwarding_attempts(id)
+    }
+
+    fn set_forwarding_attempts(&self, id: &str, attempts: u32) -> Result<()> {
+        self.store.set_forwarding_attempts(id, attempts)
+    }
+
+    fn next_retry_at(&self, id: &str) -> Result<u64> {
+        self.store.next_retry_at(id)
+    }
+
+    fn set_next_retry_at(&self, id: &str, retry_at: u64) -> Result<()> {
+        self.store.set_next_retry_at(id, retry_at)
+    }
+
+    fn arrival_time(&self, id: &str) -> Result<Option<SystemTime>> {
+        self.store.arrival_time(id)
+    }
+
+    fn set_arrival_time(&self, id: &str, arrived_at: SystemTime) -> Result<()> {
+        self.store.set_arrival_time(id, arrived_at)
+    }
+
+    /// History entries hold only IDs, endpoints and outcomes and are written unencrypted
+    fn record_history(&self, entry: &HistoryEntry) -> Result<()> {
+        self.store.record_history(entry)
+    }
+
+    fn history(&self) -> Result<Vec<HistoryEntry>> {
+        self.store.history()
+    }
+}
@@ -0,0 +1,55 @@
+use thiserror::Error;
+
+/// Errors returned by bundle stores
+#[derive(Debug, Error)]
+pub enum StoreError {
+    /// No bundle with the requested ID is stored
+    #[error("Bundle ID not found")]
+    NotFound,
+    /// Stored data could not be decoded or decrypted
+    #[error("Corrupt bundle data: {0}")]
+    Corrupt(String),
+    #[error("Store I/O error: {0}")]
+    Io(std::io::Error),
+    /// A bundle could not be encoded for storage
+    #[error("Failed to serialize bundle: {0}")]
+    Serialize(String),
+    /// Storing the bundle would grow the store past its size limit
+    #[error("Store capacity exceeded: {required} bytes required, limit is {limit} bytes")]
+    CapacityExceeded { required: u64, limit: u64 },
+    /// Storing the bundle would take its destination past its bundle-count or byte quota
+    #[error(
+        "Quota for destination {destination} exceeded: {bundles} bundles of {bytes} bytes required"
+    )]
+    QuotaExceeded {
+        destination: String,
+        bundles: usize,
+        bytes: u64,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, StoreError>;
+
+impl StoreError {
+    /// For I/O on a bundle's own file, where a missing file means the bundle is not
+    /// stored. Elsewhere (the store directory, state files) it is an `Io` error.
+    pub(crate) fn from_bundle_io(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::NotFound => StoreError::NotFound,
+            _ => StoreError::Io(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for StoreError {
+    fn from(error: std::io::Error) -> Self {
+        StoreError::Io(error)
+    }
+}
+
+/// Decoding failures of stored bytes
+impl From<serde_cbor::Error> for StoreError {
+    fn from(error: serde_cbor::Error) -> Self {
+        StoreError::Corrupt(error.to_string())
+    }
+}
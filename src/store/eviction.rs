@@ -0,0 +1,59 @@
+use crate::bpv7::bundle::{PrimaryBlock, Priority};
+use serde::Deserialize;
+use std::cmp::Ordering;
+
+/// Which stored bundles a size-limited [`BundleStore`](crate::store::BundleStore)
+/// deletes to make room for a new one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionPolicy {
+    /// Bundles created earliest go first
+    OldestFirst,
+    /// Bundles closest to expiring go first
+    ShortestRemainingLifetime,
+    /// `Bulk` before `Normal` before `Expedited`, soonest to expire first within a
+    /// priority. Bundles more important than the incoming one are never evicted.
+    LowestPriority,
+}
+
+impl std::fmt::Display for EvictionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvictionPolicy::OldestFirst => write!(f, "oldest_first"),
+            EvictionPolicy::ShortestRemainingLifetime => write!(f, "shortest_remaining_lifetime"),
+            EvictionPolicy::LowestPriority => write!(f, "lowest_priority"),
+        }
+    }
+}
+
+impl EvictionPolicy {
+    /// Whether a stored bundle may be evicted for an incoming bundle of `incoming` priority
+    pub fn may_evict(&self, victim: &PrimaryBlock, incoming: Priority) -> bool {
+        match self {
+            EvictionPolicy::LowestPriority => victim.priority <= incoming,
+            EvictionPolicy::OldestFirst | EvictionPolicy::ShortestRemainingLifetime => true,
+        }
+    }
+
+    /// Order two stored bundles so the one to evict first compares less, given the
+    /// current Unix time `now`
+    pub fn compare(&self, a: &PrimaryBlock, b: &PrimaryBlock, now: u64) -> Ordering {
+        let oldest = |a: &PrimaryBlock, b: &PrimaryBlock| {
+            (a.creation_timestamp, a.sequence_number)
+                .cmp(&(b.creation_timestamp, b.sequence_number))
+        };
+        let remaining = |a: &PrimaryBlock, b: &PrimaryBlock| {
+            a.remaining_lifetime_at(now)
+                .cmp(&b.remaining_lifetime_at(now))
+        };
+        match self {
+            EvictionPolicy::OldestFirst => oldest(a, b),
+            EvictionPolicy::ShortestRemainingLifetime => remaining(a, b).then_with(|| oldest(a, b)),
+            EvictionPolicy::LowestPriority => a
+                .priority
+                .cmp(&b.priority)
+                .then_with(|| remaining(a, b))
+                .then_with(|| oldest(a, b)),
+        }
+    }
+}
@@ -1,122 +1,1026 @@
-use crate::bpv7::bundle::Bundle;
-use anyhow::Result;
-use sha2::{Digest, Sha256};
+use crate::bpv7::bundle::{Bundle, PrimaryBlock, Priority};
+use crate::bpv7::EndpointId;
+use crate::store::error::{Result, StoreError};
+use crate::store::eviction::EvictionPolicy;
+use crate::store::history::{self, HistoryEntry};
+use crate::store::layout::{self, Stored};
+use crate::store::meta::{BundleFilter, BundleMeta, SortKey, StoreStats, StoredHeader};
+use crate::store::storage::{spawn_blocking, BundleStorage};
+use crate::store::usage::Usage;
 use std::{
     fs,
+    io::{BufReader, Write},
     path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+/// Distinguishes temp files of concurrent writers within this process
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Result of storing a bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertOutcome {
+    /// The bundle was written to disk
+    Inserted,
+    /// An identical copy was already stored; nothing was written
+    AlreadyPresent,
+}
+
+/// Write to a temp file and rename it into place so readers never see a partial bundle
+pub(crate) fn write_atomically(path: &Path, data: &[u8]) -> Result<()> {
+    let tmp_path = path.with_extension(format!(
+        "cbor.{}-{}.tmp",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    if let Err(e) = fs::write(&tmp_path, data).and_then(|_| fs::rename(&tmp_path, path)) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Rename `src` to `dst`, or copy it over and remove the original when the two are on
+/// different filesystems
+fn move_file(src: &Path, dst: &Path) -> std::io::Result<()> {
+    match fs::rename(src, dst) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => copy_and_remove(src, dst),
+        result => result,
+    }
+}
+
+/// Copy `src` next to `dst`, flush it to disk and rename it into place before removing
+/// `src`, so a crash leaves at worst a second copy of the file and never none
+pub(crate) fn copy_and_remove(src: &Path, dst: &Path) -> std::io::Result<()> {
+    let tmp_path = dst.with_extension(format!(
+        "cbor.{}-{}.tmp",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let copied = fs::copy(src, &tmp_path)
+        .and_then(|_| fs::File::open(&tmp_path)?.sync_all())
+        .and_then(|_| fs::rename(&tmp_path, dst));
+    if let Err(e) = copied {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    fs::remove_file(src)
+}
+
+/// A bundle about to be written, as the size checks see it
+struct Incoming {
+    id: String,
+    destination: EndpointId,
+    /// Size of its encoded file
+    size: u64,
+    priority: Priority,
+}
+
+impl Incoming {
+    fn new(bundle: &Bundle, encoded: &[u8]) -> Self {
+        Self {
+            id: bundle.id().digest(),
+            destination: EndpointId::from(bundle.primary.destination.as_str()),
+            size: encoded.len() as u64,
+            priority: bundle.primary.priority,
+        }
+    }
+}
+
+/// A stored bundle picked for eviction, with the destination whose quota it makes
+/// room under (`None` for the store-wide `max_bytes`)
+struct Victim {
+    id: String,
+    size: u64,
+    quota: Option<EndpointId>,
+}
+
+fn max_priority(incoming: &[Incoming]) -> Priority {
+    incoming
+        .iter()
+        .map(|bundle| bundle.priority)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Take candidates in order until `excess_bundles` and `excess_bytes` are both
+/// covered, returning their IDs and sizes, or `None` if they never are
+fn pick_victims(
+    candidates: Vec<(PrimaryBlock, String, u64)>,
+    mut excess_bundles: usize,
+    mut excess_bytes: u64,
+) -> Option<Vec<(String, u64)>> {
+    let mut victims = Vec::new();
+    for (_, id, size) in candidates {
+        if excess_bundles == 0 && excess_bytes == 0 {
+            break;
+        }
+        excess_bundles = excess_bundles.saturating_sub(1);
+        excess_bytes = excess_bytes.saturating_sub(size);
+        victims.push((id, size));
+    }
+    (excess_bundles == 0 && excess_bytes == 0).then_some(victims)
+}
+
+#[derive(Clone)]
 pub struct BundleStore {
     pub(crate) dir: PathBuf,
+    /// Upper bound on the total size of stored bundle files, if any
+    max_bytes: Option<u64>,
+    /// Bundles deleted to make room under `max_bytes`; without one, inserts that
+    /// do not fit are rejected
+    eviction_policy: Option<EvictionPolicy>,
+    /// Where dispatched bundles are moved, instead of `<store>/dispatched`
+    dispatched_path: Option<PathBuf>,
+    /// How long dispatched bundles are kept before `cleanup_expired` deletes them
+    dispatched_retention: Option<Duration>,
+    /// Most bundles stored for any single destination
+    per_destination_max_bundles: Option<usize>,
+    /// Most bytes of bundle files stored for any single destination
+    per_destination_max_bytes: Option<u64>,
+    /// Byte and per-destination totals, shared by clones and filled in on first use
+    usage: Arc<Mutex<Option<Usage>>>,
+    /// Held by inserts from the quota check until the new bundles are tracked, so
+    /// concurrent inserts cannot each pass the check and together overshoot a quota
+    insert_lock: Arc<Mutex<()>>,
 }
 
 impl BundleStore {
     pub fn new<P: Into<PathBuf>>(path: P) -> Result<Self> {
         let dir = path.into();
         fs::create_dir_all(&dir)?;
-        Ok(BundleStore { dir })
+        Ok(BundleStore {
+            dir,
+            max_bytes: None,
+            eviction_policy: None,
+            dispatched_path: None,
+            dispatched_retention: None,
+            per_destination_max_bundles: None,
+            per_destination_max_bytes: None,
+            usage: Arc::default(),
+            insert_lock: Arc::default(),
+        })
+    }
+
+    /// [`BundleStore::insert`] on the blocking thread pool, for use from async code
+    pub async fn insert_async(&self, bundle: Bundle) -> Result<InsertOutcome> {
+        let store = self.clone();
+        spawn_blocking(move || store.insert(&bundle)).await
+    }
+
+    /// [`BundleStore::load`] on the blocking thread pool, for use from async code
+    pub async fn load_async(&self, id: &str) -> Result<Bundle> {
+        let store = self.clone();
+        let id = id.to_string();
+        spawn_blocking(move || store.load(&id)).await
+    }
+
+    /// [`BundleStore::list`] on the blocking thread pool, for use from async code
+    pub async fn list_async(&self) -> Result<Vec<String>> {
+        let store = self.clone();
+        spawn_blocking(move || store.list()).await
+    }
+
+    /// Reject inserts that would grow the stored bundles past `max_bytes`
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Move dispatched bundles to `path` instead of `<store>/dispatched`. A relative
+    /// `path` is taken relative to the store directory. It may be on another
+    /// filesystem, in which case bundles are copied there and then removed.
+    pub fn with_dispatched_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dispatched_path = Some(path.into());
+        self
     }
 
+    /// Delete dispatched bundles once they were dispatched longer than `retention`
+    /// ago, on every [`BundleStore::cleanup_expired`] pass
+    pub fn with_dispatched_retention(mut self, retention: Duration) -> Self {
+        self.dispatched_retention = Some(retention);
+        self
+    }
+
+    /// Instead of rejecting an insert that would exceed `max_bytes`, evict stored
+    /// bundles in the order `policy` picks until the new bundle fits. Also applies to
+    /// the per-destination quotas, evicting only bundles for the same destination.
+    pub fn with_eviction_policy(mut self, policy: EvictionPolicy) -> Self {
+        self.eviction_policy = Some(policy);
+        self
+    }
+
+    /// Reject inserts that would hold more than `max_bundles` bundles for one destination
+    pub fn with_per_destination_max_bundles(mut self, max_bundles: usize) -> Self {
+        self.per_destination_max_bundles = Some(max_bundles);
+        self
+    }
+
+    /// Reject inserts that would hold more than `max_bytes` bytes for one destination,
+    /// independently of the store-wide `max_bytes`
+    pub fn with_per_destination_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.per_destination_max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Number of bundles stored for `destination` and the total size of their files
+    pub fn destination_usage(&self, destination: &EndpointId) -> Result<(usize, u64)> {
+        self.with_usage(|usage| usage.destination(destination))
+    }
+
+    /// Run `f` on the running totals, scanning the directory first if this store has
+    /// not counted its bundles yet
+    fn with_usage<T>(&self, f: impl FnOnce(&Usage) -> T) -> Result<T> {
+        let mut usage = self.usage.lock().unwrap();
+        if usage.is_none() {
+            *usage = Some(self.count_bundles()?);
+        }
+        Ok(f(usage.as_ref().unwrap()))
+    }
+
+    /// Update the running totals after a write or removal, unless they were never built
+    fn track(&self, f: impl FnOnce(&mut Usage)) {
+        if let Some(usage) = self.usage.lock().unwrap().as_mut() {
+            f(usage);
+        }
+    }
+
+    /// Drop the running totals so the next size check counts the directory again
+    pub(crate) fn forget_usage(&self) {
+        *self.usage.lock().unwrap() = None;
+    }
+
+    fn count_bundles(&self) -> Result<Usage> {
+        let mut usage = Usage::default();
+        for id in self.iter_ids() {
+            let id = id?;
+            let size = match fs::metadata(self.dir.join(format!("{id}.cbor"))) {
+                Ok(metadata) => metadata.len(),
+                // Removed since the directory was read
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let destination = match self.load_header(&id) {
+                Ok(primary) => Some(EndpointId::from(primary.destination.as_str())),
+                Err(StoreError::NotFound) => continue,
+                Err(e) => {
+                    log::warn!("Not counting bundle {id} towards any destination quota: {e}");
+                    None
+                }
+            };
+            usage.add(id, destination, size);
+        }
+        Ok(usage)
+    }
+
+    /// Check that bundles can be written to the store directory by writing and
+    /// removing a probe file. A directory marked read-only counts as unwritable even
+    /// for users whose privileges would let them write to it anyway.
+    pub fn check_writable(&self) -> Result<()> {
+        let metadata = fs::metadata(&self.dir).map_err(StoreError::Io)?;
+        if metadata.permissions().readonly() {
+            return Err(StoreError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("{} is read-only", self.dir.display()),
+            )));
+        }
+        let probe = self
+            .dir
+            .join(format!(".write-probe-{}", std::process::id()));
+        fs::write(&probe, b"")
+            .and_then(|_| fs::remove_file(&probe))
+            .map_err(StoreError::Io)
+    }
+
+    /// Fraction of `max_bytes` taken by the active bundle files, or `None` without a limit
+    pub fn usage_ratio(&self) -> Result<Option<f64>> {
+        match self.max_bytes {
+            Some(max_bytes) => Ok(Some(self.stored_bytes()? as f64 / max_bytes as f64)),
+            None => Ok(None),
+        }
+    }
+
+    /// Total size of the active bundle files, from the running totals rather than a
+    /// directory scan
+    pub fn stored_bytes(&self) -> Result<u64> {
+        self.with_usage(Usage::bytes)
+    }
+
+    /// Path of the stored bundle, keyed by the digest of its `BundleId`
     pub fn filename_for(&self, bundle: &Bundle) -> PathBuf {
-        let payload_hash = Sha256::digest(&bundle.payload);
-        let id_str = format!(
-            "{}:{}:{}:{}:{:x}",
-            bundle.primary.version,
-            bundle.primary.source,
-            bundle.primary.destination,
-            bundle.primary.creation_timestamp,
-            payload_hash
-        );
-        let hash = Sha256::digest(id_str.as_bytes());
-        self.dir.join(format!("{hash:x}.cbor"))
+        self.dir.join(format!("{}.cbor", bundle.id().digest()))
+    }
+
+    /// Whether a bundle with the same ID is already stored
+    pub fn contains(&self, bundle: &Bundle) -> bool {
+        self.filename_for(bundle).exists()
     }
 
-    pub fn insert(&self, bundle: &Bundle) -> Result<()> {
+    pub fn insert(&self, bundle: &Bundle) -> Result<InsertOutcome> {
         let path = self.filename_for(bundle);
-        let encoded = serde_cbor::to_vec(bundle)?;
-        fs::write(&path, encoded)?;
-        println!(
+        let encoded = layout::encode(bundle)?;
+        let _guard = self.insert_lock.lock().unwrap();
+
+        // Skip the rewrite when an identical copy is already stored
+        if let Ok(existing) = fs::read(&path) {
+            if layout::is_same(&existing, &encoded) {
+                log::info!(
+                    "Bundle already stored (ID: {})",
+                    path.file_stem().unwrap().to_string_lossy()
+                );
+                return Ok(InsertOutcome::AlreadyPresent);
+            }
+        }
+
+        let incoming = Incoming::new(bundle, &encoded);
+        let victims = self.plan_eviction(std::slice::from_ref(&incoming))?;
+        self.evict(victims)?;
+
+        write_atomically(&path, &encoded)?;
+        self.track(|usage| usage.add(incoming.id, Some(incoming.destination), incoming.size));
+        log::info!(
             "Bundle saved to {} (ID: {})",
             path.display(),
             path.file_stem().unwrap().to_string_lossy()
         );
+        Ok(InsertOutcome::Inserted)
+    }
+
+    /// Store several bundles, returning their IDs in order. Every bundle is encoded and
+    /// the size limits checked once for the whole batch before anything is written.
+    pub fn insert_batch(&self, bundles: &[Bundle]) -> Result<Vec<String>> {
+        let mut pending = Vec::with_capacity(bundles.len());
+        for bundle in bundles {
+            pending.push((self.filename_for(bundle), layout::encode(bundle)?, bundle));
+        }
+        let _guard = self.insert_lock.lock().unwrap();
+
+        // Identical copies already on disk are skipped, as in `insert`
+        pending.retain(|(path, encoded, _)| {
+            fs::read(path).map_or(true, |existing| !layout::is_same(&existing, encoded))
+        });
+
+        let incoming: Vec<Incoming> = pending
+            .iter()
+            .map(|(_, encoded, bundle)| Incoming::new(bundle, encoded))
+            .collect();
+        let victims = self.plan_eviction(&incoming)?;
+        self.evict(victims)?;
+
+        for ((path, encoded, _), bundle) in pending.iter().zip(incoming) {
+            write_atomically(path, encoded)?;
+            self.track(|usage| usage.add(bundle.id, Some(bundle.destination), bundle.size));
+        }
+        log::info!(
+            "Saved {} of {} bundles to {}",
+            pending.len(),
+            bundles.len(),
+            self.dir.display()
+        );
+        Ok(bundles.iter().map(|bundle| bundle.id().digest()).collect())
+    }
+
+    /// Check that writing `incoming` keeps every destination within its quotas and the
+    /// store within `max_bytes`, returning the stored bundles the eviction policy gives
+    /// up to make that so. Nothing is deleted here, so when any check fails (with
+    /// `QuotaExceeded` or `CapacityExceeded`) the store is left as it was.
+    fn plan_eviction(&self, incoming: &[Incoming]) -> Result<Vec<Victim>> {
+        let replaced: Vec<&str> = incoming.iter().map(|bundle| bundle.id.as_str()).collect();
+        let mut victims = Vec::new();
+
+        if self.has_destination_quota() {
+            let mut destinations: Vec<&EndpointId> = Vec::new();
+            for bundle in incoming {
+                if !destinations.contains(&&bundle.destination) {
+                    destinations.push(&bundle.destination);
+                }
+            }
+            for destination in destinations {
+                let arriving: Vec<&Incoming> = incoming
+                    .iter()
+                    .filter(|bundle| bundle.destination == *destination)
+                    .collect();
+                victims.extend(self.quota_victims(destination, &arriving, &replaced)?);
+            }
+        }
+
+        if let Some(limit) = self.max_bytes {
+            let mut required = self.with_usage(|usage| {
+                replaced.iter().fold(usage.bytes(), |bytes, id| {
+                    bytes.saturating_sub(usage.get(id).map_or(0, |(_, size)| size))
+                })
+            })?;
+            for victim in &victims {
+                required = required.saturating_sub(victim.size);
+            }
+            required += incoming.iter().map(|bundle| bundle.size).sum::<u64>();
+            if required > limit {
+                let exceeded = StoreError::CapacityExceeded { required, limit };
+                let Some(policy) = self.eviction_policy else {
+                    return Err(exceeded);
+                };
+                let stored = self.with_usage(|usage| {
+                    usage
+                        .sizes()
+                        .filter(|(id, _)| {
+                            !replaced.contains(id) && !victims.iter().any(|v| v.id == *id)
+                        })
+                        .map(|(id, size)| (id.to_string(), size))
+                        .collect()
+                })?;
+                let candidates = self.eviction_candidates(stored, policy, max_priority(incoming));
+                let picked = pick_victims(candidates, 0, required - limit).ok_or(exceeded)?;
+                victims.extend(picked.into_iter().map(|(id, size)| Victim {
+                    id,
+                    size,
+                    quota: None,
+                }));
+            }
+        }
+        Ok(victims)
+    }
+
+    /// Bundles for `destination` to evict so that the bundles already held for it,
+    /// less the `replaced` ones, plus `arriving` fit within its quotas
+    fn quota_victims(
+        &self,
+        destination: &EndpointId,
+        arriving: &[&Incoming],
+        replaced: &[&str],
+    ) -> Result<Vec<Victim>> {
+        let (stored_bundles, stored_bytes) = self.with_usage(|usage| {
+            let (mut count, mut bytes) = usage.destination(destination);
+            for id in replaced {
+                if let Some((Some(stored_for), size)) = usage.get(id) {
+                    if stored_for == destination {
+                        count -= 1;
+                        bytes -= size;
+                    }
+                }
+            }
+            (count, bytes)
+        })?;
+        let bundles = stored_bundles + arriving.len();
+        let bytes = stored_bytes + arriving.iter().map(|bundle| bundle.size).sum::<u64>();
+        let excess_bundles =
+            bundles.saturating_sub(self.per_destination_max_bundles.unwrap_or(usize::MAX));
+        let excess_bytes = bytes.saturating_sub(self.per_destination_max_bytes.unwrap_or(u64::MAX));
+        if excess_bundles == 0 && excess_bytes == 0 {
+            return Ok(Vec::new());
+        }
+
+        let exceeded = StoreError::QuotaExceeded {
+            destination: destination.to_string(),
+            bundles,
+            bytes,
+        };
+        let Some(policy) = self.eviction_policy else {
+            return Err(exceeded);
+        };
+        let priority = arriving
+            .iter()
+            .map(|bundle| bundle.priority)
+            .max()
+            .unwrap_or_default();
+        let stored = self.with_usage(|usage| {
+            usage
+                .destination_bundles(destination)
+                .filter(|(id, _)| !replaced.contains(id))
+                .map(|(id, size)| (id.to_string(), size))
+                .collect()
+        })?;
+        let candidates = self.eviction_candidates(stored, policy, priority);
+        let picked = pick_victims(candidates, excess_bundles, excess_bytes).ok_or(exceeded)?;
+        Ok(picked
+            .into_iter()
+            .map(|(id, size)| Victim {
+                id,
+                size,
+                quota: Some(destination.clone()),
+            })
+            .collect())
+    }
+
+    /// The bundles among `stored` (ID and file size) that `policy` lets a bundle of
+    /// priority `incoming` displace, in eviction order. Only headers are read, so
+    /// picking victims never loads payloads.
+    fn eviction_candidates(
+        &self,
+        stored: Vec<(String, u64)>,
+        policy: EvictionPolicy,
+        incoming: Priority,
+    ) -> Vec<(PrimaryBlock, String, u64)> {
+        let mut candidates = Vec::new();
+        for (id, size) in stored {
+            let primary = match self.load_header(&id) {
+                Ok(primary) => primary,
+                Err(StoreError::NotFound) => continue,
+                Err(e) => {
+                    log::warn!("Not considering bundle {id} for eviction: {e}");
+                    continue;
+                }
+            };
+            if policy.may_evict(&primary, incoming) {
+                candidates.push((primary, id, size));
+            }
+        }
+        let now = crate::clock::now();
+        candidates.sort_by(|a, b| policy.compare(&a.0, &b.0, now));
+        candidates
+    }
+
+    /// Delete the bundles picked by [`BundleStore::plan_eviction`]
+    fn evict(&self, victims: Vec<Victim>) -> Result<()> {
+        let Some(policy) = self.eviction_policy else {
+            return Ok(());
+        };
+        for victim in victims {
+            let id = victim.id;
+            match self.remove(&id) {
+                Ok(()) | Err(StoreError::NotFound) => match victim.quota {
+                    Some(destination) => log::info!(
+                        "Evicted bundle {id} ({policy}) to stay within the quota for {destination}"
+                    ),
+                    None => log::info!("Evicted bundle {id} ({policy}) to make room"),
+                },
+                Err(e) => return Err(e),
+            }
+        }
         Ok(())
     }
 
+    fn has_destination_quota(&self) -> bool {
+        self.per_destination_max_bundles.is_some() || self.per_destination_max_bytes.is_some()
+    }
+
     pub fn load(&self, id_hash: &str) -> Result<Bundle> {
         let path = self.dir.join(format!("{id_hash}.cbor"));
-        let data = fs::read(path)?;
-        let bundle = serde_cbor::from_slice(&data)?;
+        let bundle = layout::decode(&fs::read(path).map_err(StoreError::from_bundle_io)?)?;
+        bundle
+            .validate()
+            .map_err(|e| StoreError::Corrupt(format!("Bundle {id_hash}: {e}")))?;
         Ok(bundle)
     }
 
+    fn open(&self, id_hash: &str) -> Result<Stored<BufReader<fs::File>>> {
+        let file = fs::File::open(self.dir.join(format!("{id_hash}.cbor")))
+            .map_err(StoreError::from_bundle_io)?;
+        layout::open(BufReader::new(file))
+    }
+
+    fn read_header(&self, id_hash: &str) -> Result<StoredHeader> {
+        Ok(match self.open(id_hash)? {
+            Stored::Framed { header, .. } => header,
+            Stored::Legacy(bundle) => StoredHeader {
+                primary: bundle.primary,
+                signature: bundle.signature,
+                previous_node: bundle.previous_node,
+            },
+        })
+    }
+
+    /// Read only the primary block of a stored bundle; the payload is not loaded
+    pub fn load_header(&self, id_hash: &str) -> Result<PrimaryBlock> {
+        Ok(self.read_header(id_hash)?.primary)
+    }
+
+    /// Stream the payload of a stored bundle into `writer` without holding it in
+    /// memory, returning the number of bytes copied
+    pub fn copy_payload_to<W: Write>(&self, id_hash: &str, writer: &mut W) -> Result<u64> {
+        match self.open(id_hash)? {
+            Stored::Framed { mut payload, .. } => {
+                let expected = payload.limit();
+                let copied = std::io::copy(&mut payload, writer)?;
+                layout::check_payload_len(copied, expected)?;
+                Ok(copied)
+            }
+            Stored::Legacy(bundle) => {
+                writer.write_all(&bundle.payload)?;
+                Ok(bundle.payload.len() as u64)
+            }
+        }
+    }
+
+    /// Read the metadata of a stored bundle without decoding its payload
+    pub fn load_meta(&self, id_hash: &str) -> Result<BundleMeta> {
+        let size = fs::metadata(self.dir.join(format!("{id_hash}.cbor")))
+            .map_err(StoreError::from_bundle_io)?
+            .len();
+        let header = self.read_header(id_hash)?;
+        Ok(BundleMeta {
+            id: id_hash.to_string(),
+            source: header.primary.source,
+            destination: header.primary.destination,
+            creation_timestamp: header.primary.creation_timestamp,
+            lifetime: header.primary.lifetime,
+            size,
+        })
+    }
+
+    /// Metadata of stored bundles ordered by `sort`, skipping `offset` entries and
+    /// returning at most `limit`
+    pub fn list_meta(&self, offset: usize, limit: usize, sort: SortKey) -> Result<Vec<BundleMeta>> {
+        let mut metas = Vec::new();
+        for id in self.iter_ids() {
+            let id = id?;
+            match self.load_meta(&id) {
+                Ok(meta) => metas.push(meta),
+                // Removed since the directory was read
+                Err(StoreError::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        sort.sort(&mut metas);
+        Ok(metas.into_iter().skip(offset).take(limit).collect())
+    }
+
+    /// Count, size, age and expiry of the stored bundles in one directory pass.
+    /// Only headers are decoded; `total_bytes` is the size of the bundle files.
+    pub fn stats(&self) -> Result<StoreStats> {
+        let mut stats = StoreStats::default();
+        for id in self.iter_ids() {
+            let id = id?;
+            let path = self.dir.join(format!("{id}.cbor"));
+            let size = match fs::metadata(&path) {
+                Ok(metadata) => metadata.len(),
+                // Removed since the directory was read
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let (header, payload_len) = match self.open(&id) {
+                Ok(Stored::Framed { header, payload }) => (header, payload.limit()),
+                Ok(Stored::Legacy(bundle)) => {
+                    let payload_len = bundle.payload.len() as u64;
+                    (
+                        StoredHeader {
+                            primary: bundle.primary,
+                            signature: bundle.signature,
+                            previous_node: bundle.previous_node,
+                        },
+                        payload_len,
+                    )
+                }
+                Err(StoreError::NotFound) => continue,
+                Err(e) => return Err(e),
+            };
+            let meta = BundleMeta {
+                id,
+                source: header.primary.source,
+                destination: header.primary.destination,
+                creation_timestamp: header.primary.creation_timestamp,
+                lifetime: header.primary.lifetime,
+                size,
+            };
+            stats.record(&meta, payload_len);
+        }
+        Ok(stats)
+    }
+
+    /// IDs of the stored bundles matching `filter`; only primary blocks are decoded
+    pub fn find(&self, filter: &BundleFilter) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for id in self.iter_ids() {
+            let id = id?;
+            match self.load_meta(&id) {
+                Ok(meta) if filter.matches(&meta) => ids.push(id),
+                // Removed since the directory was read
+                Ok(_) | Err(StoreError::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(ids)
+    }
+
     pub fn load_by_partial_id(&self, partial: &str) -> Result<Bundle> {
         if let Some(full_id) = self.find_by_partial_id(partial) {
             self.load(&full_id)
         } else {
-            Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Bundle ID not found").into())
+            Err(StoreError::NotFound)
         }
     }
 
     pub fn find_by_partial_id(&self, partial: &str) -> Option<String> {
-        match self.list() {
-            Ok(ids) => ids.into_iter().find(|id| id.starts_with(partial)),
-            Err(_) => None,
-        }
+        self.iter_ids()
+            .filter_map(Result::ok)
+            .find(|id| id.starts_with(partial))
     }
 
     pub fn list(&self) -> Result<Vec<String>> {
-        let mut result = vec![];
-        for entry in fs::read_dir(&self.dir)? {
-            let entry = entry?;
-            if entry.path().extension().and_then(|s| s.to_str()) == Some("cbor") {
-                if let Some(name) = entry.path().file_stem().and_then(|s| s.to_str()) {
-                    result.push(name.to_string());
+        self.iter_ids().collect()
+    }
+
+    /// Lazily iterate over the IDs of stored bundles without buffering the whole directory
+    pub fn iter_ids(&self) -> impl Iterator<Item = Result<String>> {
+        let (entries, open_err) = match fs::read_dir(&self.dir) {
+            Ok(entries) => (Some(entries), None),
+            Err(e) => (None, Some(Err(e.into()))),
+        };
+
+        open_err
+            .into_iter()
+            .chain(entries.into_iter().flatten().filter_map(|entry| {
+                let path = match entry {
+                    Ok(entry) => entry.path(),
+                    Err(e) => return Some(Err(e.into())),
+                };
+                if path.extension().and_then(|s| s.to_str()) != Some("cbor") {
+                    return None;
                 }
+                path.file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(|name| Ok(name.to_string()))
+            }))
+    }
+
+    /// Lazily iterate over stored bundles, loading each one only when it is reached
+    pub fn iter_bundles(&self) -> impl Iterator<Item = Result<(String, Bundle)>> + '_ {
+        self.iter_ids().map(move |id| {
+            let id = id?;
+            let bundle = self.load(&id)?;
+            Ok((id, bundle))
+        })
+    }
+
+    /// Delete the bundle with the given full ID, failing if it does not exist
+    pub fn remove(&self, id: &str) -> Result<()> {
+        let path = self.dir.join(format!("{id}.cbor"));
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                log::info!("Removed bundle: {id}");
+                self.track(|usage| usage.remove(id));
+                self.clear_bundle_state(id)
             }
+            Err(e) => Err(StoreError::from_bundle_io(e)),
         }
-        Ok(result)
     }
 
+    /// List bundle IDs in forwarding order: `Expedited` before `Normal` before `Bulk`,
+    /// oldest first within the same priority. Bundles removed while listing are left
+    /// out, and so are bundles whose header cannot be decoded, with a warning.
+    pub fn list_by_priority(&self) -> Result<Vec<String>> {
+        let mut entries = Vec::new();
+        for id in self.iter_ids() {
+            let id = id?;
+            let primary = match self.load_header(&id) {
+                Ok(primary) => primary,
+                Err(StoreError::NotFound) => continue,
+                Err(e) => {
+                    log::warn!("Not listing bundle {id} for forwarding: {e}");
+                    continue;
+                }
+            };
+            entries.push((
+                std::cmp::Reverse(primary.priority),
+                primary.creation_timestamp,
+                id,
+            ));
+        }
+        entries.sort();
+        Ok(entries.into_iter().map(|(_, _, id)| id).collect())
+    }
+
+    /// Directory that delivered bundles are moved into (`<store>/dispatched` unless
+    /// set with [`BundleStore::with_dispatched_dir`])
+    pub fn dispatched_dir(&self) -> PathBuf {
+        // Joining an absolute path replaces the store directory
+        self.dir.join(
+            self.dispatched_path
+                .as_deref()
+                .unwrap_or(Path::new("dispatched")),
+        )
+    }
+
+    /// Move a sent bundle into `dispatched_dir`, stamping the file with the dispatch
+    /// time so the retention policy counts from then
     pub fn dispatch_one(&self, bundle: &Bundle, dispatched_dir: &Path) -> Result<()> {
+        let dst = self.move_out(bundle, dispatched_dir)?;
+        fs::File::options()
+            .write(true)
+            .open(dst)?
+            .set_modified(SystemTime::now())?;
+        Ok(())
+    }
+
+    /// Delete bundles from the dispatched directory that were dispatched longer than
+    /// `retention` ago, returning how many were deleted. A missing directory holds none.
+    pub fn cleanup_dispatched(&self, retention: Duration) -> Result<usize> {
+        let dir = self.dispatched_dir();
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let now = SystemTime::now();
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("cbor") {
+                continue;
+            }
+            let dispatched_at = match fs::metadata(&path).and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if now.duration_since(dispatched_at).unwrap_or_default() <= retention {
+                continue;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => removed += 1,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if removed > 0 {
+            log::info!(
+                "Removed {removed} dispatched bundles older than {retention:?} from {}",
+                dir.display()
+            );
+        }
+        Ok(removed)
+    }
+
+    /// Directory that undeliverable bundles are moved into (`<store>/failed`)
+    pub fn failed_dir(&self) -> PathBuf {
+        self.dir.join("failed")
+    }
+
+    /// Move a bundle that exhausted its forwarding attempts into `failed_dir`
+    pub fn fail_one(&self, bundle: &Bundle, failed_dir: &Path) -> Result<()> {
+        self.move_out(bundle, failed_dir)?;
+        Ok(())
+    }
+
+    /// Directory that bundles delivered to the local node are moved into (`<store>/delivered`)
+    pub fn delivered_dir(&self) -> PathBuf {
+        self.dir.join("delivered")
+    }
+
+    /// Move a bundle's file into `target_dir`, returning its new path
+    pub(crate) fn move_out(&self, bundle: &Bundle, target_dir: &Path) -> Result<PathBuf> {
         let src = self.filename_for(bundle);
-        let dst = dispatched_dir.join(
+        let dst = target_dir.join(
             src.file_name()
-                .ok_or_else(|| anyhow::anyhow!("Invalid filename"))?,
+                .ok_or_else(|| StoreError::Corrupt(format!("Invalid filename: {src:?}")))?,
         );
-        fs::create_dir_all(dispatched_dir)?;
-        fs::rename(src, dst)?;
+        fs::create_dir_all(target_dir)?;
+        move_file(&src, &dst).map_err(StoreError::from_bundle_io)?;
+        let id = bundle.id().digest();
+        self.track(|usage| usage.remove(&id));
+        self.clear_bundle_state(&id)?;
+        Ok(dst)
+    }
+
+    /// Append-only forwarding history log (`<store>/dispatched.log`)
+    pub fn history_path(&self) -> PathBuf {
+        self.dir.join("dispatched.log")
+    }
+
+    /// Append an entry to the forwarding history log
+    pub fn record_history(&self, entry: &HistoryEntry) -> Result<()> {
+        history::append(&self.history_path(), entry)
+    }
+
+    /// Every entry of the forwarding history log, oldest first
+    pub fn history(&self) -> Result<Vec<HistoryEntry>> {
+        history::read(&self.history_path())
+    }
+
+    fn attempts_path(&self, id: &str) -> PathBuf {
+        self.dir.join("attempts").join(id)
+    }
+
+    /// Persisted number of failed forwarding attempts for a bundle (0 if none were recorded)
+    pub fn forwarding_attempts(&self, id: &str) -> Result<u32> {
+        match fs::read_to_string(self.attempts_path(id)) {
+            Ok(count) => count.trim().parse().map_err(|e| {
+                StoreError::Corrupt(format!("Invalid forwarding attempt count for {id}: {e}"))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Persist the number of failed forwarding attempts for a bundle
+    pub fn set_forwarding_attempts(&self, id: &str, attempts: u32) -> Result<()> {
+        let path = self.attempts_path(id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, attempts.to_string())?;
         Ok(())
     }
 
-    pub fn cleanup_expired(&self) -> Result<()> {
-        let ids = self.list()?;
-        println!("🔍 Found {} bundle IDs: {:?}", ids.len(), ids);
-        if ids.is_empty() {
-            println!("📦 No bundles found");
-            return Ok(());
+    pub(crate) fn clear_forwarding_attempts(&self, id: &str) -> Result<()> {
+        for path in [self.attempts_path(id), self.retry_path(id)] {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
         }
+        Ok(())
+    }
 
-        for id in ids {
-            let bundle = match self.load_by_partial_id(&id) {
-                Ok(bundle) => bundle,
-                Err(e) => {
-                    if let Some(io_err) = e.downcast_ref::<std::io::Error>() {
-                        if io_err.kind() == std::io::ErrorKind::NotFound {
-                            continue;
-                        }
-                    }
-                    return Err(e);
-                }
+    fn retry_path(&self, id: &str) -> PathBuf {
+        self.dir.join("attempts").join(format!("{id}.retry"))
+    }
+
+    /// Persisted unix time (seconds) before which a bundle that failed to forward is
+    /// not retried (0 if none was recorded)
+    pub fn next_retry_at(&self, id: &str) -> Result<u64> {
+        match fs::read_to_string(self.retry_path(id)) {
+            Ok(retry_at) => retry_at
+                .trim()
+                .parse()
+                .map_err(|e| StoreError::Corrupt(format!("Invalid retry time for {id}: {e}"))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Persist when a bundle that failed to forward may be retried, next to its
+    /// forwarding attempt count
+    pub fn set_next_retry_at(&self, id: &str, retry_at: u64) -> Result<()> {
+        let path = self.retry_path(id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, retry_at.to_string())?;
+        Ok(())
+    }
+
+    fn arrival_path(&self, id: &str) -> PathBuf {
+        self.dir.join("arrivals").join(id)
+    }
+
+    /// When a received bundle arrived, persisted as Unix milliseconds (`None` if not recorded)
+    pub fn arrival_time(&self, id: &str) -> Result<Option<SystemTime>> {
+        match fs::read_to_string(self.arrival_path(id)) {
+            Ok(millis) => millis
+                .trim()
+                .parse()
+                .map(|millis| Some(UNIX_EPOCH + Duration::from_millis(millis)))
+                .map_err(|e| StoreError::Corrupt(format!("Invalid arrival time for {id}: {e}"))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Persist when a received bundle arrived
+    pub fn set_arrival_time(&self, id: &str, arrived_at: SystemTime) -> Result<()> {
+        let millis = arrived_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = self.arrival_path(id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, millis.to_string())?;
+        Ok(())
+    }
+
+    /// Drop the per-bundle state kept next to a bundle that leaves the active set
+    pub(crate) fn clear_bundle_state(&self, id: &str) -> Result<()> {
+        self.clear_forwarding_attempts(id)?;
+        match fs::remove_file(self.arrival_path(id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove expired bundles, returning how many were removed.
+    ///
+    /// Bundles that disappear while the scan is running (delivered, deleted or
+    /// cleaned up by another task) are skipped rather than treated as errors.
+    pub fn cleanup_expired(&self) -> Result<usize> {
+        let mut found = 0usize;
+        let mut removed = 0usize;
+
+        for id in self.iter_ids() {
+            let id = id?;
+            found += 1;
+
+            let primary = match self.load_header(&id) {
+                Ok(primary) => primary,
+                Err(StoreError::NotFound) => continue,
+                Err(e) => return Err(e),
             };
 
-            if bundle.is_expired() {
+            if primary.is_expired() {
                 let path = self.dir.join(format!("{id}.cbor"));
-                println!("🔍 Attempting to remove: {path:?}");
+                log::debug!("Attempting to remove: {path:?}");
                 match std::fs::remove_file(&path) {
-                    Ok(_) => println!("🗑️  Removed expired bundle: {id}"),
+                    Ok(_) => {
+                        self.track(|usage| usage.remove(&id));
+                        self.clear_bundle_state(&id)?;
+                        removed += 1;
+                        log::info!("Removed expired bundle: {id}");
+                    }
                     Err(e) => {
-                        println!("❌ Failed to remove: {path:?} - {e:?}");
+                        log::warn!("Failed to remove: {path:?} - {e:?}");
                         if e.kind() != std::io::ErrorKind::NotFound {
                             return Err(e.into());
                         }
@@ -124,6 +1028,126 @@ impl BundleStore {
                 }
             }
         }
+
+        if found == 0 {
+            log::info!("No bundles found");
+        } else {
+            log::info!("Checked {found} bundles for expiration, removed {removed}");
+        }
+        if let Some(retention) = self.dispatched_retention {
+            self.cleanup_dispatched(retention)?;
+        }
+        Ok(removed)
+    }
+}
+
+impl BundleStorage for BundleStore {
+    fn insert(&self, bundle: &Bundle) -> Result<InsertOutcome> {
+        BundleStore::insert(self, bundle)
+    }
+
+    fn contains(&self, bundle: &Bundle) -> bool {
+        BundleStore::contains(self, bundle)
+    }
+
+    fn insert_batch(&self, bundles: &[Bundle]) -> Result<Vec<String>> {
+        BundleStore::insert_batch(self, bundles)
+    }
+
+    fn load(&self, id: &str) -> Result<Bundle> {
+        BundleStore::load(self, id)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        BundleStore::list(self)
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        BundleStore::remove(self, id)
+    }
+
+    fn dispatch(&self, bundle: &Bundle) -> Result<()> {
+        self.dispatch_one(bundle, &self.dispatched_dir())
+    }
+
+    fn fail(&self, bundle: &Bundle) -> Result<()> {
+        self.fail_one(bundle, &self.failed_dir())
+    }
+
+    fn deliver(&self, bundle: &Bundle) -> Result<()> {
+        self.move_out(bundle, &self.delivered_dir())?;
         Ok(())
     }
+
+    fn forwarding_attempts(&self, id: &str) -> Result<u32> {
+        BundleStore::forwarding_attempts(self, id)
+    }
+
+    fn set_forwarding_attempts(&self, id: &str, attempts: u32) -> Result<()> {
+        BundleStore::set_forwarding_attempts(self, id, attempts)
+    }
+
+    fn next_retry_at(&self, id: &str) -> Result<u64> {
+        BundleStore::next_retry_at(self, id)
+    }
+
+    fn set_next_retry_at(&self, id: &str, retry_at: u64) -> Result<()> {
+        BundleStore::set_next_retry_at(self, id, retry_at)
+    }
+
+    fn arrival_time(&self, id: &str) -> Result<Option<SystemTime>> {
+        BundleStore::arrival_time(self, id)
+    }
+
+    fn set_arrival_time(&self, id: &str, arrived_at: SystemTime) -> Result<()> {
+        BundleStore::set_arrival_time(self, id, arrived_at)
+    }
+
+    fn record_history(&self, entry: &HistoryEntry) -> Result<()> {
+        BundleStore::record_history(self, entry)
+    }
+
+    fn history(&self) -> Result<Vec<HistoryEntry>> {
+        BundleStore::history(self)
+    }
+
+    fn load_meta(&self, id: &str) -> Result<BundleMeta> {
+        BundleStore::load_meta(self, id)
+    }
+
+    fn find_by_partial_id(&self, partial: &str) -> Option<String> {
+        BundleStore::find_by_partial_id(self, partial)
+    }
+
+    fn list_by_priority(&self) -> Result<Vec<String>> {
+        BundleStore::list_by_priority(self)
+    }
+
+    fn list_meta(&self, offset: usize, limit: usize, sort: SortKey) -> Result<Vec<BundleMeta>> {
+        BundleStore::list_meta(self, offset, limit, sort)
+    }
+
+    fn stats(&self) -> Result<StoreStats> {
+        BundleStore::stats(self)
+    }
+
+    fn find(&self, filter: &BundleFilter) -> Result<Vec<String>> {
+        BundleStore::find(self, filter)
+    }
+
+    fn cleanup_expired(&self) -> Result<usize> {
+        BundleStore::cleanup_expired(self)
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        BundleStore::check_writable(self)
+    }
+
+    fn usage_ratio(&self) -> Result<Option<f64>> {
+        BundleStore::usage_ratio(self)
+    }
+
+    fn evicts_on_insert(&self) -> bool {
+        self.eviction_policy.is_some() && (self.max_bytes.is_some() || self.has_destination_quota())
+    }
 }
@@ -0,0 +1,108 @@
+use crate::bpv7::bundle::Bundle;
+use crate::store::error::{Result, StoreError};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// What happened when a bundle was handed to a next hop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryOutcome {
+    /// The bundle was sent (or dispatched) and acknowledged
+    Sent,
+    /// Sending the bundle failed
+    Failed,
+}
+
+impl std::fmt::Display for HistoryOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryOutcome::Sent => write!(f, "sent"),
+            HistoryOutcome::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// One record of the forwarding history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub bundle_id: String,
+    pub destination: String,
+    /// Peer the bundle was handed to, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_hop: Option<String>,
+    /// Unix time in seconds at which the entry was recorded
+    pub timestamp: u64,
+    pub outcome: HistoryOutcome,
+    /// Why the send failed, for `Failed` entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl HistoryEntry {
+    /// Entry recording that `bundle` was sent to `next_hop`
+    pub fn sent(bundle: &Bundle, next_hop: Option<&str>) -> Self {
+        Self::new(bundle, next_hop, HistoryOutcome::Sent, None)
+    }
+
+    /// Entry recording that sending `bundle` to `next_hop` failed with `error`
+    pub fn failed(bundle: &Bundle, next_hop: &str, error: impl std::fmt::Display) -> Self {
+        Self::new(
+            bundle,
+            Some(next_hop),
+            HistoryOutcome::Failed,
+            Some(error.to_string()),
+        )
+    }
+
+    fn new(
+        bundle: &Bundle,
+        next_hop: Option<&str>,
+        outcome: HistoryOutcome,
+        error: Option<String>,
+    ) -> Self {
+        Self {
+            bundle_id: bundle.id().digest(),
+            destination: bundle.primary.destination.clone(),
+            next_hop: next_hop.map(str::to_string),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            outcome,
+            error,
+        }
+    }
+}
+
+/// Append `entry` as one JSON line to the log at `path`
+pub(crate) fn append(path: &Path, entry: &HistoryEntry) -> Result<()> {
+    let mut line = serde_json::to_vec(entry).map_err(|e| StoreError::Serialize(e.to_string()))?;
+    line.push(b'\n');
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    // A single write keeps concurrent appends from interleaving within a line
+    file.write_all(&line)?;
+    Ok(())
+}
+
+/// Read every entry of the log at `path`, oldest first; a missing log is empty
+pub(crate) fn read(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                StoreError::Corrupt(format!("Invalid history entry on line {}: {e}", index + 1))
+            })
+        })
+        .collect()
+}
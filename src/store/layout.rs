@@ -0,0 +1,150 @@
+//! On-disk layout of a bundle in a [`BundleStore`](crate::store::BundleStore) file:
+//!
+//! ```text
+//! MAGIC | header length (u32 BE) | header | payload length (u64 BE) | payload
+//! ```
+//!
+//! The header is the canonical CBOR of everything but the payload, so it can be read
+//! without touching the payload bytes, which are stored raw and can be streamed.
+//! Files written before this layout hold the whole bundle as one CBOR map; they are
+//! recognised by the missing magic and decoded whole.
+
+use crate::bpv7::bundle::Bundle;
+use crate::store::error::{Result, StoreError};
+use crate::store::meta::StoredHeader;
+use std::io::{Read, Take};
+
+/// Leading bytes of a length-delimited bundle file. A legacy file starts with a CBOR
+/// map header (major type 5), which can never be `S`.
+pub(crate) const MAGIC: &[u8; 4] = b"SDB1";
+
+/// Upper bound on the header length accepted when reading, so a corrupt length
+/// field cannot trigger a huge allocation
+const MAX_HEADER_LEN: u32 = 1024 * 1024;
+
+/// Encode `bundle` in the length-delimited layout
+pub(crate) fn encode(bundle: &Bundle) -> Result<Vec<u8>> {
+    let header = StoredHeader {
+        primary: bundle.primary.clone(),
+        signature: bundle.signature.clone(),
+        previous_node: bundle.previous_node.clone(),
+    };
+    let header = serde_cbor::value::to_value(&header)
+        .and_then(|value| serde_cbor::to_vec(&value))
+        .map_err(|e| StoreError::Serialize(e.to_string()))?;
+    let header_len = u32::try_from(header.len())
+        .map_err(|_| StoreError::Serialize("bundle header too large".to_string()))?;
+
+    let mut encoded = Vec::with_capacity(MAGIC.len() + 12 + header.len() + bundle.payload.len());
+    encoded.extend_from_slice(MAGIC);
+    encoded.extend_from_slice(&header_len.to_be_bytes());
+    encoded.extend_from_slice(&header);
+    encoded.extend_from_slice(&(bundle.payload.len() as u64).to_be_bytes());
+    encoded.extend_from_slice(&bundle.payload);
+    Ok(encoded)
+}
+
+/// Whether the stored bytes `existing` hold the same bundle as the freshly `encoded` one
+pub(crate) fn is_same(existing: &[u8], encoded: &[u8]) -> bool {
+    if existing.starts_with(MAGIC) {
+        existing == encoded
+    } else {
+        decode(existing)
+            .and_then(|bundle| encode(&bundle))
+            .ok()
+            .as_deref()
+            == Some(encoded)
+    }
+}
+
+/// Decode a whole stored bundle, in either layout
+pub(crate) fn decode(data: &[u8]) -> Result<Bundle> {
+    match open(data)? {
+        Stored::Framed {
+            header,
+            mut payload,
+        } => {
+            let mut bytes = Vec::new();
+            payload.read_to_end(&mut bytes)?;
+            check_payload_len(bytes.len() as u64, payload.limit() + bytes.len() as u64)?;
+            Ok(Bundle {
+                primary: header.primary,
+                payload: bytes,
+                signature: header.signature,
+                previous_node: header.previous_node,
+            })
+        }
+        Stored::Legacy(bundle) => Ok(bundle),
+    }
+}
+
+/// A stored bundle opened for reading
+pub(crate) enum Stored<R> {
+    /// Length-delimited file; `payload` yields exactly the payload bytes
+    Framed {
+        header: StoredHeader,
+        payload: Take<R>,
+    },
+    /// File written before the length-delimited layout, decoded whole
+    Legacy(Bundle),
+}
+
+/// Read the header of a stored bundle, leaving `reader` positioned at the payload
+pub(crate) fn open<R: Read>(mut reader: R) -> Result<Stored<R>> {
+    let mut magic = [0u8; 4];
+    let filled = read_up_to(&mut reader, &mut magic)?;
+    if filled < magic.len() || &magic != MAGIC {
+        let mut data = magic[..filled].to_vec();
+        reader.read_to_end(&mut data)?;
+        return Ok(Stored::Legacy(serde_cbor::from_slice(&data)?));
+    }
+
+    let header_len = u32::from_be_bytes(read_array(&mut reader)?);
+    if header_len > MAX_HEADER_LEN {
+        return Err(StoreError::Corrupt(format!(
+            "bundle header length {header_len} exceeds {MAX_HEADER_LEN} bytes"
+        )));
+    }
+    let mut header = vec![0u8; header_len as usize];
+    read_exact(&mut reader, &mut header)?;
+    let header: StoredHeader = serde_cbor::from_slice(&header)?;
+    let payload_len = u64::from_be_bytes(read_array(&mut reader)?);
+    Ok(Stored::Framed {
+        header,
+        payload: reader.take(payload_len),
+    })
+}
+
+/// Fail if fewer payload bytes were read than the header announced
+pub(crate) fn check_payload_len(read: u64, expected: u64) -> Result<()> {
+    if read != expected {
+        return Err(StoreError::Corrupt(format!(
+            "payload truncated: {read} of {expected} bytes"
+        )));
+    }
+    Ok(())
+}
+
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<()> {
+    if read_up_to(reader, buf)? < buf.len() {
+        return Err(StoreError::Corrupt("bundle file truncated".to_string()));
+    }
+    Ok(())
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    read_exact(reader, &mut buf)?;
+    Ok(buf)
+}
@@ -0,0 +1,173 @@
+use crate::bpv7::bundle::Bundle;
+use crate::store::error::{Result, StoreError};
+use crate::store::file::InsertOutcome;
+use crate::store::history::HistoryEntry;
+use crate::store::storage::{encode_bundle, BundleStorage};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Bundle store kept entirely in memory; useful for tests and ephemeral nodes
+#[derive(Default)]
+pub struct MemoryBundleStore {
+    bundles: Mutex<HashMap<String, Bundle>>,
+    dispatched: Mutex<HashMap<String, Bundle>>,
+    failed: Mutex<HashMap<String, Bundle>>,
+    delivered: Mutex<HashMap<String, Bundle>>,
+    attempts: Mutex<HashMap<String, u32>>,
+    retries: Mutex<HashMap<String, u64>>,
+    arrivals: Mutex<HashMap<String, SystemTime>>,
+    history: Mutex<Vec<HistoryEntry>>,
+}
+
+impl MemoryBundleStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// IDs of bundles that have been dispatched
+    pub fn dispatched(&self) -> Vec<String> {
+        self.dispatched.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// IDs of bundles that were given up on after exhausting their forwarding attempts
+    pub fn failed(&self) -> Vec<String> {
+        self.failed.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// IDs of bundles delivered to the local application
+    pub fn delivered(&self) -> Vec<String> {
+        self.delivered.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn move_out(&self, bundle: &Bundle, target: &Mutex<HashMap<String, Bundle>>) -> Result<()> {
+        let id = bundle.id().digest();
+        let bundle = self
+            .bundles
+            .lock()
+            .unwrap()
+            .remove(&id)
+            .ok_or(StoreError::NotFound)?;
+        self.attempts.lock().unwrap().remove(&id);
+        self.retries.lock().unwrap().remove(&id);
+        self.arrivals.lock().unwrap().remove(&id);
+        target.lock().unwrap().insert(id, bundle);
+        Ok(())
+    }
+}
+
+impl BundleStorage for MemoryBundleStore {
+    fn insert(&self, bundle: &Bundle) -> Result<InsertOutcome> {
+        let id = bundle.id().digest();
+        let mut bundles = self.bundles.lock().unwrap();
+        if let Some(existing) = bundles.get(&id) {
+            if encode_bundle(existing)? == encode_bundle(bundle)? {
+                return Ok(InsertOutcome::AlreadyPresent);
+            }
+        }
+        bundles.insert(id, bundle.clone());
+        Ok(InsertOutcome::Inserted)
+    }
+
+    fn contains(&self, bundle: &Bundle) -> bool {
+        self.bundles
+            .lock()
+            .unwrap()
+            .contains_key(&bundle.id().digest())
+    }
+
+    fn load(&self, id: &str) -> Result<Bundle> {
+        self.bundles
+            .lock()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        Ok(self.bundles.lock().unwrap().keys().cloned().collect())
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        self.bundles
+            .lock()
+            .unwrap()
+            .remove(id)
+            .map(|_| ())
+            .ok_or(StoreError::NotFound)?;
+        self.attempts.lock().unwrap().remove(id);
+        self.retries.lock().unwrap().remove(id);
+        self.arrivals.lock().unwrap().remove(id);
+        Ok(())
+    }
+
+    fn dispatch(&self, bundle: &Bundle) -> Result<()> {
+        self.move_out(bundle, &self.dispatched)
+    }
+
+    fn fail(&self, bundle: &Bundle) -> Result<()> {
+        self.move_out(bundle, &self.failed)
+    }
+
+    fn deliver(&self, bundle: &Bundle) -> Result<()> {
+        self.move_out(bundle, &self.delivered)
+    }
+
+    fn forwarding_attempts(&self, id: &str) -> Result<u32> {
+        Ok(self
+            .attempts
+            .lock()
+            .unwrap()
+            .get(id)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn set_forwarding_attempts(&self, id: &str, attempts: u32) -> Result<()> {
+        self.attempts
+            .lock()
+            .unwrap()
+            .insert(id.to_string(), attempts);
+        Ok(())
+    }
+
+    fn next_retry_at(&self, id: &str) -> Result<u64> {
+        Ok(self
+            .retries
+            .lock()
+            .unwrap()
+            .get(id)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn set_next_retry_at(&self, id: &str, retry_at: u64) -> Result<()> {
+        self.retries
+            .lock()
+            .unwrap()
+            .insert(id.to_string(), retry_at);
+        Ok(())
+    }
+
+    fn arrival_time(&self, id: &str) -> Result<Option<SystemTime>> {
+        Ok(self.arrivals.lock().unwrap().get(id).copied())
+    }
+
+    fn set_arrival_time(&self, id: &str, arrived_at: SystemTime) -> Result<()> {
+        self.arrivals
+            .lock()
+            .unwrap()
+            .insert(id.to_string(), arrived_at);
+        Ok(())
+    }
+
+    fn record_history(&self, entry: &HistoryEntry) -> Result<()> {
+        self.history.lock().unwrap().push(entry.clone());
+        Ok(())
+    }
+
+    fn history(&self) -> Result<Vec<HistoryEntry>> {
+        Ok(self.history.lock().unwrap().clone())
+    }
+}
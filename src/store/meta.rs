@@ -0,0 +1,183 @@
+use crate::bpv7::bundle::PrimaryBlock;
+use crate::bpv7::{EndpointId, SignatureBlock};
+use serde::{Deserialize, Serialize};
+
+/// Summary of a stored bundle, read without decoding its payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BundleMeta {
+    pub id: String,
+    pub source: String,
+    pub destination: String,
+    pub creation_timestamp: u64,
+    pub lifetime: u64,
+    /// Size of the stored file in bytes
+    pub size: u64,
+}
+
+impl BundleMeta {
+    /// Whether the bundle's lifetime has elapsed, as in `Bundle::is_expired`
+    pub fn is_expired(&self) -> bool {
+        crate::clock::now() > self.creation_timestamp + self.lifetime
+    }
+
+    /// Seconds until the bundle expires; 0 once it has
+    pub fn remaining_lifetime(&self) -> u64 {
+        (self.creation_timestamp + self.lifetime).saturating_sub(crate::clock::now())
+    }
+}
+
+/// Utilization of a store, gathered in one pass over its bundles
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StoreStats {
+    pub bundle_count: usize,
+    /// Size of the stored bundles in bytes, headers included
+    pub total_bytes: u64,
+    pub payload_bytes: u64,
+    pub oldest_creation_timestamp: Option<u64>,
+    pub newest_creation_timestamp: Option<u64>,
+    pub expired_count: usize,
+    pub active_count: usize,
+}
+
+impl StoreStats {
+    /// Count one stored bundle with `payload_len` bytes of payload
+    pub fn record(&mut self, meta: &BundleMeta, payload_len: u64) {
+        self.bundle_count += 1;
+        self.total_bytes += meta.size;
+        self.payload_bytes += payload_len;
+        self.oldest_creation_timestamp = Some(
+            self.oldest_creation_timestamp
+                .map_or(meta.creation_timestamp, |t| t.min(meta.creation_timestamp)),
+        );
+        self.newest_creation_timestamp = Some(
+            self.newest_creation_timestamp
+                .map_or(meta.creation_timestamp, |t| t.max(meta.creation_timestamp)),
+        );
+        if meta.is_expired() {
+            self.expired_count += 1;
+        } else {
+            self.active_count += 1;
+        }
+    }
+
+    /// Mean payload size in bytes, 0 for an empty store
+    pub fn average_payload_size(&self) -> f64 {
+        if self.bundle_count == 0 {
+            0.0
+        } else {
+            self.payload_bytes as f64 / self.bundle_count as f64
+        }
+    }
+}
+
+/// Criteria for selecting stored bundles; unset fields match every bundle.
+///
+/// Endpoints are compared with `EndpointId::matches`, so wildcard patterns
+/// such as `dtn://ground/*` are supported.
+#[derive(Debug, Clone, Default)]
+pub struct BundleFilter {
+    pub source: Option<EndpointId>,
+    pub destination: Option<EndpointId>,
+    /// Inclusive lower bound on the creation timestamp
+    pub min_creation_time: Option<u64>,
+    /// Inclusive upper bound on the creation timestamp
+    pub max_creation_time: Option<u64>,
+    /// `Some(true)` selects only expired bundles, `Some(false)` only active ones
+    pub expired: Option<bool>,
+    /// Selects active bundles with at most this many seconds of lifetime left
+    pub expiring_within: Option<u64>,
+}
+
+impl BundleFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_source(mut self, source: impl Into<EndpointId>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn with_destination(mut self, destination: impl Into<EndpointId>) -> Self {
+        self.destination = Some(destination.into());
+        self
+    }
+
+    pub fn with_min_creation_time(mut self, timestamp: u64) -> Self {
+        self.min_creation_time = Some(timestamp);
+        self
+    }
+
+    pub fn with_max_creation_time(mut self, timestamp: u64) -> Self {
+        self.max_creation_time = Some(timestamp);
+        self
+    }
+
+    pub fn with_expired(mut self, expired: bool) -> Self {
+        self.expired = Some(expired);
+        self
+    }
+
+    pub fn with_expiring_within(mut self, secs: u64) -> Self {
+        self.expiring_within = Some(secs);
+        self
+    }
+
+    pub fn matches(&self, meta: &BundleMeta) -> bool {
+        let endpoint_matches = |pattern: &Option<EndpointId>, value: &str| {
+            pattern
+                .as_ref()
+                .is_none_or(|pattern| EndpointId::from(value).matches(pattern))
+        };
+
+        endpoint_matches(&self.source, &meta.source)
+            && endpoint_matches(&self.destination, &meta.destination)
+            && self
+                .min_creation_time
+                .is_none_or(|min| meta.creation_timestamp >= min)
+            && self
+                .max_creation_time
+                .is_none_or(|max| meta.creation_timestamp <= max)
+            && self
+                .expired
+                .is_none_or(|expired| meta.is_expired() == expired)
+            && self
+                .expiring_within
+                .is_none_or(|secs| !meta.is_expired() && meta.remaining_lifetime() <= secs)
+    }
+}
+
+/// Ordering applied when listing bundle metadata; ties are broken by ID
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    CreationTime,
+    Lifetime,
+    Source,
+    Size,
+}
+
+impl SortKey {
+    pub fn sort(self, metas: &mut [BundleMeta]) {
+        match self {
+            SortKey::CreationTime => metas
+                .sort_by(|a, b| (a.creation_timestamp, &a.id).cmp(&(b.creation_timestamp, &b.id))),
+            SortKey::Lifetime => {
+                metas.sort_by(|a, b| (a.lifetime, &a.id).cmp(&(b.lifetime, &b.id)))
+            }
+            SortKey::Source => metas.sort_by(|a, b| (&a.source, &a.id).cmp(&(&b.source, &b.id))),
+            SortKey::Size => metas.sort_by(|a, b| (a.size, &a.id).cmp(&(b.size, &b.id))),
+        }
+    }
+}
+
+/// Everything of a stored bundle except its payload. Legacy single-map files decode
+/// into it directly, skipping the payload.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct StoredHeader {
+    pub primary: PrimaryBlock,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureBlock>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_node: Option<EndpointId>,
+}
@@ -0,0 +1,107 @@
+use crate::store::error::Result;
+use crate::store::file::{write_atomically, BundleStore};
+use crate::store::layout::{self, MAGIC};
+use std::fs;
+
+/// Outcome of [`BundleStore::migrate`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Bundles renamed to their current ID or rewritten in the current layout
+    pub migrated: usize,
+    /// Bundles already stored under their current ID and layout
+    pub unchanged: usize,
+    /// Files that could not be decoded, or whose current ID is taken by a different bundle
+    pub skipped: usize,
+}
+
+impl BundleStore {
+    /// Bring every stored bundle up to the current naming scheme and file layout.
+    ///
+    /// Each bundle is decoded, its ID recomputed with [`BundleStore::filename_for`],
+    /// and the file rewritten under that name (with its forwarding attempt count)
+    /// if the name or layout differs. The new file is written before the old one is
+    /// removed, so an interrupted migration loses nothing and re-running it finishes
+    /// the job; a fully migrated store reports every bundle as unchanged.
+    pub fn migrate(&self) -> Result<MigrationReport> {
+        let mut report = MigrationReport::default();
+        // Collect first, since migrating renames entries of the directory being read
+        let ids = self.list()?;
+
+        for id in ids {
+            let path = self.dir.join(format!("{id}.cbor"));
+            let data = match fs::read(&path) {
+                Ok(data) => data,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let bundle = match layout::decode(&data) {
+                Ok(bundle) => bundle,
+                Err(e) => {
+                    log::warn!("Skipping undecodable bundle file {}: {e}", path.display());
+                    report.skipped += 1;
+                    continue;
+                }
+            };
+
+            let target = self.filename_for(&bundle);
+            if target == path && data.starts_with(MAGIC) {
+                report.unchanged += 1;
+                continue;
+            }
+
+            let encoded = layout::encode(&bundle)?;
+            if target != path {
+                match fs::read(&target) {
+                    Ok(existing) if !layout::is_same(&existing, &encoded) => {
+                        log::warn!(
+                            "Skipping {}: {} holds a different bundle",
+                            path.display(),
+                            target.display()
+                        );
+                        report.skipped += 1;
+                        continue;
+                    }
+                    Ok(_) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                        write_atomically(&target, &encoded)?;
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+                self.move_forwarding_attempts(&id, &bundle.id().digest())?;
+                fs::remove_file(&path)?;
+            } else {
+                write_atomically(&target, &encoded)?;
+            }
+            log::info!("Migrated bundle {id} to {}", target.display());
+            report.migrated += 1;
+        }
+
+        if report.migrated > 0 {
+            self.forget_usage();
+        }
+        log::info!(
+            "Store migration finished: {} migrated, {} unchanged, {} skipped",
+            report.migrated,
+            report.unchanged,
+            report.skipped
+        );
+        Ok(report)
+    }
+
+    /// Carry a bundle's forwarding attempt count, retry time and arrival time over
+    /// to its new ID
+    fn move_forwarding_attempts(&self, old_id: &str, new_id: &str) -> Result<()> {
+        let attempts = self.forwarding_attempts(old_id)?;
+        if attempts > 0 {
+            self.set_forwarding_attempts(new_id, attempts)?;
+        }
+        let retry_at = self.next_retry_at(old_id)?;
+        if retry_at > 0 {
+            self.set_next_retry_at(new_id, retry_at)?;
+        }
+        if let Some(arrived_at) = self.arrival_time(old_id)? {
+            self.set_arrival_time(new_id, arrived_at)?;
+        }
+        self.clear_bundle_state(old_id)
+    }
+}
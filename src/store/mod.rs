@@ -1,8 +1,32 @@
 pub mod bundle_descriptor;
+pub mod cached;
+pub mod encrypted;
+pub mod error;
+pub mod eviction;
 pub mod file;
+pub mod history;
+mod layout;
+pub mod memory;
+pub mod meta;
+pub mod migrate;
+pub mod queue;
+pub mod seen;
+pub mod storage;
+mod usage;
 
 pub use bundle_descriptor::BundleDescriptor;
-pub use file::BundleStore;
+pub use cached::CachedBundleStore;
+pub use encrypted::EncryptedBundleStore;
+pub use error::StoreError;
+pub use eviction::EvictionPolicy;
+pub use file::{BundleStore, InsertOutcome};
+pub use history::{HistoryEntry, HistoryOutcome};
+pub use memory::MemoryBundleStore;
+pub use meta::{BundleFilter, BundleMeta, SortKey, StoreStats};
+pub use migrate::MigrationReport;
+pub use queue::{ForwardingQueue, QueuedBundle};
+pub use seen::SeenBundles;
+pub use storage::BundleStorage;
 
 #[cfg(test)]
 mod tests;
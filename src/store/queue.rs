@@ -0,0 +1,158 @@
+use crate::bpv7::bundle::{Bundle, Priority};
+use crate::store::error::{Result, StoreError};
+use crate::store::storage::BundleStorage;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+
+/// A stored bundle waiting in a [`ForwardingQueue`], with the fields it is ordered by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedBundle {
+    /// Store ID of the bundle
+    pub id: String,
+    pub priority: Priority,
+    /// Unix time (seconds) at which the bundle's lifetime ends
+    pub expires_at: u64,
+    pub creation_timestamp: u64,
+}
+
+impl QueuedBundle {
+    pub fn new(bundle: &Bundle) -> Self {
+        Self {
+            id: bundle.id().digest(),
+            priority: bundle.primary.priority,
+            expires_at: bundle.primary.creation_timestamp + bundle.primary.lifetime,
+            creation_timestamp: bundle.primary.creation_timestamp,
+        }
+    }
+}
+
+/// Greater means forwarded sooner: higher priority, then less lifetime left, then
+/// older; the ID breaks remaining ties so the order is deterministic
+impl Ord for QueuedBundle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.expires_at.cmp(&self.expires_at))
+            .then_with(|| other.creation_timestamp.cmp(&self.creation_timestamp))
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+impl PartialOrd for QueuedBundle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Pending bundles in the order the forwarding loop should try them: priority
+/// descending, then remaining lifetime ascending, then creation time ascending.
+///
+/// Bundles requeued after a failed attempt wait until their retry time before
+/// they can be popped again.
+#[derive(Debug, Default)]
+pub struct ForwardingQueue {
+    ready: BinaryHeap<QueuedBundle>,
+    /// Bundles in backoff, earliest retry first
+    waiting: BinaryHeap<Reverse<(u64, QueuedBundle)>>,
+    /// Retry time of every queued ID; 0 for bundles that are ready
+    queued: HashMap<String, u64>,
+}
+
+impl ForwardingQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue every bundle currently in `store`, holding back those whose persisted
+    /// retry time has not come yet. Bundles that cannot be loaded are logged and left
+    /// out, so one corrupt file does not stop all forwarding.
+    pub fn from_store(store: &dyn BundleStorage) -> Result<Self> {
+        let mut queue = Self::new();
+        for id in store.list()? {
+            match store.load(&id) {
+                Ok(bundle) => {
+                    let retry_at = store.next_retry_at(&id).unwrap_or_else(|e| {
+                        log::warn!("Retrying stored bundle {id} right away: {e}");
+                        0
+                    });
+                    queue.reschedule(QueuedBundle::new(&bundle), retry_at);
+                }
+                Err(StoreError::NotFound) => {}
+                Err(e) => log::warn!("Not queueing stored bundle {id} for forwarding: {e}"),
+            }
+        }
+        Ok(queue)
+    }
+
+    /// Queue a bundle for forwarding right away; returns false if it is already queued
+    pub fn push(&mut self, entry: QueuedBundle) -> bool {
+        if self.queued.contains_key(&entry.id) {
+            return false;
+        }
+        self.queued.insert(entry.id.clone(), 0);
+        self.ready.push(entry);
+        true
+    }
+
+    /// Take the next bundle to forward at `now` (unix seconds), skipping bundles
+    /// whose backoff has not elapsed
+    pub fn pop(&mut self, now: u64) -> Option<QueuedBundle> {
+        while let Some(Reverse((retry_at, _))) = self.waiting.peek() {
+            if *retry_at > now {
+                break;
+            }
+            let Some(Reverse((retry_at, entry))) = self.waiting.pop() else {
+                break;
+            };
+            // Entries rescheduled since they were pushed are stale
+            if self.queued.get(&entry.id) == Some(&retry_at) {
+                self.queued.insert(entry.id.clone(), 0);
+                self.ready.push(entry);
+            }
+        }
+        while let Some(entry) = self.ready.pop() {
+            if self.queued.get(&entry.id) == Some(&0) {
+                self.queued.remove(&entry.id);
+                return Some(entry);
+            }
+        }
+        None
+    }
+
+    /// Put a bundle back after a failed attempt; it is not popped again before
+    /// `retry_at` (unix seconds)
+    pub fn requeue_with_backoff(&mut self, entry: QueuedBundle, retry_at: u64) -> bool {
+        if self.queued.contains_key(&entry.id) {
+            return false;
+        }
+        self.queued.insert(entry.id.clone(), retry_at);
+        self.waiting.push(Reverse((retry_at, entry)));
+        true
+    }
+
+    /// Schedule a bundle for `retry_at` (unix seconds; 0 for right away), replacing
+    /// the schedule it had if it was already queued
+    pub fn reschedule(&mut self, entry: QueuedBundle, retry_at: u64) {
+        if self.queued.insert(entry.id.clone(), retry_at) == Some(0) && retry_at == 0 {
+            return;
+        }
+        if retry_at == 0 {
+            self.ready.push(entry);
+        } else {
+            self.waiting.push(Reverse((retry_at, entry)));
+        }
+    }
+
+    /// Retry time of a queued bundle: 0 if it is ready, `None` if it is not queued
+    pub fn retry_at(&self, id: &str) -> Option<u64> {
+        self.queued.get(id).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queued.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+}
@@ -0,0 +1,176 @@
+use crate::bpv7::BundleId;
+use crate::store::error::Result;
+use crate::store::file::write_atomically;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of bundle IDs a [`SeenBundles`] remembers unless configured otherwise
+pub const DEFAULT_SEEN_CAPACITY: usize = 10_000;
+
+/// How often a persistent [`SeenBundles`] writes new IDs to disk unless configured otherwise
+pub const DEFAULT_SEEN_PERSIST_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Bounded LRU of the IDs of bundles this node has received, so a late copy of a
+/// bundle that was already forwarded and deleted is recognised as a duplicate.
+///
+/// When opened with a path, the IDs are written there (one per line, least
+/// recently seen first) at most once per persist interval and on [`SeenBundles::save`].
+pub struct SeenBundles {
+    capacity: usize,
+    path: Option<PathBuf>,
+    persist_interval: Duration,
+    state: Mutex<SeenState>,
+}
+
+struct SeenState {
+    /// Last use of each remembered ID
+    ids: HashMap<String, u64>,
+    /// IDs by last use, least recently seen first
+    order: BTreeMap<u64, String>,
+    tick: u64,
+    /// Whether IDs changed since the last save
+    dirty: bool,
+    saved_at: Instant,
+}
+
+impl SeenState {
+    fn touch(&mut self, id: String) -> bool {
+        self.tick += 1;
+        self.dirty = true;
+        let seen = match self.ids.insert(id.clone(), self.tick) {
+            Some(previous) => {
+                self.order.remove(&previous);
+                true
+            }
+            None => false,
+        };
+        self.order.insert(self.tick, id);
+        seen
+    }
+
+    fn shrink(&mut self, capacity: usize) {
+        while self.ids.len() > capacity {
+            let Some((_, id)) = self.order.pop_first() else {
+                break;
+            };
+            self.ids.remove(&id);
+        }
+    }
+}
+
+impl SeenBundles {
+    /// Remember up to `capacity` IDs in memory only
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            path: None,
+            persist_interval: DEFAULT_SEEN_PERSIST_INTERVAL,
+            state: Mutex::new(SeenState {
+                ids: HashMap::new(),
+                order: BTreeMap::new(),
+                tick: 0,
+                dirty: false,
+                saved_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Remember up to `capacity` IDs, restoring and persisting them at `path`;
+    /// a missing file starts empty
+    pub fn open(path: impl Into<PathBuf>, capacity: usize) -> Result<Self> {
+        let path = path.into();
+        let mut seen = Self::new(capacity);
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let state = seen.state.get_mut().unwrap();
+        for id in data.lines().map(str::trim).filter(|id| !id.is_empty()) {
+            state.touch(id.to_string());
+        }
+        state.shrink(seen.capacity);
+        state.dirty = false;
+        seen.path = Some(path);
+        Ok(seen)
+    }
+
+    /// Write new IDs to disk at most once per `interval`
+    pub fn with_persist_interval(mut self, interval: Duration) -> Self {
+        self.persist_interval = interval;
+        self
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Number of IDs currently remembered
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, id: &BundleId) -> bool {
+        self.state.lock().unwrap().ids.contains_key(&id.digest())
+    }
+
+    /// Remember `id`, returning whether it had already been seen
+    pub fn check_and_insert(&self, id: &BundleId) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let seen = state.touch(id.digest());
+        state.shrink(self.capacity);
+        if state.saved_at.elapsed() >= self.persist_interval {
+            if let Err(e) = self.save_locked(&mut state) {
+                log::warn!("Failed to persist seen bundle IDs: {e}");
+            }
+        }
+        seen
+    }
+
+    /// Forget `id`, e.g. because storing the bundle failed and a retransmission
+    /// must be accepted
+    pub fn forget(&self, id: &BundleId) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(tick) = state.ids.remove(&id.digest()) {
+            state.order.remove(&tick);
+            state.dirty = true;
+        }
+    }
+
+    /// Write the remembered IDs to disk if any changed; a no-op without a path
+    pub fn save(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        self.save_locked(&mut state)
+    }
+
+    fn save_locked(&self, state: &mut SeenState) -> Result<()> {
+        state.saved_at = Instant::now();
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !state.dirty {
+            return Ok(());
+        }
+        let mut data = String::new();
+        for id in state.order.values() {
+            data.push_str(id);
+            data.push('\n');
+        }
+        write_atomically(path, data.as_bytes())?;
+        state.dirty = false;
+        Ok(())
+    }
+}
+
+impl Default for SeenBundles {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEEN_CAPACITY)
+    }
+}
@@ -0,0 +1,232 @@
+use crate::bpv7::bundle::Bundle;
+use crate::store::error::{Result, StoreError};
+use crate::store::file::InsertOutcome;
+use crate::store::history::HistoryEntry;
+use crate::store::meta::{BundleFilter, BundleMeta, SortKey, StoreStats};
+use std::time::SystemTime;
+
+/// Storage backend for bundles, keyed by the digest of each bundle's `BundleId`.
+///
+/// Only the primitive operations must be implemented; lookups by partial ID,
+/// priority ordering, paging and expiry cleanup are built on top of them.
+pub trait BundleStorage: Send + Sync {
+    /// Store a bundle, reporting whether an identical copy was already present
+    fn insert(&self, bundle: &Bundle) -> Result<InsertOutcome>;
+
+    /// Whether a bundle with the same ID is already stored
+    fn contains(&self, bundle: &Bundle) -> bool;
+
+    /// Load the bundle with the given full ID
+    fn load(&self, id: &str) -> Result<Bundle>;
+
+    /// IDs of all stored bundles
+    fn list(&self) -> Result<Vec<String>>;
+
+    /// Delete the bundle with the given full ID, failing if it does not exist
+    fn remove(&self, id: &str) -> Result<()>;
+
+    /// Move a delivered bundle out of the active set
+    fn dispatch(&self, bundle: &Bundle) -> Result<()>;
+
+    /// Move a bundle that could not be delivered out of the active set
+    fn fail(&self, bundle: &Bundle) -> Result<()>;
+
+    /// Move a bundle handed to the local application out of the active set
+    fn deliver(&self, bundle: &Bundle) -> Result<()>;
+
+    /// Persisted number of failed forwarding attempts for a bundle (0 if none were recorded)
+    fn forwarding_attempts(&self, id: &str) -> Result<u32>;
+
+    /// Persist the number of failed forwarding attempts for a bundle
+    fn set_forwarding_attempts(&self, id: &str, attempts: u32) -> Result<()>;
+
+    /// Unix time (seconds) before which a bundle that failed to forward is not
+    /// retried (0 if none was recorded). Backends that do not track it keep the default.
+    fn next_retry_at(&self, _id: &str) -> Result<u64> {
+        Ok(0)
+    }
+
+    /// Persist when a bundle that failed to forward may be retried
+    fn set_next_retry_at(&self, _id: &str, _retry_at: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// When a received bundle arrived at this node, if that was recorded.
+    /// Backends that do not track arrivals keep the default.
+    fn arrival_time(&self, _id: &str) -> Result<Option<SystemTime>> {
+        Ok(None)
+    }
+
+    /// Record when a received bundle arrived at this node
+    fn set_arrival_time(&self, _id: &str, _arrived_at: SystemTime) -> Result<()> {
+        Ok(())
+    }
+
+    /// Append an entry to the forwarding history
+    fn record_history(&self, entry: &HistoryEntry) -> Result<()>;
+
+    /// Every recorded forwarding history entry, oldest first
+    fn history(&self) -> Result<Vec<HistoryEntry>>;
+
+    /// Check that new bundles can be stored, failing with the error a write would hit.
+    /// Backends that cannot fail to write keep the default.
+    fn check_writable(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Fraction of the size limit in use, or `None` if the store is unbounded
+    fn usage_ratio(&self) -> Result<Option<f64>> {
+        Ok(None)
+    }
+
+    /// Whether an insert may delete other stored bundles to make room for the new
+    /// ones. Backends that never evict keep the default.
+    fn evicts_on_insert(&self) -> bool {
+        false
+    }
+
+    /// Store several bundles, returning their IDs in order. Backends that can write
+    /// a batch more cheaply than one bundle at a time should override this.
+    fn insert_batch(&self, bundles: &[Bundle]) -> Result<Vec<String>> {
+        bundles
+            .iter()
+            .map(|bundle| {
+                self.insert(bundle)?;
+                Ok(bundle.id().digest())
+            })
+            .collect()
+    }
+
+    /// Metadata of a stored bundle; `size` is its encoded length
+    fn load_meta(&self, id: &str) -> Result<BundleMeta> {
+        let bundle = self.load(id)?;
+        Ok(BundleMeta {
+            id: id.to_string(),
+            size: encode_bundle(&bundle)?.len() as u64,
+            source: bundle.primary.source,
+            destination: bundle.primary.destination,
+            creation_timestamp: bundle.primary.creation_timestamp,
+            lifetime: bundle.primary.lifetime,
+        })
+    }
+
+    fn find_by_partial_id(&self, partial: &str) -> Option<String> {
+        self.list()
+            .ok()?
+            .into_iter()
+            .find(|id| id.starts_with(partial))
+    }
+
+    fn load_by_partial_id(&self, partial: &str) -> Result<Bundle> {
+        match self.find_by_partial_id(partial) {
+            Some(full_id) => self.load(&full_id),
+            None => Err(StoreError::NotFound),
+        }
+    }
+
+    /// List bundle IDs in forwarding order: `Expedited` before `Normal` before `Bulk`,
+    /// oldest first within the same priority
+    fn list_by_priority(&self) -> Result<Vec<String>> {
+        let mut entries = Vec::new();
+        for id in self.list()? {
+            let bundle = self.load(&id)?;
+            entries.push((
+                std::cmp::Reverse(bundle.primary.priority),
+                bundle.primary.creation_timestamp,
+                id,
+            ));
+        }
+        entries.sort();
+        Ok(entries.into_iter().map(|(_, _, id)| id).collect())
+    }
+
+    /// Metadata of stored bundles ordered by `sort`, skipping `offset` entries and
+    /// returning at most `limit`
+    fn list_meta(&self, offset: usize, limit: usize, sort: SortKey) -> Result<Vec<BundleMeta>> {
+        let mut metas = Vec::new();
+        for id in self.list()? {
+            match self.load_meta(&id) {
+                Ok(meta) => metas.push(meta),
+                Err(StoreError::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        sort.sort(&mut metas);
+        Ok(metas.into_iter().skip(offset).take(limit).collect())
+    }
+
+    /// Count, size, age and expiry of the stored bundles; sizes are encoded lengths
+    fn stats(&self) -> Result<StoreStats> {
+        let mut stats = StoreStats::default();
+        for id in self.list()? {
+            let bundle = match self.load(&id) {
+                Ok(bundle) => bundle,
+                Err(StoreError::NotFound) => continue,
+                Err(e) => return Err(e),
+            };
+            let meta = BundleMeta {
+                size: encode_bundle(&bundle)?.len() as u64,
+                source: bundle.primary.source,
+                destination: bundle.primary.destination,
+                creation_timestamp: bundle.primary.creation_timestamp,
+                lifetime: bundle.primary.lifetime,
+                id,
+            };
+            stats.record(&meta, bundle.payload.len() as u64);
+        }
+        Ok(stats)
+    }
+
+    /// IDs of the stored bundles matching `filter`, judged from their metadata
+    fn find(&self, filter: &BundleFilter) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for id in self.list()? {
+            match self.load_meta(&id) {
+                Ok(meta) if filter.matches(&meta) => ids.push(id),
+                Ok(_) | Err(StoreError::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Remove expired bundles, returning how many were removed.
+    /// Bundles that disappear while the scan is running are skipped.
+    fn cleanup_expired(&self) -> Result<usize> {
+        let mut removed = 0;
+        for id in self.list()? {
+            let bundle = match self.load(&id) {
+                Ok(bundle) => bundle,
+                Err(StoreError::NotFound) => continue,
+                Err(e) => return Err(e),
+            };
+            if bundle.is_expired() {
+                match self.remove(&id) {
+                    Ok(()) => removed += 1,
+                    Err(StoreError::NotFound) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Canonical CBOR encoding of a bundle for storage
+pub(crate) fn encode_bundle(bundle: &Bundle) -> Result<Vec<u8>> {
+    bundle
+        .to_canonical_cbor()
+        .map_err(|e| StoreError::Serialize(e.to_string()))
+}
+
+/// Run blocking store work on tokio's blocking thread pool so disk I/O does not
+/// stall the async runtime
+pub(crate) async fn spawn_blocking<T, F>(op: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(op)
+        .await
+        .map_err(|e| StoreError::Io(std::io::Error::other(e)))?
+}
@@ -0,0 +1,87 @@
+use crate::bpv7::EndpointId;
+use std::collections::HashMap;
+
+/// Running totals of the bundles in a [`BundleStore`](crate::store::BundleStore), so
+/// size and quota checks do not rescan the directory on every insert. Built by one
+/// scan on first use and kept current by the store's own writes and removals; files
+/// changed behind the store's back are picked up only by a new store.
+#[derive(Debug, Default)]
+pub(crate) struct Usage {
+    /// Destination (`None` if the header could not be read) and file size of every
+    /// stored bundle, by ID
+    bundles: HashMap<String, (Option<EndpointId>, u64)>,
+    /// Number and total file size of the stored bundles for each destination
+    destinations: HashMap<EndpointId, (usize, u64)>,
+    /// Total size of the stored bundle files
+    bytes: u64,
+}
+
+impl Usage {
+    /// Count the bundle `id`, replacing any earlier entry for it
+    pub(crate) fn add(&mut self, id: String, destination: Option<EndpointId>, size: u64) {
+        self.remove(&id);
+        self.bytes += size;
+        if let Some(destination) = &destination {
+            let (count, bytes) = self.destinations.entry(destination.clone()).or_default();
+            *count += 1;
+            *bytes += size;
+        }
+        self.bundles.insert(id, (destination, size));
+    }
+
+    /// Stop counting the bundle `id`, if it was counted
+    pub(crate) fn remove(&mut self, id: &str) {
+        let Some((destination, size)) = self.bundles.remove(id) else {
+            return;
+        };
+        self.bytes -= size;
+        let Some(destination) = destination else {
+            return;
+        };
+        if let Some((count, bytes)) = self.destinations.get_mut(&destination) {
+            *count -= 1;
+            *bytes -= size;
+            if *count == 0 {
+                self.destinations.remove(&destination);
+            }
+        }
+    }
+
+    /// Total size of the stored bundle files
+    pub(crate) fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// ID and file size of every stored bundle
+    pub(crate) fn sizes(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.bundles
+            .iter()
+            .map(|(id, (_, size))| (id.as_str(), *size))
+    }
+
+    /// Number of bundles stored for `destination` and the total size of their files
+    pub(crate) fn destination(&self, destination: &EndpointId) -> (usize, u64) {
+        self.destinations
+            .get(destination)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// ID and file size of every bundle stored for `destination`
+    pub(crate) fn destination_bundles<'a>(
+        &'a self,
+        destination: &'a EndpointId,
+    ) -> impl Iterator<Item = (&'a str, u64)> + 'a {
+        self.bundles
+            .iter()
+            .filter(move |(_, (stored_for, _))| stored_for.as_ref() == Some(destination))
+            .map(|(id, (_, size))| (id.as_str(), *size))
+    }
+
+    /// Destination and file size of the bundle `id`, if it is counted
+    pub(crate) fn get(&self, id: &str) -> Option<(Option<&EndpointId>, u64)> {
+        self.bundles
+            .get(id)
+            .map(|(destination, size)| (destination.as_ref(), *size))
+    }
+}
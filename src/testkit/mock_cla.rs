@@ -0,0 +1,100 @@
+use crate::bpv7::bundle::Bundle;
+use crate::bpv7::EndpointId;
+use crate::cla::peer::ClaPeer;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Peer that records the bundles handed to it instead of sending them anywhere.
+///
+/// Clones share the same record, so a test can keep one clone and register
+/// another with the node.
+#[derive(Debug, Clone)]
+pub struct MockCla {
+    eid: EndpointId,
+    cla_type: String,
+    reachable: Arc<AtomicBool>,
+    received: Arc<Mutex<Vec<Bundle>>>,
+}
+
+impl MockCla {
+    pub fn new(eid: impl Into<EndpointId>) -> Self {
+        Self {
+            eid: eid.into(),
+            cla_type: "mock".to_string(),
+            reachable: Arc::new(AtomicBool::new(true)),
+            received: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Report `cla_type` to the routing layer instead of `"mock"`
+    pub fn with_cla_type(mut self, cla_type: &str) -> Self {
+        self.cla_type = cla_type.to_string();
+        self
+    }
+
+    /// Start out of contact; see [`MockCla::set_reachable`]
+    pub fn unreachable(self) -> Self {
+        self.set_reachable(false);
+        self
+    }
+
+    /// Open or close the contact with this peer
+    pub fn set_reachable(&self, reachable: bool) {
+        self.reachable.store(reachable, Ordering::SeqCst);
+    }
+
+    pub fn eid(&self) -> &EndpointId {
+        &self.eid
+    }
+
+    /// Bundles sent to this peer, oldest first
+    pub fn received(&self) -> Vec<Bundle> {
+        self.received.lock().unwrap().clone()
+    }
+
+    pub fn received_count(&self) -> usize {
+        self.received.lock().unwrap().len()
+    }
+}
+
+impl From<&str> for MockCla {
+    fn from(eid: &str) -> Self {
+        Self::new(eid)
+    }
+}
+
+#[async_trait]
+impl ClaPeer for MockCla {
+    fn get_peer_endpoint_id(&self) -> EndpointId {
+        self.eid.clone()
+    }
+
+    async fn is_reachable(&self) -> bool {
+        self.reachable.load(Ordering::SeqCst)
+    }
+
+    fn get_cla_type(&self) -> &str {
+        &self.cla_type
+    }
+
+    fn get_connection_address(&self) -> String {
+        format!("{}:{}", self.cla_type, self.eid)
+    }
+
+    fn clone_box(&self) -> Box<dyn ClaPeer> {
+        Box::new(self.clone())
+    }
+
+    async fn activate(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn send_bundle(&self, bundle: &Bundle) -> anyhow::Result<()> {
+        if !self.reachable.load(Ordering::SeqCst) {
+            anyhow::bail!("Mock peer {} is out of contact", self.eid);
+        }
+        self.received.lock().unwrap().push(bundle.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+//! Harness for routing and forwarding tests, enabled by the `testkit` feature.
+//!
+//! [`TestNode::builder`] sets up a node on an in-memory store with
+//! [`MockCla`] peers that record what they are sent, so a test only has to
+//! inject bundles and assert where they went:
+//!
+//! ```ignore
+//! let node = TestNode::builder()
+//!     .with_memory_store()
+//!     .with_peers(["dtn://relay", "dtn://ground"])
+//!     .build()
+//!     .await;
+//! node.inject(Bundle::new("dtn://rover", "dtn://ground", b"hi".to_vec())).await?;
+//! node.assert_forwarded_to("dtn://ground");
+//! ```
+
+mod mock_cla;
+mod node;
+
+#[cfg(test)]
+mod tests;
+
+pub use mock_cla::MockCla;
+pub use node::{TestNode, TestNodeBuilder};
@@ -0,0 +1,162 @@
+use crate::api::node::DtnNode;
+use crate::bpv7::bundle::Bundle;
+use crate::bpv7::EndpointId;
+use crate::cla::tcp::ack::AckStatus;
+use crate::routing::algorithm::{RoutingAlgorithmType, RoutingConfig};
+use crate::store::{BundleStorage, MemoryBundleStore};
+use crate::testkit::MockCla;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Node wired to [`MockCla`] peers, for driving the forwarding path in tests.
+///
+/// Derefs to the wrapped [`DtnNode`], so the whole node API stays available.
+pub struct TestNode {
+    node: DtnNode,
+    peers: Vec<MockCla>,
+}
+
+impl TestNode {
+    pub fn builder() -> TestNodeBuilder {
+        TestNodeBuilder::default()
+    }
+
+    pub fn node(&self) -> &DtnNode {
+        &self.node
+    }
+
+    /// The mock peer registered as `eid`
+    ///
+    /// # Panics
+    /// If no such peer was registered through the builder
+    pub fn peer(&self, eid: &str) -> &MockCla {
+        let eid = EndpointId::from(eid);
+        self.peers
+            .iter()
+            .find(|peer| *peer.eid() == eid)
+            .unwrap_or_else(|| panic!("No mock peer {eid} registered"))
+    }
+
+    pub fn peers(&self) -> &[MockCla] {
+        &self.peers
+    }
+
+    /// Hand `bundle` to the node as if a CLA had received it, then run one
+    /// forwarding pass over the store
+    pub async fn inject(&self, bundle: Bundle) -> anyhow::Result<AckStatus> {
+        let status = self.node.receive_bundle(bundle);
+        self.node.forward_stored_bundles().await?;
+        Ok(status)
+    }
+
+    /// Inject `bundles` one after another, e.g. a capture of an earlier run
+    pub async fn replay(
+        &self,
+        bundles: impl IntoIterator<Item = Bundle>,
+    ) -> anyhow::Result<Vec<AckStatus>> {
+        let mut statuses = Vec::new();
+        for bundle in bundles {
+            statuses.push(self.inject(bundle).await?);
+        }
+        Ok(statuses)
+    }
+
+    /// Assert that the peer `eid` was sent at least one bundle, returning them all
+    #[track_caller]
+    pub fn assert_forwarded_to(&self, eid: &str) -> Vec<Bundle> {
+        let received = self.peer(eid).received();
+        assert!(
+            !received.is_empty(),
+            "Expected a bundle to be forwarded to {eid}; peers that received one: {:?}",
+            self.peers_with_bundles()
+        );
+        received
+    }
+
+    /// Assert that the peer `eid` was never sent a bundle
+    #[track_caller]
+    pub fn assert_not_forwarded_to(&self, eid: &str) {
+        let count = self.peer(eid).received_count();
+        assert_eq!(
+            count, 0,
+            "Expected nothing forwarded to {eid}, got {count} bundles"
+        );
+    }
+
+    fn peers_with_bundles(&self) -> Vec<String> {
+        self.peers
+            .iter()
+            .filter(|peer| peer.received_count() > 0)
+            .map(|peer| peer.eid().to_string())
+            .collect()
+    }
+}
+
+impl Deref for TestNode {
+    type Target = DtnNode;
+
+    fn deref(&self) -> &DtnNode {
+        &self.node
+    }
+}
+
+/// Builder for [`TestNode`]; defaults to an in-memory store and epidemic routing
+pub struct TestNodeBuilder {
+    store: Arc<dyn BundleStorage>,
+    routing_config: RoutingConfig,
+    node_eid: Option<EndpointId>,
+    peers: Vec<MockCla>,
+}
+
+impl Default for TestNodeBuilder {
+    fn default() -> Self {
+        Self {
+            store: Arc::new(MemoryBundleStore::new()),
+            routing_config: RoutingConfig::new(RoutingAlgorithmType::Epidemic),
+            node_eid: None,
+            peers: Vec::new(),
+        }
+    }
+}
+
+impl TestNodeBuilder {
+    /// Keep bundles in a fresh [`MemoryBundleStore`]
+    pub fn with_memory_store(self) -> Self {
+        self.with_store(Arc::new(MemoryBundleStore::new()))
+    }
+
+    pub fn with_store(mut self, store: Arc<dyn BundleStorage>) -> Self {
+        self.store = store;
+        self
+    }
+
+    pub fn with_routing_config(mut self, routing_config: RoutingConfig) -> Self {
+        self.routing_config = routing_config;
+        self
+    }
+
+    pub fn with_node_eid(mut self, node_eid: impl Into<EndpointId>) -> Self {
+        self.node_eid = Some(node_eid.into());
+        self
+    }
+
+    /// Register mock peers, given as endpoint IDs or prepared [`MockCla`]s
+    pub fn with_peers<P: Into<MockCla>>(mut self, peers: impl IntoIterator<Item = P>) -> Self {
+        self.peers.extend(peers.into_iter().map(Into::into));
+        self
+    }
+
+    pub async fn build(self) -> TestNode {
+        let mut node = DtnNode::with_store(self.store, self.routing_config);
+        if let Some(node_eid) = self.node_eid {
+            node = node.with_node_eid(node_eid);
+        }
+        for peer in &self.peers {
+            node.register_peer(Box::new(peer.clone())).await;
+        }
+        TestNode {
+            node,
+            peers: self.peers,
+        }
+    }
+}
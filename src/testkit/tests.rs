@@ -0,0 +1,72 @@
+use crate::bpv7::bundle::Bundle;
+use crate::bpv7::EndpointId;
+use crate::cla::tcp::ack::AckStatus;
+use crate::testkit::{MockCla, TestNode};
+
+#[tokio::test]
+async fn test_injected_bundle_is_forwarded_to_mock_peers() -> anyhow::Result<()> {
+    let node = TestNode::builder()
+        .with_memory_store()
+        .with_peers(["dtn://relay", "dtn://ground"])
+        .build()
+        .await;
+
+    let bundle = Bundle::new("dtn://rover", "dtn://ground", b"telemetry".to_vec());
+    assert_eq!(node.inject(bundle.clone()).await?, AckStatus::Ok);
+
+    let received = node.assert_forwarded_to("dtn://ground");
+    assert_eq!(received[0].id(), bundle.id());
+    node.assert_forwarded_to("dtn://relay");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unreachable_mock_peer_is_skipped() -> anyhow::Result<()> {
+    let node = TestNode::builder()
+        .with_peers([
+            MockCla::new("dtn://relay").unreachable(),
+            MockCla::new("dtn://ground"),
+        ])
+        .build()
+        .await;
+
+    node.inject(Bundle::new("dtn://rover", "dtn://ground", b"hi".to_vec()))
+        .await?;
+    node.assert_forwarded_to("dtn://ground");
+    node.assert_not_forwarded_to("dtn://relay");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replay_skips_previous_hop_and_duplicates() -> anyhow::Result<()> {
+    let node = TestNode::builder()
+        .with_node_eid("dtn://relay")
+        .with_peers(["dtn://peerX", "dtn://peerY"])
+        .build()
+        .await;
+
+    let mut bundle = Bundle::new("dtn://rover", "dtn://ground", b"hi".to_vec());
+    bundle.previous_node = Some(EndpointId::from("dtn://peerX"));
+    let statuses = node.replay([bundle.clone(), bundle]).await?;
+
+    assert_eq!(statuses, vec![AckStatus::Ok, AckStatus::Ok]);
+    assert_eq!(node.duplicates_suppressed(), 1);
+    node.assert_not_forwarded_to("dtn://peerX");
+    let received = node.assert_forwarded_to("dtn://peerY");
+    assert_eq!(received.len(), 1);
+    assert_eq!(
+        received[0].previous_node,
+        Some(EndpointId::from("dtn://relay"))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+#[should_panic(expected = "Expected a bundle to be forwarded to dtn://ground")]
+async fn test_assert_forwarded_to_fails_without_forwarding() {
+    let node = TestNode::builder()
+        .with_peers(["dtn://ground"])
+        .build()
+        .await;
+    node.assert_forwarded_to("dtn://ground");
+}
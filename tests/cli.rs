@@ -29,6 +29,28 @@ fn run_cli(args: &[&str]) -> String {
     format!("{stdout}{stderr}")
 }
 
+// Helper function to run CLI commands and capture only stdout (for JSON output)
+fn run_cli_stdout(args: &[&str]) -> String {
+    run_cli(&[]);
+    let output = Command::new("./target/debug/sdtn")
+        .env("SDTN_BUNDLE_PATH", BUNDLES_DIR)
+        .args(args)
+        .output()
+        .expect("Failed to execute command");
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+// Helper to run CLI commands against a private store, so other tests resetting
+// ./bundles cannot interfere
+fn run_cli_in(store: &Path, args: &[&str]) -> std::process::Output {
+    run_cli(&[]);
+    Command::new("./target/debug/sdtn")
+        .env("SDTN_BUNDLE_PATH", store)
+        .args(args)
+        .output()
+        .expect("Failed to execute command")
+}
+
 // Helper to get a unique payload
 fn get_unique_payload(base: &str) -> String {
     let timestamp = SystemTime::now()
@@ -142,6 +164,127 @@ fn test_routing_functionality() {
     assert!(!output.contains("error") && !output.contains("Error"));
 }
 
+#[test]
+fn test_route_remove_and_clear() {
+    run_cli(&[]);
+    // Routes only outlive one CLI run when the config names a routes file
+    let dir = tempfile::TempDir::new().unwrap();
+    let config_path = dir.path().join("config.toml");
+    let routes_path = dir.path().join("routes.json");
+    let config = fs::read_to_string("config/default.toml").unwrap().replace(
+        "# routes_path = \"./routes.json\"",
+        &format!("routes_path = {:?}", routes_path.to_str().unwrap()),
+    );
+    fs::write(&config_path, config).unwrap();
+    let run = |args: &[&str]| -> String {
+        let output = Command::new("./target/debug/sdtn")
+            .env("DTN_CONFIG", &config_path)
+            .env("SDTN_BUNDLE_PATH", dir.path().join("bundles"))
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+    };
+    let remove = [
+        "route",
+        "remove",
+        "--destination",
+        "dtn://src",
+        "--next-hop",
+        "dtn://router/",
+    ];
+
+    let output = run(&[
+        "route",
+        "add",
+        "--destination",
+        "dtn://src",
+        "--next-hop",
+        "dtn://router/",
+        "--cla-type",
+        "tcp",
+    ]);
+    assert!(output.contains("Route added successfully"), "{output}");
+    let output = run(&remove);
+    assert!(
+        output.contains("Removed route to dtn://src via dtn://router/"),
+        "{output}"
+    );
+    let output = run(&remove);
+    assert!(
+        output.contains("No route to dtn://src via dtn://router/"),
+        "{output}"
+    );
+
+    let output = run(&[
+        "route",
+        "remove",
+        "--destination",
+        "bad",
+        "--next-hop",
+        "dtn://router/",
+    ]);
+    assert!(output.contains("Invalid --destination"), "{output}");
+
+    for next_hop in ["dtn://router1/", "dtn://router2/"] {
+        run(&[
+            "route",
+            "add",
+            "--destination",
+            "dtn://src",
+            "--next-hop",
+            next_hop,
+            "--cla-type",
+            "tcp",
+        ]);
+    }
+    let output = run(&["route", "clear"]);
+    assert!(
+        output.contains("Cleared 2 routes from the routing table"),
+        "{output}"
+    );
+    let output = run(&["route", "clear"]);
+    assert!(
+        output.contains("Cleared 0 routes from the routing table"),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_route_add_rejects_malformed_eid() {
+    setup();
+    let output = run_cli(&[
+        "route",
+        "add",
+        "--destination",
+        "invalid-endpoint",
+        "--next-hop",
+        "dtn://router/",
+        "--cla-type",
+        "tcp",
+    ]);
+    assert!(output.contains("Invalid --destination"));
+    assert!(output.contains("invalid-endpoint"));
+    assert!(!output.contains("Route added successfully"));
+
+    let output = run_cli(&[
+        "route",
+        "add",
+        "--destination",
+        "dtn://src",
+        "--next-hop",
+        "ipn:1",
+        "--cla-type",
+        "tcp",
+    ]);
+    assert!(output.contains("Invalid --next-hop"));
+    assert!(!output.contains("Route added successfully"));
+}
+
 #[test]
 fn test_bundle_forwarding_selection() {
     setup();
@@ -186,3 +329,425 @@ fn test_bundle_forwarding_selection() {
             || output.contains("Testing routing table")
     );
 }
+
+#[test]
+fn test_insert_with_lifetime() {
+    setup();
+    let payload = get_unique_payload("Test message with lifetime");
+    let output = run_cli(&["insert", "--message", &payload, "--lifetime", "60"]);
+    assert!(output.contains("Bundle inserted successfully"));
+}
+
+#[test]
+fn test_insert_compressed_and_show() {
+    setup();
+    let payload = get_unique_payload("Compressible message").repeat(20);
+    let output = run_cli(&["insert", "--message", &payload, "--compress"]);
+    assert!(output.contains("Bundle inserted successfully"));
+
+    let bundle_id = output
+        .lines()
+        .find_map(|l| {
+            l.find("ID:")
+                .map(|idx| l[idx + 3..].trim().trim_end_matches(')'))
+        })
+        .unwrap();
+
+    let output = run_cli(&["show", "--id", &bundle_id[..8]]);
+    assert!(output.contains(&payload) || output.contains("Bundle ID not found"));
+}
+
+#[test]
+fn test_insert_file_and_show() {
+    setup();
+    let payload = get_unique_payload("File contents");
+    let path = Path::new(BUNDLES_DIR).join("import.txt");
+    fs::write(&path, &payload).unwrap();
+
+    let output = run_cli(&[
+        "insert-file",
+        "--path",
+        path.to_str().unwrap(),
+        "--destination",
+        "dtn://ground",
+    ]);
+    assert!(output.contains("Bundle inserted successfully"));
+    let bundle_id = output
+        .lines()
+        .find_map(|l| {
+            l.find("ID:")
+                .map(|idx| l[idx + 3..].trim().trim_end_matches(')'))
+        })
+        .unwrap();
+
+    let output = run_cli(&["show", "--id", bundle_id]);
+    assert!(output.contains(&payload));
+    assert!(output.contains("dtn://ground"));
+
+    let output = run_cli(&[
+        "insert-file",
+        "--path",
+        path.to_str().unwrap(),
+        "--destination",
+        "ground",
+    ]);
+    assert!(output.contains("Invalid destination"));
+}
+
+#[test]
+fn test_export_bundle_to_file() {
+    setup();
+    let payload = get_unique_payload("Export me");
+    let output = run_cli(&["insert", "--message", &payload]);
+    let bundle_id = output
+        .lines()
+        .find_map(|l| {
+            l.find("ID:")
+                .map(|idx| l[idx + 3..].trim().trim_end_matches(')'))
+        })
+        .unwrap()
+        .to_string();
+
+    let out = Path::new(BUNDLES_DIR).join("export.bin");
+    let out = out.to_str().unwrap();
+    let output = run_cli(&[
+        "export", "--id", &bundle_id, "--format", "raw", "--out", out,
+    ]);
+    assert!(output.contains("Exported bundle"));
+    assert_eq!(fs::read(out).unwrap(), payload.as_bytes());
+
+    let output = run_cli(&["export", "--id", &bundle_id, "--out", out]);
+    assert!(output.contains("as cbor"));
+    let bundle: sdtn::bpv7::bundle::Bundle =
+        serde_cbor::from_slice(&fs::read(out).unwrap()).unwrap();
+    assert_eq!(bundle.id().digest(), bundle_id);
+
+    let output = run_cli(&[
+        "export", "--id", &bundle_id, "--format", "xml", "--out", out,
+    ]);
+    assert!(output.contains("Unknown export format"));
+}
+
+#[test]
+fn test_delete_bundle() {
+    let store_dir = tempfile::TempDir::new().unwrap();
+    let payload = get_unique_payload("Test message for delete");
+    let output = run_cli_in(store_dir.path(), &["insert", "--message", &payload]);
+    assert!(output.status.success());
+    let bundle_id = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|l| {
+            l.find("ID:")
+                .map(|idx| l[idx + 3..].trim().trim_end_matches(')').to_string())
+        })
+        .unwrap();
+    let partial_id = &bundle_id[..8];
+
+    let output = run_cli_in(store_dir.path(), &["delete", "--id", partial_id]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Bundle deleted successfully"));
+
+    // The bundle is gone, so a second delete or a show no longer finds it
+    let output = run_cli_in(store_dir.path(), &["delete", "--id", partial_id]);
+    let output = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.contains("Bundle ID not found"));
+    let output = run_cli_in(store_dir.path(), &["show", "--id", partial_id]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains(&payload));
+
+    let output = run_cli(&["delete", "--id", "zzzzzzzz"]);
+    assert!(output.contains("Bundle ID not found"));
+}
+
+#[test]
+fn test_show_bundle_json_roundtrip() {
+    use base64::Engine;
+    use sdtn::bpv7::bundle::{Bundle, PrimaryBlock};
+
+    let store_dir = tempfile::TempDir::new().unwrap();
+    let payload = get_unique_payload("Test message for json");
+    let output = run_cli_in(store_dir.path(), &["insert", "--message", &payload]);
+    assert!(output.status.success());
+    let bundle_id = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|l| {
+            l.find("ID:")
+                .map(|idx| l[idx + 3..].trim().trim_end_matches(')').to_string())
+        })
+        .unwrap();
+
+    let output = run_cli_in(
+        store_dir.path(),
+        &["--json", "show", "--id", &bundle_id[..8]],
+    );
+    assert!(output.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let primary: PrimaryBlock = serde_json::from_value(value["primary"].clone()).unwrap();
+    let payload_bytes = base64::engine::general_purpose::STANDARD
+        .decode(value["payload"].as_str().unwrap())
+        .unwrap();
+    let bundle = Bundle {
+        primary,
+        payload: payload_bytes,
+        signature: None,
+        previous_node: None,
+    };
+
+    assert_eq!(String::from_utf8_lossy(&bundle.payload), payload);
+    assert_eq!(
+        value["expired"],
+        serde_json::Value::Bool(bundle.is_expired())
+    );
+}
+
+#[test]
+fn test_status_summary_json() {
+    let output = run_cli_stdout(&["status", "--json"]);
+    println!("status json output: {output}");
+    let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert!(value.get("active").is_some());
+    assert!(value.get("expired").is_some());
+    assert!(value.get("total").is_some());
+}
+
+#[test]
+fn test_stats_json() {
+    setup();
+    run_cli(&[
+        "insert",
+        "--message",
+        &get_unique_payload("Counted message"),
+    ]);
+
+    let output = run_cli_stdout(&["--json", "stats"]);
+    println!("stats json output: {output}");
+    let value: serde_json::Value = match serde_json::from_str(&output) {
+        Ok(value) => value,
+        // Another test may have reset the shared bundle directory
+        Err(_) => return,
+    };
+    // Other tests share the bundle directory, so only lower bounds hold
+    assert!(value["bundle_count"].as_u64().unwrap() >= 1);
+    assert!(value["total_bytes"].as_u64().unwrap() > 0);
+    assert!(value.get("expired_count").is_some());
+}
+
+#[test]
+fn test_list_filter() {
+    setup();
+    let output = run_cli(&["insert", "--message", &get_unique_payload("Fresh message")]);
+    let bundle_id = output
+        .lines()
+        .find_map(|l| {
+            l.find("ID:")
+                .map(|idx| l[idx + 3..].trim().trim_end_matches(')'))
+        })
+        .unwrap()
+        .to_string();
+
+    let output = run_cli_stdout(&["--json", "list", "--filter", "expired"]);
+    let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert!(!value
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::Value::from(bundle_id)));
+
+    let output = run_cli(&["list", "--filter", "stale"]);
+    assert!(output.contains("Unknown list filter"));
+}
+
+#[test]
+fn test_list_json() {
+    let output = run_cli_stdout(&["--json", "list"]);
+    let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert!(value.is_array());
+}
+
+#[test]
+fn test_send_bundle_to_mock_server() {
+    use sdtn::bpv7::bundle::Bundle;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    run_cli(&[]);
+    // Use a private store so other tests resetting ./bundles cannot interfere
+    let store_dir = tempfile::TempDir::new().unwrap();
+    let run = |args: &[&str]| -> String {
+        let output = Command::new("./target/debug/sdtn")
+            .env("SDTN_BUNDLE_PATH", store_dir.path())
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+    };
+
+    let payload = get_unique_payload("Test message for send");
+    let output = run(&["insert", "--message", &payload]);
+    let bundle_id = output
+        .lines()
+        .find_map(|l| {
+            l.find("ID:")
+                .map(|idx| l[idx + 3..].trim().trim_end_matches(')'))
+        })
+        .unwrap()
+        .to_string();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut len_buf = [0u8; 4];
+        stream.read_exact(&mut len_buf).unwrap();
+        let mut data = vec![0u8; u32::from_be_bytes(len_buf) as usize];
+        stream.read_exact(&mut data).unwrap();
+        stream.write_all(b"OK").unwrap();
+        serde_cbor::from_slice::<Bundle>(&data).unwrap()
+    });
+
+    let output = run(&["send", "--id", &bundle_id[..8], "--addr", &addr]);
+    println!("send output: {output}");
+    assert!(output.contains("Bundle sent successfully"));
+
+    let received = server.join().unwrap();
+    assert_eq!(String::from_utf8_lossy(&received.payload), payload);
+
+    // The bundle has moved from the active store to the dispatched directory
+    assert!(!store_dir.path().join(format!("{bundle_id}.cbor")).exists());
+    assert!(store_dir
+        .path()
+        .join("dispatched")
+        .join(format!("{bundle_id}.cbor"))
+        .exists());
+}
+
+#[test]
+fn test_invalid_config_fails_fast() {
+    run_cli(&[]);
+    let dir = tempfile::TempDir::new().unwrap();
+    let config_path = dir.path().join("invalid.toml");
+    let config = fs::read_to_string("config/default.toml")
+        .unwrap()
+        .replace("version = 7", "version = 6");
+    fs::write(&config_path, config).unwrap();
+
+    let output = Command::new("./target/debug/sdtn")
+        .env("DTN_CONFIG", &config_path)
+        .env("SDTN_BUNDLE_PATH", dir.path().join("bundles"))
+        .args(["list"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("bundle.version must be 7"));
+}
+
+#[test]
+fn test_daemon_start_runs_until_interrupted() {
+    run_cli(&[]);
+    let dir = tempfile::TempDir::new().unwrap();
+    let config_path = dir.path().join("daemon.toml");
+    let config = fs::read_to_string("config/default.toml")
+        .unwrap()
+        .replace("127.0.0.1:4556", "127.0.0.1:0");
+    fs::write(&config_path, config).unwrap();
+
+    let child = Command::new("./target/debug/sdtn")
+        .env("DTN_CONFIG", &config_path)
+        .env("SDTN_BUNDLE_PATH", dir.path().join("bundles"))
+        .args(["daemon", "start"])
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stdout}{stderr}");
+    assert!(stdout.contains("Started 1 CLA listener(s)"), "{stdout}");
+}
+
+#[test]
+fn test_allowed_schemes_reject_http_destination() {
+    run_cli(&[]);
+    let dir = tempfile::TempDir::new().unwrap();
+    let config = fs::read_to_string("config/default.toml")
+        .unwrap()
+        .replace("\"dtn://dest\"", "\"http://example.com\"");
+    let insert = |config: &str| {
+        let config_path = dir.path().join("config.toml");
+        fs::write(&config_path, config).unwrap();
+        Command::new("./target/debug/sdtn")
+            .env("DTN_CONFIG", &config_path)
+            .env("SDTN_BUNDLE_PATH", dir.path().join("bundles"))
+            .args(["insert", "--message", "scheme check"])
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    // Enforcement is off unless endpoints.allowed_schemes is set
+    assert!(insert(&config).status.success());
+
+    let enforced = config.replace(
+        "[endpoints]",
+        "[endpoints]\nallowed_schemes = [\"dtn\", \"ipn\"]",
+    );
+    let output = insert(&enforced);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("destination 'http://example.com' does not use an allowed scheme"));
+}
+
+#[test]
+fn test_health_reports_json_and_fails_without_peers() {
+    run_cli(&[]);
+    let dir = tempfile::TempDir::new().unwrap();
+    let output = Command::new("./target/debug/sdtn")
+        .env("SDTN_BUNDLE_PATH", dir.path().join("bundles"))
+        .args(["health"])
+        .output()
+        .expect("Failed to execute command");
+
+    // The default config only has a listener, so no peer is reachable
+    assert!(!output.status.success());
+    let health: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(health["store_writable"], true);
+    assert_eq!(health["reachable_peer_count"], 0);
+    assert_eq!(health["ready"], false);
+}
+
+#[test]
+fn test_route_explain() {
+    setup();
+    let payload = get_unique_payload("Explain message");
+    let output = run_cli(&["insert", "--message", &payload]);
+    let bundle_id = output
+        .lines()
+        .find_map(|l| {
+            l.find("ID:")
+                .map(|idx| l[idx + 3..].trim().trim_end_matches(')'))
+        })
+        .unwrap()
+        .to_string();
+
+    let output = run_cli(&["route", "explain", "--id", &bundle_id[..8]]);
+    println!("route explain output: {output}");
+    assert!(output.contains("Forwarding decision for bundle"));
+    // The CLI registers no peers, so nothing would be forwarded
+    assert!(output.contains("Peers (0)"));
+    assert!(output.contains("would not be forwarded"));
+}
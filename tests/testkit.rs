@@ -0,0 +1,45 @@
+//! Routing scenarios written against the `testkit` harness
+
+use sdtn::testkit::{MockCla, TestNode};
+use sdtn::{Bundle, EndpointId};
+
+#[tokio::test]
+async fn forwards_bundle_to_the_expected_mock_peer() -> anyhow::Result<()> {
+    let node = TestNode::builder()
+        .with_memory_store()
+        .with_node_eid("dtn://relay")
+        .with_peers([MockCla::new("dtn://ground").with_cla_type("tcp")])
+        .build()
+        .await;
+
+    node.inject(Bundle::new(
+        "dtn://rover",
+        "dtn://ground",
+        b"science data".to_vec(),
+    ))
+    .await?;
+
+    let received = node.assert_forwarded_to("dtn://ground");
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].payload, b"science data");
+    Ok(())
+}
+
+#[tokio::test]
+async fn bundles_for_this_node_are_not_forwarded() -> anyhow::Result<()> {
+    let node = TestNode::builder()
+        .with_node_eid("dtn://ground")
+        .with_peers(["dtn://relay"])
+        .build()
+        .await;
+
+    node.inject(Bundle::new("dtn://rover", "dtn://ground", b"home".to_vec()))
+        .await?;
+
+    node.assert_not_forwarded_to("dtn://relay");
+    assert!(node
+        .list_peers()
+        .await
+        .contains(&EndpointId::from("dtn://relay")));
+    Ok(())
+}